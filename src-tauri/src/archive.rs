@@ -0,0 +1,345 @@
+use std::{fs, path::Path};
+
+use chrono::{Datelike, Local, Timelike};
+use rusqlite::params;
+use tauri::Manager;
+
+use crate::{
+    map_invoice, open_connection, resolve_db_path,
+    spreadsheet::{Cell, Sheet},
+    ubl::iso_date,
+    INVOICE_COLUMNS,
+};
+
+/// Bundles the PDFs of every invoice and credit note issued between
+/// `start_time` and `end_time` into a ZIP in Downloads, with a CSV manifest
+/// listing them. Returns the archive's path.
+#[tauri::command]
+pub async fn export_invoices_archive(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+) -> Result<String, String> {
+    if end_time < start_time {
+        return Err("The end of the range must be after its start".into());
+    }
+    let db_path = resolve_db_path(&app_handle)?;
+    let downloads_path = app_handle
+        .path()
+        .download_dir()
+        .map_err(|e| format!("Failed to get downloads directory: {}", e))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {INVOICE_COLUMNS} FROM invoices
+                 WHERE created_at >= ?1 AND created_at <= ?2
+                 ORDER BY created_at ASC, id ASC"
+            ))
+            .map_err(|err| err.to_string())?;
+        let invoices = stmt
+            .query_map(params![start_time, end_time], map_invoice)
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        if invoices.is_empty() {
+            return Err("No invoices were issued in the selected range".into());
+        }
+
+        let mut zip = ZipWriter::new();
+        let mut manifest = Sheet {
+            name: "Invoices".into(),
+            header: [
+                "Number",
+                "Type",
+                "Issue date",
+                "Due date",
+                "Client",
+                "Currency",
+                "Hours",
+                "Amount",
+                "Status",
+                "File",
+            ]
+            .map(String::from)
+            .to_vec(),
+            rows: Vec::new(),
+        };
+        for invoice in &invoices {
+            let file_name = Path::new(&invoice.file_path)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or("Invalid file path")?;
+            // Prefixed with the number, since older names only carry the
+            // second they were saved at.
+            let archived_name = format!("{}-{}", invoice.id, file_name);
+            let pdf = fs::read(&invoice.file_path).map_err(|_| {
+                format!(
+                    "The PDF of invoice #{} is missing; regenerate it first",
+                    invoice.id
+                )
+            })?;
+            zip.add_file(&archived_name, &pdf)?;
+
+            let client = serde_json::from_str::<serde_json::Value>(&invoice.bill_to_info)
+                .ok()
+                .and_then(|bill_to| bill_to["name"].as_str().map(str::to_string));
+            manifest.rows.push(vec![
+                invoice.id.to_string().into(),
+                invoice.invoice_type.as_str().into(),
+                iso_date(invoice.created_at).into(),
+                invoice
+                    .due_date
+                    .map(iso_date)
+                    .map_or(Cell::Empty, Cell::from),
+                client.map_or(Cell::Empty, Cell::from),
+                invoice.currency.as_str().into(),
+                invoice.total_hours.into(),
+                invoice.total_amount.into(),
+                invoice.status.as_str().into(),
+                archived_name.into(),
+            ]);
+        }
+        zip.add_file("manifest.csv", manifest.to_csv().as_bytes())?;
+
+        let output_path = downloads_path.join(format!(
+            "invoices_{}_{}.zip",
+            iso_date(start_time),
+            iso_date(end_time)
+        ));
+        fs::write(&output_path, zip.finish()?)
+            .map_err(|e| format!("Failed to write the archive: {}", e))?;
+        output_path
+            .to_str()
+            .map(str::to_string)
+            .ok_or_else(|| "Invalid file path".to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Builds a ZIP archive in memory. Files are stored uncompressed: the
+/// archives this app writes hold mostly PDFs, which are compressed already,
+/// and small XML parts or a database snapshot.
+pub struct ZipWriter {
+    data: Vec<u8>,
+    central_directory: Vec<u8>,
+    entries: u16,
+    /// Modification time and date of every file, in MS-DOS format.
+    dos_time: u16,
+    dos_date: u16,
+}
+
+impl ZipWriter {
+    pub fn new() -> Self {
+        let now = Local::now();
+        Self {
+            data: Vec::new(),
+            central_directory: Vec::new(),
+            entries: 0,
+            dos_time: ((now.hour() << 11) | (now.minute() << 5) | (now.second() / 2)) as u16,
+            dos_date: (((now.year() - 1980).max(0) as u32) << 9 | (now.month() << 5) | now.day())
+                as u16,
+        }
+    }
+
+    /// Adds a file at `name`, a `/`-separated path inside the archive.
+    pub fn add_file(&mut self, name: &str, contents: &[u8]) -> Result<(), String> {
+        let size = u32::try_from(contents.len())
+            .map_err(|_| format!("{} is too large for a ZIP archive", name))?;
+        let offset = u32::try_from(self.data.len())
+            .map_err(|_| "The ZIP archive is too large".to_string())?;
+        self.entries = self
+            .entries
+            .checked_add(1)
+            .ok_or("Too many files for a ZIP archive")?;
+        let crc = crc32(contents);
+        let name_len = name.len() as u16;
+
+        // Version 2.0, UTF-8 names, stored.
+        let mut common = Vec::with_capacity(26);
+        common.extend_from_slice(&20u16.to_le_bytes());
+        common.extend_from_slice(&0x0800u16.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+        common.extend_from_slice(&self.dos_time.to_le_bytes());
+        common.extend_from_slice(&self.dos_date.to_le_bytes());
+        common.extend_from_slice(&crc.to_le_bytes());
+        common.extend_from_slice(&size.to_le_bytes());
+        common.extend_from_slice(&size.to_le_bytes());
+        common.extend_from_slice(&name_len.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+
+        self.data.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        self.data.extend_from_slice(&common);
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(contents);
+
+        self.central_directory
+            .extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        self.central_directory
+            .extend_from_slice(&20u16.to_le_bytes());
+        self.central_directory.extend_from_slice(&common);
+        // No comment, disk 0, no attributes.
+        self.central_directory.extend_from_slice(&[0; 6]);
+        self.central_directory
+            .extend_from_slice(&0u32.to_le_bytes());
+        self.central_directory
+            .extend_from_slice(&offset.to_le_bytes());
+        self.central_directory.extend_from_slice(name.as_bytes());
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<Vec<u8>, String> {
+        let directory_offset = u32::try_from(self.data.len())
+            .map_err(|_| "The ZIP archive is too large".to_string())?;
+        let directory_size = self.central_directory.len() as u32;
+        self.data.extend_from_slice(&self.central_directory);
+        self.data.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        self.data.extend_from_slice(&[0; 4]);
+        self.data.extend_from_slice(&self.entries.to_le_bytes());
+        self.data.extend_from_slice(&self.entries.to_le_bytes());
+        self.data.extend_from_slice(&directory_size.to_le_bytes());
+        self.data.extend_from_slice(&directory_offset.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes());
+        Ok(self.data)
+    }
+}
+
+/// The files in a ZIP archive as written by `ZipWriter`, by name. Archives
+/// with compressed files are refused, as are files that fail their CRC.
+pub fn read_zip(data: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let invalid = || "The file isn't a valid ZIP archive".to_string();
+    let end = (0..data.len().saturating_sub(21))
+        .rev()
+        .find(|&pos| u32_at(data, pos) == Some(0x0605_4b50))
+        .ok_or_else(invalid)?;
+    let entries = u16_at(data, end + 10).ok_or_else(invalid)?;
+    let mut pos = u32_at(data, end + 16).ok_or_else(invalid)? as usize;
+
+    let mut files = Vec::with_capacity(usize::from(entries));
+    for _ in 0..entries {
+        if u32_at(data, pos) != Some(0x0201_4b50) {
+            return Err(invalid());
+        }
+        let field = |offset: usize| u16_at(data, pos + offset).map(usize::from);
+        let method = field(10).ok_or_else(invalid)?;
+        let crc = u32_at(data, pos + 16).ok_or_else(invalid)?;
+        let size = u32_at(data, pos + 20).ok_or_else(invalid)? as usize;
+        let name_len = field(28).ok_or_else(invalid)?;
+        let skipped = field(30).ok_or_else(invalid)? + field(32).ok_or_else(invalid)?;
+        let local = u32_at(data, pos + 42).ok_or_else(invalid)? as usize;
+        let name = data
+            .get(pos + 46..pos + 46 + name_len)
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .ok_or_else(invalid)?;
+        pos += 46 + name_len + skipped;
+
+        if method != 0 {
+            return Err(format!("{} is compressed, which isn't supported", name));
+        }
+        if u32_at(data, local) != Some(0x0403_4b50) {
+            return Err(invalid());
+        }
+        let start = local
+            + 30
+            + usize::from(u16_at(data, local + 26).ok_or_else(invalid)?)
+            + usize::from(u16_at(data, local + 28).ok_or_else(invalid)?);
+        let contents = data.get(start..start + size).ok_or_else(invalid)?;
+        if crc32(contents) != crc {
+            return Err(format!("{} is corrupted", name));
+        }
+        files.push((name, contents.to_vec()));
+    }
+    Ok(files)
+}
+
+fn u16_at(data: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(pos..pos + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+/// The CRC-32 used by ZIP, the same one the PNG encoder uses.
+fn crc32(data: &[u8]) -> u32 {
+    crc32fast::hash(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<u8> {
+        let mut zip = ZipWriter::new();
+        zip.add_file("backup.json", b"{\"version\":1}").unwrap();
+        zip.add_file("invoices/2025/invoice_1.pdf", &[0, 1, 2, 255])
+            .unwrap();
+        zip.add_file("empty.txt", b"").unwrap();
+        zip.finish().unwrap()
+    }
+
+    #[test]
+    fn crc32_matches_the_check_value() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414F_A339
+        );
+    }
+
+    #[test]
+    fn written_archives_read_back() {
+        assert_eq!(
+            read_zip(&sample()).unwrap(),
+            vec![
+                ("backup.json".to_string(), b"{\"version\":1}".to_vec()),
+                (
+                    "invoices/2025/invoice_1.pdf".to_string(),
+                    vec![0, 1, 2, 255]
+                ),
+                ("empty.txt".to_string(), Vec::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn an_empty_archive_has_no_files() {
+        assert!(read_zip(&ZipWriter::new().finish().unwrap())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn truncated_archives_are_refused() {
+        let data = sample();
+        for len in [0, 4, 30, data.len() / 2, data.len() - 1] {
+            assert!(read_zip(&data[..len]).is_err(), "accepted {} bytes", len);
+        }
+        assert!(read_zip(b"not a zip at all").is_err());
+    }
+
+    #[test]
+    fn corrupted_contents_are_refused() {
+        let mut data = sample();
+        // The first file's contents follow its 30-byte header and name.
+        data[30 + "backup.json".len()] ^= 0x20;
+        assert_eq!(
+            read_zip(&data).unwrap_err(),
+            "backup.json is corrupted".to_string()
+        );
+    }
+
+    #[test]
+    fn compressed_files_are_refused() {
+        let mut data = sample();
+        let directory = data
+            .windows(4)
+            .position(|window| window == 0x0201_4b50u32.to_le_bytes())
+            .unwrap();
+        data[directory + 10] = 8;
+        assert!(read_zip(&data).unwrap_err().contains("compressed"));
+    }
+}
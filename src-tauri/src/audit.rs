@@ -0,0 +1,174 @@
+use rusqlite::{params, params_from_iter, types::Value as SqlValue, Connection};
+use serde::Serialize;
+
+use crate::{current_unix_timestamp, open_connection, resolve_db_path, TimeEntry};
+
+/// One row per change to a time entry. `old_values` and `new_values` are the
+/// entry as JSON before and after; a create has no old values and a delete
+/// no new ones. Rows outlive the entries they describe.
+pub const CREATE_ENTRY_AUDIT_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS entry_audit (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        entry_id INTEGER NOT NULL,
+        action TEXT NOT NULL,
+        source TEXT NOT NULL,
+        old_values TEXT,
+        new_values TEXT,
+        changed_at INTEGER NOT NULL
+    )
+"#;
+
+const DEFAULT_AUDIT_LIMIT: i64 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+}
+
+impl AuditAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+        }
+    }
+}
+
+/// Where a change came from: edits in the window, a timer stopped from the
+/// tray, a timer stopped anywhere else, or a recurring entry rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntrySource {
+    Manual,
+    Tray,
+    Timer,
+    Recurring,
+}
+
+impl EntrySource {
+    fn as_str(self) -> &'static str {
+        match self {
+            EntrySource::Manual => "manual",
+            EntrySource::Tray => "tray",
+            EntrySource::Timer => "timer",
+            EntrySource::Recurring => "recurring",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    pub id: i64,
+    pub entry_id: i64,
+    pub action: String,
+    pub source: String,
+    pub old_values: Option<serde_json::Value>,
+    pub new_values: Option<serde_json::Value>,
+    pub changed_at: i64,
+}
+
+/// Newest changes first, optionally for one entry and within
+/// `start_time..end_time` of when they were made.
+#[tauri::command]
+pub async fn get_entry_audit(
+    app_handle: tauri::AppHandle,
+    entry_id: Option<i64>,
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<AuditRecord>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut clauses: Vec<&str> = Vec::new();
+        let mut values: Vec<SqlValue> = Vec::new();
+        if let Some(entry_id) = entry_id {
+            clauses.push("entry_id = ?");
+            values.push(entry_id.into());
+        }
+        if let Some(start) = start_time {
+            clauses.push("changed_at >= ?");
+            values.push(start.into());
+        }
+        if let Some(end) = end_time {
+            clauses.push("changed_at < ?");
+            values.push(end.into());
+        }
+        let where_sql = if clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", clauses.join(" AND "))
+        };
+        values.push(limit.unwrap_or(DEFAULT_AUDIT_LIMIT).max(1).into());
+
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT id, entry_id, action, source, old_values, new_values, changed_at
+                 FROM entry_audit
+                 {where_sql}
+                 ORDER BY changed_at DESC, id DESC
+                 LIMIT ?"
+            ))
+            .map_err(|err| err.to_string())?;
+        let records = stmt
+            .query_map(params_from_iter(values), |row| {
+                let old_values: Option<String> = row.get(4)?;
+                let new_values: Option<String> = row.get(5)?;
+                Ok(AuditRecord {
+                    id: row.get(0)?,
+                    entry_id: row.get(1)?,
+                    action: row.get(2)?,
+                    source: row.get(3)?,
+                    old_values: old_values.and_then(|raw| serde_json::from_str(&raw).ok()),
+                    new_values: new_values.and_then(|raw| serde_json::from_str(&raw).ok()),
+                    changed_at: row.get(6)?,
+                })
+            })
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        Ok(records)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Logs one change on `conn`, so it commits or rolls back with the change
+/// itself when `conn` is a transaction.
+pub fn record_change(
+    conn: &Connection,
+    action: AuditAction,
+    source: EntrySource,
+    old: Option<&TimeEntry>,
+    new: Option<&TimeEntry>,
+) -> Result<(), String> {
+    let Some(entry_id) = new.or(old).map(|entry| entry.id) else {
+        return Ok(());
+    };
+    let old_values = old
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|err| err.to_string())?;
+    let new_values = new
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|err| err.to_string())?;
+    conn.execute(
+        "INSERT INTO entry_audit (entry_id, action, source, old_values, new_values, changed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            entry_id,
+            action.as_str(),
+            source.as_str(),
+            old_values,
+            new_values,
+            current_unix_timestamp()
+        ],
+    )
+    .map_err(|err| err.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,641 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Mutex,
+    },
+};
+
+use chrono::{Duration, Local, NaiveDateTime, TimeZone};
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    archive::{self, ZipWriter},
+    current_settings, current_unix_timestamp, open_connection, reload_active_timers,
+    resolve_db_path, resolve_invoices_dir, send_notification, undo, DB_FILE_NAME,
+};
+
+/// Identifies a ZIP as one of our backups, and which layout it has.
+pub const BACKUP_FORMAT: &str = "time-tracker-backup";
+pub const BACKUP_VERSION: u64 = 1;
+pub const BACKUP_MANIFEST: &str = "backup.json";
+/// Where the invoice PDFs sit inside the archive.
+pub const BACKUP_INVOICES_DIR: &str = "invoices";
+/// Backups are named after when they were taken, e.g.
+/// `time-tracker-backup-20250301-120000.zip`.
+const BACKUP_FILE_PREFIX: &str = "time-tracker-backup-";
+const BACKUP_FILE_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+/// Wait after a failed scheduled backup before trying again.
+const BACKUP_RETRY_SECONDS: i64 = 60 * 60;
+/// Tells apart the snapshots of backups taken at the same time.
+static SNAPSHOT_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// Backups taken in the background while the app runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSchedule {
+    pub folder: String,
+    #[serde(default)]
+    pub frequency: BackupFrequency,
+    /// Backups kept in the folder, oldest deleted first; unset keeps them
+    /// all. Backups made with `create_backup` into the folder count too.
+    pub keep: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupFrequency {
+    /// Once every local day.
+    #[default]
+    Daily,
+    /// Seven days after the last backup.
+    Weekly,
+}
+
+/// Whether a scheduled backup is being written and how the last one failed.
+#[derive(Default)]
+pub struct BackupState {
+    running: AtomicBool,
+    last_failure: Mutex<Option<(i64, String)>>,
+}
+
+/// Where scheduled backups stand.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupStatus {
+    /// Unset when scheduled backups are off.
+    pub schedule: Option<BackupSchedule>,
+    /// The newest backup in the scheduled folder.
+    pub last_backup_at: Option<i64>,
+    pub last_backup_path: Option<String>,
+    pub next_backup_at: Option<i64>,
+    pub backup_count: usize,
+    pub running: bool,
+    /// Why the last scheduled backup failed, until one succeeds.
+    pub last_error: Option<String>,
+}
+
+/// What a restore brought back and where the replaced data was kept.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreSummary {
+    /// When the restored backup was taken.
+    pub backup_created_at: i64,
+    pub invoice_files: usize,
+    /// The database as it was before the restore.
+    pub previous_database: String,
+    /// The invoice PDFs as they were, if there were any.
+    pub previous_invoices: Option<String>,
+}
+
+/// Saves the database and the invoice PDFs to a timestamped ZIP in the
+/// folder `path` and returns the archive's path. The database is copied
+/// with SQLite's backup API, so it is consistent even while the app writes
+/// to it.
+#[tauri::command]
+pub async fn create_backup(app_handle: tauri::AppHandle, path: String) -> Result<String, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let invoices_dir = resolve_invoices_dir(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let archive = write_backup(db_path, &invoices_dir, Path::new(&path))?;
+        archive
+            .to_str()
+            .map(str::to_string)
+            .ok_or_else(|| "Invalid file path".to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Replaces the database and invoice PDFs with those in a `create_backup`
+/// archive. The current ones aren't deleted but renamed with a
+/// `.before-restore-<time>` suffix next to where they were, and are put
+/// back if the restore fails partway. Invoice and estimate PDF paths are
+/// pointed at this install's invoices folder. Running timers are reloaded
+/// from the restored database.
+#[tauri::command]
+pub async fn restore_backup(
+    app_handle: tauri::AppHandle,
+    path: String,
+) -> Result<RestoreSummary, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let invoices_dir = resolve_invoices_dir(&app_handle)?;
+
+    let summary = tauri::async_runtime::spawn_blocking(move || {
+        let data = fs::read(&path).map_err(|e| format!("Failed to read the backup: {}", e))?;
+        let files = archive::read_zip(&data).map_err(|_| "The file isn't a time tracker backup")?;
+        let manifest = files
+            .iter()
+            .find(|(name, _)| name == BACKUP_MANIFEST)
+            .and_then(|(_, contents)| serde_json::from_slice::<Map<String, Value>>(contents).ok())
+            .filter(|manifest| {
+                manifest.get("format").and_then(Value::as_str) == Some(BACKUP_FORMAT)
+            })
+            .ok_or("The file isn't a time tracker backup")?;
+        match manifest.get("version").and_then(Value::as_u64) {
+            Some(version) if version <= BACKUP_VERSION => {}
+            Some(_) => return Err("The backup was made by a newer version of the app".into()),
+            None => return Err("The backup has no version".into()),
+        }
+        let database = files
+            .iter()
+            .find(|(name, _)| name == DB_FILE_NAME)
+            .map(|(_, contents)| contents)
+            .ok_or("The backup has no database")?;
+
+        // Checked in a file of its own before anything is replaced.
+        let restoring_path = db_path.with_extension("db.restoring");
+        fs::write(&restoring_path, database)
+            .map_err(|e| format!("Failed to write the restored database: {}", e))?;
+        if let Err(err) = check_database(&restoring_path) {
+            let _ = fs::remove_file(&restoring_path);
+            return Err(err);
+        }
+
+        let suffix = format!("before-restore-{}", Local::now().format("%Y%m%d-%H%M%S"));
+        let previous_database = db_path.with_extension(format!("db.{}", suffix));
+        if db_path.exists() {
+            fs::rename(&db_path, &previous_database)
+                .map_err(|e| format!("Failed to set the current database aside: {}", e))?;
+        }
+        if let Err(err) = fs::rename(&restoring_path, &db_path) {
+            let _ = fs::rename(&previous_database, &db_path);
+            return Err(format!("Failed to restore the database: {}", err));
+        }
+
+        let backup_invoices_dir = manifest.get("invoicesDir").and_then(Value::as_str);
+        let restored = restore_contents(
+            &db_path,
+            &invoices_dir,
+            &suffix,
+            &files,
+            backup_invoices_dir,
+        );
+        let (invoice_files, previous_invoices) = match restored {
+            Ok(restored) => restored,
+            Err(err) => {
+                let _ = fs::remove_file(&db_path);
+                let _ = fs::rename(&previous_database, &db_path);
+                return Err(err);
+            }
+        };
+
+        Ok::<_, String>(RestoreSummary {
+            backup_created_at: manifest
+                .get("createdAt")
+                .and_then(Value::as_i64)
+                .unwrap_or_default(),
+            invoice_files,
+            previous_database: previous_database.to_string_lossy().into_owned(),
+            previous_invoices: previous_invoices
+                .map(|previous| previous.to_string_lossy().into_owned()),
+        })
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    // Recorded changes and running timers belong to the replaced database.
+    undo::clear(&app_handle);
+    reload_active_timers(&app_handle)?;
+    Ok(summary)
+}
+
+/// Brings the restored database up to date, then swaps in the backup's
+/// invoice PDFs. Returns how many there were and where the current ones
+/// were set aside. If writing them fails the current ones are put back;
+/// putting the database back is left to the caller.
+fn restore_contents(
+    db_path: &Path,
+    invoices_dir: &Path,
+    suffix: &str,
+    files: &[(String, Vec<u8>)],
+    backup_invoices_dir: Option<&str>,
+) -> Result<(usize, Option<PathBuf>), String> {
+    // Brings a backup from an older version up to the current schema.
+    let mut conn = open_connection(db_path.to_path_buf())?;
+    relocate_file_paths(&mut conn, backup_invoices_dir, invoices_dir)?;
+    drop(conn);
+
+    let previous_invoices = if invoices_dir.exists() {
+        let previous = invoices_dir.with_file_name(format!("{}.{}", BACKUP_INVOICES_DIR, suffix));
+        fs::rename(invoices_dir, &previous)
+            .map_err(|e| format!("Failed to set the current invoices aside: {}", e))?;
+        Some(previous)
+    } else {
+        None
+    };
+    match write_invoice_files(invoices_dir, files) {
+        Ok(invoice_files) => Ok((invoice_files, previous_invoices)),
+        Err(err) => {
+            let _ = fs::remove_dir_all(invoices_dir);
+            if let Some(previous) = &previous_invoices {
+                let _ = fs::rename(previous, invoices_dir);
+            }
+            Err(err)
+        }
+    }
+}
+
+fn write_invoice_files(invoices_dir: &Path, files: &[(String, Vec<u8>)]) -> Result<usize, String> {
+    let mut invoice_files = 0;
+    for (name, contents) in files {
+        let Some(relative) = name.strip_prefix(&format!("{}/", BACKUP_INVOICES_DIR)) else {
+            continue;
+        };
+        let relative = Path::new(relative);
+        // Only plain names below the folder; nothing may land outside it.
+        if relative
+            .components()
+            .any(|part| !matches!(part, std::path::Component::Normal(_)))
+        {
+            continue;
+        }
+        let target = invoices_dir.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        fs::write(&target, contents).map_err(|e| format!("Failed to restore {}: {}", name, e))?;
+        invoice_files += 1;
+    }
+    Ok(invoice_files)
+}
+
+/// Points the stored invoice and estimate PDF paths, which name the backed-up
+/// install's folders, at the same files under `invoices_dir`. Paths outside
+/// the backup's invoices folder are left alone.
+fn relocate_file_paths(
+    conn: &mut Connection,
+    backup_invoices_dir: Option<&str>,
+    invoices_dir: &Path,
+) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|err| err.to_string())?;
+    for table in ["invoices", "estimates"] {
+        let stored: Vec<(i64, String)> = tx
+            .prepare(&format!("SELECT id, file_path FROM {table}"))
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect()
+            })
+            .map_err(|err| err.to_string())?;
+        for (id, file_path) in stored {
+            let Some(relocated) = relocated_path(&file_path, backup_invoices_dir, invoices_dir)
+            else {
+                continue;
+            };
+            tx.execute(
+                &format!("UPDATE {table} SET file_path = ?1 WHERE id = ?2"),
+                rusqlite::params![relocated.to_string_lossy(), id],
+            )
+            .map_err(|err| err.to_string())?;
+        }
+    }
+    tx.commit().map_err(|err| err.to_string())
+}
+
+/// `stored` moved under `invoices_dir`: the part after the backup's invoices
+/// folder, named in the manifest of newer backups and otherwise taken to be
+/// the last folder called `invoices`. Either separator is read, so a backup
+/// from Windows restores on macOS and the other way round.
+fn relocated_path(
+    stored: &str,
+    backup_invoices_dir: Option<&str>,
+    invoices_dir: &Path,
+) -> Option<PathBuf> {
+    let parts = |path: &str| -> Vec<String> {
+        path.split(['/', '\\'])
+            .filter(|part| !part.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let stored = parts(stored);
+    let relative = match backup_invoices_dir.map(parts) {
+        Some(dir) if !dir.is_empty() && stored.starts_with(&dir) => &stored[dir.len()..],
+        _ => {
+            let folder = stored
+                .iter()
+                .rposition(|part| part == BACKUP_INVOICES_DIR)?;
+            &stored[folder + 1..]
+        }
+    };
+    if relative.is_empty() || relative.iter().any(|part| part == "..") {
+        return None;
+    }
+    Some(
+        relative
+            .iter()
+            .fold(invoices_dir.to_path_buf(), |path, part| path.join(part)),
+    )
+}
+
+/// The restored file must be an intact SQLite database of this app.
+fn check_database(path: &Path) -> Result<(), String> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|_| "The backup's database can't be opened")?;
+    let integrity: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|_| "The backup's database is damaged")?;
+    if integrity != "ok" {
+        return Err("The backup's database is damaged".into());
+    }
+    let has_entries: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'time_entries'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|err| err.to_string())?;
+    if !has_entries {
+        return Err("The backup's database has no time entries table".into());
+    }
+    Ok(())
+}
+
+/// The last and next scheduled backup, and the error if the last one failed.
+#[tauri::command]
+pub async fn get_backup_status(app_handle: tauri::AppHandle) -> Result<BackupStatus, String> {
+    let schedule = current_settings(&app_handle)?.scheduled_backups;
+    let state = app_handle.state::<BackupState>();
+    let running = state.running.load(Ordering::SeqCst);
+    let last_error = state
+        .last_failure
+        .lock()
+        .expect("backup state poisoned")
+        .as_ref()
+        .map(|(_, err)| err.clone());
+
+    let backups = schedule
+        .as_ref()
+        .map(|schedule| list_backups(Path::new(schedule.folder.trim())))
+        .unwrap_or_default();
+    let last_backup = backups.last();
+    let next_backup_at = schedule.as_ref().map(|schedule| {
+        next_backup_at(
+            schedule.frequency,
+            last_backup.map(|(_, taken_at)| *taken_at),
+        )
+        .unwrap_or_else(current_unix_timestamp)
+    });
+    Ok(BackupStatus {
+        last_backup_at: last_backup.map(|(_, taken_at)| *taken_at),
+        last_backup_path: last_backup.map(|(path, _)| path.to_string_lossy().into_owned()),
+        next_backup_at,
+        backup_count: backups.len(),
+        schedule,
+        running,
+        last_error,
+    })
+}
+
+pub fn validate_schedule(schedule: &BackupSchedule) -> Result<(), String> {
+    if !Path::new(schedule.folder.trim()).is_absolute() {
+        return Err("Choose the folder backups are saved to".into());
+    }
+    if schedule.keep == Some(0) {
+        return Err("Keep at least one backup".into());
+    }
+    Ok(())
+}
+
+/// Called at launch and on every scheduler tick; writes a backup into the
+/// scheduled folder once one is due, then deletes the oldest ones past the
+/// number to keep. Failures are notified and retried an hour later.
+pub fn check_schedule(app: &AppHandle) {
+    let Ok(Some(schedule)) = current_settings(app).map(|settings| settings.scheduled_backups)
+    else {
+        return;
+    };
+    let state = app.state::<BackupState>();
+    let now = current_unix_timestamp();
+    let retry_pending = state
+        .last_failure
+        .lock()
+        .expect("backup state poisoned")
+        .as_ref()
+        .is_some_and(|(failed_at, _)| now - failed_at < BACKUP_RETRY_SECONDS);
+    if retry_pending || state.running.load(Ordering::SeqCst) {
+        return;
+    }
+    let folder = PathBuf::from(schedule.folder.trim());
+    let last_backup_at = list_backups(&folder).last().map(|(_, taken_at)| *taken_at);
+    if next_backup_at(schedule.frequency, last_backup_at).is_some_and(|due| due > now) {
+        return;
+    }
+    let (Ok(db_path), Ok(invoices_dir)) = (resolve_db_path(app), resolve_invoices_dir(app)) else {
+        return;
+    };
+    if state.running.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = write_backup(db_path, &invoices_dir, &folder)
+            .and_then(|_| prune_backups(&folder, schedule.keep));
+        let state = app.state::<BackupState>();
+        let mut last_failure = state.last_failure.lock().expect("backup state poisoned");
+        match result {
+            Ok(()) => *last_failure = None,
+            Err(err) => {
+                eprintln!("Scheduled backup failed: {}", err);
+                send_notification(&app, "Backup failed", &err);
+                *last_failure = Some((current_unix_timestamp(), err));
+            }
+        }
+        state.running.store(false, Ordering::SeqCst);
+    });
+}
+
+/// Local midnight on the day a backup after one taken at `last_backup_at`
+/// is due; `None` when there's no backup yet.
+fn next_backup_at(frequency: BackupFrequency, last_backup_at: Option<i64>) -> Option<i64> {
+    let last_day = Local
+        .timestamp_opt(last_backup_at?, 0)
+        .single()?
+        .date_naive();
+    let days = match frequency {
+        BackupFrequency::Daily => 1,
+        BackupFrequency::Weekly => 7,
+    };
+    let due = (last_day + Duration::days(days)).and_hms_opt(0, 0, 0)?;
+    Local
+        .from_local_datetime(&due)
+        .earliest()
+        .map(|due| due.timestamp())
+}
+
+/// The backups in `folder` with when each was taken, oldest first.
+fn list_backups(folder: &Path) -> Vec<(PathBuf, i64)> {
+    let Ok(read_dir) = fs::read_dir(folder) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<(PathBuf, i64)> = read_dir
+        .filter_map(|child| {
+            let path = child.ok()?.path();
+            let name = path.file_name()?.to_str()?;
+            let taken = name
+                .strip_prefix(BACKUP_FILE_PREFIX)?
+                .strip_suffix(".zip")?;
+            let taken = NaiveDateTime::parse_from_str(taken, BACKUP_FILE_TIME_FORMAT).ok()?;
+            let taken_at = Local.from_local_datetime(&taken).earliest()?.timestamp();
+            Some((path, taken_at))
+        })
+        .collect();
+    backups.sort_by_key(|(_, taken_at)| *taken_at);
+    backups
+}
+
+/// Deletes all but the newest `keep` backups in `folder`.
+fn prune_backups(folder: &Path, keep: Option<u32>) -> Result<(), String> {
+    let Some(keep) = keep else {
+        return Ok(());
+    };
+    let backups = list_backups(folder);
+    let excess = backups.len().saturating_sub(keep as usize);
+    for (path, _) in &backups[..excess] {
+        fs::remove_file(path).map_err(|e| format!("Failed to delete an old backup: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Writes a backup ZIP into `folder`, creating it if needed.
+pub fn write_backup(
+    db_path: PathBuf,
+    invoices_dir: &Path,
+    folder: &Path,
+) -> Result<PathBuf, String> {
+    fs::create_dir_all(folder).map_err(|e| format!("Failed to create the backup folder: {}", e))?;
+    let created_at = current_unix_timestamp();
+    let mut zip = ZipWriter::new();
+
+    let snapshot_path = std::env::temp_dir().join(format!(
+        "time_tracker_snapshot_{}_{}.db",
+        std::process::id(),
+        SNAPSHOT_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let snapshot = open_connection(db_path)
+        .and_then(|conn| {
+            conn.backup(DatabaseName::Main, &snapshot_path, None)
+                .map_err(|e| format!("Failed to snapshot the database: {}", e))
+        })
+        .and_then(|_| fs::read(&snapshot_path).map_err(|err| err.to_string()));
+    let _ = fs::remove_file(&snapshot_path);
+    zip.add_file(DB_FILE_NAME, &snapshot?)?;
+
+    let mut invoice_count = 0;
+    add_folder(
+        &mut zip,
+        invoices_dir,
+        BACKUP_INVOICES_DIR,
+        &mut invoice_count,
+    )?;
+
+    let manifest = json!({
+        "format": BACKUP_FORMAT,
+        "version": BACKUP_VERSION,
+        "createdAt": created_at,
+        "database": DB_FILE_NAME,
+        "invoiceFiles": invoice_count,
+        "invoicesDir": invoices_dir.to_string_lossy(),
+    });
+    zip.add_file(BACKUP_MANIFEST, manifest.to_string().as_bytes())?;
+
+    let archive = folder.join(format!(
+        "{}{}.zip",
+        BACKUP_FILE_PREFIX,
+        Local::now().format(BACKUP_FILE_TIME_FORMAT)
+    ));
+    fs::write(&archive, zip.finish()?).map_err(|e| format!("Failed to write the backup: {}", e))?;
+    Ok(archive)
+}
+
+/// Adds every file under `dir` at `prefix/...`, leaving out the previews,
+/// which are regenerated on demand.
+fn add_folder(
+    zip: &mut ZipWriter,
+    dir: &Path,
+    prefix: &str,
+    count: &mut usize,
+) -> Result<(), String> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+    let mut children: Vec<PathBuf> = read_dir
+        .filter_map(|child| child.ok().map(|child| child.path()))
+        .collect();
+    children.sort();
+    for child in children {
+        let Some(name) = child.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let archived_name = format!("{}/{}", prefix, name);
+        if child.is_dir() {
+            if name != "previews" {
+                add_folder(zip, &child, &archived_name, count)?;
+            }
+        } else {
+            let contents =
+                fs::read(&child).map_err(|e| format!("Failed to read {}: {}", name, e))?;
+            zip.add_file(&archived_name, &contents)?;
+            *count += 1;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_paths_move_under_this_installs_invoices_folder() {
+        let here = Path::new("/Users/sam/Library/time-tracker/invoices");
+        assert_eq!(
+            relocated_path(
+                "/home/alex/.local/share/tt/invoices/estimates/estimate_3.pdf",
+                Some("/home/alex/.local/share/tt/invoices"),
+                here,
+            ),
+            Some(here.join("estimates").join("estimate_3.pdf"))
+        );
+        // Backups from before the manifest named the folder.
+        assert_eq!(
+            relocated_path(
+                r"C:\Users\Alex\AppData\tt\invoices\invoice_7.pdf",
+                None,
+                here
+            ),
+            Some(here.join("invoice_7.pdf"))
+        );
+        assert_eq!(relocated_path("/tmp/elsewhere.pdf", None, here), None);
+        assert_eq!(relocated_path("/data/invoices/../x.pdf", None, here), None);
+    }
+
+    #[test]
+    fn a_restored_database_points_at_the_restored_pdfs() {
+        let path = std::env::temp_dir().join(format!("tt-restore-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut conn = open_connection(path).expect("test database");
+        conn.execute(
+            "INSERT INTO invoices (created_at, business_info, bill_to_info, total_hours,
+                                   total_amount, file_path, entry_count)
+             VALUES (0, '{}', '{}', 1, 100, '/old/invoices/invoice_1.pdf', 1)",
+            [],
+        )
+        .unwrap();
+
+        relocate_file_paths(&mut conn, Some("/old/invoices"), Path::new("/new/invoices")).unwrap();
+
+        let file_path: String = conn
+            .query_row("SELECT file_path FROM invoices", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(
+            Path::new(&file_path),
+            Path::new("/new/invoices").join("invoice_1.pdf")
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+
+use crate::clients::non_empty;
+
+/// EPC QR codes (the "GiroCode") only carry euro SEPA transfers.
+const EPC_CURRENCY: &str = "EUR";
+const EPC_MAX_NAME_CHARS: usize = 70;
+const EPC_MAX_REMITTANCE_CHARS: usize = 140;
+const EPC_MAX_AMOUNT: f64 = 999_999_999.99;
+
+/// Account details printed in an invoice's payment section. Every field is
+/// optional so domestic accounts without an IBAN can be given too.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankAccount {
+    pub account_holder: Option<String>,
+    pub bank_name: Option<String>,
+    pub iban: Option<String>,
+    pub bic: Option<String>,
+    /// Local account number, for accounts without an IBAN.
+    pub account_number: Option<String>,
+}
+
+impl BankAccount {
+    /// The IBAN in groups of four, as it's usually printed.
+    pub fn formatted_iban(&self) -> Option<String> {
+        let iban = self.iban.as_deref()?;
+        let groups: Vec<String> = iban
+            .as_bytes()
+            .chunks(4)
+            .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+            .collect();
+        Some(groups.join(" "))
+    }
+}
+
+/// The EPC069-12 payload for a SEPA credit transfer of `amount` to this
+/// account, or `None` when there's no IBAN, the currency isn't euro or the
+/// amount is out of range. `beneficiary` is used when no account holder is
+/// given.
+pub fn epc_qr_payload(
+    account: &BankAccount,
+    beneficiary: &str,
+    currency: &str,
+    amount: f64,
+    remittance: &str,
+) -> Option<String> {
+    let iban = account.iban.as_deref()?;
+    if !currency.eq_ignore_ascii_case(EPC_CURRENCY) || !(0.01..=EPC_MAX_AMOUNT).contains(&amount) {
+        return None;
+    }
+    let name: String = account
+        .account_holder
+        .as_deref()
+        .unwrap_or(beneficiary)
+        .chars()
+        .filter(|ch| *ch != '\n' && *ch != '\r')
+        .take(EPC_MAX_NAME_CHARS)
+        .collect();
+    if name.trim().is_empty() {
+        return None;
+    }
+    let remittance: String = remittance
+        .chars()
+        .filter(|ch| *ch != '\n' && *ch != '\r')
+        .take(EPC_MAX_REMITTANCE_CHARS)
+        .collect();
+    Some(
+        [
+            "BCD",
+            "002",
+            "1",
+            "SCT",
+            account.bic.as_deref().unwrap_or_default(),
+            name.trim(),
+            iban,
+            &format!("{}{:.2}", EPC_CURRENCY, amount),
+            "",
+            "",
+            remittance.trim(),
+        ]
+        .join("\n"),
+    )
+}
+
+/// Trims every field, normalises the IBAN and BIC to upper case without
+/// spaces and checks them. An account with nothing filled in becomes `None`.
+pub fn sanitize(account: Option<BankAccount>) -> Result<Option<BankAccount>, String> {
+    let Some(account) = account else {
+        return Ok(None);
+    };
+    let account = BankAccount {
+        account_holder: non_empty(account.account_holder),
+        bank_name: non_empty(account.bank_name),
+        iban: non_empty(account.iban).map(|iban| compact_upper(&iban)),
+        bic: non_empty(account.bic).map(|bic| compact_upper(&bic)),
+        account_number: non_empty(account.account_number),
+    };
+    if account == BankAccount::default() {
+        return Ok(None);
+    }
+    if let Some(iban) = account.iban.as_deref() {
+        if !valid_iban(iban) {
+            return Err(format!("\"{}\" isn't a valid IBAN", iban));
+        }
+    }
+    if let Some(bic) = account.bic.as_deref() {
+        if !valid_bic(bic) {
+            return Err(format!("\"{}\" isn't a valid BIC", bic));
+        }
+    }
+    Ok(Some(account))
+}
+
+fn compact_upper(value: &str) -> String {
+    value
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Country code, check digits and up to 30 letters or digits, passing the
+/// ISO 13616 mod-97 check.
+fn valid_iban(iban: &str) -> bool {
+    let bytes = iban.as_bytes();
+    if !(15..=34).contains(&bytes.len())
+        || !bytes[..2].iter().all(u8::is_ascii_uppercase)
+        || !bytes[2..4].iter().all(u8::is_ascii_digit)
+        || !bytes.iter().all(u8::is_ascii_alphanumeric)
+    {
+        return false;
+    }
+    let rearranged = bytes[4..].iter().chain(&bytes[..4]);
+    let mut remainder: u32 = 0;
+    for &byte in rearranged {
+        let value = if byte.is_ascii_digit() {
+            u32::from(byte - b'0')
+        } else {
+            u32::from(byte - b'A') + 10
+        };
+        let width = if value >= 10 { 100 } else { 10 };
+        remainder = (remainder * width + value) % 97;
+    }
+    remainder == 1
+}
+
+/// Four letters for the bank, two for the country, two letters or digits for
+/// the location and an optional three-character branch code.
+fn valid_bic(bic: &str) -> bool {
+    let bytes = bic.as_bytes();
+    (bytes.len() == 8 || bytes.len() == 11)
+        && bytes[..6].iter().all(u8::is_ascii_uppercase)
+        && bytes[6..].iter().all(u8::is_ascii_alphanumeric)
+}
@@ -0,0 +1,319 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::{current_unix_timestamp, open_connection, projects, resolve_db_path, send_notification};
+
+pub const BUDGET_ALERT_EVENT: &str = "project://budget-alert";
+
+/// Percent-of-budget levels that trigger an alert, lowest first.
+const ALERT_THRESHOLDS: [i64; 2] = [80, 100];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub project_name: String,
+    pub budget_hours: Option<f64>,
+    pub budget_amount: Option<f64>,
+    pub spent_hours: f64,
+    pub spent_amount: f64,
+    pub remaining_hours: Option<f64>,
+    pub remaining_amount: Option<f64>,
+    /// Highest usage across the hours and money budgets, as a percentage.
+    pub percent_used: Option<f64>,
+}
+
+/// Budget status plus the pace it is being spent at.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetBurn {
+    #[serde(flatten)]
+    pub status: BudgetStatus,
+    pub first_entry_at: Option<i64>,
+    /// Average spend per calendar day since the first entry.
+    pub daily_burn_hours: f64,
+    pub daily_burn_amount: f64,
+    /// When the first budget runs out at the current pace; `None` if it
+    /// already has or nothing is being spent.
+    pub projected_completion: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetAlertPayload {
+    pub threshold: i64,
+    pub status: BudgetStatus,
+}
+
+#[tauri::command]
+pub async fn set_project_budget(
+    app_handle: tauri::AppHandle,
+    project_name: String,
+    budget_hours: Option<f64>,
+    budget_amount: Option<f64>,
+) -> Result<BudgetStatus, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let name = crate::sanitize_project_name(project_name);
+    let budget_hours = sanitize_budget(budget_hours)?;
+    let budget_amount = sanitize_budget(budget_amount)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        apply_budget(&conn, &name, budget_hours, budget_amount)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Sets the project's budgets, creating the project if needed, and re-arms
+/// alerts against them. Budgets are expected to be sanitized already.
+pub fn apply_budget(
+    conn: &Connection,
+    project_name: &str,
+    budget_hours: Option<f64>,
+    budget_amount: Option<f64>,
+) -> Result<BudgetStatus, String> {
+    let project_id = projects::ensure_project(conn, project_name)?;
+    conn.execute(
+        "UPDATE projects SET budget_hours = ?1, budget_amount = ?2 WHERE id = ?3",
+        params![budget_hours, budget_amount, project_id],
+    )
+    .map_err(|err| err.to_string())?;
+    // Re-arm alerts against the new budget.
+    let status = query_budget_status(conn, project_name)?;
+    store_alert_level(conn, project_name, crossed_threshold(&status))?;
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn get_project_budget_status(
+    app_handle: tauri::AppHandle,
+    project_name: String,
+) -> Result<BudgetStatus, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let name = crate::sanitize_project_name(project_name);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_budget_status(&conn, &name)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Burn rate for every active project with an hours or money budget:
+/// exhausted budgets first, then the ones projected to run out soonest.
+#[tauri::command]
+pub async fn get_budget_burn_report(app_handle: tauri::AppHandle) -> Result<Vec<BudgetBurn>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let now = current_unix_timestamp();
+        let mut stmt = conn
+            .prepare(
+                "SELECT p.name, p.budget_hours, p.budget_amount, MIN(e.start_time),
+                        COALESCE(SUM(e.duration), 0), COALESCE(SUM(e.amount), 0)
+                 FROM projects p
+                 LEFT JOIN time_entries e ON e.project_name = p.name AND e.entry_type = 'work'
+                 WHERE p.archived = 0
+                   AND (p.budget_hours IS NOT NULL OR p.budget_amount IS NOT NULL)
+                 GROUP BY p.id",
+            )
+            .map_err(|err| err.to_string())?;
+        let mut report = stmt
+            .query_map([], |row| {
+                let status = build_budget_status(
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get::<_, i64>(4)? as f64 / 3600.0,
+                    row.get(5)?,
+                );
+                Ok(build_budget_burn(status, row.get(3)?, now))
+            })
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        report.sort_by_key(|burn| match burn.projected_completion {
+            Some(at) => at,
+            None if burn.status.percent_used.is_some_and(|percent| percent >= 100.0) => i64::MIN,
+            None => i64::MAX,
+        });
+        Ok(report)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+fn build_budget_burn(status: BudgetStatus, first_entry_at: Option<i64>, now: i64) -> BudgetBurn {
+    const DAY: i64 = 86_400;
+    // Count the first day as a whole day so a fresh project isn't divided by ~0.
+    let days = first_entry_at
+        .map(|first| ((now - first).max(0) / DAY + 1) as f64)
+        .unwrap_or(1.0);
+    let daily_burn_hours = status.spent_hours / days;
+    let daily_burn_amount = status.spent_amount / days;
+
+    let days_left = |remaining: Option<f64>, burn: f64| {
+        remaining.filter(|left| *left > 0.0 && burn > 0.0).map(|left| left / burn)
+    };
+    let exhausted = [status.remaining_hours, status.remaining_amount]
+        .iter()
+        .any(|remaining| remaining.is_some_and(|left| left <= 0.0));
+    let projected_completion = if exhausted {
+        None
+    } else {
+        [
+            days_left(status.remaining_hours, daily_burn_hours),
+            days_left(status.remaining_amount, daily_burn_amount),
+        ]
+        .into_iter()
+        .flatten()
+        .reduce(f64::min)
+        .map(|days| now + (days * DAY as f64) as i64)
+    };
+
+    BudgetBurn {
+        status,
+        first_entry_at,
+        daily_burn_hours,
+        daily_burn_amount,
+        projected_completion,
+    }
+}
+
+/// Emits an alert (event + notification) the first time a project's tracked
+/// totals cross one of the budget thresholds. Errors are swallowed since the
+/// alert is advisory and the entry has already been saved.
+pub fn check_budget_alerts(app: &AppHandle, project_name: &str) {
+    let Ok(conn) = resolve_db_path(app).and_then(open_connection) else {
+        return;
+    };
+    let Ok(status) = query_budget_status(&conn, project_name) else {
+        return;
+    };
+    let crossed = crossed_threshold(&status);
+    let previous = stored_alert_level(&conn, project_name).unwrap_or(0);
+    if crossed == previous {
+        return;
+    }
+    let _ = store_alert_level(&conn, project_name, crossed);
+    if crossed < previous {
+        // Budget was raised or entries were removed; re-arm silently.
+        return;
+    }
+
+    let _ = app.emit(
+        BUDGET_ALERT_EVENT,
+        BudgetAlertPayload {
+            threshold: crossed,
+            status: status.clone(),
+        },
+    );
+    let body = if crossed >= 100 {
+        format!("{} is over budget.", status.project_name)
+    } else {
+        format!("{} has used {}% of its budget.", status.project_name, crossed)
+    };
+    send_notification(app, "Project budget", &body);
+}
+
+pub fn query_budget_status(conn: &Connection, project_name: &str) -> Result<BudgetStatus, String> {
+    let (budget_hours, budget_amount) = conn
+        .query_row(
+            "SELECT budget_hours, budget_amount FROM projects WHERE name = ?1",
+            params![project_name],
+            |row| Ok((row.get::<_, Option<f64>>(0)?, row.get::<_, Option<f64>>(1)?)),
+        )
+        .optional()
+        .map_err(|err| err.to_string())?
+        .unwrap_or((None, None));
+
+    let (spent_seconds, spent_amount) = conn
+        .query_row(
+            "SELECT COALESCE(SUM(duration), 0), COALESCE(SUM(amount), 0)
+             FROM time_entries
+             WHERE project_name = ?1 AND entry_type = 'work'",
+            params![project_name],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?)),
+        )
+        .map_err(|err| err.to_string())?;
+
+    Ok(build_budget_status(
+        project_name.to_string(),
+        budget_hours,
+        budget_amount,
+        spent_seconds as f64 / 3600.0,
+        spent_amount,
+    ))
+}
+
+pub fn build_budget_status(
+    project_name: String,
+    budget_hours: Option<f64>,
+    budget_amount: Option<f64>,
+    spent_hours: f64,
+    spent_amount: f64,
+) -> BudgetStatus {
+    let ratio = |spent: f64, budget: Option<f64>| {
+        budget.filter(|budget| *budget > 0.0).map(|budget| spent / budget * 100.0)
+    };
+    let percent_used = match (ratio(spent_hours, budget_hours), ratio(spent_amount, budget_amount)) {
+        (Some(hours), Some(amount)) => Some(hours.max(amount)),
+        (hours, amount) => hours.or(amount),
+    };
+
+    BudgetStatus {
+        project_name,
+        budget_hours,
+        budget_amount,
+        spent_hours,
+        spent_amount,
+        remaining_hours: budget_hours.map(|budget| budget - spent_hours),
+        remaining_amount: budget_amount.map(|budget| budget - spent_amount),
+        percent_used,
+    }
+}
+
+fn crossed_threshold(status: &BudgetStatus) -> i64 {
+    let Some(percent) = status.percent_used else {
+        return 0;
+    };
+    ALERT_THRESHOLDS
+        .iter()
+        .copied()
+        .filter(|threshold| percent >= *threshold as f64)
+        .max()
+        .unwrap_or(0)
+}
+
+fn stored_alert_level(conn: &Connection, project_name: &str) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT budget_alert_level FROM projects WHERE name = ?1",
+        params![project_name],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(|level| level.unwrap_or(0))
+    .map_err(|err| err.to_string())
+}
+
+fn store_alert_level(conn: &Connection, project_name: &str, level: i64) -> Result<(), String> {
+    conn.execute(
+        "UPDATE projects SET budget_alert_level = ?1 WHERE name = ?2",
+        params![level, project_name],
+    )
+    .map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+fn sanitize_budget(value: Option<f64>) -> Result<Option<f64>, String> {
+    match value {
+        Some(value) if !value.is_finite() || value < 0.0 => {
+            Err("Budget must be a non-negative number".into())
+        }
+        Some(0.0) => Ok(None),
+        other => Ok(other),
+    }
+}
@@ -0,0 +1,244 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    current_unix_timestamp, invoice_labels::InvoiceLanguage, open_connection, resolve_db_path,
+    sanitize_hourly_rate,
+};
+
+pub const CREATE_CLIENTS_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS clients (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        address TEXT,
+        email TEXT,
+        default_rate REAL NOT NULL DEFAULT 0,
+        currency TEXT NOT NULL DEFAULT 'USD',
+        created_at INTEGER NOT NULL,
+        invoice_terms TEXT,
+        invoice_language TEXT
+    )
+"#;
+
+const CLIENT_COLUMNS: &str =
+    "id, name, address, email, default_rate, currency, created_at, invoice_terms, invoice_language";
+const DEFAULT_CURRENCY: &str = "USD";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Client {
+    pub id: i64,
+    pub name: String,
+    pub address: Option<String>,
+    pub email: Option<String>,
+    pub default_rate: f64,
+    pub currency: String,
+    pub created_at: i64,
+    /// Printed on every invoice billed to this client (payment terms, PO number).
+    pub invoice_terms: Option<String>,
+    /// Language of invoices billed to this client; English when unset.
+    pub invoice_language: Option<InvoiceLanguage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientInput {
+    pub name: String,
+    pub address: Option<String>,
+    pub email: Option<String>,
+    pub default_rate: Option<f64>,
+    pub currency: Option<String>,
+    pub invoice_terms: Option<String>,
+    pub invoice_language: Option<InvoiceLanguage>,
+}
+
+#[tauri::command]
+pub async fn list_clients(app_handle: tauri::AppHandle) -> Result<Vec<Client>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {CLIENT_COLUMNS} FROM clients ORDER BY name COLLATE NOCASE ASC"
+            ))
+            .map_err(|err| err.to_string())?;
+        let clients = stmt
+            .query_map([], map_client)
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        Ok(clients)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn create_client(
+    app_handle: tauri::AppHandle,
+    client: ClientInput,
+) -> Result<Client, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let input = sanitize_client_input(client)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        conn.execute(
+            "INSERT INTO clients (name, address, email, default_rate, currency, created_at, invoice_terms, invoice_language)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                input.name,
+                input.address,
+                input.email,
+                input.default_rate,
+                input.currency,
+                current_unix_timestamp(),
+                input.invoice_terms,
+                input.invoice_language
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+        fetch_client(&conn, conn.last_insert_rowid())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn update_client(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    client: ClientInput,
+) -> Result<Client, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let input = sanitize_client_input(client)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let changed = conn
+            .execute(
+                "UPDATE clients
+                 SET name = ?1, address = ?2, email = ?3, default_rate = ?4, currency = ?5,
+                     invoice_terms = ?6, invoice_language = ?7
+                 WHERE id = ?8",
+                params![
+                    input.name,
+                    input.address,
+                    input.email,
+                    input.default_rate,
+                    input.currency,
+                    input.invoice_terms,
+                    input.invoice_language,
+                    id
+                ],
+            )
+            .map_err(|err| err.to_string())?;
+        if changed == 0 {
+            return Err("Client not found".into());
+        }
+        fetch_client(&conn, id)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Deletes the client and its retainers and detaches its projects. Invoices
+/// keep the id so the historical record of who was billed is not rewritten.
+#[tauri::command]
+pub async fn delete_client(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut conn = open_connection(db_path)?;
+        let tx = conn.transaction().map_err(|err| err.to_string())?;
+        tx.execute(
+            "UPDATE projects SET client_id = NULL WHERE client_id = ?1",
+            params![id],
+        )
+        .map_err(|err| err.to_string())?;
+        tx.execute("DELETE FROM retainers WHERE client_id = ?1", params![id])
+            .map_err(|err| err.to_string())?;
+        tx.execute("DELETE FROM clients WHERE id = ?1", params![id])
+            .map_err(|err| err.to_string())?;
+        tx.commit().map_err(|err| err.to_string())?;
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub fn fetch_client(conn: &Connection, id: i64) -> Result<Client, String> {
+    conn.query_row(
+        &format!("SELECT {CLIENT_COLUMNS} FROM clients WHERE id = ?1"),
+        params![id],
+        map_client,
+    )
+    .map_err(|err| match err {
+        rusqlite::Error::QueryReturnedNoRows => "Client not found".to_string(),
+        other => other.to_string(),
+    })
+}
+
+fn map_client(row: &rusqlite::Row) -> rusqlite::Result<Client> {
+    Ok(Client {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        address: row.get(2)?,
+        email: row.get(3)?,
+        default_rate: row.get(4)?,
+        currency: row.get(5)?,
+        created_at: row.get(6)?,
+        invoice_terms: row.get(7)?,
+        invoice_language: row
+            .get::<_, Option<String>>(8)?
+            .as_deref()
+            .and_then(InvoiceLanguage::parse),
+    })
+}
+
+struct SanitizedClient {
+    name: String,
+    address: Option<String>,
+    email: Option<String>,
+    default_rate: f64,
+    currency: String,
+    invoice_terms: Option<String>,
+    invoice_language: Option<&'static str>,
+}
+
+fn sanitize_client_input(input: ClientInput) -> Result<SanitizedClient, String> {
+    let name = input.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Client name is required".into());
+    }
+
+    let currency = match input.currency {
+        Some(code) => sanitize_currency_code(&code)?,
+        None => DEFAULT_CURRENCY.to_string(),
+    };
+
+    Ok(SanitizedClient {
+        name,
+        address: non_empty(input.address),
+        email: non_empty(input.email),
+        default_rate: sanitize_hourly_rate(input.default_rate.unwrap_or(0.0)),
+        currency,
+        invoice_terms: non_empty(input.invoice_terms),
+        invoice_language: input.invoice_language.map(InvoiceLanguage::as_str),
+    })
+}
+
+pub fn sanitize_currency_code(code: &str) -> Result<String, String> {
+    let code = code.trim().to_ascii_uppercase();
+    if code.len() != 3 || !code.chars().all(|ch| ch.is_ascii_alphabetic()) {
+        return Err("Currency must be a three-letter ISO code".into());
+    }
+    Ok(code)
+}
+
+pub fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
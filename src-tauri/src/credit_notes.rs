@@ -0,0 +1,209 @@
+use std::fs;
+
+use rusqlite::{params, Connection, TransactionBehavior};
+
+use crate::{
+    current_unix_timestamp, load_stored_invoice, open_connection, pdf_generator,
+    render_invoice_pdf, resolve_db_path, resolve_invoices_dir, Invoice, InvoiceRender,
+    INVOICE_STATUS_ISSUED, INVOICE_TYPE_CREDIT_NOTE, INVOICE_TYPE_INVOICE, MAX_NOTES_LENGTH,
+};
+
+/// Issues a credit note for `amount` of a saved invoice, printed as a negative
+/// total that refers back to the invoice. Credit notes are stored alongside
+/// invoices; together they can't credit more than the invoice's total. The
+/// invoice's entries stay marked as invoiced.
+#[tauri::command]
+pub async fn create_credit_note(
+    app_handle: tauri::AppHandle,
+    invoice_id: i64,
+    amount: f64,
+    reason: String,
+) -> Result<Invoice, String> {
+    if !amount.is_finite() || amount <= 0.0 {
+        return Err("The credited amount must be greater than zero".to_string());
+    }
+    let reason = reason.trim().to_string();
+    if reason.is_empty() {
+        return Err("A credit note needs a reason".to_string());
+    }
+    if reason.chars().count() > MAX_NOTES_LENGTH {
+        return Err(format!(
+            "The reason must be at most {} characters",
+            MAX_NOTES_LENGTH
+        ));
+    }
+    let amount = (amount * 100.0).round() / 100.0;
+    let db_path = resolve_db_path(&app_handle)?;
+    let invoices_dir = resolve_invoices_dir(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut conn = open_connection(db_path)?;
+        // Taken before the remaining credit is read, so two credit notes for
+        // the same invoice can't both pass the check.
+        let tx = conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .map_err(|err| err.to_string())?;
+        let (invoice, original) = load_stored_invoice(&tx, invoice_id)?;
+        if invoice.invoice_type != INVOICE_TYPE_INVOICE {
+            return Err("Only invoices can be credited".to_string());
+        }
+        let remaining = remaining_credit(&tx, &invoice)?;
+        if amount > remaining + 0.005 {
+            return Err(format!(
+                "Only {:.2} {} of this invoice is left to credit",
+                remaining.max(0.0),
+                invoice.currency
+            ));
+        }
+
+        let created_at = current_unix_timestamp();
+        let line_items = vec![pdf_generator::InvoiceLineItem {
+            date: None,
+            description: reason,
+            quantity: 1.0,
+            unit_price: -amount,
+            amount: -amount,
+            fixed_fee: true,
+        }];
+        let line_items_json = serde_json::to_string(&line_items)
+            .map_err(|e| format!("Failed to serialize line items: {}", e))?;
+        let mut business_info = original.business_info;
+        // The invoice's own notes and terms don't apply to the credit.
+        business_info.notes = None;
+        business_info.terms = None;
+        let business_info_json = serde_json::to_string(&business_info)
+            .map_err(|e| format!("Failed to serialize business info: {}", e))?;
+        let language = original.language;
+
+        tx.execute(
+            "INSERT INTO invoices (created_at, business_info, bill_to_info, total_hours, total_amount, file_path, entry_count, client_id, currency, line_items, language, invoice_type, credited_invoice_id)
+             VALUES (?1, ?2, ?3, 0, ?4, '', 0, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                created_at,
+                business_info_json,
+                invoice.bill_to_info,
+                -amount,
+                invoice.client_id,
+                invoice.currency,
+                line_items_json,
+                language.as_str(),
+                INVOICE_TYPE_CREDIT_NOTE,
+                invoice_id
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+
+        // Named by id, like invoices, so credit notes issued within the same
+        // second don't share a file.
+        let id = tx.last_insert_rowid();
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        let output_path_str = invoices_dir
+            .join(format!("credit_note_{}_{}.pdf", timestamp, id))
+            .to_str()
+            .ok_or("Invalid file path")?
+            .to_string();
+        tx.execute(
+            "UPDATE invoices SET file_path = ?1 WHERE id = ?2",
+            params![output_path_str, id],
+        )
+        .map_err(|err| err.to_string())?;
+
+        let render = InvoiceRender {
+            id: Some(id),
+            business_info,
+            entries: Vec::new(),
+            line_items,
+            period: None,
+            currency: original.currency,
+            client_id: original.client_id,
+            language,
+            due_date: None,
+            payment_terms: None,
+            issued_at: created_at,
+            preview: false,
+            kind: pdf_generator::DocumentKind::CreditNote {
+                invoice_id,
+                invoice_issued_at: invoice.created_at,
+            },
+            timesheet: false,
+            rounding: None,
+        };
+        if let Err(err) = render_invoice_pdf(&app_handle, render, &output_path_str) {
+            let _ = fs::remove_file(&output_path_str);
+            return Err(err);
+        }
+        tx.commit().map_err(|err| err.to_string())?;
+
+        Ok(Invoice {
+            id,
+            created_at,
+            business_info: business_info_json,
+            bill_to_info: invoice.bill_to_info,
+            total_hours: 0.0,
+            total_amount: -amount,
+            file_path: output_path_str,
+            entry_count: 0,
+            client_id: invoice.client_id,
+            currency: invoice.currency,
+            due_date: None,
+            payment_terms: None,
+            invoice_type: INVOICE_TYPE_CREDIT_NOTE.to_string(),
+            credited_invoice_id: Some(invoice_id),
+            status: INVOICE_STATUS_ISSUED.to_string(),
+            sent_at: None,
+            paid_at: None,
+        })
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// What's left to credit of `invoice`: its total less the credit notes
+/// already issued for it, which are stored with negative totals.
+fn remaining_credit(conn: &Connection, invoice: &Invoice) -> Result<f64, String> {
+    let credited: f64 = conn
+        .query_row(
+            "SELECT COALESCE(SUM(total_amount), 0) FROM invoices WHERE credited_invoice_id = ?1",
+            params![invoice.id],
+            |row| row.get(0),
+        )
+        .map_err(|err| err.to_string())?;
+    Ok(invoice.total_amount + credited)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{map_invoice, INVOICE_COLUMNS};
+
+    fn insert(conn: &Connection, total: f64, credited_invoice_id: Option<i64>) -> Invoice {
+        conn.execute(
+            "INSERT INTO invoices (created_at, business_info, bill_to_info, total_hours, total_amount, file_path, entry_count, credited_invoice_id)
+             VALUES (0, '{}', '{}', 0, ?1, '', 0, ?2)",
+            params![total, credited_invoice_id],
+        )
+        .expect("insert invoice");
+        conn.query_row(
+            &format!("SELECT {INVOICE_COLUMNS} FROM invoices WHERE id = ?1"),
+            params![conn.last_insert_rowid()],
+            map_invoice,
+        )
+        .expect("fetch invoice")
+    }
+
+    #[test]
+    fn earlier_credit_notes_reduce_what_is_left() {
+        let path = std::env::temp_dir().join(format!("tt-credit-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let conn = open_connection(path).expect("test database");
+        let invoice = insert(&conn, 100.0, None);
+        let other = insert(&conn, 80.0, None);
+        assert!((remaining_credit(&conn, &invoice).unwrap() - 100.0).abs() < 1e-9);
+
+        insert(&conn, -30.0, Some(invoice.id));
+        insert(&conn, -20.5, Some(invoice.id));
+        insert(&conn, -80.0, Some(other.id));
+        assert!((remaining_credit(&conn, &invoice).unwrap() - 49.5).abs() < 1e-9);
+        assert!(remaining_credit(&conn, &other).unwrap().abs() < 1e-9);
+    }
+}
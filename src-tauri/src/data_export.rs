@@ -0,0 +1,334 @@
+use std::fs;
+
+use rusqlite::{params_from_iter, types::Value as SqlValue, Connection};
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+use crate::{current_unix_timestamp, open_connection, resolve_db_path, settings, undo};
+
+/// Identifies the file as ours, whatever it was renamed to.
+const EXPORT_FORMAT: &str = "time-tracker-data";
+/// Bumped when a change to the layout would make older versions misread it.
+/// Added columns don't need it: unknown ones are skipped on import and
+/// missing ones take their defaults.
+const EXPORT_VERSION: u64 = 1;
+
+/// Exported tables under the key each is written as, in an order where
+/// every table comes after the ones it refers to.
+const EXPORTED_TABLES: [(&str, &str); 4] = [
+    ("clients", "clients"),
+    ("projects", "projects"),
+    ("invoices", "invoices"),
+    ("timeEntries", "time_entries"),
+];
+
+/// Tables that refer to the exported ones by id or project name. They are
+/// replaced along with those tables, and cleared when the file doesn't have
+/// them, so nothing is left pointing at a replaced row, such as a recurring
+/// invoice billing a client whose id now belongs to another.
+const DEPENDENT_TABLES: [(&str, &str); 9] = [
+    ("billToProfiles", "bill_to_profiles"),
+    ("businessProfiles", "business_profiles"),
+    ("retainers", "retainers"),
+    ("estimates", "estimates"),
+    ("entryTemplates", "entry_templates"),
+    ("recurringEntries", "recurring_entries"),
+    ("recurringEntryRuns", "recurring_entry_runs"),
+    ("recurringInvoices", "recurring_invoices"),
+    ("invoiceEntries", "invoice_entries"),
+];
+
+/// Rows keyed by time entry id, with the part of each table that belongs to
+/// saved entries. They are replaced whenever the entries are, so they can't
+/// end up on an imported entry that reuses an id; segments of running
+/// timers are left alone.
+const ENTRY_TABLES: [(&str, &str, &str); 2] = [
+    (
+        "timeEntrySegments",
+        "time_entry_segments",
+        "entry_id IS NOT NULL",
+    ),
+    ("entryAudit", "entry_audit", "1 = 1"),
+];
+
+/// How many rows of each kind were exported or imported.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCounts {
+    pub clients: usize,
+    pub projects: usize,
+    pub invoices: usize,
+    pub time_entries: usize,
+    pub settings: usize,
+}
+
+impl DataCounts {
+    fn set(&mut self, table: &str, count: usize) {
+        match table {
+            "clients" => self.clients = count,
+            "projects" => self.projects = count,
+            "invoices" => self.invoices = count,
+            "time_entries" => self.time_entries = count,
+            _ => {}
+        }
+    }
+}
+
+/// Writes clients, projects, time entries with their segments and audit
+/// history, invoice records (not their PDFs), the profiles, retainers,
+/// estimates, templates and recurring rules that refer to them, and settings
+/// to `path` as JSON, one object per row with the database's column names.
+/// Settings are written without the mail password and signing passphrase.
+#[tauri::command]
+pub async fn export_all_data(
+    app_handle: tauri::AppHandle,
+    path: String,
+) -> Result<DataCounts, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut counts = DataCounts::default();
+        let mut document = Map::new();
+        document.insert("format".into(), EXPORT_FORMAT.into());
+        document.insert("version".into(), EXPORT_VERSION.into());
+        document.insert("exportedAt".into(), current_unix_timestamp().into());
+
+        let settings = read_settings(&conn)?;
+        counts.settings = settings.len();
+        document.insert("settings".into(), Value::Object(settings));
+        for (key, table) in EXPORTED_TABLES.into_iter().chain(DEPENDENT_TABLES) {
+            let rows = read_table(&conn, table, "1 = 1")?;
+            counts.set(table, rows.len());
+            document.insert(key.into(), Value::Array(rows));
+        }
+        for (key, table, scope) in ENTRY_TABLES {
+            let rows = read_table(&conn, table, scope)?;
+            document.insert(key.into(), Value::Array(rows));
+        }
+
+        let json = serde_json::to_string_pretty(&Value::Object(document))
+            .map_err(|err| err.to_string())?;
+        fs::write(&path, json).map_err(|e| format!("Failed to write the export: {}", e))?;
+        Ok(counts)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Replaces clients, projects, time entries, invoice records and settings
+/// with those in an `export_all_data` file, keeping their ids so references
+/// between them hold. Replacing any of them also replaces the tables that
+/// refer to them, and replacing the entries their segments and audit
+/// history; those missing from the file are cleared. The mail password and
+/// signing passphrase stored here are kept. Nothing changes if any row fails
+/// to import.
+#[tauri::command]
+pub async fn import_all_data(
+    app_handle: tauri::AppHandle,
+    path: String,
+) -> Result<DataCounts, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read the file: {}", e))?;
+    let document: Map<String, Value> =
+        serde_json::from_str(&json).map_err(|_| "The file isn't a time tracker export")?;
+    if document.get("format").and_then(Value::as_str) != Some(EXPORT_FORMAT) {
+        return Err("The file isn't a time tracker export".into());
+    }
+    match document.get("version").and_then(Value::as_u64) {
+        Some(version) if version <= EXPORT_VERSION => {}
+        Some(_) => return Err("The export was made by a newer version of the app".into()),
+        None => return Err("The export has no version".into()),
+    }
+
+    let counts = tauri::async_runtime::spawn_blocking(move || {
+        let mut conn = open_connection(db_path)?;
+        let tx = conn.transaction().map_err(|err| err.to_string())?;
+        let mut counts = DataCounts::default();
+        let current_settings = settings::load_settings(&tx)?;
+
+        if let Some(settings) = document.get("settings") {
+            let settings = settings.as_object().ok_or("Settings must be an object")?;
+            tx.execute("DELETE FROM settings", [])
+                .map_err(|err| err.to_string())?;
+            // Stored as-is; values that don't parse fall back to defaults
+            // when settings are loaded.
+            for (key, value) in settings {
+                tx.execute(
+                    "INSERT INTO settings (key, value) VALUES (?1, ?2)",
+                    [key, &value.to_string()],
+                )
+                .map_err(|err| err.to_string())?;
+            }
+            counts.settings = settings.len();
+            settings::restore_secrets(&tx, &current_settings)?;
+        }
+        for (key, table) in EXPORTED_TABLES {
+            let Some(rows) = document.get(key) else {
+                continue;
+            };
+            let rows = rows
+                .as_array()
+                .ok_or_else(|| format!("{} must be a list", key))?;
+            replace_table(&tx, table, "1 = 1", rows)?;
+            counts.set(table, rows.len());
+        }
+        let mut dependents = Vec::new();
+        if EXPORTED_TABLES
+            .iter()
+            .any(|(key, _)| document.contains_key(*key))
+        {
+            dependents.extend(DEPENDENT_TABLES.map(|(key, table)| (key, table, "1 = 1")));
+        }
+        if document.contains_key("timeEntries") {
+            dependents.extend(ENTRY_TABLES);
+        }
+        for (key, table, scope) in dependents {
+            let rows = match document.get(key) {
+                Some(rows) => rows
+                    .as_array()
+                    .ok_or_else(|| format!("{} must be a list", key))?,
+                None => &Vec::new(),
+            };
+            replace_table(&tx, table, scope, rows)?;
+        }
+
+        tx.commit().map_err(|err| err.to_string())?;
+        Ok::<_, String>(counts)
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    // Recorded changes refer to entries that no longer exist.
+    undo::clear(&app_handle);
+    Ok(counts)
+}
+
+/// Settings keyed by name, with their stored JSON values less any secrets.
+fn read_settings(conn: &Connection) -> Result<Map<String, Value>, String> {
+    let mut stmt = conn
+        .prepare("SELECT key, value FROM settings ORDER BY key")
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|err| err.to_string())?;
+    let mut settings = Map::new();
+    for row in rows {
+        let (key, raw) = row.map_err(|err| err.to_string())?;
+        let mut value = serde_json::from_str(&raw).unwrap_or(Value::String(raw));
+        for (setting, field) in settings::SECRET_FIELDS {
+            if key == setting {
+                if let Some(object) = value.as_object_mut() {
+                    object.remove(field);
+                }
+            }
+        }
+        settings.insert(key, value);
+    }
+    Ok(settings)
+}
+
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({})", table))
+        .map_err(|err| err.to_string())?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+    Ok(columns)
+}
+
+/// The rows of `table` matching the SQL condition `scope`.
+fn read_table(conn: &Connection, table: &str, scope: &str) -> Result<Vec<Value>, String> {
+    let columns = table_columns(conn, table)?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM {} WHERE {} ORDER BY rowid",
+            columns.join(", "),
+            table,
+            scope
+        ))
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            let mut object = Map::new();
+            for (index, column) in columns.iter().enumerate() {
+                let value = match row.get::<_, SqlValue>(index)? {
+                    SqlValue::Null | SqlValue::Blob(_) => Value::Null,
+                    SqlValue::Integer(number) => number.into(),
+                    SqlValue::Real(number) => {
+                        Number::from_f64(number).map_or(Value::Null, Value::Number)
+                    }
+                    SqlValue::Text(text) => text.into(),
+                };
+                object.insert(column.clone(), value);
+            }
+            Ok(Value::Object(object))
+        })
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+    Ok(rows)
+}
+
+/// Swaps the table's rows matching `scope` for `rows`. Fields that aren't
+/// columns of the table are ignored; columns a row leaves out take their
+/// defaults.
+fn replace_table(
+    conn: &Connection,
+    table: &str,
+    scope: &str,
+    rows: &[Value],
+) -> Result<(), String> {
+    let columns = table_columns(conn, table)?;
+    conn.execute(&format!("DELETE FROM {} WHERE {}", table, scope), [])
+        .map_err(|err| err.to_string())?;
+
+    for (index, row) in rows.iter().enumerate() {
+        let object = row
+            .as_object()
+            .ok_or_else(|| format!("Row {} of {} must be an object", index + 1, table))?;
+        let fields: Vec<(&String, SqlValue)> = object
+            .iter()
+            .filter(|(key, _)| columns.contains(key))
+            .map(|(key, value)| (key, sql_value(value)))
+            .collect();
+        if fields.is_empty() {
+            return Err(format!(
+                "Row {} of {} has no known fields",
+                index + 1,
+                table
+            ));
+        }
+        let names: Vec<&str> = fields.iter().map(|(key, _)| key.as_str()).collect();
+        let placeholders = vec!["?"; fields.len()].join(", ");
+        conn.execute(
+            &format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                table,
+                names.join(", "),
+                placeholders
+            ),
+            params_from_iter(fields.into_iter().map(|(_, value)| value)),
+        )
+        .map_err(|e| format!("Row {} of {}: {}", index + 1, table, e))?;
+    }
+    Ok(())
+}
+
+fn sql_value(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(flag) => SqlValue::Integer(i64::from(*flag)),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => SqlValue::Integer(integer),
+            None => SqlValue::Real(number.as_f64().unwrap_or_default()),
+        },
+        Value::String(text) => SqlValue::Text(text.clone()),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
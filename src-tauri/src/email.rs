@@ -0,0 +1,450 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    current_unix_timestamp, map_invoice, open_connection, resolve_db_path, resolve_scratch_dir,
+    settings, Invoice, PrivateFile, INVOICE_COLUMNS, INVOICE_STATUS_SENT,
+};
+
+/// Longest email body accepted; invoices go out with a short cover note.
+const MAX_BODY_LENGTH: usize = 10_000;
+/// Seconds before giving up on the mail server.
+const SEND_TIMEOUT_SECS: u32 = 60;
+
+/// The outgoing mail server invoices are sent through.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmtpSettings {
+    pub host: String,
+    /// Defaults to 465 for TLS, 587 for STARTTLS and 25 without encryption.
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    /// Never sent back to the webview. Saving the server without one keeps
+    /// the stored password for the same user; an empty one removes it.
+    pub password: Option<String>,
+    pub from_address: String,
+    pub from_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS, which must succeed.
+    #[default]
+    Starttls,
+    /// TLS from the start ("SMTPS").
+    Tls,
+    /// Unencrypted, for local relays only.
+    None,
+}
+
+impl SmtpSettings {
+    fn url(&self) -> String {
+        let (scheme, default_port) = match self.security {
+            SmtpSecurity::Tls => ("smtps", 465),
+            SmtpSecurity::Starttls => ("smtp", 587),
+            SmtpSecurity::None => ("smtp", 25),
+        };
+        format!(
+            "{}://{}:{}",
+            scheme,
+            self.host.trim(),
+            self.port.unwrap_or(default_port)
+        )
+    }
+}
+
+/// Checked when the settings are saved and again before sending, so sending
+/// only fails on the server.
+pub fn validate_smtp(smtp: &SmtpSettings) -> Result<(), String> {
+    let host = smtp.host.trim();
+    if host.is_empty() || host.contains(|ch: char| ch.is_whitespace() || "/?#@".contains(ch)) {
+        return Err("Enter the mail server's host name".into());
+    }
+    if smtp.port == Some(0) {
+        return Err("The mail server port must be between 1 and 65535".into());
+    }
+    if !valid_address(smtp.from_address.trim()) {
+        return Err(format!(
+            "\"{}\" isn't a valid sender address",
+            smtp.from_address
+        ));
+    }
+    if smtp.from_name.as_deref().is_some_and(has_line_break) {
+        return Err("The sender name must be on one line".into());
+    }
+    if smtp.password.is_some() && smtp.username.is_none() {
+        return Err("Enter the user name the password belongs to".into());
+    }
+    if smtp.password.is_some() && smtp.security == SmtpSecurity::None {
+        return Err("A password can only be sent over TLS or STARTTLS".into());
+    }
+    // Values are handed to curl one per line of its config.
+    let values = [
+        Some(host),
+        smtp.username.as_deref(),
+        smtp.password.as_deref(),
+        Some(smtp.from_address.as_str()),
+        smtp.from_name.as_deref(),
+    ];
+    if values.into_iter().flatten().any(has_control) {
+        return Err("Mail server settings can't contain line breaks or control characters".into());
+    }
+    Ok(())
+}
+
+/// Emails the invoice PDF to `to` (one or more addresses separated by commas)
+/// through the SMTP server in settings, then marks the invoice as sent.
+///
+/// Mail is handed to the system's `curl`, which ships with macOS, Windows 10
+/// and later and most Linux distributions and brings the TLS support the
+/// app doesn't otherwise have. The credentials are passed on its standard
+/// input rather than the command line, so they don't show in process lists.
+#[tauri::command]
+pub async fn send_invoice_email(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    to: String,
+    subject: String,
+    body: String,
+) -> Result<Invoice, String> {
+    let recipients: Vec<String> = to
+        .split(',')
+        .map(|address| address.trim().to_string())
+        .filter(|address| !address.is_empty())
+        .collect();
+    if recipients.is_empty() {
+        return Err("Enter at least one recipient".into());
+    }
+    if let Some(address) = recipients.iter().find(|address| !valid_address(address)) {
+        return Err(format!("\"{}\" isn't a valid email address", address));
+    }
+    let subject = subject.trim().to_string();
+    if subject.is_empty() {
+        return Err("Enter a subject".into());
+    }
+    if has_line_break(&subject) {
+        return Err("The subject must be on one line".into());
+    }
+    if body.chars().count() > MAX_BODY_LENGTH {
+        return Err(format!(
+            "The message must be at most {} characters",
+            MAX_BODY_LENGTH
+        ));
+    }
+    let db_path = resolve_db_path(&app_handle)?;
+    let scratch_dir = resolve_scratch_dir(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let (smtp, invoice) = {
+            let conn = open_connection(db_path.clone())?;
+            let smtp = settings::load_settings(&conn)?
+                .smtp
+                .ok_or("Set up an outgoing mail server in settings first")?;
+            (smtp, fetch_invoice(&conn, id)?)
+        };
+        let pdf = fs::read(&invoice.file_path)
+            .map_err(|e| format!("Couldn't read the invoice PDF: {}", e))?;
+        let file_name = Path::new(&invoice.file_path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("invoice.pdf")
+            .to_string();
+
+        let message = build_message(&smtp, &recipients, &subject, &body, &file_name, &pdf);
+        // Deleted when this closure returns, sent or not.
+        let message_file = PrivateFile::create(
+            scratch_dir.join(format!("invoice_{}_{}.eml", id, current_unix_timestamp())),
+            message.as_bytes(),
+        )
+        .map_err(|e| format!("Couldn't prepare the email: {}", e))?;
+        deliver(&smtp, &recipients, message_file.path())?;
+
+        let conn = open_connection(db_path)?;
+        conn.execute(
+            // A paid invoice sent again, e.g. as a receipt, stays paid.
+            "UPDATE invoices
+             SET status = CASE WHEN status = 'paid' THEN status ELSE ?1 END, sent_at = ?2
+             WHERE id = ?3",
+            params![INVOICE_STATUS_SENT, current_unix_timestamp(), id],
+        )
+        .map_err(|err| err.to_string())?;
+        fetch_invoice(&conn, id)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+fn fetch_invoice(conn: &rusqlite::Connection, id: i64) -> Result<Invoice, String> {
+    conn.query_row(
+        &format!("SELECT {INVOICE_COLUMNS} FROM invoices WHERE id = ?1"),
+        params![id],
+        map_invoice,
+    )
+    .map_err(|e| format!("Invoice not found: {}", e))
+}
+
+fn deliver(smtp: &SmtpSettings, recipients: &[String], message_path: &Path) -> Result<(), String> {
+    // Settings saved before these checks existed are checked again here.
+    validate_smtp(smtp)?;
+    let mut config = vec![
+        format!("url = {}", curl_quote(&smtp.url())?),
+        format!("mail-from = {}", curl_quote(smtp.from_address.trim())?),
+        format!(
+            "upload-file = {}",
+            curl_quote(&message_path.to_string_lossy())?
+        ),
+        format!("max-time = {}", SEND_TIMEOUT_SECS),
+        "silent".to_string(),
+        "show-error".to_string(),
+    ];
+    for address in recipients {
+        config.push(format!("mail-rcpt = {}", curl_quote(address)?));
+    }
+    if smtp.security == SmtpSecurity::Starttls {
+        config.push("ssl-reqd".to_string());
+    }
+    if let Some(username) = smtp.username.as_deref() {
+        let password = smtp.password.as_deref().unwrap_or_default();
+        config.push(format!(
+            "user = {}",
+            curl_quote(&format!("{}:{}", username, password))?
+        ));
+    }
+
+    let mut child = Command::new("curl")
+        .args(["--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => "Sending email needs curl, which wasn't found".to_string(),
+            _ => format!("Couldn't start curl: {}", err),
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(config.join("\n").as_bytes())
+            .map_err(|err| err.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|err| err.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let error = String::from_utf8_lossy(&output.stderr);
+        Err(format!(
+            "The mail server refused the message: {}",
+            error.trim().trim_start_matches("curl: ")
+        ))
+    }
+}
+
+/// A multipart message with the body as UTF-8 text and the PDF attached.
+fn build_message(
+    smtp: &SmtpSettings,
+    recipients: &[String],
+    subject: &str,
+    body: &str,
+    file_name: &str,
+    pdf: &[u8],
+) -> String {
+    let now = chrono::Local::now();
+    let boundary = format!("invoice-{}", now.timestamp_nanos_opt().unwrap_or_default());
+    let from_address = smtp.from_address.trim();
+    let domain = from_address.rsplit('@').next().unwrap_or("localhost");
+    let from = match smtp.from_name.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => format!("{} <{}>", display_name(name), from_address),
+        _ => from_address.to_string(),
+    };
+
+    let mut message = String::new();
+    let mut header = |name: &str, value: &str| {
+        message.push_str(name);
+        message.push_str(": ");
+        message.push_str(value);
+        message.push_str("\r\n");
+    };
+    header("Date", &now.to_rfc2822());
+    header("From", &from);
+    header("To", &recipients.join(", "));
+    header("Subject", &encode_header(subject));
+    header("Message-ID", &format!("<{}@{}>", boundary, domain));
+    header("MIME-Version", "1.0");
+    header(
+        "Content-Type",
+        &format!("multipart/mixed; boundary=\"{}\"", boundary),
+    );
+    message.push_str("\r\n");
+
+    let body = body.replace("\r\n", "\n").replace('\n', "\r\n");
+    message.push_str(&format!("--{}\r\n", boundary));
+    message.push_str("Content-Type: text/plain; charset=utf-8\r\n");
+    message.push_str("Content-Transfer-Encoding: base64\r\n\r\n");
+    message.push_str(&base64_lines(body.as_bytes()));
+
+    message.push_str(&format!("--{}\r\n", boundary));
+    message.push_str(&format!(
+        "Content-Type: application/pdf; name=\"{}\"\r\n",
+        file_name
+    ));
+    message.push_str(&format!(
+        "Content-Disposition: attachment; filename=\"{}\"\r\n",
+        file_name
+    ));
+    message.push_str("Content-Transfer-Encoding: base64\r\n\r\n");
+    message.push_str(&base64_lines(pdf));
+    message.push_str(&format!("--{}--\r\n", boundary));
+    message
+}
+
+/// A display name quoted, or encoded when it isn't plain ASCII.
+fn display_name(name: &str) -> String {
+    if name.is_ascii() {
+        format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        encode_header(name)
+    }
+}
+
+/// Non-ASCII header text as RFC 2047 encoded words, folded so every line
+/// stays under the 78-character limit.
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        return value.to_string();
+    }
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for ch in value.chars() {
+        // 45 bytes encode to 60 characters, 72 with the wrapper.
+        if chunk.len() + ch.len_utf8() > 45 {
+            words.push(format!("=?UTF-8?B?{}?=", base64(chunk.as_bytes())));
+            chunk.clear();
+        }
+        chunk.push(ch);
+    }
+    if !chunk.is_empty() {
+        words.push(format!("=?UTF-8?B?{}?=", base64(chunk.as_bytes())));
+    }
+    words.join("\r\n ")
+}
+
+fn base64_lines(data: &[u8]) -> String {
+    let encoded = base64(data);
+    let mut lines = String::with_capacity(encoded.len() + encoded.len() / 38 + 2);
+    for line in encoded.as_bytes().chunks(76) {
+        lines.push_str(std::str::from_utf8(line).unwrap_or_default());
+        lines.push_str("\r\n");
+    }
+    lines
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let triple = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+        for (index, shift) in [18, 12, 6, 0].into_iter().enumerate() {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(triple >> shift & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// A double-quoted value for a curl config file. Control characters are
+/// refused: a line break would end the value and start another option.
+fn curl_quote(value: &str) -> Result<String, String> {
+    if has_control(value) {
+        return Err("Email settings and addresses can't contain control characters".into());
+    }
+    Ok(format!(
+        "\"{}\"",
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    ))
+}
+
+/// A plain `local@domain` address; display names and comments aren't
+/// accepted.
+fn valid_address(address: &str) -> bool {
+    let Some((local, domain)) = address.rsplit_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.is_empty()
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !address.contains(|ch: char| {
+            ch.is_whitespace() || ch.is_control() || "<>(),;:\"[]\\".contains(ch)
+        })
+        && !local.contains('@')
+}
+
+fn has_line_break(value: &str) -> bool {
+    value.contains(['\r', '\n'])
+}
+
+fn has_control(value: &str) -> bool {
+    value.contains(char::is_control)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn smtp() -> SmtpSettings {
+        SmtpSettings {
+            host: "mail.example.com".into(),
+            port: None,
+            security: SmtpSecurity::Starttls,
+            username: Some("me@example.com".into()),
+            password: Some("secret".into()),
+            from_address: "me@example.com".into(),
+            from_name: Some("Me".into()),
+        }
+    }
+
+    #[test]
+    fn quoting_refuses_line_breaks() {
+        assert_eq!(curl_quote(r#"a"b\c"#).unwrap(), r#""a\"b\\c""#);
+        for value in ["pw\nupload-file = /etc/passwd", "pw\r", "pw\0"] {
+            assert!(curl_quote(value).is_err(), "{:?} was quoted", value);
+        }
+    }
+
+    #[test]
+    fn settings_with_control_characters_are_refused() {
+        assert!(validate_smtp(&smtp()).is_ok());
+        let mut user = smtp();
+        user.username = Some("me\nurl = \"file:///\"".into());
+        assert!(validate_smtp(&user).is_err());
+        let mut password = smtp();
+        password.password = Some("secret\noutput = \"/tmp/x\"".into());
+        assert!(validate_smtp(&password).is_err());
+    }
+
+    #[test]
+    fn a_password_needs_an_encrypted_connection() {
+        let mut plain = smtp();
+        plain.security = SmtpSecurity::None;
+        assert!(validate_smtp(&plain).is_err());
+        plain.password = None;
+        assert!(validate_smtp(&plain).is_ok());
+    }
+}
@@ -0,0 +1,619 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone};
+use rusqlite::{params, params_from_iter, types::Value as SqlValue, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::{
+    audit::{self, AuditAction, EntrySource},
+    calculate_amount, current_settings, current_unix_timestamp, enforce_no_overlap,
+    fetch_time_entry, map_time_entry, open_connection, page_bounds, persist_time_entry, projects,
+    query_entries_page, resolve_db_path, run_entry_checks, sanitize_hourly_rate,
+    sanitize_project_name,
+    settings::WorkBlock,
+    suspicious_entry_warnings, undo, EntrySaveError, NewTimeEntry, OverlapWarning, Page, TimeEntry,
+    TimerState, UpdateResult, ENTRY_TYPE_BREAK, TIME_ENTRY_COLUMNS,
+};
+
+/// Changes applied by `bulk_update_entries`; `None` fields stay as they are.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryPatch {
+    pub project_name: Option<String>,
+    pub hourly_rate: Option<f64>,
+    pub billable: Option<bool>,
+}
+
+/// Copies an entry to the day containing `target_date` (today when omitted),
+/// at the same local time of day and with the same project, duration, rate
+/// and notes.
+#[tauri::command]
+pub async fn duplicate_time_entry(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    target_date: Option<i64>,
+) -> Result<TimeEntry, EntrySaveError> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let target_date = target_date.unwrap_or_else(current_unix_timestamp);
+    let (source, start_time) = {
+        let db_path = db_path.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let conn = open_connection(db_path)?;
+            let source = fetch_time_entry(&conn, id)?;
+            let start_time = shift_to_day(source.start_time, target_date)?;
+            let end_time = start_time + (source.end_time - source.start_time);
+            enforce_no_overlap(&conn, 0, start_time, end_time)?;
+            Ok::<_, EntrySaveError>((source, start_time))
+        })
+        .await
+        .map_err(|err| err.to_string())??
+    };
+
+    let span = source.end_time - source.start_time;
+    let new_entry = NewTimeEntry {
+        project_name: source.project_name,
+        start_time,
+        end_time: start_time + span,
+        hourly_rate: source.hourly_rate,
+        notes: source.notes,
+        // Whatever the source spent paused or lost to rounding stays out.
+        paused_seconds: (span - source.duration).max(0),
+        apply_rounding: false,
+        is_break: source.entry_type == ENTRY_TYPE_BREAK,
+        billable: source.billable,
+        utc_offset: None,
+        source: EntrySource::Manual,
+    };
+
+    let entry = persist_time_entry(db_path, new_entry).await?;
+    undo::record_created(&app_handle, "Duplicate entry", vec![entry.id]);
+    run_entry_checks(&app_handle, &entry.project_name);
+    Ok(entry)
+}
+
+/// One project's entries, newest first, optionally within
+/// `start_time..end_time`.
+#[tauri::command]
+pub async fn get_entries_by_project(
+    app_handle: tauri::AppHandle,
+    project_name: String,
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Page<TimeEntry>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let (limit, offset) = page_bounds(limit, offset);
+    let mut where_sql = "project_name = ?".to_string();
+    let mut values: Vec<SqlValue> = vec![sanitize_project_name(project_name).into()];
+    if let Some(start) = start_time {
+        where_sql.push_str(" AND start_time >= ?");
+        values.push(start.into());
+    }
+    if let Some(end) = end_time {
+        where_sql.push_str(" AND start_time < ?");
+        values.push(end.into());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_entries_page(&conn, &where_sql, values, limit, offset)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Stars or unstars an entry. Stars aren't part of the undo history.
+#[tauri::command]
+pub async fn set_entry_starred(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    starred: bool,
+) -> Result<TimeEntry, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let before = fetch_time_entry(&conn, id)?;
+        conn.execute(
+            "UPDATE time_entries SET starred = ?1 WHERE id = ?2",
+            params![starred, id],
+        )
+        .map_err(|err| err.to_string())?;
+        let entry = fetch_time_entry(&conn, id)?;
+        audit::record_change(
+            &conn,
+            AuditAction::Update,
+            EntrySource::Manual,
+            Some(&before),
+            Some(&entry),
+        )?;
+        Ok(entry)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Starred entries, newest first, optionally within `start_time..end_time`.
+#[tauri::command]
+pub async fn get_starred_entries(
+    app_handle: tauri::AppHandle,
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Page<TimeEntry>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let (limit, offset) = page_bounds(limit, offset);
+    let mut where_sql = "starred = 1".to_string();
+    let mut values: Vec<SqlValue> = Vec::new();
+    if let Some(start) = start_time {
+        where_sql.push_str(" AND start_time >= ?");
+        values.push(start.into());
+    }
+    if let Some(end) = end_time {
+        where_sql.push_str(" AND start_time < ?");
+        values.push(end.into());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_entries_page(&conn, &where_sql, values, limit, offset)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Shifts an entry to start at `new_start_time`, keeping its duration. Like
+/// `update_time_entry`, overlaps are reported unless the
+/// `block_overlapping_entries` setting refuses them.
+#[tauri::command]
+pub async fn move_time_entry(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    new_start_time: i64,
+) -> Result<UpdateResult, EntrySaveError> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    let (result, before) = tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let current = fetch_time_entry(&conn, id)?;
+        let new_end_time = new_start_time + (current.end_time - current.start_time);
+
+        let overlapping = enforce_no_overlap(&conn, id, new_start_time, new_end_time)?;
+        let warnings = suspicious_entry_warnings(&conn, new_start_time, new_end_time)?;
+        let overlap_warning = (!overlapping.is_empty()).then_some(OverlapWarning {
+            overlapping_entries: overlapping,
+        });
+
+        conn.execute(
+            "UPDATE time_entries SET start_time = ?1, end_time = ?2 WHERE id = ?3",
+            params![new_start_time, new_end_time, id],
+        )
+        .map_err(|err| err.to_string())?;
+
+        let entry = fetch_time_entry(&conn, id)?;
+        audit::record_change(
+            &conn,
+            AuditAction::Update,
+            EntrySource::Manual,
+            Some(&current),
+            Some(&entry),
+        )?;
+        Ok::<_, EntrySaveError>((
+            UpdateResult {
+                entry,
+                overlap_warning,
+                warnings,
+            },
+            current,
+        ))
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    undo::record_updated(&app_handle, "Move entry", vec![before]);
+    Ok(result)
+}
+
+/// Applies `patch` to every entry in `ids` in one transaction and returns the
+/// number of entries changed. A missing id or a break among them rolls the
+/// whole batch back.
+#[tauri::command]
+pub async fn bulk_update_entries(
+    app_handle: tauri::AppHandle,
+    ids: Vec<i64>,
+    patch: EntryPatch,
+) -> Result<usize, String> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    if patch.project_name.is_none() && patch.hourly_rate.is_none() && patch.billable.is_none() {
+        return Err("Nothing to update".into());
+    }
+    let db_path = resolve_db_path(&app_handle)?;
+    let project_name = patch.project_name.map(sanitize_project_name);
+    let hourly_rate = patch.hourly_rate.map(sanitize_hourly_rate);
+
+    let (changed, before) = tauri::async_runtime::spawn_blocking({
+        let project_name = project_name.clone();
+        move || {
+            let mut conn = open_connection(db_path)?;
+            let tx = conn.transaction().map_err(|err| err.to_string())?;
+            if let Some(name) = &project_name {
+                projects::ensure_project(&tx, name)?;
+            }
+
+            let mut changed = 0;
+            let mut before = Vec::with_capacity(ids.len());
+            for id in ids {
+                let current = fetch_time_entry(&tx, id)
+                    .map_err(|_| format!("Time entry {} not found", id))?;
+                if current.entry_type == ENTRY_TYPE_BREAK {
+                    return Err("Breaks can't be bulk edited".into());
+                }
+                let rate = hourly_rate.unwrap_or(current.hourly_rate);
+                changed += tx
+                    .execute(
+                        "UPDATE time_entries
+                         SET project_name = ?1,
+                             hourly_rate = ?2,
+                             amount = ?3,
+                             billable = ?4
+                         WHERE id = ?5",
+                        params![
+                            project_name.as_deref().unwrap_or(&current.project_name),
+                            rate,
+                            calculate_amount(current.duration, rate),
+                            patch.billable.unwrap_or(current.billable),
+                            id
+                        ],
+                    )
+                    .map_err(|err| err.to_string())?;
+                let updated = fetch_time_entry(&tx, id)?;
+                audit::record_change(
+                    &tx,
+                    AuditAction::Update,
+                    EntrySource::Manual,
+                    Some(&current),
+                    Some(&updated),
+                )?;
+                before.push(current);
+            }
+            tx.commit().map_err(|err| err.to_string())?;
+            Ok::<_, String>((changed, before))
+        }
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    undo::record_updated(&app_handle, format!("Edit {} entries", changed), before);
+    if let Some(name) = &project_name {
+        run_entry_checks(&app_handle, name);
+    }
+    Ok(changed)
+}
+
+/// Deletes every entry starting in `start_time..end_time`, optionally only
+/// those of `project_name`, in one transaction. Returns how many entries
+/// matched; with `dry_run` nothing is deleted.
+#[tauri::command]
+pub async fn delete_entries_matching(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+    project_name: Option<String>,
+    dry_run: bool,
+) -> Result<usize, String> {
+    if end_time <= start_time {
+        return Err("End time must be after start time".into());
+    }
+    let db_path = resolve_db_path(&app_handle)?;
+    let mut where_sql = "start_time >= ? AND start_time < ?".to_string();
+    let mut values: Vec<SqlValue> = vec![start_time.into(), end_time.into()];
+    if let Some(name) = project_name.map(sanitize_project_name) {
+        where_sql.push_str(" AND project_name = ?");
+        values.push(name.into());
+    }
+
+    let deleted = tauri::async_runtime::spawn_blocking(move || {
+        let mut conn = open_connection(db_path)?;
+        let tx = conn.transaction().map_err(|err| err.to_string())?;
+        let matched = {
+            let mut stmt = tx
+                .prepare(&format!(
+                    "SELECT {TIME_ENTRY_COLUMNS} FROM time_entries WHERE {where_sql}"
+                ))
+                .map_err(|err| err.to_string())?;
+            let rows = stmt
+                .query_map(params_from_iter(values.iter()), map_time_entry)
+                .map_err(|err| err.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|err| err.to_string())?
+        };
+        if dry_run || matched.is_empty() {
+            return Ok((matched.len(), None));
+        }
+        tx.execute(
+            &format!(
+                "DELETE FROM time_entry_segments
+                 WHERE entry_id IN (SELECT id FROM time_entries WHERE {where_sql})"
+            ),
+            params_from_iter(values.iter()),
+        )
+        .map_err(|err| err.to_string())?;
+        for entry in &matched {
+            audit::record_change(
+                &tx,
+                AuditAction::Delete,
+                EntrySource::Manual,
+                Some(entry),
+                None,
+            )?;
+        }
+        tx.execute(
+            &format!("DELETE FROM time_entries WHERE {where_sql}"),
+            params_from_iter(values.iter()),
+        )
+        .map_err(|err| err.to_string())?;
+        tx.commit().map_err(|err| err.to_string())?;
+        Ok::<_, String>((matched.len(), Some(matched)))
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    let (count, removed) = deleted;
+    if let Some(removed) = removed {
+        undo::record_deleted(&app_handle, format!("Delete {} entries", count), removed);
+    }
+    Ok(count)
+}
+
+/// Entries of one project whose times overlap, likely recorded twice.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    /// Every entry has the same start and end.
+    pub exact: bool,
+    pub entries: Vec<TimeEntry>,
+}
+
+/// Groups of same-project entries starting in `start_time..end_time` that
+/// overlap or match exactly, e.g. after importing the same CSV twice.
+#[tauri::command]
+pub async fn find_duplicates(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+) -> Result<Vec<DuplicateGroup>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {TIME_ENTRY_COLUMNS}
+                 FROM time_entries
+                 WHERE start_time >= ?1 AND start_time < ?2
+                 ORDER BY project_name, start_time, id"
+            ))
+            .map_err(|err| err.to_string())?;
+        let entries = stmt
+            .query_map(params![start_time, end_time], map_time_entry)
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+
+        let mut groups: Vec<Vec<TimeEntry>> = Vec::new();
+        let mut group_end = i64::MIN;
+        for entry in entries {
+            let joins = groups.last().is_some_and(|group| {
+                group[0].project_name == entry.project_name && entry.start_time < group_end
+            });
+            if joins {
+                group_end = group_end.max(entry.end_time);
+                groups.last_mut().expect("checked above").push(entry);
+            } else {
+                group_end = entry.end_time;
+                groups.push(vec![entry]);
+            }
+        }
+        Ok(groups
+            .into_iter()
+            .filter(|group| group.len() > 1)
+            .map(|entries| DuplicateGroup {
+                exact: entries.iter().all(|entry| {
+                    entry.start_time == entries[0].start_time
+                        && entry.end_time == entries[0].end_time
+                }),
+                entries,
+            })
+            .collect())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// An existing entry with the same project, start and end, if any.
+pub fn find_exact_duplicate(
+    conn: &Connection,
+    project_name: &str,
+    start_time: i64,
+    end_time: i64,
+) -> Result<Option<i64>, String> {
+    conn.query_row(
+        "SELECT id FROM time_entries
+         WHERE project_name = ?1 AND start_time = ?2 AND end_time = ?3
+         LIMIT 1",
+        params![project_name, start_time, end_time],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|err| err.to_string())
+}
+
+/// Untracked time between entries.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Gap {
+    pub start_time: i64,
+    pub end_time: i64,
+    pub duration: i64,
+}
+
+/// Untracked stretches of at least `min_gap_minutes` in `start_time..end_time`,
+/// oldest first. With working hours configured only time inside them counts;
+/// otherwise each day is checked from its first entry to its last. Breaks and
+/// running timers count as tracked.
+#[tauri::command]
+pub async fn find_gaps(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+    min_gap_minutes: Option<u32>,
+) -> Result<Vec<Gap>, String> {
+    if end_time <= start_time {
+        return Err("End time must be after start time".into());
+    }
+    let working_hours = current_settings(&app_handle)?.working_hours;
+    let min_gap = i64::from(min_gap_minutes.unwrap_or(15)) * 60;
+    let now = current_unix_timestamp();
+    let running: Vec<(i64, i64)> = app_handle
+        .state::<TimerState>()
+        .statuses()
+        .iter()
+        .filter_map(|status| status.start_time)
+        .map(|start| (start, now))
+        .collect();
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT start_time, end_time FROM time_entries
+                 WHERE end_time > ?1 AND start_time < ?2
+                 ORDER BY start_time ASC",
+            )
+            .map_err(|err| err.to_string())?;
+        let mut tracked = stmt
+            .query_map(params![start_time, end_time], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<(i64, i64)>, _>>()
+            .map_err(|err| err.to_string())?;
+        tracked.extend(running);
+        tracked.sort_unstable();
+
+        let windows = if working_hours.is_empty() {
+            tracked_day_spans(&tracked)
+        } else {
+            working_windows(&working_hours, start_time, end_time)
+        };
+        let mut gaps = Vec::new();
+        for (window_start, window_end) in windows {
+            let window_start = window_start.max(start_time);
+            let window_end = window_end.min(end_time).min(now);
+            let mut cursor = window_start;
+            for &(entry_start, entry_end) in &tracked {
+                if entry_end <= cursor || entry_start >= window_end {
+                    continue;
+                }
+                if entry_start - cursor >= min_gap {
+                    gaps.push(Gap {
+                        start_time: cursor,
+                        end_time: entry_start,
+                        duration: entry_start - cursor,
+                    });
+                }
+                cursor = cursor.max(entry_end);
+            }
+            if window_end - cursor >= min_gap {
+                gaps.push(Gap {
+                    start_time: cursor,
+                    end_time: window_end,
+                    duration: window_end - cursor,
+                });
+            }
+        }
+        Ok(gaps)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Each configured work block on every local day of `start..end`.
+fn working_windows(blocks: &[WorkBlock], start: i64, end: i64) -> Vec<(i64, i64)> {
+    let local_date = |ts: i64| {
+        Local
+            .timestamp_opt(ts, 0)
+            .single()
+            .map(|at| at.date_naive())
+    };
+    let (Some(first), Some(last)) = (local_date(start), local_date(end)) else {
+        return Vec::new();
+    };
+    let mut windows = Vec::new();
+    let mut day: NaiveDate = first;
+    while day <= last {
+        let weekday = day.weekday().number_from_monday();
+        for block in blocks.iter().filter(|block| block.days.contains(&weekday)) {
+            let Some((block_start, block_end)) = block.bounds() else {
+                continue;
+            };
+            let block_start = Local
+                .from_local_datetime(&day.and_time(block_start))
+                .earliest();
+            let block_end = Local
+                .from_local_datetime(&day.and_time(block_end))
+                .earliest();
+            if let (Some(block_start), Some(block_end)) = (block_start, block_end) {
+                windows.push((block_start.timestamp(), block_end.timestamp()));
+            }
+        }
+        day += Duration::days(1);
+    }
+    windows.sort_unstable();
+    windows
+}
+
+/// From the first tracked start to the last tracked end of each local day.
+fn tracked_day_spans(tracked: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    let mut spans: Vec<(NaiveDate, i64, i64)> = Vec::new();
+    for &(start, end) in tracked {
+        let Some(day) = Local
+            .timestamp_opt(start, 0)
+            .single()
+            .map(|at| at.date_naive())
+        else {
+            continue;
+        };
+        match spans.last_mut() {
+            Some((last_day, _, last_end)) if *last_day == day => *last_end = (*last_end).max(end),
+            _ => spans.push((day, start, end)),
+        }
+    }
+    spans
+        .into_iter()
+        .map(|(_, start, end)| (start, end))
+        .collect()
+}
+
+/// `timestamp`'s local time of day on the local day containing `day`.
+fn shift_to_day(timestamp: i64, day: i64) -> Result<i64, String> {
+    let time = Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .ok_or("Unable to resolve local time")?
+        .time();
+    let date = Local
+        .timestamp_opt(day, 0)
+        .single()
+        .ok_or("Unable to resolve target date")?
+        .date_naive();
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|at| at.timestamp())
+        .ok_or_else(|| "That time doesn't exist on the target date".to_string())
+}
@@ -0,0 +1,425 @@
+use chrono::{Local, TimeZone};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bank_account, budgets, clients, current_unix_timestamp, invoice_labels::InvoiceLanguage,
+    open_connection, pdf_generator, prefill_bill_to, projects, render_invoice_pdf, resolve_db_path,
+    resolve_invoices_dir, sanitize_line_items, sanitize_notes, sanitize_project_name, BusinessInfo,
+    InvoiceRender,
+};
+
+pub const CREATE_ESTIMATES_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS estimates (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        created_at INTEGER NOT NULL,
+        client_id INTEGER REFERENCES clients(id),
+        project_name TEXT NOT NULL,
+        business_info TEXT NOT NULL,
+        line_items TEXT NOT NULL,
+        currency TEXT NOT NULL DEFAULT 'USD',
+        language TEXT,
+        valid_until INTEGER NOT NULL,
+        total_hours REAL NOT NULL,
+        total_amount REAL NOT NULL,
+        file_path TEXT NOT NULL,
+        accepted_at INTEGER,
+        project_id INTEGER REFERENCES projects(id)
+    )
+"#;
+
+const ESTIMATE_COLUMNS: &str = "id, created_at, client_id, project_name, business_info, line_items, currency, language, valid_until, total_hours, total_amount, file_path, accepted_at, project_id";
+
+/// How long an estimate can be accepted for when no date is given.
+const DEFAULT_VALIDITY_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Estimate {
+    pub id: i64,
+    pub created_at: i64,
+    pub client_id: Option<i64>,
+    /// The project the work is quoted for; created when the estimate is
+    /// accepted.
+    pub project_name: String,
+    pub business_info: String,
+    pub line_items: Vec<pdf_generator::InvoiceLineItem>,
+    pub currency: String,
+    pub language: InvoiceLanguage,
+    pub valid_until: i64,
+    /// Hours quoted on hourly rows; fixed-fee rows don't count.
+    pub total_hours: f64,
+    pub total_amount: f64,
+    pub file_path: String,
+    pub accepted_at: Option<i64>,
+    /// The project the estimate became when accepted.
+    pub project_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimateInput {
+    pub business_info: BusinessInfo,
+    pub client_id: Option<i64>,
+    pub project_name: String,
+    /// Defaults to the client's currency, then USD.
+    pub currency: Option<String>,
+    /// Defaults to the client's invoice language.
+    pub language: Option<InvoiceLanguage>,
+    /// Defaults to 30 days from today.
+    pub valid_until: Option<i64>,
+    pub line_items: Vec<pdf_generator::InvoiceLineItem>,
+}
+
+/// Renders an "Estimate" PDF for the quoted line items and stores it. Bill
+/// To fields left blank are filled from the client.
+#[tauri::command]
+pub async fn create_estimate(
+    app_handle: tauri::AppHandle,
+    estimate: EstimateInput,
+) -> Result<Estimate, String> {
+    let created_at = current_unix_timestamp();
+    let valid_until = estimate
+        .valid_until
+        .unwrap_or(created_at + DEFAULT_VALIDITY_DAYS * 86_400);
+    if valid_until <= created_at {
+        return Err("The validity date must be in the future".to_string());
+    }
+    if estimate.line_items.is_empty() {
+        return Err("An estimate needs at least one line item".to_string());
+    }
+    let line_items = sanitize_line_items(estimate.line_items)?;
+    let project_name = sanitize_project_name(estimate.project_name);
+    let mut business_info = estimate.business_info;
+    business_info.bank_account = bank_account::sanitize(business_info.bank_account)?;
+    business_info.notes = sanitize_notes(business_info.notes)?;
+    business_info.terms = clients::non_empty(business_info.terms);
+    let db_path = resolve_db_path(&app_handle)?;
+    let estimates_dir = resolve_invoices_dir(&app_handle)?.join("estimates");
+    std::fs::create_dir_all(&estimates_dir)
+        .map_err(|e| format!("Failed to create estimates directory: {}", e))?;
+    let client_id = estimate.client_id;
+    let total_hours: f64 = line_items
+        .iter()
+        .filter(|item| !item.fixed_fee)
+        .map(|item| item.quantity)
+        .sum();
+    let total_amount: f64 = line_items.iter().map(|item| item.amount).sum();
+    let line_items_json = serde_json::to_string(&line_items)
+        .map_err(|e| format!("Failed to serialize line items: {}", e))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut conn = open_connection(db_path)?;
+        let client = client_id
+            .map(|id| clients::fetch_client(&conn, id))
+            .transpose()?;
+        if let Some(client) = client.as_ref() {
+            prefill_bill_to(
+                &mut business_info,
+                &client.name,
+                client.address.as_deref(),
+                client.email.as_deref(),
+            );
+        }
+        let currency = match estimate.currency {
+            Some(code) => clients::sanitize_currency_code(&code)?,
+            None => client
+                .as_ref()
+                .map(|client| client.currency.clone())
+                .unwrap_or_else(|| "USD".to_string()),
+        };
+        let language = estimate
+            .language
+            .or(client.and_then(|client| client.invoice_language))
+            .unwrap_or_default();
+        let business_info_json = serde_json::to_string(&business_info)
+            .map_err(|e| format!("Failed to serialize business info: {}", e))?;
+
+        // Saved first so the PDF can be named by id, which estimates issued
+        // within the same second don't share; nothing is saved if rendering
+        // fails.
+        let tx = conn.transaction().map_err(|err| err.to_string())?;
+        tx.execute(
+            "INSERT INTO estimates (created_at, client_id, project_name, business_info, line_items, currency, language, valid_until, total_hours, total_amount, file_path)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, '')",
+            params![
+                created_at,
+                client_id,
+                project_name,
+                business_info_json,
+                line_items_json,
+                currency,
+                language.as_str(),
+                valid_until,
+                total_hours,
+                total_amount
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+        let id = tx.last_insert_rowid();
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        let output_path_str = estimates_dir
+            .join(format!("estimate_{}_{}.pdf", timestamp, id))
+            .to_str()
+            .ok_or("Invalid file path")?
+            .to_string();
+        tx.execute(
+            "UPDATE estimates SET file_path = ?1 WHERE id = ?2",
+            params![output_path_str, id],
+        )
+        .map_err(|err| err.to_string())?;
+
+        let render = InvoiceRender {
+            id: None,
+            business_info,
+            entries: Vec::new(),
+            line_items,
+            period: None,
+            currency,
+            client_id,
+            language,
+            due_date: None,
+            payment_terms: None,
+            issued_at: created_at,
+            preview: false,
+            kind: pdf_generator::DocumentKind::Estimate { valid_until },
+            timesheet: false,
+            rounding: None,
+        };
+        if let Err(err) = render_invoice_pdf(&app_handle, render, &output_path_str) {
+            let _ = std::fs::remove_file(&output_path_str);
+            return Err(err);
+        }
+        let estimate = fetch_estimate(&tx, id)?;
+        tx.commit().map_err(|err| err.to_string())?;
+        Ok(estimate)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn list_estimates(
+    app_handle: tauri::AppHandle,
+    client_id: Option<i64>,
+) -> Result<Vec<Estimate>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {ESTIMATE_COLUMNS} FROM estimates
+                 WHERE ?1 IS NULL OR client_id = ?1
+                 ORDER BY created_at DESC, id DESC"
+            ))
+            .map_err(|err| err.to_string())?;
+        let estimates = stmt
+            .query_map(params![client_id], map_estimate)
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        Ok(estimates)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Deletes the estimate and its PDF. A project it was accepted into is kept.
+#[tauri::command]
+pub async fn delete_estimate(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    let file_path = tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let estimate = fetch_estimate(&conn, id)?;
+        conn.execute("DELETE FROM estimates WHERE id = ?1", params![id])
+            .map_err(|err| err.to_string())?;
+        Ok::<_, String>(estimate.file_path)
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    if let Err(e) = std::fs::remove_file(&file_path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            eprintln!("Failed to delete estimate file {}: {}", file_path, e);
+        }
+    }
+    Ok(())
+}
+
+/// Turns an accepted estimate into its project: the project is created if
+/// needed, assigned to the estimate's client when it has none, and budgeted
+/// at the quoted hours and total. Expired estimates can't be accepted, and a
+/// project that already has a budget is only rebudgeted with
+/// `replace_budget`.
+#[tauri::command]
+pub async fn accept_estimate(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    replace_budget: Option<bool>,
+) -> Result<projects::Project, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut conn = open_connection(db_path)?;
+        accept(
+            &mut conn,
+            id,
+            current_unix_timestamp(),
+            replace_budget.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+fn accept(
+    conn: &mut Connection,
+    id: i64,
+    now: i64,
+    replace_budget: bool,
+) -> Result<projects::Project, String> {
+    let tx = conn.transaction().map_err(|err| err.to_string())?;
+    let estimate = fetch_estimate(&tx, id)?;
+    if estimate.accepted_at.is_some() {
+        return Err("This estimate has already been accepted".to_string());
+    }
+    if estimate.valid_until < now {
+        let expired_on = Local
+            .timestamp_opt(estimate.valid_until, 0)
+            .single()
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        return Err(format!("This estimate expired on {}", expired_on));
+    }
+    let has_budget: bool = tx
+        .query_row(
+            "SELECT budget_hours IS NOT NULL OR budget_amount IS NOT NULL
+             FROM projects WHERE name = ?1",
+            params![estimate.project_name],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|err| err.to_string())?
+        .unwrap_or(false);
+    if has_budget && !replace_budget {
+        return Err(format!(
+            "\"{}\" already has a budget; confirm to replace it with the estimate's",
+            estimate.project_name
+        ));
+    }
+
+    let project_id = projects::ensure_project(&tx, &estimate.project_name)?;
+    tx.execute(
+        "UPDATE projects SET client_id = COALESCE(client_id, ?1) WHERE id = ?2",
+        params![estimate.client_id, project_id],
+    )
+    .map_err(|err| err.to_string())?;
+    let budget_hours = Some(estimate.total_hours).filter(|hours| *hours > 0.0);
+    let budget_amount = Some(estimate.total_amount).filter(|amount| *amount > 0.0);
+    budgets::apply_budget(&tx, &estimate.project_name, budget_hours, budget_amount)?;
+    tx.execute(
+        "UPDATE estimates SET accepted_at = ?1, project_id = ?2 WHERE id = ?3",
+        params![now, project_id, id],
+    )
+    .map_err(|err| err.to_string())?;
+    let project = projects::fetch_project(&tx, &estimate.project_name)?;
+    tx.commit().map_err(|err| err.to_string())?;
+    Ok(project)
+}
+
+fn fetch_estimate(conn: &Connection, id: i64) -> Result<Estimate, String> {
+    conn.query_row(
+        &format!("SELECT {ESTIMATE_COLUMNS} FROM estimates WHERE id = ?1"),
+        params![id],
+        map_estimate,
+    )
+    .optional()
+    .map_err(|err| err.to_string())?
+    .ok_or_else(|| "Estimate not found".to_string())
+}
+
+fn map_estimate(row: &rusqlite::Row) -> rusqlite::Result<Estimate> {
+    let line_items: String = row.get(5)?;
+    let language: Option<String> = row.get(7)?;
+    Ok(Estimate {
+        id: row.get(0)?,
+        created_at: row.get(1)?,
+        client_id: row.get(2)?,
+        project_name: row.get(3)?,
+        business_info: row.get(4)?,
+        line_items: serde_json::from_str(&line_items).map_err(|err| {
+            rusqlite::Error::FromSqlConversionFailure(5, rusqlite::types::Type::Text, Box::new(err))
+        })?,
+        currency: row.get(6)?,
+        language: language
+            .as_deref()
+            .and_then(InvoiceLanguage::parse)
+            .unwrap_or_default(),
+        valid_until: row.get(8)?,
+        total_hours: row.get(9)?,
+        total_amount: row.get(10)?,
+        file_path: row.get(11)?,
+        accepted_at: row.get(12)?,
+        project_id: row.get(13)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection(name: &str) -> Connection {
+        let path = std::env::temp_dir().join(format!("tt-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        open_connection(path).expect("test database")
+    }
+
+    fn insert_estimate(conn: &Connection, project_name: &str, hours: f64, amount: f64) -> i64 {
+        conn.execute(
+            "INSERT INTO estimates (created_at, project_name, business_info, line_items, valid_until, total_hours, total_amount, file_path)
+             VALUES (0, ?1, '{}', '[]', 1000, ?2, ?3, '')",
+            params![project_name, hours, amount],
+        )
+        .expect("insert estimate");
+        conn.last_insert_rowid()
+    }
+
+    #[test]
+    fn accepting_budgets_the_project_at_the_quote() {
+        let mut conn = connection("estimate-accept");
+        let id = insert_estimate(&conn, "Website", 12.5, 1250.0);
+        let project = accept(&mut conn, id, 500, false).unwrap();
+        assert_eq!(project.name, "Website");
+        assert_eq!(project.budget_hours, Some(12.5));
+        assert_eq!(project.budget_amount, Some(1250.0));
+        assert!(fetch_estimate(&conn, id).unwrap().accepted_at.is_some());
+        assert!(accept(&mut conn, id, 500, false).is_err());
+    }
+
+    #[test]
+    fn an_existing_budget_is_only_replaced_when_confirmed() {
+        let mut conn = connection("estimate-budget");
+        let first = insert_estimate(&conn, "Website", 10.0, 1000.0);
+        accept(&mut conn, first, 500, false).unwrap();
+        let second = insert_estimate(&conn, "Website", 4.0, 400.0);
+
+        assert!(accept(&mut conn, second, 500, false).is_err());
+        let project = projects::fetch_project(&conn, "Website").unwrap();
+        assert_eq!(project.budget_hours, Some(10.0));
+        assert!(fetch_estimate(&conn, second).unwrap().accepted_at.is_none());
+
+        let project = accept(&mut conn, second, 500, true).unwrap();
+        assert_eq!(project.budget_hours, Some(4.0));
+    }
+
+    #[test]
+    fn expired_estimates_cannot_be_accepted() {
+        let mut conn = connection("estimate-expired");
+        let id = insert_estimate(&conn, "Website", 1.0, 100.0);
+        assert!(accept(&mut conn, id, 1001, false)
+            .unwrap_err()
+            .starts_with("This estimate expired"));
+    }
+}
@@ -0,0 +1,278 @@
+use std::sync::Mutex;
+
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    current_settings, current_unix_timestamp, emit_timer_status, persist_active_timer,
+    refresh_tray, resolve_db_path, send_notification, stop_timer_internal, StopOrigin, TimerState,
+    TimerStatusPayload,
+};
+
+pub const IDLE_EVENT: &str = "timer://idle";
+
+/// A stretch without keyboard or mouse input while timers were running.
+#[derive(Debug, Clone, Serialize)]
+pub struct IdlePeriod {
+    pub started_at: i64,
+    /// When input resumed; `None` while the user is still away.
+    pub returned_at: Option<i64>,
+    /// Timers that were running (and not paused) when idle began.
+    pub timer_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdleAction {
+    /// Count the idle time as worked.
+    Keep,
+    /// Leave the idle time out of the timer, as if it had been paused.
+    Discard,
+    /// Save the entry ending when idle began.
+    Stop,
+}
+
+#[derive(Default)]
+pub struct IdleState {
+    inner: Mutex<IdleInner>,
+}
+
+#[derive(Default)]
+struct IdleInner {
+    pending: Option<IdlePeriod>,
+    /// Set once a period is flagged, so a resolved period isn't flagged again
+    /// before the user has actually come back.
+    awaiting_input: bool,
+}
+
+/// The idle period waiting for the user's decision, if any.
+#[tauri::command]
+pub async fn get_idle_period(app_handle: tauri::AppHandle) -> Result<Option<IdlePeriod>, String> {
+    let state = app_handle.state::<IdleState>();
+    let guard = state
+        .inner
+        .lock()
+        .map_err(|_| "Idle state is unavailable")?;
+    Ok(guard.pending.clone())
+}
+
+/// Applies `action` to `timer_id`, or to every timer in the pending idle
+/// period when `None`. Returns the running timers afterwards.
+#[tauri::command]
+pub async fn resolve_idle(
+    app_handle: tauri::AppHandle,
+    action: IdleAction,
+    timer_id: Option<i64>,
+) -> Result<Vec<TimerStatusPayload>, String> {
+    let period = take_pending(&app_handle, timer_id)?;
+    let running: Vec<i64> = app_handle
+        .state::<TimerState>()
+        .statuses()
+        .iter()
+        .filter_map(|status| status.timer_id)
+        .collect();
+    // Timers stopped since idle began have nothing left to adjust.
+    let targets: Vec<i64> = match timer_id {
+        Some(id) => vec![id],
+        None => period.timer_ids.clone(),
+    }
+    .into_iter()
+    .filter(|id| running.contains(id))
+    .collect();
+
+    match action {
+        IdleAction::Keep => {}
+        IdleAction::Discard => {
+            let until = period.returned_at.unwrap_or_else(current_unix_timestamp);
+            let timer_state = app_handle.state::<TimerState>();
+            for id in targets {
+                let timer = timer_state.discard_span(id, period.started_at, until)?;
+                persist_active_timer(resolve_db_path(&app_handle)?, &timer)?;
+            }
+            refresh_tray(&app_handle).map_err(|err| err.to_string())?;
+            emit_timer_status(&app_handle, &timer_state.status());
+        }
+        IdleAction::Stop => {
+            for id in targets {
+                stop_timer_internal(
+                    &app_handle,
+                    Some(id),
+                    StopOrigin::Window,
+                    Some(period.started_at),
+                )
+                .await?;
+            }
+        }
+    }
+
+    Ok(app_handle.state::<TimerState>().statuses())
+}
+
+/// Removes the resolved timers from the pending period, clearing it once
+/// every timer has been handled, and returns the period as it was.
+fn take_pending(app_handle: &AppHandle, timer_id: Option<i64>) -> Result<IdlePeriod, String> {
+    let state = app_handle.state::<IdleState>();
+    let mut guard = state
+        .inner
+        .lock()
+        .map_err(|_| "Idle state is unavailable")?;
+    let Some(period) = guard.pending.clone() else {
+        return Err("There is no idle time to resolve".into());
+    };
+    match timer_id {
+        Some(id) => {
+            if !period.timer_ids.contains(&id) {
+                return Err("That timer was not running when idle began".into());
+            }
+            let remaining: Vec<i64> = period
+                .timer_ids
+                .iter()
+                .copied()
+                .filter(|other| *other != id)
+                .collect();
+            guard.pending = if remaining.is_empty() {
+                None
+            } else {
+                Some(IdlePeriod {
+                    timer_ids: remaining,
+                    ..period.clone()
+                })
+            };
+        }
+        None => guard.pending = None,
+    }
+    Ok(period)
+}
+
+/// Flags an idle period once input has stopped for the configured threshold.
+/// Platforms where idle time can't be read simply never report one.
+pub fn check_idle(app: &AppHandle) {
+    let threshold = match current_settings(app) {
+        Ok(settings) if settings.idle_threshold_minutes > 0 => {
+            i64::from(settings.idle_threshold_minutes) * 60
+        }
+        _ => return,
+    };
+    let Some(idle_seconds) = system_idle_seconds() else {
+        return;
+    };
+    let now = current_unix_timestamp();
+    let state = app.state::<IdleState>();
+    let mut guard = state.inner.lock().expect("idle state poisoned");
+
+    if idle_seconds < threshold {
+        guard.awaiting_input = false;
+        if let Some(period) = guard
+            .pending
+            .as_mut()
+            .filter(|period| period.returned_at.is_none())
+        {
+            period.returned_at = Some(now - idle_seconds);
+            let _ = app.emit(IDLE_EVENT, period.clone());
+        }
+        return;
+    }
+    if guard.awaiting_input || guard.pending.is_some() {
+        return;
+    }
+
+    let timer_ids: Vec<i64> = app
+        .state::<TimerState>()
+        .statuses()
+        .iter()
+        .filter(|status| !status.is_paused && !status.is_break)
+        .filter_map(|status| status.timer_id)
+        .collect();
+    if timer_ids.is_empty() {
+        return;
+    }
+
+    let period = IdlePeriod {
+        started_at: now - idle_seconds,
+        returned_at: None,
+        timer_ids,
+    };
+    guard.awaiting_input = true;
+    guard.pending = Some(period.clone());
+    drop(guard);
+
+    let since = Local
+        .timestamp_opt(period.started_at, 0)
+        .single()
+        .map(|at| at.format("%H:%M").to_string())
+        .unwrap_or_else(|| "a while".into());
+    let _ = app.emit(IDLE_EVENT, period);
+    send_notification(
+        app,
+        "Are you still working?",
+        &format!(
+            "No activity since {}. Open Time Tracker to keep, discard or stop at that time.",
+            since
+        ),
+    );
+}
+
+/// Seconds since the last keyboard or mouse input, if the platform exposes it.
+#[cfg(target_os = "windows")]
+fn system_idle_seconds() -> Option<i64> {
+    #[repr(C)]
+    struct LastInputInfo {
+        cb_size: u32,
+        dw_time: u32,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetLastInputInfo(plii: *mut LastInputInfo) -> i32;
+    }
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetTickCount() -> u32;
+    }
+
+    let mut info = LastInputInfo {
+        cb_size: std::mem::size_of::<LastInputInfo>() as u32,
+        dw_time: 0,
+    };
+    // SAFETY: `info` is a correctly sized LASTINPUTINFO that outlives the call.
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return None;
+    }
+    // SAFETY: GetTickCount has no preconditions.
+    let now = unsafe { GetTickCount() };
+    // Both counters wrap after ~49 days; wrapping_sub keeps the difference right.
+    Some(i64::from(now.wrapping_sub(info.dw_time)) / 1000)
+}
+
+/// Reads `HIDIdleTime` (nanoseconds) from the IOHIDSystem registry entry.
+#[cfg(target_os = "macos")]
+fn system_idle_seconds() -> Option<i64> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-c", "IOHIDSystem", "-d", "4"])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let line = text.lines().find(|line| line.contains("\"HIDIdleTime\""))?;
+    let nanos: i64 = line.split('=').nth(1)?.trim().parse().ok()?;
+    Some(nanos / 1_000_000_000)
+}
+
+/// X11 sessions via `xprintidle` (milliseconds); unavailable on bare Wayland.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn system_idle_seconds() -> Option<i64> {
+    let output = std::process::Command::new("xprintidle").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let millis: i64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some(millis / 1000)
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn system_idle_seconds() -> Option<i64> {
+    None
+}
@@ -0,0 +1,352 @@
+use serde::{Deserialize, Serialize};
+
+/// Language of an invoice's fixed wording and date format. Names, notes and
+/// terms are printed as entered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvoiceLanguage {
+    #[default]
+    En,
+    De,
+    Fr,
+    Es,
+}
+
+impl InvoiceLanguage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            InvoiceLanguage::En => "en",
+            InvoiceLanguage::De => "de",
+            InvoiceLanguage::Fr => "fr",
+            InvoiceLanguage::Es => "es",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "en" => Some(InvoiceLanguage::En),
+            "de" => Some(InvoiceLanguage::De),
+            "fr" => Some(InvoiceLanguage::Fr),
+            "es" => Some(InvoiceLanguage::Es),
+            _ => None,
+        }
+    }
+
+    pub fn labels(self) -> &'static InvoiceLabels {
+        match self {
+            InvoiceLanguage::En => &EN,
+            InvoiceLanguage::De => &DE,
+            InvoiceLanguage::Fr => &FR,
+            InvoiceLanguage::Es => &ES,
+        }
+    }
+}
+
+/// Every fixed string printed on an invoice.
+pub struct InvoiceLabels {
+    pub invoice: &'static str,
+    pub credit_note: &'static str,
+    /// "Credit for invoice" in "Credit for invoice #12, 03/10/2026".
+    pub credit_for: &'static str,
+    pub estimate: &'static str,
+    pub valid_until: &'static str,
+    /// Watermark on previews.
+    pub draft: &'static str,
+    pub issue_date: &'static str,
+    pub due_date: &'static str,
+    pub bill_from: &'static str,
+    pub bill_to: &'static str,
+    pub tax_id: &'static str,
+    pub email: &'static str,
+    pub phone: &'static str,
+    pub date: &'static str,
+    pub description: &'static str,
+    pub quantity: &'static str,
+    pub unit_price: &'static str,
+    pub amount: &'static str,
+    pub carried_forward: &'static str,
+    pub brought_forward: &'static str,
+    pub subtotal: &'static str,
+    pub total: &'static str,
+    pub retainer: &'static str,
+    pub purchased: &'static str,
+    pub used: &'static str,
+    pub remaining: &'static str,
+    pub payment_details: &'static str,
+    pub account_holder: &'static str,
+    pub bank: &'static str,
+    pub account_number: &'static str,
+    /// Caption under the payment QR code.
+    pub scan_to_pay: &'static str,
+    pub terms: &'static str,
+    pub notes: &'static str,
+    /// "Page" and "of" in "Page 2 of 3".
+    pub page: &'static str,
+    pub page_of: &'static str,
+    pub hours_worked: &'static str,
+    /// "fixed fee" and "tracked" in "Website (fixed fee, 12.50 h tracked)".
+    pub fixed_fee: &'static str,
+    /// Replaces "fixed fee" once an earlier invoice has billed it.
+    pub fixed_fee_billed: &'static str,
+    pub tracked: &'static str,
+    /// Heading and columns of the timesheet appendix.
+    pub timesheet: &'static str,
+    pub time: &'static str,
+    pub project: &'static str,
+    pub hours: &'static str,
+    pub day_total: &'static str,
+    /// Sign-off block closing a standalone timesheet.
+    pub submitted_by: &'static str,
+    pub approved_by: &'static str,
+    pub name: &'static str,
+    pub signature: &'static str,
+    /// Rounding policy under the totals, e.g. "Hours rounded up to
+    /// 15-minute increments per line." `{direction}`, `{minutes}` and
+    /// `{scope}` are filled in.
+    pub rounding_note: &'static str,
+    pub rounded_up: &'static str,
+    pub rounded_down: &'static str,
+    pub rounded_nearest: &'static str,
+    pub per_line: &'static str,
+    pub on_total: &'static str,
+    /// Row making up the difference when the total is rounded.
+    pub rounding_adjustment: &'static str,
+    /// chrono format for dates.
+    pub date_format: &'static str,
+}
+
+const EN: InvoiceLabels = InvoiceLabels {
+    invoice: "Invoice",
+    credit_note: "Credit Note",
+    credit_for: "Credit for invoice",
+    estimate: "Estimate",
+    valid_until: "Valid until",
+    draft: "DRAFT",
+    issue_date: "Issue date",
+    due_date: "Due date",
+    bill_from: "Bill from",
+    bill_to: "Bill to",
+    tax_id: "Tax ID",
+    email: "Email",
+    phone: "Phone",
+    date: "Date",
+    description: "Description",
+    quantity: "Quantity",
+    unit_price: "Unit Price",
+    amount: "Amount",
+    carried_forward: "Carried forward",
+    brought_forward: "Brought forward",
+    subtotal: "SUBTOTAL",
+    total: "TOTAL",
+    retainer: "Retainer",
+    purchased: "Purchased",
+    used: "Used",
+    remaining: "Remaining",
+    payment_details: "Payment details",
+    account_holder: "Account holder",
+    bank: "Bank",
+    account_number: "Account number",
+    scan_to_pay: "Scan to pay",
+    terms: "Terms",
+    notes: "Notes",
+    page: "Page",
+    page_of: "of",
+    hours_worked: "Hours worked",
+    fixed_fee: "fixed fee",
+    fixed_fee_billed: "fixed fee already billed",
+    tracked: "tracked",
+    timesheet: "Timesheet",
+    time: "Time",
+    project: "Project",
+    hours: "Hours",
+    day_total: "Day total",
+    submitted_by: "Submitted by",
+    approved_by: "Approved by",
+    name: "Name",
+    signature: "Signature",
+    rounding_note: "Hours {direction} to {minutes}-minute increments {scope}.",
+    rounded_up: "rounded up",
+    rounded_down: "rounded down",
+    rounded_nearest: "rounded",
+    per_line: "per line",
+    on_total: "on the total",
+    rounding_adjustment: "Rounding adjustment",
+    date_format: "%d/%m/%Y",
+};
+
+const DE: InvoiceLabels = InvoiceLabels {
+    invoice: "Rechnung",
+    credit_note: "Gutschrift",
+    credit_for: "Gutschrift zu Rechnung",
+    estimate: "Kostenvoranschlag",
+    valid_until: "Gültig bis",
+    draft: "ENTWURF",
+    issue_date: "Rechnungsdatum",
+    due_date: "Fällig am",
+    bill_from: "Rechnungssteller",
+    bill_to: "Rechnungsempfänger",
+    tax_id: "Steuernummer",
+    email: "E-Mail",
+    phone: "Telefon",
+    date: "Datum",
+    description: "Beschreibung",
+    quantity: "Menge",
+    unit_price: "Einzelpreis",
+    amount: "Betrag",
+    carried_forward: "Übertrag",
+    brought_forward: "Übertrag",
+    subtotal: "ZWISCHENSUMME",
+    total: "GESAMT",
+    retainer: "Stundenkontingent",
+    purchased: "Gekauft",
+    used: "Verbraucht",
+    remaining: "Verbleibend",
+    payment_details: "Zahlungsinformationen",
+    account_holder: "Kontoinhaber",
+    bank: "Bank",
+    account_number: "Kontonummer",
+    scan_to_pay: "Zum Bezahlen scannen",
+    terms: "Bedingungen",
+    notes: "Anmerkungen",
+    page: "Seite",
+    page_of: "von",
+    hours_worked: "Geleistete Stunden",
+    fixed_fee: "Pauschale",
+    fixed_fee_billed: "Pauschale bereits berechnet",
+    tracked: "erfasst",
+    timesheet: "Stundennachweis",
+    time: "Zeit",
+    project: "Projekt",
+    hours: "Stunden",
+    day_total: "Tagessumme",
+    submitted_by: "Eingereicht von",
+    approved_by: "Freigegeben von",
+    name: "Name",
+    signature: "Unterschrift",
+    rounding_note: "Stunden {direction} auf {minutes}-Minuten-Schritte {scope}.",
+    rounded_up: "aufgerundet",
+    rounded_down: "abgerundet",
+    rounded_nearest: "gerundet",
+    per_line: "je Position",
+    on_total: "auf die Summe",
+    rounding_adjustment: "Rundungsausgleich",
+    date_format: "%d.%m.%Y",
+};
+
+const FR: InvoiceLabels = InvoiceLabels {
+    invoice: "Facture",
+    credit_note: "Avoir",
+    credit_for: "Avoir sur la facture",
+    estimate: "Devis",
+    valid_until: "Valable jusqu'au",
+    draft: "BROUILLON",
+    issue_date: "Date d'émission",
+    due_date: "Date d'échéance",
+    bill_from: "Émetteur",
+    bill_to: "Facturé à",
+    tax_id: "N° de TVA",
+    email: "E-mail",
+    phone: "Téléphone",
+    date: "Date",
+    description: "Description",
+    quantity: "Quantité",
+    unit_price: "Prix unitaire",
+    amount: "Montant",
+    carried_forward: "À reporter",
+    brought_forward: "Report",
+    subtotal: "SOUS-TOTAL",
+    total: "TOTAL",
+    retainer: "Forfait d'heures",
+    purchased: "Achetées",
+    used: "Utilisées",
+    remaining: "Restantes",
+    payment_details: "Coordonnées bancaires",
+    account_holder: "Titulaire du compte",
+    bank: "Banque",
+    account_number: "Numéro de compte",
+    scan_to_pay: "Scanner pour payer",
+    terms: "Conditions",
+    notes: "Remarques",
+    page: "Page",
+    page_of: "sur",
+    hours_worked: "Heures travaillées",
+    fixed_fee: "forfait",
+    fixed_fee_billed: "forfait déjà facturé",
+    tracked: "suivies",
+    timesheet: "Relevé d'heures",
+    time: "Heure",
+    project: "Projet",
+    hours: "Heures",
+    day_total: "Total du jour",
+    submitted_by: "Soumis par",
+    approved_by: "Approuvé par",
+    name: "Nom",
+    signature: "Signature",
+    rounding_note: "Heures {direction} par tranches de {minutes} minutes {scope}.",
+    rounded_up: "arrondies au supérieur",
+    rounded_down: "arrondies à l'inférieur",
+    rounded_nearest: "arrondies",
+    per_line: "par ligne",
+    on_total: "sur le total",
+    rounding_adjustment: "Ajustement d'arrondi",
+    date_format: "%d/%m/%Y",
+};
+
+const ES: InvoiceLabels = InvoiceLabels {
+    invoice: "Factura",
+    credit_note: "Nota de crédito",
+    credit_for: "Abono de la factura",
+    estimate: "Presupuesto",
+    valid_until: "Válido hasta",
+    draft: "BORRADOR",
+    issue_date: "Fecha de emisión",
+    due_date: "Fecha de vencimiento",
+    bill_from: "Emisor",
+    bill_to: "Facturar a",
+    tax_id: "NIF",
+    email: "Correo",
+    phone: "Teléfono",
+    date: "Fecha",
+    description: "Descripción",
+    quantity: "Cantidad",
+    unit_price: "Precio unitario",
+    amount: "Importe",
+    carried_forward: "Suma y sigue",
+    brought_forward: "Suma anterior",
+    subtotal: "SUBTOTAL",
+    total: "TOTAL",
+    retainer: "Bolsa de horas",
+    purchased: "Compradas",
+    used: "Usadas",
+    remaining: "Restantes",
+    payment_details: "Datos de pago",
+    account_holder: "Titular de la cuenta",
+    bank: "Banco",
+    account_number: "Número de cuenta",
+    scan_to_pay: "Escanear para pagar",
+    terms: "Condiciones",
+    notes: "Notas",
+    page: "Página",
+    page_of: "de",
+    hours_worked: "Horas trabajadas",
+    fixed_fee: "tarifa fija",
+    fixed_fee_billed: "tarifa fija ya facturada",
+    tracked: "registradas",
+    timesheet: "Registro de horas",
+    time: "Hora",
+    project: "Proyecto",
+    hours: "Horas",
+    day_total: "Total del día",
+    submitted_by: "Presentado por",
+    approved_by: "Aprobado por",
+    name: "Nombre",
+    signature: "Firma",
+    rounding_note: "Horas {direction} en intervalos de {minutes} minutos {scope}.",
+    rounded_up: "redondeadas hacia arriba",
+    rounded_down: "redondeadas hacia abajo",
+    rounded_nearest: "redondeadas",
+    per_line: "por línea",
+    on_total: "sobre el total",
+    rounding_adjustment: "Ajuste por redondeo",
+    date_format: "%d/%m/%Y",
+};
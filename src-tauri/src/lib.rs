@@ -1,18 +1,54 @@
-use std::{fs, io, path::PathBuf, sync::Mutex};
+use std::{fs, io, path::{Path, PathBuf}, sync::{Mutex, OnceLock}, time::Instant};
 
-use chrono::{Duration, Local, LocalResult, TimeZone, Utc};
-use rusqlite::{params, Connection};
+use chrono::{Datelike, Duration, FixedOffset, Local, LocalResult, NaiveDate, Offset, TimeZone, Utc};
+use rusqlite::{params, params_from_iter, types::Value as SqlValue, Connection};
 use serde::{Deserialize, Serialize};
 use tauri::{
     image::Image,
     menu::{MenuBuilder, MenuItemBuilder},
     tray::{TrayIconBuilder, TrayIconEvent},
-    AppHandle, Emitter, Manager, Runtime, WindowEvent,
+    AppHandle, Emitter, Manager, RunEvent, Runtime, WindowEvent,
 };
+use tauri_plugin_notification::NotificationExt;
 use tauri_plugin_sql::{Migration, MigrationKind};
 use tauri_plugin_opener::OpenerExt;
 
+mod archive;
+mod audit;
+mod backup;
+mod bank_account;
+mod budgets;
+mod clients;
+mod credit_notes;
+mod data_export;
+mod email;
+mod entries;
+mod estimates;
+mod idle;
+mod invoice_labels;
+mod overdue;
 mod pdf_generator;
+mod pdf_raster;
+mod profiles;
+mod projects;
+mod qr_code;
+mod quick_add;
+mod recovery;
+mod recurring;
+mod recurring_invoices;
+mod reminders;
+mod reports;
+mod retainers;
+mod segments;
+mod settings;
+mod signing;
+mod spreadsheet;
+mod suspend;
+mod templates;
+mod thumbnails;
+mod timesheets;
+mod ubl;
+mod undo;
 
 const DB_FILE_NAME: &str = "time_tracker.db";
 const DB_URL: &str = "sqlite:time_tracker.db";
@@ -20,10 +56,53 @@ const TRAY_ID: &str = "time-tracker-tray";
 const MENU_STATUS_ID: &str = "status";
 const MENU_START_ID: &str = "start-timer";
 const MENU_STOP_ID: &str = "stop-timer";
+const MENU_PAUSE_ID: &str = "pause-timer";
+const MENU_BREAK_ID: &str = "start-break";
 const MENU_TOGGLE_WINDOW_ID: &str = "toggle-window";
 const MENU_QUIT_ID: &str = "quit";
 const MENU_TOTAL_ID: &str = "total-today";
+const FALLBACK_TRAY_PROJECT: &str = "Quick Task";
+/// Project name recorded on break entries; breaks get no project row.
+const BREAK_PROJECT: &str = "Break";
+const ENTRY_TYPE_WORK: &str = "work";
+const ENTRY_TYPE_BREAK: &str = "break";
+const INVOICE_TYPE_INVOICE: &str = "invoice";
+const INVOICE_TYPE_CREDIT_NOTE: &str = "credit_note";
+const INVOICE_STATUS_ISSUED: &str = "issued";
+const INVOICE_STATUS_SENT: &str = "sent";
+const INVOICE_STATUS_PAID: &str = "paid";
 const TIMER_STATUS_EVENT: &str = "timer://status";
+const TIMERS_EVENT: &str = "timer://timers";
+const TIMER_STOPPED_EVENT: &str = "timer://stopped";
+const TIMER_DISCARDED_EVENT: &str = "timer://discarded";
+const CLOCK_JUMP_EVENT: &str = "timer://clock-jump";
+/// Wall-clock drift from `continuous_seconds` that counts as a clock change.
+const CLOCK_JUMP_TOLERANCE_SECONDS: i64 = 120;
+const ENTRY_FOCUS_EVENT: &str = "entry://focus";
+const MAX_NOTES_LENGTH: usize = 2000;
+const DEFAULT_PAGE_SIZE: i64 = 100;
+const MAX_PAGE_SIZE: i64 = 500;
+/// Entries longer than this are flagged unless the setting says otherwise.
+const DEFAULT_MAX_ENTRY_HOURS: f64 = 12.0;
+/// UTC-12:00 through UTC+14:00 are the extremes in use.
+const MAX_UTC_OFFSET_MINUTES: i32 = 14 * 60;
+const BACKGROUND_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+const TIME_ENTRY_COLUMNS: &str = "id, project_name, start_time, end_time, duration, hourly_rate, amount, notes,
+     (SELECT color FROM projects WHERE projects.name = time_entries.project_name),
+     (SELECT icon FROM projects WHERE projects.name = time_entries.project_name),
+     raw_duration,
+     COALESCE(
+         (SELECT COALESCE(p.currency, c.currency) FROM projects p
+          LEFT JOIN clients c ON c.id = p.client_id
+          WHERE p.name = time_entries.project_name),
+         'USD'
+     ),
+     entry_type,
+     billable,
+     starred,
+     utc_offset,
+     invoice_id";
+const INVOICE_COLUMNS: &str = "id, created_at, business_info, bill_to_info, total_hours, total_amount, file_path, entry_count, client_id, currency, due_date, payment_terms, invoice_type, credited_invoice_id, status, sent_at, paid_at";
 const CREATE_TIME_ENTRIES_TABLE_SQL: &str = r#"
     CREATE TABLE IF NOT EXISTS time_entries (
         id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -32,10 +111,19 @@ const CREATE_TIME_ENTRIES_TABLE_SQL: &str = r#"
         end_time INTEGER NOT NULL,
         duration INTEGER NOT NULL,
         hourly_rate REAL NOT NULL DEFAULT 0,
-        amount REAL NOT NULL DEFAULT 0
+        amount REAL NOT NULL DEFAULT 0,
+        notes TEXT,
+        raw_duration INTEGER,
+        entry_type TEXT NOT NULL DEFAULT 'work',
+        billable INTEGER NOT NULL DEFAULT 1,
+        starred INTEGER NOT NULL DEFAULT 0,
+        utc_offset INTEGER,
+        invoice_id INTEGER REFERENCES invoices(id)
     )
 "#;
 
+/// Single-timer table from before concurrent timers; only kept for the
+/// original migration and moved into `active_timers` on open.
 const CREATE_ACTIVE_TIMER_TABLE_SQL: &str = r#"
     CREATE TABLE IF NOT EXISTS active_timer (
         id INTEGER PRIMARY KEY CHECK (id = 1),
@@ -45,6 +133,22 @@ const CREATE_ACTIVE_TIMER_TABLE_SQL: &str = r#"
     )
 "#;
 
+const CREATE_ACTIVE_TIMERS_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS active_timers (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        project_name TEXT NOT NULL,
+        start_time INTEGER NOT NULL,
+        hourly_rate REAL NOT NULL DEFAULT 0,
+        notes TEXT,
+        paused_at INTEGER,
+        paused_seconds INTEGER NOT NULL DEFAULT 0,
+        target_seconds INTEGER,
+        last_heartbeat INTEGER,
+        is_break INTEGER NOT NULL DEFAULT 0,
+        utc_offset INTEGER
+    )
+"#;
+
 const CREATE_INVOICES_TABLE_SQL: &str = r#"
     CREATE TABLE IF NOT EXISTS invoices (
         id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -54,11 +158,40 @@ const CREATE_INVOICES_TABLE_SQL: &str = r#"
         total_hours REAL NOT NULL,
         total_amount REAL NOT NULL,
         file_path TEXT NOT NULL,
-        entry_count INTEGER NOT NULL
+        entry_count INTEGER NOT NULL,
+        client_id INTEGER REFERENCES clients(id),
+        currency TEXT NOT NULL DEFAULT 'USD',
+        due_date INTEGER,
+        payment_terms TEXT,
+        line_items TEXT,
+        language TEXT,
+        period_start INTEGER,
+        period_end INTEGER,
+        invoice_type TEXT NOT NULL DEFAULT 'invoice',
+        credited_invoice_id INTEGER REFERENCES invoices(id),
+        status TEXT NOT NULL DEFAULT 'issued',
+        sent_at INTEGER,
+        paid_at INTEGER,
+        timesheet INTEGER NOT NULL DEFAULT 0,
+        rounding TEXT,
+        line_mode TEXT,
+        group_by TEXT,
+        entry_filter TEXT
     )
 "#;
 
-#[derive(Debug, Serialize)]
+/// Every invoice an entry was billed on. `time_entries.invoice_id` holds the
+/// latest; this keeps the earlier ones of entries invoiced again, so deleting
+/// the later invoice links them back.
+const CREATE_INVOICE_ENTRIES_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS invoice_entries (
+        invoice_id INTEGER NOT NULL REFERENCES invoices(id),
+        entry_id INTEGER NOT NULL REFERENCES time_entries(id),
+        PRIMARY KEY (invoice_id, entry_id)
+    )
+"#;
+
+#[derive(Debug, Clone, Serialize)]
 pub struct TimeEntry {
     pub id: i64,
     pub project_name: String,
@@ -67,6 +200,41 @@ pub struct TimeEntry {
     pub duration: i64,
     pub hourly_rate: f64,
     pub amount: f64,
+    pub notes: Option<String>,
+    pub project_color: Option<String>,
+    pub project_icon: Option<String>,
+    /// Tracked duration before project rounding; `None` when not rounded.
+    pub raw_duration: Option<i64>,
+    /// The project's currency, falling back to its client's and then USD.
+    pub currency: String,
+    /// "work" or "break"; breaks are left out of billable totals and invoices.
+    pub entry_type: String,
+    /// Non-billable work is tracked but never invoiced.
+    pub billable: bool,
+    /// Flagged as a milestone or handoff worth finding again.
+    pub starred: bool,
+    /// Minutes east of UTC where the entry was tracked; `None` for entries
+    /// from before offsets were stored, which follow the device's zone.
+    pub utc_offset: Option<i32>,
+    /// The invoice that billed this entry; invoiced entries are left out of
+    /// later invoices.
+    pub invoice_id: Option<i64>,
+}
+
+impl From<TimeEntry> for pdf_generator::TimeEntry {
+    fn from(e: TimeEntry) -> Self {
+        pdf_generator::TimeEntry {
+            id: e.id,
+            project_name: e.project_name,
+            currency: e.currency,
+            start_time: e.start_time,
+            end_time: e.end_time,
+            duration: e.duration,
+            hourly_rate: e.hourly_rate,
+            amount: e.amount,
+            notes: e.notes,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,10 +244,22 @@ pub struct BusinessInfo {
     pub address: Option<String>,
     pub email: Option<String>,
     pub phone: Option<String>,
+    pub tax_id: Option<String>,
+    pub bank_details: Option<String>,
     pub client_name: Option<String>,
     pub client_address: Option<String>,
     pub client_email: Option<String>,
     pub client_phone: Option<String>,
+    /// Two-letter ISO 3166-1 codes of the business's and the client's
+    /// countries, which e-invoices require in both addresses.
+    pub country: Option<String>,
+    pub client_country: Option<String>,
+    /// Structured account details, printed above the free-text `bank_details`.
+    pub bank_account: Option<bank_account::BankAccount>,
+    /// Printed in the invoice's Notes section.
+    pub notes: Option<String>,
+    /// This invoice's own terms, printed ahead of the client's and projects'.
+    pub terms: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -93,27 +273,179 @@ pub struct Invoice {
     pub total_amount: f64,
     pub file_path: String,
     pub entry_count: i64,
+    pub client_id: Option<i64>,
+    pub currency: String,
+    /// When payment is due; `None` for invoices issued without terms.
+    pub due_date: Option<i64>,
+    /// Payment terms printed on the invoice, e.g. "Net 30".
+    pub payment_terms: Option<String>,
+    /// "invoice" or "credit_note"; credit notes have negative totals.
+    pub invoice_type: String,
+    /// The invoice a credit note credits.
+    pub credited_invoice_id: Option<i64>,
+    /// "issued", "sent" once emailed from the app, or "paid".
+    pub status: String,
+    /// When it was last emailed.
+    pub sent_at: Option<i64>,
+    pub paid_at: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize)]
 struct TimerStatusPayload {
+    timer_id: Option<i64>,
     is_running: bool,
     project_name: Option<String>,
     start_time: Option<i64>,
     elapsed_seconds: Option<i64>,
     hourly_rate: Option<f64>,
+    project_color: Option<String>,
+    project_icon: Option<String>,
+    notes: Option<String>,
+    is_paused: bool,
+    /// Time spent paused so far, already excluded from `elapsed_seconds`.
+    paused_seconds: Option<i64>,
+    /// Countdown length for target-duration timers.
+    target_seconds: Option<i64>,
+    remaining_seconds: Option<i64>,
+    /// Amount accrued so far at the timer's rate, in `currency`.
+    earned_amount: Option<f64>,
+    currency: Option<String>,
+    is_break: bool,
+}
+
+impl TimerStatusPayload {
+    fn idle() -> Self {
+        TimerStatusPayload {
+            timer_id: None,
+            is_running: false,
+            project_name: None,
+            start_time: None,
+            elapsed_seconds: None,
+            hourly_rate: None,
+            project_color: None,
+            project_icon: None,
+            notes: None,
+            is_paused: false,
+            paused_seconds: None,
+            target_seconds: None,
+            remaining_seconds: None,
+            earned_amount: None,
+            currency: None,
+            is_break: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct StoppedTimer {
+    #[serde(flatten)]
+    entry: TimeEntry,
+    needs_notes: bool,
+    /// Entries for earlier days when the timer ran past midnight.
+    earlier_entries: Vec<TimeEntry>,
+}
+
+/// Sent when a stopped timer was too short to keep.
+#[derive(Debug, Clone, Serialize)]
+struct TimerDiscardedPayload {
+    project_name: String,
+    duration: i64,
+    min_entry_seconds: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TimerStoppedPayload {
+    entry_id: i64,
+    needs_notes: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct EntryFocusPayload {
+    entry_id: i64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum StopOrigin {
+    Window,
+    Tray,
 }
 
 #[derive(Clone)]
 struct ActiveTimer {
+    /// Row id in `active_timers`.
+    id: i64,
     project_name: String,
     start_time: i64,
     hourly_rate: f64,
+    project_color: Option<String>,
+    project_icon: Option<String>,
+    notes: Option<String>,
+    /// Set while the timer is paused.
+    paused_at: Option<i64>,
+    /// Length of completed pauses.
+    paused_seconds: i64,
+    /// Tracked time after which the user is told the target was reached.
+    target_seconds: Option<i64>,
+    /// The project's billing currency, looked up like its color and icon.
+    currency: String,
+    is_break: bool,
+    /// Minutes east of UTC when the timer started; `None` for timers
+    /// restored from before offsets were stored.
+    utc_offset: Option<i32>,
+}
+
+impl ActiveTimer {
+    fn status_payload(&self) -> TimerStatusPayload {
+        let now = current_unix_timestamp();
+        let worked = self.worked_seconds(now);
+        TimerStatusPayload {
+            timer_id: Some(self.id),
+            is_running: true,
+            project_name: Some(self.project_name.clone()),
+            start_time: Some(self.start_time),
+            elapsed_seconds: Some(worked),
+            hourly_rate: Some(self.hourly_rate),
+            project_color: self.project_color.clone(),
+            project_icon: self.project_icon.clone(),
+            notes: self.notes.clone(),
+            is_paused: self.paused_at.is_some(),
+            paused_seconds: Some(self.total_paused_seconds(now)),
+            target_seconds: self.target_seconds,
+            remaining_seconds: self.target_seconds.map(|target| (target - worked).max(0)),
+            earned_amount: Some(calculate_amount(worked, self.hourly_rate)),
+            currency: Some(self.currency.clone()),
+            is_break: self.is_break,
+        }
+    }
+
+    /// Paused time up to `now`, including a pause still in progress.
+    fn total_paused_seconds(&self, now: i64) -> i64 {
+        let current = self.paused_at.map(|at| (now - at).max(0)).unwrap_or(0);
+        self.paused_seconds + current
+    }
+
+    fn worked_seconds(&self, now: i64) -> i64 {
+        (now - self.start_time - self.total_paused_seconds(now)).max(0)
+    }
+}
+
+/// Sent when the system clock was set forwards or backwards under running
+/// timers and they were shifted to keep their elapsed time.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct ClockJumpPayload {
+    /// How far the timers' starts moved: positive when the clock was set
+    /// forwards, negative when it went back.
+    jump_seconds: i64,
+    timer_ids: Vec<i64>,
 }
 
+/// Running timers, oldest first. The newest one is the "primary" timer that
+/// commands without a timer id act on.
 #[derive(Default)]
 struct TimerInner {
-    active: Option<ActiveTimer>,
+    timers: Vec<ActiveTimer>,
+    /// `continuous_seconds` and wall-clock readings from the last tick.
+    last_clock: Option<(i64, i64)>,
 }
 
 #[derive(Default)]
@@ -121,71 +453,321 @@ struct TimerState {
     inner: Mutex<TimerInner>,
 }
 
+impl TimerInner {
+    /// Compares wall-clock progress with `continuous` since the last tick.
+    /// When the wall clock was set (NTP, a manual change) either way, timers
+    /// are shifted by the same amount so elapsed time carries on, though
+    /// never to start after `now`; a start still in the future is clamped
+    /// to now. Time asleep moves both clocks, so it isn't a jump.
+    fn correct_clock(&mut self, now: i64, continuous: i64) -> Option<ClockJumpPayload> {
+        let previous = self.last_clock.replace((continuous, now));
+        let jump = previous
+            .map(|(then, then_wall)| (now - then_wall) - (continuous - then))
+            .filter(|jump| jump.abs() > CLOCK_JUMP_TOLERANCE_SECONDS)
+            .unwrap_or(0);
+
+        let mut largest = 0i64;
+        let mut timer_ids = Vec::new();
+        for timer in &mut self.timers {
+            let shift = if jump > 0 {
+                jump.min(now - timer.start_time).max(0)
+            } else {
+                jump.min(now - timer.start_time)
+            };
+            if shift == 0 {
+                continue;
+            }
+            timer.start_time += shift;
+            if let Some(paused_at) = timer.paused_at.as_mut() {
+                *paused_at = (*paused_at + shift).min(now);
+            }
+            if shift.abs() > largest.abs() {
+                largest = shift;
+            }
+            timer_ids.push(timer.id);
+        }
+        (!timer_ids.is_empty()).then_some(ClockJumpPayload {
+            jump_seconds: largest,
+            timer_ids,
+        })
+    }
+
+    fn find_mut(&mut self, timer_id: Option<i64>) -> Result<&mut ActiveTimer, String> {
+        match timer_id {
+            Some(id) => self
+                .timers
+                .iter_mut()
+                .find(|timer| timer.id == id)
+                .ok_or_else(|| "Timer not found".to_string()),
+            None => self
+                .timers
+                .last_mut()
+                .ok_or_else(|| "No timer is currently running".to_string()),
+        }
+    }
+}
+
 impl TimerState {
+    /// Status of the primary timer, or an idle payload when none is running.
     fn status(&self) -> TimerStatusPayload {
         let guard = self.inner.lock().expect("timer state poisoned");
-        if let Some(active) = &guard.active {
-            let elapsed = (current_unix_timestamp() - active.start_time).max(0);
-            TimerStatusPayload {
-                is_running: true,
-                project_name: Some(active.project_name.clone()),
-                start_time: Some(active.start_time),
-                elapsed_seconds: Some(elapsed),
-                hourly_rate: Some(active.hourly_rate),
-            }
-        } else {
-            TimerStatusPayload {
-                is_running: false,
-                project_name: None,
-                start_time: None,
-                elapsed_seconds: None,
-                hourly_rate: None,
+        guard
+            .timers
+            .last()
+            .map(ActiveTimer::status_payload)
+            .unwrap_or_else(TimerStatusPayload::idle)
+    }
+
+    fn statuses(&self) -> Vec<TimerStatusPayload> {
+        let guard = self.inner.lock().expect("timer state poisoned");
+        guard.timers.iter().map(ActiveTimer::status_payload).collect()
+    }
+
+    /// Refuses a second timer on a project that already has one running. A
+    /// break isn't a project, even when a project shares its name.
+    fn ensure_can_start(&self, project_name: &str) -> Result<(), String> {
+        let guard = self
+            .inner
+            .lock()
+            .map_err(|_| "Timer state is unavailable")?;
+        if guard
+            .timers
+            .iter()
+            .any(|timer| !timer.is_break && timer.project_name == project_name)
+        {
+            return Err(format!("A timer is already running for {}", project_name));
+        }
+        Ok(())
+    }
+
+    /// Pauses a running timer and returns its new state for persisting.
+    fn pause(&self, timer_id: Option<i64>, now: i64) -> Result<ActiveTimer, String> {
+        let mut guard = self.inner.lock().map_err(|_| "Timer state is unavailable")?;
+        let active = guard.find_mut(timer_id)?;
+        if active.paused_at.is_some() {
+            return Err("The timer is already paused".into());
+        }
+        active.paused_at = Some(now);
+        Ok(active.clone())
+    }
+
+    fn resume(&self, timer_id: Option<i64>, now: i64) -> Result<ActiveTimer, String> {
+        let mut guard = self.inner.lock().map_err(|_| "Timer state is unavailable")?;
+        let active = guard.find_mut(timer_id)?;
+        let Some(paused_at) = active.paused_at.take() else {
+            return Err("The timer is not paused".into());
+        };
+        active.paused_seconds += (now - paused_at).max(0);
+        Ok(active.clone())
+    }
+
+    /// Leaves `from..to` out of a timer's tracked time, as if it had been
+    /// paused then. Time already inside the current pause is not counted twice.
+    fn discard_span(&self, timer_id: i64, from: i64, to: i64) -> Result<ActiveTimer, String> {
+        let mut guard = self.inner.lock().map_err(|_| "Timer state is unavailable")?;
+        let active = guard.find_mut(Some(timer_id))?;
+        let from = from.max(active.start_time);
+        let to = active.paused_at.map_or(to, |paused_at| to.min(paused_at));
+        active.paused_seconds += (to - from).max(0);
+        Ok(active.clone())
+    }
+
+    /// Pauses every running work timer for a break; returns them for persisting.
+    fn pause_for_break(&self, now: i64) -> Vec<ActiveTimer> {
+        let mut guard = self.inner.lock().expect("timer state poisoned");
+        guard
+            .timers
+            .iter_mut()
+            .filter(|timer| !timer.is_break && timer.paused_at.is_none())
+            .map(|timer| {
+                timer.paused_at = Some(now);
+                timer.clone()
+            })
+            .collect()
+    }
+
+    /// Resumes the timers a break paused: those paused the moment it began.
+    fn resume_after_break(&self, break_start: i64, now: i64) -> Vec<ActiveTimer> {
+        let mut guard = self.inner.lock().expect("timer state poisoned");
+        guard
+            .timers
+            .iter_mut()
+            .filter(|timer| timer.paused_at == Some(break_start))
+            .map(|timer| {
+                timer.paused_at = None;
+                timer.paused_seconds += (now - break_start).max(0);
+                timer.clone()
+            })
+            .collect()
+    }
+
+    /// Applies an edit to a running timer and returns it for persisting.
+    fn apply_edit(&self, timer_id: Option<i64>, edit: TimerEdit) -> Result<ActiveTimer, String> {
+        let mut guard = self.inner.lock().map_err(|_| "Timer state is unavailable")?;
+        if let Some(project) = &edit.project {
+            let editing = guard.find_mut(timer_id)?.id;
+            if guard
+                .timers
+                .iter()
+                .any(|timer| {
+                    timer.id != editing && !timer.is_break && timer.project_name == project.name
+                })
+            {
+                return Err(format!("A timer is already running for {}", project.name));
             }
         }
+
+        let active = guard.find_mut(timer_id)?;
+        if active.is_break && (edit.project.is_some() || edit.hourly_rate.is_some()) {
+            return Err("A break has no project or rate to change".into());
+        }
+        if let Some(project) = edit.project {
+            active.project_name = project.name;
+            active.project_color = project.color;
+            active.project_icon = project.icon;
+            active.currency = project.currency;
+        }
+        if let Some(rate) = edit.hourly_rate {
+            active.hourly_rate = rate;
+        }
+        if let Some(notes) = edit.notes {
+            active.notes = notes;
+        }
+        Ok(active.clone())
     }
 
-    fn start(
-        &self,
-        project_name: String,
-        start_time: i64,
-        hourly_rate: f64,
-    ) -> Result<TimerStatusPayload, String> {
+    /// Takes a pending clock correction along with the timers it changed.
+    /// Runs the clock check and returns the correction with the timers it
+    /// shifted, for persisting.
+    fn check_clock(&self) -> Option<(ClockJumpPayload, Vec<ActiveTimer>)> {
+        let mut guard = self.inner.lock().expect("timer state poisoned");
+        let jump = guard.correct_clock(current_unix_timestamp(), continuous_seconds())?;
+        let timers = guard
+            .timers
+            .iter()
+            .filter(|timer| jump.timer_ids.contains(&timer.id))
+            .cloned()
+            .collect();
+        Some((jump, timers))
+    }
+
+    fn is_on_break(&self) -> bool {
+        let guard = self.inner.lock().expect("timer state poisoned");
+        guard.timers.iter().any(|timer| timer.is_break)
+    }
+
+    fn start(&self, timer: ActiveTimer) -> Result<TimerStatusPayload, String> {
         let mut guard = self
             .inner
             .lock()
             .map_err(|_| "Timer state is unavailable")?;
-        if guard.active.is_some() {
-            return Err("A timer is already running".into());
+        if guard
+            .timers
+            .iter()
+            .any(|running| {
+                running.is_break == timer.is_break && running.project_name == timer.project_name
+            })
+        {
+            return Err(format!("A timer is already running for {}", timer.project_name));
         }
 
-        guard.active = Some(ActiveTimer {
-            project_name: project_name.clone(),
-            start_time,
-            hourly_rate,
-        });
+        let status = timer.status_payload();
+        guard.timers.push(timer);
+        Ok(status)
+    }
 
-        Ok(TimerStatusPayload {
-            is_running: true,
-            project_name: Some(project_name),
-            start_time: Some(start_time),
-            elapsed_seconds: Some(0),
-            hourly_rate: Some(hourly_rate),
-        })
+    /// Removes and returns a timer; `None` picks the primary timer.
+    fn take(&self, timer_id: Option<i64>) -> Option<ActiveTimer> {
+        let mut guard = self.inner.lock().expect("timer state poisoned");
+        let index = match timer_id {
+            Some(id) => guard.timers.iter().position(|timer| timer.id == id)?,
+            None => guard.timers.len().checked_sub(1)?,
+        };
+        Some(guard.timers.remove(index))
     }
 
-    fn take_active(&self) -> Option<ActiveTimer> {
+    fn restore(&self, timers: Vec<ActiveTimer>) {
         let mut guard = self.inner.lock().expect("timer state poisoned");
-        guard.active.take()
+        guard.timers = timers;
     }
 
-    fn clear(&self) {
+    /// Moves timers running on `from` onto `project`; returns true if any moved.
+    fn retarget(&self, from: &str, project: &projects::Project) -> bool {
         let mut guard = self.inner.lock().expect("timer state poisoned");
-        guard.active = None;
+        let mut changed = false;
+        for active in guard
+            .timers
+            .iter_mut()
+            .filter(|timer| !timer.is_break && timer.project_name == from)
+        {
+            active.project_name = project.name.clone();
+            active.project_color = project.color.clone();
+            active.project_icon = project.icon.clone();
+            active.currency = project.effective_currency.clone();
+            changed = true;
+        }
+        changed
     }
 
-    fn restore(&self, timer: ActiveTimer) {
+    /// Returns true when a running timer belongs to `project_name` and was updated.
+    fn update_appearance(
+        &self,
+        project_name: &str,
+        color: Option<String>,
+        icon: Option<String>,
+    ) -> bool {
         let mut guard = self.inner.lock().expect("timer state poisoned");
-        guard.active = Some(timer);
+        let mut changed = false;
+        for active in guard
+            .timers
+            .iter_mut()
+            .filter(|timer| !timer.is_break && timer.project_name == project_name)
+        {
+            active.project_color = color.clone();
+            active.project_icon = icon.clone();
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// Changes for `update_active_timer`; `None` fields stay as they are.
+struct TimerEdit {
+    project: Option<TimerProject>,
+    hourly_rate: Option<f64>,
+    notes: Option<Option<String>>,
+}
+
+struct TimerProject {
+    name: String,
+    color: Option<String>,
+    icon: Option<String>,
+    currency: String,
+}
+
+/// Entry the user was asked to annotate from a tray notification; the window
+/// focuses it the next time it gains focus.
+#[derive(Default)]
+struct NotesPromptState {
+    pending_entry: Mutex<Option<i64>>,
+}
+
+impl NotesPromptState {
+    fn set_pending(&self, entry_id: i64) {
+        let mut guard = self.pending_entry.lock().expect("notes prompt state poisoned");
+        *guard = Some(entry_id);
+    }
+
+    fn take_pending(&self) -> Option<i64> {
+        let mut guard = self.pending_entry.lock().expect("notes prompt state poisoned");
+        guard.take()
+    }
+
+    fn clear_if(&self, entry_id: i64) {
+        let mut guard = self.pending_entry.lock().expect("notes prompt state poisoned");
+        if *guard == Some(entry_id) {
+            *guard = None;
+        }
     }
 }
 
@@ -207,6 +789,16 @@ impl TrayAssets {
 struct TodayTotals {
     total_seconds: i64,
     total_amount: f64,
+    /// `total_amount` split by currency, for when projects bill in several.
+    currency_totals: Vec<CurrencyTotal>,
+    /// Break time, kept out of `total_seconds`.
+    break_seconds: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct CurrencyTotal {
+    currency: String,
+    amount: f64,
 }
 
 #[tauri::command]
@@ -214,7 +806,8 @@ async fn initialize_database(app_handle: tauri::AppHandle) -> Result<(), String>
     let db_path = resolve_db_path(&app_handle)?;
     let _ = resolve_invoices_dir(&app_handle)?;
     tauri::async_runtime::spawn_blocking(move || {
-        let _ = open_connection(db_path)?;
+        let conn = open_connection(db_path)?;
+        projects::backfill_projects(&conn)?;
         Ok::<(), String>(())
     })
     .await
@@ -222,9 +815,12 @@ async fn initialize_database(app_handle: tauri::AppHandle) -> Result<(), String>
 }
 
 #[tauri::command]
-async fn get_today_entries(app_handle: tauri::AppHandle) -> Result<Vec<TimeEntry>, String> {
+async fn get_today_entries(
+    app_handle: tauri::AppHandle,
+    utc_offset: Option<i32>,
+) -> Result<Vec<TimeEntry>, String> {
     let db_path = resolve_db_path(&app_handle)?;
-    let (start_ts, end_ts) = day_bounds_timestamps()?;
+    let (start_ts, end_ts) = day_bounds_timestamps(utc_offset)?;
 
     tauri::async_runtime::spawn_blocking(move || {
         let conn = open_connection(db_path)?;
@@ -250,32 +846,106 @@ async fn get_entries_in_range(
     .map_err(|err| err.to_string())?
 }
 
+/// One page of a listing, newest first, and the row count across all pages.
+#[derive(Debug, Serialize)]
+struct Page<T> {
+    items: Vec<T>,
+    total: i64,
+    limit: i64,
+    offset: i64,
+}
+
+/// `get_entries_in_range` one page at a time.
+#[tauri::command]
+async fn get_entries_page(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Page<TimeEntry>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let (limit, offset) = page_bounds(limit, offset);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_entries_page(
+            &conn,
+            "start_time >= ? AND start_time < ?",
+            vec![start_time.into(), end_time.into()],
+            limit,
+            offset,
+        )
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Entries matching `where_sql` (with `?` placeholders bound to `values`),
+/// newest first.
+fn query_entries_page(
+    conn: &Connection,
+    where_sql: &str,
+    values: Vec<SqlValue>,
+    limit: i64,
+    offset: i64,
+) -> Result<Page<TimeEntry>, String> {
+    let total: i64 = conn
+        .query_row(
+            &format!("SELECT COUNT(*) FROM time_entries WHERE {where_sql}"),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        )
+        .map_err(|err| err.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {TIME_ENTRY_COLUMNS}
+             FROM time_entries
+             WHERE {where_sql}
+             ORDER BY start_time DESC, id DESC
+             LIMIT ? OFFSET ?"
+        ))
+        .map_err(|err| err.to_string())?;
+    let mut bound = values;
+    bound.push(limit.into());
+    bound.push(offset.into());
+    let items = stmt
+        .query_map(params_from_iter(bound), map_time_entry)
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+    Ok(Page {
+        items,
+        total,
+        limit,
+        offset,
+    })
+}
+
+/// Clamps page parameters to 1..=`MAX_PAGE_SIZE` rows from a non-negative offset.
+fn page_bounds(limit: Option<i64>, offset: Option<i64>) -> (i64, i64) {
+    (
+        limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE),
+        offset.unwrap_or(0).max(0),
+    )
+}
+
 fn query_entries_between(
     conn: &Connection,
     start_ts: i64,
     end_ts: i64,
 ) -> Result<Vec<TimeEntry>, String> {
     let mut stmt = conn
-        .prepare(
-            "SELECT id, project_name, start_time, end_time, duration, hourly_rate, amount
+        .prepare(&format!(
+            "SELECT {TIME_ENTRY_COLUMNS}
              FROM time_entries
              WHERE start_time >= ?1 AND start_time < ?2
-             ORDER BY start_time DESC",
-        )
+             ORDER BY start_time DESC"
+        ))
         .map_err(|err| err.to_string())?;
 
     let rows = stmt
-        .query_map(params![start_ts, end_ts], |row| {
-            Ok(TimeEntry {
-                id: row.get(0)?,
-                project_name: row.get(1)?,
-                start_time: row.get(2)?,
-                end_time: row.get(3)?,
-                duration: row.get(4)?,
-                hourly_rate: row.get(5)?,
-                amount: row.get(6)?,
-            })
-        })
+        .query_map(params![start_ts, end_ts], map_time_entry)
         .map_err(|err| err.to_string())?;
 
     let mut entries = Vec::new();
@@ -287,9 +957,12 @@ fn query_entries_between(
 }
 
 #[tauri::command]
-async fn get_today_total(app_handle: tauri::AppHandle) -> Result<TodayTotals, String> {
+async fn get_today_total(
+    app_handle: tauri::AppHandle,
+    utc_offset: Option<i32>,
+) -> Result<TodayTotals, String> {
     let db_path = resolve_db_path(&app_handle)?;
-    let (start_ts, end_ts) = day_bounds_timestamps()?;
+    let (start_ts, end_ts) = day_bounds_timestamps(utc_offset)?;
 
     tauri::async_runtime::spawn_blocking(move || {
         let conn = open_connection(db_path)?;
@@ -300,6 +973,8 @@ async fn get_today_total(app_handle: tauri::AppHandle) -> Result<TodayTotals, St
     .map_err(|err| err.to_string())?
 }
 
+/// `utc_offset` is the zone the entry was tracked in, in minutes east of
+/// UTC; the device's zone is used when it is omitted.
 #[tauri::command]
 async fn create_time_entry(
     app_handle: tauri::AppHandle,
@@ -307,108 +982,515 @@ async fn create_time_entry(
     start_time: i64,
     end_time: i64,
     hourly_rate: Option<f64>,
-) -> Result<TimeEntry, String> {
+    notes: Option<String>,
+    utc_offset: Option<i32>,
+) -> Result<UpdateResult, EntrySaveError> {
     if end_time <= start_time {
-        return Err("End time must be after start time".into());
+        return Err(String::from("End time must be after start time").into());
     }
 
     let db_path = resolve_db_path(&app_handle)?;
-    let sanitized_name = sanitize_project_name(project_name);
-    let rate = sanitize_hourly_rate(hourly_rate.unwrap_or(0.0));
+    let project_name = sanitize_project_name(project_name);
+    let (overlapping, warnings) = {
+        let db_path = db_path.clone();
+        let project_name = project_name.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let conn = open_connection(db_path)?;
+            let overlapping = enforce_no_overlap(&conn, 0, start_time, end_time)?;
+            let mut warnings = suspicious_entry_warnings(&conn, start_time, end_time)?;
+            // Re-imports tend to repeat rows exactly.
+            if let Some(id) =
+                entries::find_exact_duplicate(&conn, &project_name, start_time, end_time)?
+            {
+                warnings.push(EntryWarning {
+                    kind: EntryWarningKind::Duplicate,
+                    message: format!("Entry #{} already has this project, start and end", id),
+                });
+            }
+            Ok::<_, EntrySaveError>((overlapping, warnings))
+        })
+        .await
+        .map_err(|err| err.to_string())??
+    };
+    let new_entry = NewTimeEntry {
+        project_name,
+        start_time,
+        end_time,
+        hourly_rate: sanitize_hourly_rate(hourly_rate.unwrap_or(0.0)),
+        notes: sanitize_notes(notes)?,
+        paused_seconds: 0,
+        apply_rounding: false,
+        is_break: false,
+        billable: true,
+        utc_offset,
+        source: audit::EntrySource::Manual,
+    };
 
-    persist_time_entry(db_path, sanitized_name, start_time, end_time, rate).await
+    let entry = persist_time_entry(db_path, new_entry).await?;
+    undo::record_created(&app_handle, "Add entry", vec![entry.id]);
+    run_entry_checks(&app_handle, &entry.project_name);
+    Ok(UpdateResult {
+        entry,
+        overlap_warning: (!overlapping.is_empty()).then_some(OverlapWarning {
+            overlapping_entries: overlapping,
+        }),
+        warnings,
+    })
 }
 
-#[derive(Debug, Serialize)]
-struct UpdateResult {
-    entry: TimeEntry,
-    overlap_warning: Option<OverlapWarning>,
+/// Narrows the entries pulled into an invoice. Empty fields don't filter.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InvoiceEntryFilter {
+    client_id: Option<i64>,
+    project_names: Option<Vec<String>>,
+    /// Bill entries again even though an earlier invoice already did.
+    include_invoiced: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
-struct OverlapWarning {
-    overlapping_entries: Vec<TimeEntry>,
+/// How hourly work is itemised on an invoice: one "Hours worked" row, or
+/// one row per entry with its date and description.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum InvoiceLineMode {
+    #[default]
+    Summary,
+    Detailed,
 }
 
-#[tauri::command]
-async fn update_time_entry(
-    app_handle: tauri::AppHandle,
-    id: i64,
-    project_name: Option<String>,
-    hourly_rate: Option<f64>,
-    duration: Option<i64>,
-) -> Result<UpdateResult, String> {
-    let db_path = resolve_db_path(&app_handle)?;
+impl InvoiceLineMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            InvoiceLineMode::Summary => "summary",
+            InvoiceLineMode::Detailed => "detailed",
+        }
+    }
 
-    tauri::async_runtime::spawn_blocking(move || {
-        let conn = open_connection(db_path)?;
-        let current = fetch_time_entry(&conn, id)?;
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "summary" => Some(InvoiceLineMode::Summary),
+            "detailed" => Some(InvoiceLineMode::Detailed),
+            _ => None,
+        }
+    }
+}
 
-        let updated_name = project_name
-            .map(sanitize_project_name)
-            .unwrap_or_else(|| current.project_name.clone());
-        let updated_rate = hourly_rate
-            .map(sanitize_hourly_rate)
-            .unwrap_or(current.hourly_rate);
+/// Aggregates hourly work into one row per project, day or week (and per
+/// rate, so every row's hours times rate matches its amount).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum InvoiceGrouping {
+    Project,
+    Day,
+    Week,
+}
 
-        // Calculate new duration and end_time
-        let updated_duration = duration.unwrap_or(current.duration);
-        let updated_end_time = current.start_time + updated_duration;
-        let updated_amount = calculate_amount(updated_duration, updated_rate);
+impl InvoiceGrouping {
+    fn as_str(self) -> &'static str {
+        match self {
+            InvoiceGrouping::Project => "project",
+            InvoiceGrouping::Day => "day",
+            InvoiceGrouping::Week => "week",
+        }
+    }
 
-        // Check for overlapping entries (excluding current entry)
-        let overlapping = check_overlapping_entries(&conn, id, current.start_time, updated_end_time)?;
-        let overlap_warning = if !overlapping.is_empty() {
-            Some(OverlapWarning {
-                overlapping_entries: overlapping,
-            })
-        } else {
-            None
-        };
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "project" => Some(InvoiceGrouping::Project),
+            "day" => Some(InvoiceGrouping::Day),
+            "week" => Some(InvoiceGrouping::Week),
+            _ => None,
+        }
+    }
+}
 
-        conn.execute(
-            "UPDATE time_entries
-             SET project_name = ?1,
-                 hourly_rate = ?2,
-                 duration = ?3,
-                 end_time = ?4,
-                 amount = ?5
-             WHERE id = ?6",
-            params![updated_name, updated_rate, updated_duration, updated_end_time, updated_amount, id],
-        )
-        .map_err(|err| err.to_string())?;
+/// Rounds an invoice's billed hours to `incrementMinutes`, on every hourly
+/// row or once on their total, and notes the policy under the totals.
+/// Fixed-fee rows are left alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InvoiceRounding {
+    increment_minutes: u32,
+    /// Nearest when unset.
+    direction: Option<projects::RoundingDirection>,
+    #[serde(default)]
+    scope: InvoiceRoundingScope,
+}
 
-        let entry = TimeEntry {
-            id,
-            project_name: updated_name,
-            start_time: current.start_time,
-            end_time: updated_end_time,
-            duration: updated_duration,
-            hourly_rate: updated_rate,
-            amount: updated_amount,
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum InvoiceRoundingScope {
+    #[default]
+    Line,
+    /// The hourly rows are kept as tracked and one adjustment row at their
+    /// average rate rounds their total.
+    Total,
+}
+
+impl InvoiceRounding {
+    fn validate(&self) -> Result<(), String> {
+        if self.increment_minutes == 0
+            || i64::from(self.increment_minutes) > projects::MAX_ROUNDING_MINUTES
+        {
+            return Err(format!(
+                "Invoice rounding must be between 1 and {} minutes",
+                projects::MAX_ROUNDING_MINUTES
+            ));
+        }
+        Ok(())
+    }
+
+    fn direction(&self) -> projects::RoundingDirection {
+        self.direction.unwrap_or(projects::RoundingDirection::Nearest)
+    }
+
+    fn round_hours(&self, hours: f64) -> f64 {
+        let seconds = (hours * 3600.0).round() as i64;
+        projects::round_duration(seconds, i64::from(self.increment_minutes), self.direction())
+            as f64
+            / 3600.0
+    }
+
+    /// Rounds the hourly rows' quantities, recomputing their amounts at the
+    /// same unit price.
+    fn apply(
+        &self,
+        line_items: &mut Vec<pdf_generator::InvoiceLineItem>,
+        labels: &invoice_labels::InvoiceLabels,
+    ) {
+        match self.scope {
+            InvoiceRoundingScope::Line => {
+                for item in line_items
+                    .iter_mut()
+                    .filter(|item| !item.fixed_fee && item.quantity > 0.0)
+                {
+                    item.quantity = self.round_hours(item.quantity);
+                    item.amount = item.quantity * item.unit_price;
+                }
+            }
+            InvoiceRoundingScope::Total => {
+                let hourly = line_items.iter().filter(|item| !item.fixed_fee);
+                let (hours, amount) = hourly.fold((0.0, 0.0), |(hours, amount), item| {
+                    (hours + item.quantity, amount + item.amount)
+                });
+                if hours <= 0.0 {
+                    return;
+                }
+                let difference = self.round_hours(hours) - hours;
+                if difference.abs() < 1e-9 {
+                    return;
+                }
+                let rate = amount / hours;
+                let position = line_items
+                    .iter()
+                    .rposition(|item| !item.fixed_fee)
+                    .map_or(line_items.len(), |index| index + 1);
+                line_items.insert(
+                    position,
+                    pdf_generator::InvoiceLineItem {
+                        date: None,
+                        description: labels.rounding_adjustment.into(),
+                        quantity: difference,
+                        unit_price: rate,
+                        amount: difference * rate,
+                        fixed_fee: false,
+                    },
+                );
+            }
+        }
+    }
+
+    fn note(&self, labels: &invoice_labels::InvoiceLabels) -> String {
+        let direction = match self.direction() {
+            projects::RoundingDirection::Up => labels.rounded_up,
+            projects::RoundingDirection::Down => labels.rounded_down,
+            projects::RoundingDirection::Nearest => labels.rounded_nearest,
         };
+        let scope = match self.scope {
+            InvoiceRoundingScope::Line => labels.per_line,
+            InvoiceRoundingScope::Total => labels.on_total,
+        };
+        labels
+            .rounding_note
+            .replace("{direction}", direction)
+            .replace("{minutes}", &self.increment_minutes.to_string())
+            .replace("{scope}", scope)
+    }
+}
 
-        Ok::<_, String>(UpdateResult {
-            entry,
-            overlap_warning,
-        })
+/// When an invoice falls due and the terms printed with it. `dueDate` wins
+/// over `dueInDays`, which counts from the issue date.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InvoicePaymentTerms {
+    due_date: Option<i64>,
+    due_in_days: Option<u32>,
+    text: Option<String>,
+}
+
+impl InvoicePaymentTerms {
+    fn due_date(&self, issued_at: i64) -> Result<Option<i64>, String> {
+        let due_date = match (self.due_date, self.due_in_days) {
+            (Some(due_date), _) => due_date,
+            (None, Some(days)) => issued_at + i64::from(days) * 86_400,
+            (None, None) => return Ok(None),
+        };
+        // Due on the day of issue is fine, whatever time the date carries.
+        if due_date < reports::local_day_start(issued_at) {
+            return Err("The due date can't be before the issue date".into());
+        }
+        Ok(Some(due_date))
+    }
+}
+
+/// An invoice worked out by `prepare_invoice`, returned to the frontend to
+/// adjust (drop entries, reword rows) and handed back to `finalize_invoice`,
+/// which rebuilds the rows' figures from the entries still listed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InvoiceDraft {
+    business_info: BusinessInfo,
+    client_id: Option<i64>,
+    currency: String,
+    language: invoice_labels::InvoiceLanguage,
+    period_start: Option<i64>,
+    period_end: Option<i64>,
+    due_date: Option<i64>,
+    payment_terms: Option<String>,
+    /// Entries the invoice bills; they're marked invoiced on finalizing.
+    entry_ids: Vec<i64>,
+    /// Only the descriptions are taken back from the frontend.
+    line_items: Vec<pdf_generator::InvoiceLineItem>,
+    /// Totals as prepared, for display; finalizing recomputes them.
+    #[serde(default)]
+    total_hours: f64,
+    #[serde(default)]
+    total_amount: f64,
+    #[serde(default)]
+    include_invoiced: bool,
+    /// Appends the billed entries as a per-day timesheet.
+    #[serde(default)]
+    timesheet: bool,
+    /// Already applied to `line_items`; kept for the note on the PDF.
+    #[serde(default)]
+    rounding: Option<InvoiceRounding>,
+    /// How `line_items` were built; kept so `duplicate_invoice` builds the
+    /// next invoice alike.
+    #[serde(default)]
+    line_mode: InvoiceLineMode,
+    #[serde(default)]
+    group_by: Option<InvoiceGrouping>,
+    /// What the entries were picked by, so duplicates bill the same projects.
+    #[serde(default)]
+    entry_filter: InvoiceEntryFilter,
+}
+
+/// What an invoice PDF is drawn from, when first saved or regenerated.
+struct InvoiceRender {
+    /// The saved row's id; none for a draft preview.
+    id: Option<i64>,
+    business_info: BusinessInfo,
+    entries: Vec<TimeEntry>,
+    line_items: Vec<pdf_generator::InvoiceLineItem>,
+    period: Option<(i64, i64)>,
+    currency: String,
+    client_id: Option<i64>,
+    language: invoice_labels::InvoiceLanguage,
+    due_date: Option<i64>,
+    payment_terms: Option<String>,
+    issued_at: i64,
+    /// Watermarked as a draft for approval.
+    preview: bool,
+    kind: pdf_generator::DocumentKind,
+    timesheet: bool,
+    rounding: Option<InvoiceRounding>,
+}
+
+impl InvoiceDraft {
+    fn into_render(self, entries: Vec<TimeEntry>, issued_at: i64, preview: bool) -> InvoiceRender {
+        InvoiceRender {
+            id: None,
+            business_info: self.business_info,
+            entries,
+            line_items: self.line_items,
+            period: self.period_start.zip(self.period_end),
+            currency: self.currency,
+            client_id: self.client_id,
+            language: self.language,
+            due_date: self.due_date,
+            payment_terms: self.payment_terms,
+            issued_at,
+            preview,
+            kind: pdf_generator::DocumentKind::Invoice,
+            timesheet: self.timesheet,
+            rounding: self.rounding,
+        }
+    }
+}
+
+/// A saved entry and anything about it worth a second look.
+#[derive(Debug, Serialize)]
+struct UpdateResult {
+    entry: TimeEntry,
+    overlap_warning: Option<OverlapWarning>,
+    warnings: Vec<EntryWarning>,
+}
+
+/// Likely typos in an entry that was saved anyway.
+#[derive(Debug, Serialize)]
+struct EntryWarning {
+    kind: EntryWarningKind,
+    message: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum EntryWarningKind {
+    TooLong,
+    EndsInFuture,
+    /// Another entry has the same project, start and end.
+    Duplicate,
+}
+
+#[derive(Debug, Serialize)]
+struct OverlapWarning {
+    overlapping_entries: Vec<TimeEntry>,
+}
+
+/// Why an entry wasn't saved. `conflicts` lists the entries an overlap ran
+/// into when the `block_overlapping_entries` setting refused it, and is
+/// empty for any other error.
+#[derive(Debug, Serialize)]
+struct EntrySaveError {
+    message: String,
+    conflicts: Vec<EntryConflict>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct EntryConflict {
+    id: i64,
+    project_name: String,
+    start_time: i64,
+    end_time: i64,
+}
+
+impl From<String> for EntrySaveError {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            conflicts: Vec::new(),
+        }
+    }
+}
+
+#[tauri::command]
+async fn update_time_entry(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    project_name: Option<String>,
+    hourly_rate: Option<f64>,
+    duration: Option<i64>,
+    notes: Option<String>,
+) -> Result<UpdateResult, EntrySaveError> {
+    let db_path = resolve_db_path(&app_handle)?;
+    // `None` leaves the notes untouched; an empty string clears them.
+    let notes = notes.map(|notes| sanitize_notes(Some(notes))).transpose()?;
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let current = fetch_time_entry(&conn, id)?;
+
+        let updated_name = project_name
+            .map(sanitize_project_name)
+            .unwrap_or_else(|| current.project_name.clone());
+        let updated_rate = hourly_rate
+            .map(sanitize_hourly_rate)
+            .unwrap_or(current.hourly_rate);
+        let updated_notes = notes.unwrap_or_else(|| current.notes.clone());
+
+        // Calculate new duration and end_time
+        let updated_duration = duration.unwrap_or(current.duration);
+        let updated_end_time = current.start_time + updated_duration;
+        let updated_amount = calculate_amount(updated_duration, updated_rate);
+
+        projects::ensure_project(&conn, &updated_name)?;
+
+        // Check for overlapping entries (excluding current entry)
+        let overlapping = enforce_no_overlap(&conn, id, current.start_time, updated_end_time)?;
+        let warnings = suspicious_entry_warnings(&conn, current.start_time, updated_end_time)?;
+        let overlap_warning = if !overlapping.is_empty() {
+            Some(OverlapWarning {
+                overlapping_entries: overlapping,
+            })
+        } else {
+            None
+        };
+
+        conn.execute(
+            "UPDATE time_entries
+             SET project_name = ?1,
+                 hourly_rate = ?2,
+                 duration = ?3,
+                 end_time = ?4,
+                 amount = ?5,
+                 notes = ?6
+             WHERE id = ?7",
+            params![updated_name, updated_rate, updated_duration, updated_end_time, updated_amount, updated_notes, id],
+        )
+        .map_err(|err| err.to_string())?;
+
+        let entry = fetch_time_entry(&conn, id)?;
+        audit::record_change(
+            &conn,
+            audit::AuditAction::Update,
+            audit::EntrySource::Manual,
+            Some(&current),
+            Some(&entry),
+        )?;
+
+        Ok::<_, EntrySaveError>((
+            UpdateResult {
+                entry,
+                overlap_warning,
+                warnings,
+            },
+            current,
+        ))
     })
     .await
-    .map_err(|err| err.to_string())?
+    .map_err(|err| err.to_string())??;
+    let (result, before) = result;
+
+    undo::record_updated(&app_handle, "Edit entry", vec![before]);
+    run_entry_checks(&app_handle, &result.entry.project_name);
+    Ok(result)
 }
 
 #[tauri::command]
 async fn delete_time_entry(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
     let db_path = resolve_db_path(&app_handle)?;
 
-    tauri::async_runtime::spawn_blocking(move || {
+    let deleted = tauri::async_runtime::spawn_blocking(move || {
         let conn = open_connection(db_path)?;
+        let entry = fetch_time_entry(&conn, id)?;
         conn.execute("DELETE FROM time_entries WHERE id = ?1", params![id])
             .map_err(|err| err.to_string())?;
-        Ok::<_, String>(())
+        conn.execute("DELETE FROM time_entry_segments WHERE entry_id = ?1", params![id])
+            .map_err(|err| err.to_string())?;
+        audit::record_change(
+            &conn,
+            audit::AuditAction::Delete,
+            audit::EntrySource::Manual,
+            Some(&entry),
+            None,
+        )?;
+        Ok::<_, String>(entry)
     })
     .await
-    .map_err(|err| err.to_string())?
+    .map_err(|err| err.to_string())??;
+
+    undo::record_deleted(&app_handle, "Delete entry", vec![deleted]);
+    Ok(())
 }
 
 #[tauri::command]
@@ -417,18 +1499,143 @@ async fn get_timer_status(app_handle: tauri::AppHandle) -> Result<TimerStatusPay
     Ok(timer_state.status())
 }
 
+/// Every running timer, oldest first.
+#[tauri::command]
+async fn list_running_timers(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<TimerStatusPayload>, String> {
+    Ok(app_handle.state::<TimerState>().statuses())
+}
+
+/// `start_time` backdates the timer ("I started 20 minutes ago"); it can't be
+/// in the future or overlap a saved entry. `target_seconds` makes it a
+/// countdown that notifies once that much time has been tracked.
 #[tauri::command]
 async fn start_timer(
     app_handle: tauri::AppHandle,
     project_name: String,
     hourly_rate: f64,
+    notes: Option<String>,
+    start_time: Option<i64>,
+    target_seconds: Option<i64>,
+) -> Result<TimerStatusPayload, String> {
+    let notes = sanitize_notes(notes)?;
+    if target_seconds.is_some_and(|target| target <= 0) {
+        return Err("Target duration must be greater than zero".into());
+    }
+    start_timer_internal(
+        &app_handle,
+        project_name,
+        hourly_rate,
+        notes,
+        start_time,
+        target_seconds,
+    )
+}
+
+/// Timer commands take an optional `timer_id`; without one they act on the
+/// most recently started timer.
+#[tauri::command]
+async fn stop_timer(
+    app_handle: tauri::AppHandle,
+    timer_id: Option<i64>,
+) -> Result<Option<StoppedTimer>, String> {
+    stop_timer_internal(&app_handle, timer_id, StopOrigin::Window, None).await
+}
+
+/// Changes the project, rate or notes of a running timer without stopping
+/// it. Omitted fields are left alone; blank notes clear them.
+#[tauri::command]
+async fn update_active_timer(
+    app_handle: tauri::AppHandle,
+    timer_id: Option<i64>,
+    project_name: Option<String>,
+    hourly_rate: Option<f64>,
+    notes: Option<String>,
+) -> Result<TimerStatusPayload, String> {
+    let notes = match notes {
+        Some(notes) => Some(sanitize_notes(Some(notes))?),
+        None => None,
+    };
+    let db_path = resolve_db_path(&app_handle)?;
+    let project = match project_name {
+        Some(name) => {
+            let name = sanitize_project_name(name);
+            let conn = open_connection(db_path.clone())?;
+            let (color, icon) = projects::project_appearance(&conn, &name)?;
+            let currency = projects::project_currency(&conn, &name)?;
+            Some(TimerProject {
+                name,
+                color,
+                icon,
+                currency,
+            })
+        }
+        None => None,
+    };
+
+    let timer_state = app_handle.state::<TimerState>();
+    let timer = timer_state.apply_edit(
+        timer_id,
+        TimerEdit {
+            project,
+            hourly_rate: hourly_rate.map(sanitize_hourly_rate),
+            notes,
+        },
+    )?;
+    persist_active_timer(db_path, &timer)?;
+    refresh_tray(&app_handle).map_err(|err| err.to_string())?;
+    emit_timer_status(&app_handle, &timer_state.status());
+    Ok(timer.status_payload())
+}
+
+/// Starts a break timer; stop it with `stop_timer` like any other timer.
+#[tauri::command]
+async fn start_break(app_handle: tauri::AppHandle) -> Result<TimerStatusPayload, String> {
+    start_break_internal(&app_handle)
+}
+
+/// Drops a timer without saving an entry, e.g. one started on the wrong
+/// project. Returns the discarded timer's final status.
+#[tauri::command]
+async fn discard_timer(
+    app_handle: tauri::AppHandle,
+    timer_id: Option<i64>,
+) -> Result<TimerStatusPayload, String> {
+    let timer_state = app_handle.state::<TimerState>();
+    let Some(active) = timer_state.take(timer_id) else {
+        return Err(match timer_id {
+            Some(_) => "Timer not found".into(),
+            None => "No timer is currently running".into(),
+        });
+    };
+    let discarded = active.status_payload();
+    clear_active_timer(resolve_db_path(&app_handle)?, active.id)?;
+    segments::clear_timer_segments(&open_connection(resolve_db_path(&app_handle)?)?, active.id)?;
+    if active.is_break {
+        resume_after_break(&app_handle, &active, current_unix_timestamp())?;
+    }
+
+    refresh_tray(&app_handle).map_err(|err| err.to_string())?;
+    emit_timer_status(&app_handle, &timer_state.status());
+    Ok(discarded)
+}
+
+/// Pauses a running timer; paused time is left out of its duration and amount.
+#[tauri::command]
+async fn pause_timer(
+    app_handle: tauri::AppHandle,
+    timer_id: Option<i64>,
 ) -> Result<TimerStatusPayload, String> {
-    start_timer_internal(&app_handle, project_name, hourly_rate)
+    set_timer_paused(&app_handle, timer_id, true)
 }
 
 #[tauri::command]
-async fn stop_timer(app_handle: tauri::AppHandle) -> Result<Option<TimeEntry>, String> {
-    stop_timer_internal(&app_handle).await
+async fn resume_timer(
+    app_handle: tauri::AppHandle,
+    timer_id: Option<i64>,
+) -> Result<TimerStatusPayload, String> {
+    set_timer_paused(&app_handle, timer_id, false)
 }
 
 #[tauri::command]
@@ -436,138 +1643,316 @@ async fn start_timer_from_tray(
     app_handle: tauri::AppHandle,
     project_name: String,
 ) -> Result<TimerStatusPayload, String> {
-    let name = if project_name.trim().is_empty() {
-        "Quick Task".to_string()
-    } else {
-        project_name
-    };
     let db_path = resolve_db_path(&app_handle)?;
-    let last_rate = tauri::async_runtime::spawn_blocking(move || last_used_hourly_rate(db_path))
-        .await
-        .map_err(|e| e.to_string())??;
-    start_timer_internal(&app_handle, name, last_rate)
+    let (name, rate) =
+        tauri::async_runtime::spawn_blocking(move || resolve_tray_start(db_path, project_name))
+            .await
+            .map_err(|e| e.to_string())??;
+    start_timer_internal(&app_handle, name, rate, None, None, None)
+}
+
+/// Picks the project and rate for a tray start. A blank name means the
+/// configured default project (with its default rate); other projects use
+/// the last rate tracked.
+fn resolve_tray_start(db_path: PathBuf, project_name: String) -> Result<(String, f64), String> {
+    if !project_name.trim().is_empty() {
+        return Ok((project_name, last_used_hourly_rate(db_path)?));
+    }
+
+    let settings = settings::load_settings(&open_connection(db_path.clone())?)?;
+    let name = settings
+        .default_tray_project
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| FALLBACK_TRAY_PROJECT.to_string());
+    let rate = match settings.default_tray_rate {
+        Some(rate) => sanitize_hourly_rate(rate),
+        None => last_used_hourly_rate(db_path)?,
+    };
+    Ok((name, rate))
 }
 
 #[tauri::command]
-async fn stop_timer_from_tray(app_handle: tauri::AppHandle) -> Result<Option<TimeEntry>, String> {
-    stop_timer_internal(&app_handle).await
+async fn stop_timer_from_tray(
+    app_handle: tauri::AppHandle,
+    timer_id: Option<i64>,
+) -> Result<Option<StoppedTimer>, String> {
+    stop_timer_internal(&app_handle, timer_id, StopOrigin::Tray, None).await
 }
 
 #[tauri::command]
+async fn set_entry_notes(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    notes: String,
+) -> Result<TimeEntry, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let sanitized = sanitize_notes(Some(notes))?;
+
+    let (entry, before) = tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let before = fetch_time_entry(&conn, id)?;
+        update_entry_notes(&conn, id, sanitized.as_deref())?;
+        let entry = fetch_time_entry(&conn, id)?;
+        audit::record_change(
+            &conn,
+            audit::AuditAction::Update,
+            audit::EntrySource::Manual,
+            Some(&before),
+            Some(&entry),
+        )?;
+        Ok::<_, String>((entry, before))
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    undo::record_updated(&app_handle, "Edit notes", vec![before]);
+
+    app_handle.state::<NotesPromptState>().clear_if(id);
+    Ok(entry)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn save_invoice(
     app_handle: tauri::AppHandle,
     business_info: BusinessInfo,
     start_time: Option<i64>,
     end_time: Option<i64>,
+    client_id: Option<i64>,
+    filter: Option<InvoiceEntryFilter>,
+    client_profile_id: Option<i64>,
+    business_profile_id: Option<i64>,
+    line_mode: Option<InvoiceLineMode>,
+    group_by: Option<InvoiceGrouping>,
+    language: Option<invoice_labels::InvoiceLanguage>,
+    payment_terms: Option<InvoicePaymentTerms>,
+    timesheet: Option<bool>,
+    rounding: Option<InvoiceRounding>,
 ) -> Result<Invoice, String> {
+    let draft = prepare_invoice(
+        app_handle.clone(),
+        business_info,
+        start_time,
+        end_time,
+        client_id,
+        filter,
+        client_profile_id,
+        business_profile_id,
+        line_mode,
+        group_by,
+        language,
+        payment_terms,
+        timesheet,
+        rounding,
+    )
+    .await?;
+    finalize_invoice(app_handle, draft).await
+}
+
+/// Works out an invoice without rendering or storing it, so the frontend can
+/// review and adjust the draft before passing it to `finalize_invoice`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn prepare_invoice(
+    app_handle: tauri::AppHandle,
+    business_info: BusinessInfo,
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+    client_id: Option<i64>,
+    filter: Option<InvoiceEntryFilter>,
+    client_profile_id: Option<i64>,
+    business_profile_id: Option<i64>,
+    line_mode: Option<InvoiceLineMode>,
+    group_by: Option<InvoiceGrouping>,
+    language: Option<invoice_labels::InvoiceLanguage>,
+    payment_terms: Option<InvoicePaymentTerms>,
+    timesheet: Option<bool>,
+    rounding: Option<InvoiceRounding>,
+) -> Result<InvoiceDraft, String> {
+    if let Some(rounding) = rounding.as_ref() {
+        rounding.validate()?;
+    }
     let db_path = resolve_db_path(&app_handle)?;
-    let invoices_dir = resolve_invoices_dir(&app_handle)?;
+    let filter = filter.unwrap_or_default();
+    let payment_terms = payment_terms.unwrap_or_default();
+    let due_date = payment_terms.due_date(current_unix_timestamp())?;
+    let payment_terms = clients::non_empty(payment_terms.text);
+    // Scoping the invoice to a client also bills that client.
+    let client_id = client_id.or(filter.client_id);
+
+    // A chosen business profile replaces the Bill From block. Bill To fields
+    // the caller left blank are filled first from the saved bill-to profile
+    // and then from the billed client.
+    let business_info = if client_id.is_some()
+        || client_profile_id.is_some()
+        || business_profile_id.is_some()
+    {
+        let db_path = db_path.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let conn = open_connection(db_path)?;
+            let mut business_info = business_info;
+            if let Some(id) = business_profile_id {
+                let profile = profiles::fetch_business_profile(&conn, id)?;
+                business_info.name = profile.name;
+                business_info.address = profile.address;
+                business_info.email = profile.email;
+                business_info.phone = profile.phone;
+                business_info.tax_id = profile.tax_id;
+                business_info.bank_details = profile.bank_details;
+            }
+            if let Some(id) = client_profile_id {
+                let profile = profiles::fetch_bill_to_profile(&conn, id)?;
+                prefill_bill_to(
+                    &mut business_info,
+                    &profile.name,
+                    profile.address.as_deref(),
+                    profile.email.as_deref(),
+                );
+                fill_if_blank(&mut business_info.client_phone, profile.phone.as_deref());
+            }
+            if let Some(id) = client_id {
+                let client = clients::fetch_client(&conn, id)?;
+                prefill_bill_to(
+                    &mut business_info,
+                    &client.name,
+                    client.address.as_deref(),
+                    client.email.as_deref(),
+                );
+            }
+            Ok::<_, String>(business_info)
+        })
+        .await
+        .map_err(|e| e.to_string())??
+    } else {
+        business_info
+    };
 
-    // Get all entries
+    // Get the entries in the period, narrowed by the optional filter
     let entries = tauri::async_runtime::spawn_blocking({
         let db_path = db_path.clone();
-        let start = start_time;
-        let end = end_time;
+        let filter = filter.clone();
         move || {
             let conn = open_connection(db_path)?;
-
-            let map_entry = |row: &rusqlite::Row| -> rusqlite::Result<TimeEntry> {
-                Ok(TimeEntry {
-                    id: row.get(0)?,
-                    project_name: row.get(1)?,
-                    start_time: row.get(2)?,
-                    end_time: row.get(3)?,
-                    duration: row.get(4)?,
-                    hourly_rate: row.get(5)?,
-                    amount: row.get(6)?,
-                })
-            };
-
-            let entries = if let (Some(start), Some(end)) = (start, end) {
-                let mut stmt = conn
-                    .prepare(
-                        "SELECT id, project_name, start_time, end_time, duration, hourly_rate, amount
-                         FROM time_entries
-                         WHERE start_time >= ?1 AND start_time < ?2
-                         ORDER BY start_time ASC",
-                    )
-                    .map_err(|e| e.to_string())?;
-                let rows = stmt
-                    .query_map(params![start, end], |row| map_entry(row))
-                    .map_err(|e| e.to_string())?;
-                rows.collect::<Result<Vec<_>, _>>()
-                    .map_err(|e| e.to_string())?
-            } else {
-                let mut stmt = conn
-                    .prepare(
-                        "SELECT id, project_name, start_time, end_time, duration, hourly_rate, amount
-                         FROM time_entries
-                         ORDER BY start_time ASC",
-                    )
-                    .map_err(|e| e.to_string())?;
-                let rows = stmt
-                    .query_map([], |row| map_entry(row))
-                    .map_err(|e| e.to_string())?;
-                rows.collect::<Result<Vec<_>, _>>()
-                    .map_err(|e| e.to_string())?
-            };
-
-            Ok::<Vec<TimeEntry>, String>(entries)
+            query_invoice_entries(&conn, start_time, end_time, &filter)
         }
     })
     .await
     .map_err(|e| e.to_string())??;
 
     if entries.is_empty() {
-        return Err("No time entries in the selected period to include in the invoice".into());
+        return Err(if filter.include_invoiced.unwrap_or(false) {
+            "No time entries in the selected period to include in the invoice".into()
+        } else {
+            "No uninvoiced time entries in the selected period to include in the invoice".into()
+        });
     }
 
-    // Calculate totals
-    let mut total_hours = 0.0;
-    let mut total_amount = 0.0;
-    for entry in &entries {
-        total_hours += entry.duration as f64 / 3600.0;
-        total_amount += entry.amount;
+    // Amounts in different currencies can't be summed on one invoice.
+    let currency = entries[0].currency.clone();
+    if entries.iter().any(|entry| entry.currency != currency) {
+        return Err(
+            "The selected entries use more than one currency; invoice each currency separately"
+                .into(),
+        );
     }
-    let entry_count = entries.len() as i64;
 
-    // Generate filename
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-    let filename = format!("invoice_{}.pdf", timestamp);
-    let output_path = invoices_dir.join(&filename);
-    let output_path_str = output_path
-        .to_str()
-        .ok_or("Invalid file path")?
-        .to_string();
+    let (line_items, language) = {
+        let conn = open_connection(db_path.clone())?;
+        // An explicit choice wins over the billed client's language.
+        let language = match (language, client_id) {
+            (Some(language), _) => language,
+            (None, Some(id)) => clients::fetch_client(&conn, id)?
+                .invoice_language
+                .unwrap_or_default(),
+            (None, None) => invoice_labels::InvoiceLanguage::default(),
+        };
+        let mut line_items = build_invoice_line_items(
+            &conn,
+            &entries,
+            line_mode.unwrap_or_default(),
+            group_by,
+            language.labels(),
+            None,
+        )?;
+        if let Some(rounding) = rounding.as_ref() {
+            rounding.apply(&mut line_items, language.labels());
+        }
+        (line_items, language)
+    };
 
-    // Convert entries to pdf_generator format
-    let pdf_entries: Vec<pdf_generator::TimeEntry> = entries
-        .into_iter()
-        .map(|e| pdf_generator::TimeEntry {
-            id: e.id,
-            project_name: e.project_name,
-            start_time: e.start_time,
-            end_time: e.end_time,
-            duration: e.duration,
-            hourly_rate: e.hourly_rate,
-            amount: e.amount,
-        })
-        .collect();
+    let total_hours: f64 = entries.iter().map(|e| e.duration as f64 / 3600.0).sum();
+    let total_amount: f64 = line_items.iter().map(|item| item.amount).sum();
+
+    Ok(InvoiceDraft {
+        business_info,
+        client_id,
+        currency,
+        language,
+        period_start: start_time,
+        period_end: end_time,
+        due_date,
+        payment_terms,
+        entry_ids: entries.iter().map(|entry| entry.id).collect(),
+        line_items,
+        total_hours,
+        total_amount,
+        include_invoiced: filter.include_invoiced.unwrap_or(false),
+        timesheet: timesheet.unwrap_or(false),
+        rounding,
+        line_mode: line_mode.unwrap_or_default(),
+        group_by,
+        entry_filter: filter,
+    })
+}
 
-    let pdf_business_info = pdf_generator::BusinessInfo {
-        name: business_info.name.clone(),
-        address: business_info.address.clone(),
-        email: business_info.email.clone(),
-        phone: business_info.phone.clone(),
-        client_name: business_info.client_name.clone(),
-        client_address: business_info.client_address.clone(),
-        client_email: business_info.client_email.clone(),
-        client_phone: business_info.client_phone.clone(),
-    };
+/// Renders and stores a draft as approved: its rows are billed from the
+/// entries it still lists, with their reworded descriptions, and only those
+/// entries are marked invoiced.
+#[tauri::command]
+async fn finalize_invoice(
+    app_handle: tauri::AppHandle,
+    draft: InvoiceDraft,
+) -> Result<Invoice, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let invoices_dir = resolve_invoices_dir(&app_handle)?;
+    let (draft, entries) = checked_draft(db_path.clone(), draft).await?;
+    let created_at = current_unix_timestamp();
 
-    // Generate PDF
-    let period = start_time.and_then(|s| end_time.map(|e| pdf_generator::InvoicePeriod { start_time: s, end_time: e }));
-    pdf_generator::generate_invoice(pdf_entries, pdf_business_info, &output_path_str, period)?;
+    // Calculate totals
+    let total_hours: f64 = entries.iter().map(|e| e.duration as f64 / 3600.0).sum();
+    let total_amount: f64 = draft.line_items.iter().map(|item| item.amount).sum();
+    let entry_count = entries.len() as i64;
+    // Kept so the PDF can be rendered again exactly as issued.
+    let line_items_json = serde_json::to_string(&draft.line_items)
+        .map_err(|e| format!("Failed to serialize line items: {}", e))?;
+    let bills_fixed_fee = draft
+        .line_items
+        .iter()
+        .any(|item| item.fixed_fee && item.amount > 0.0);
+    let mut render = draft.clone().into_render(entries, created_at, false);
+    let InvoiceDraft {
+        business_info,
+        client_id,
+        currency,
+        language,
+        period_start,
+        period_end,
+        due_date,
+        payment_terms,
+        entry_ids,
+        timesheet,
+        rounding,
+        line_mode,
+        group_by,
+        entry_filter,
+        ..
+    } = draft;
+    let rounding_json = rounding
+        .map(|rounding| serde_json::to_string(&rounding))
+        .transpose()
+        .map_err(|e| format!("Failed to serialize rounding: {}", e))?;
+    let entry_filter_json = serde_json::to_string(&entry_filter)
+        .map_err(|e| format!("Failed to serialize the entry filter: {}", e))?;
 
     // Serialize business info to JSON
     let business_info_json = serde_json::to_string(&business_info)
@@ -580,20 +1965,50 @@ async fn save_invoice(
     }))
     .map_err(|e| format!("Failed to serialize bill to info: {}", e))?;
 
-    let created_at = current_unix_timestamp();
-
-    // Save to database
+    // Save to database, then render the PDF under a name carrying the new
+    // id, so invoices finalized within the same second don't share a file.
+    // Nothing is saved if rendering fails.
     let invoice = tauri::async_runtime::spawn_blocking(move || {
-        let conn = open_connection(db_path)?;
+        let mut conn = open_connection(db_path)?;
+        let tx = conn.transaction().map_err(|err| err.to_string())?;
 
-        conn.execute(
-            "INSERT INTO invoices (created_at, business_info, bill_to_info, total_hours, total_amount, file_path, entry_count)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![created_at, business_info_json, bill_to_json, total_hours, total_amount, output_path_str, entry_count],
+        tx.execute(
+            "INSERT INTO invoices (created_at, business_info, bill_to_info, total_hours, total_amount, file_path, entry_count, client_id, currency, due_date, payment_terms, line_items, language, period_start, period_end, timesheet, rounding, line_mode, group_by, entry_filter)
+             VALUES (?1, ?2, ?3, ?4, ?5, '', ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
+            params![created_at, business_info_json, bill_to_json, total_hours, total_amount, entry_count, client_id, currency, due_date, payment_terms, line_items_json, language.as_str(), period_start, period_end, timesheet, rounding_json, line_mode.as_str(), group_by.map(InvoiceGrouping::as_str), entry_filter_json],
         )
         .map_err(|err| err.to_string())?;
 
-        let id = conn.last_insert_rowid();
+        let id = tx.last_insert_rowid();
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        let output_path_str = invoices_dir
+            .join(format!("invoice_{}_{}.pdf", timestamp, id))
+            .to_str()
+            .ok_or("Invalid file path")?
+            .to_string();
+        tx.execute(
+            "UPDATE invoices SET file_path = ?1 WHERE id = ?2",
+            params![output_path_str, id],
+        )
+        .map_err(|err| err.to_string())?;
+        link_invoice_entries(&tx, id, &entry_ids)?;
+        // Later invoices for these projects don't bill the flat fee again.
+        if bills_fixed_fee {
+            tx.execute(
+                "UPDATE projects SET fixed_fee_invoice_id = ?1
+                 WHERE billing_mode = 'fixed' AND fixed_fee_invoice_id IS NULL
+                   AND name IN (SELECT project_name FROM time_entries WHERE invoice_id = ?1)",
+                params![id],
+            )
+            .map_err(|err| err.to_string())?;
+        }
+
+        render.id = Some(id);
+        if let Err(err) = render_invoice_pdf(&app_handle, render, &output_path_str) {
+            let _ = fs::remove_file(&output_path_str);
+            return Err(err);
+        }
+        tx.commit().map_err(|err| err.to_string())?;
 
         Ok::<Invoice, String>(Invoice {
             id,
@@ -604,6 +2019,15 @@ async fn save_invoice(
             total_amount,
             file_path: output_path_str,
             entry_count,
+            client_id,
+            currency,
+            due_date,
+            payment_terms,
+            invoice_type: INVOICE_TYPE_INVOICE.to_string(),
+            credited_invoice_id: None,
+            status: INVOICE_STATUS_ISSUED.to_string(),
+            sent_at: None,
+            paid_at: None,
         })
     })
     .await
@@ -612,6 +2036,276 @@ async fn save_invoice(
     Ok(invoice)
 }
 
+/// Issues the next invoice of a series: invoice `id`'s business and bill-to
+/// details, language, line layout, payment terms, timesheet and rounding,
+/// billing the uninvoiced entries of the client and projects it covered
+/// between `start_time` and `end_time`. A fixed due date carries over as the
+/// same number of days to pay.
+#[tauri::command]
+async fn duplicate_invoice(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    start_time: i64,
+    end_time: i64,
+) -> Result<Invoice, String> {
+    if end_time < start_time {
+        return Err("The end of the period must be after its start".into());
+    }
+    let db_path = resolve_db_path(&app_handle)?;
+    let (source, render, line_mode, group_by, filter) =
+        tauri::async_runtime::spawn_blocking(move || {
+            let conn = open_connection(db_path)?;
+            let (source, render) = load_stored_invoice(&conn, id)?;
+            if source.invoice_type == INVOICE_TYPE_CREDIT_NOTE {
+                return Err("Credit notes can't be duplicated".to_string());
+            }
+            let (line_mode, group_by, filter): (Option<String>, Option<String>, Option<String>) =
+                conn.query_row(
+                    "SELECT line_mode, group_by, entry_filter FROM invoices WHERE id = ?1",
+                    params![id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .map_err(|err| err.to_string())?;
+            // Invoices saved before the filter was stored covered the client.
+            let filter = filter
+                .map(|json| serde_json::from_str::<InvoiceEntryFilter>(&json))
+                .transpose()
+                .map_err(|e| format!("Failed to read the stored entry filter: {}", e))?
+                .unwrap_or_default();
+            Ok((
+                source,
+                render,
+                line_mode.as_deref().and_then(InvoiceLineMode::parse),
+                group_by.as_deref().and_then(InvoiceGrouping::parse),
+                filter,
+            ))
+        })
+        .await
+        .map_err(|err| err.to_string())??;
+
+    let payment_terms = InvoicePaymentTerms {
+        due_date: None,
+        due_in_days: source.due_date.map(|due_date| {
+            ((due_date - source.created_at).max(0) as f64 / 86_400.0).round() as u32
+        }),
+        text: source.payment_terms,
+    };
+    let draft = prepare_invoice(
+        app_handle.clone(),
+        render.business_info,
+        Some(start_time),
+        Some(end_time),
+        source.client_id,
+        // The next invoice bills only what hasn't been billed yet.
+        Some(InvoiceEntryFilter {
+            include_invoiced: None,
+            ..filter
+        }),
+        None,
+        None,
+        line_mode,
+        group_by,
+        Some(render.language),
+        Some(payment_terms),
+        Some(render.timesheet),
+        render.rounding,
+    )
+    .await?;
+    finalize_invoice(app_handle, draft).await
+}
+
+/// Renders a draft with a DRAFT watermark so it can be sent for approval.
+/// Nothing is stored and no entries are marked invoiced. Returns the path of
+/// the preview, which the next preview replaces.
+#[tauri::command]
+async fn preview_invoice(app_handle: tauri::AppHandle, draft: InvoiceDraft) -> Result<String, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let previews_dir = resolve_invoices_dir(&app_handle)?.join("previews");
+    fs::create_dir_all(&previews_dir)
+        .map_err(|e| format!("Failed to create previews directory: {}", e))?;
+    let output_path = previews_dir
+        .join("invoice_preview.pdf")
+        .to_str()
+        .ok_or("Invalid file path")?
+        .to_string();
+
+    let (draft, entries) = checked_draft(db_path, draft).await?;
+    let render = draft.into_render(entries, current_unix_timestamp(), true);
+    render_invoice_pdf(&app_handle, render, &output_path)?;
+    Ok(output_path)
+}
+
+/// A saved invoice and what its PDF is drawn from: the stored business info
+/// and line items, and the entries linked to it. Invoices saved before line
+/// items were stored get one "Hours worked" row from their linked entries,
+/// or from their stored totals when they predate entry links too.
+fn load_stored_invoice(conn: &Connection, id: i64) -> Result<(Invoice, InvoiceRender), String> {
+    let invoice = conn
+        .query_row(
+            &format!("SELECT {INVOICE_COLUMNS} FROM invoices WHERE id = ?1"),
+            params![id],
+            map_invoice,
+        )
+        .map_err(|e| format!("Invoice not found: {}", e))?;
+    /// Columns kept only to render the PDF again.
+    struct StoredRender {
+        line_items: Option<String>,
+        language: Option<String>,
+        period_start: Option<i64>,
+        period_end: Option<i64>,
+        timesheet: bool,
+        rounding: Option<String>,
+    }
+    let StoredRender {
+        line_items: line_items_json,
+        language,
+        period_start,
+        period_end,
+        timesheet,
+        rounding,
+    } = conn
+        .query_row(
+            "SELECT line_items, language, period_start, period_end, timesheet, rounding FROM invoices WHERE id = ?1",
+            params![id],
+            |row| {
+                Ok(StoredRender {
+                    line_items: row.get(0)?,
+                    language: row.get(1)?,
+                    period_start: row.get(2)?,
+                    period_end: row.get(3)?,
+                    timesheet: row.get(4)?,
+                    rounding: row.get(5)?,
+                })
+            },
+        )
+        .map_err(|err| err.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {TIME_ENTRY_COLUMNS} FROM time_entries
+             WHERE invoice_id = ?1
+                OR id IN (SELECT entry_id FROM invoice_entries WHERE invoice_id = ?1)
+             ORDER BY start_time ASC"
+        ))
+        .map_err(|err| err.to_string())?;
+    let entries = stmt
+        .query_map(params![id], map_time_entry)
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    let language = language
+        .as_deref()
+        .and_then(invoice_labels::InvoiceLanguage::parse)
+        .unwrap_or_default();
+    let line_items = match line_items_json {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to read stored line items: {}", e))?,
+        None if entries.is_empty() => vec![stored_totals_row(&invoice, language.labels())],
+        None => build_invoice_line_items(
+            conn,
+            &entries,
+            InvoiceLineMode::Summary,
+            None,
+            language.labels(),
+            Some(id),
+        )?,
+    };
+    let business_info: BusinessInfo = serde_json::from_str(&invoice.business_info)
+        .map_err(|e| format!("Failed to read stored business info: {}", e))?;
+    let kind = match invoice.credited_invoice_id {
+        Some(invoice_id) if invoice.invoice_type == INVOICE_TYPE_CREDIT_NOTE => {
+            let invoice_issued_at = conn
+                .query_row(
+                    "SELECT created_at FROM invoices WHERE id = ?1",
+                    params![invoice_id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Credited invoice not found: {}", e))?;
+            pdf_generator::DocumentKind::CreditNote {
+                invoice_id,
+                invoice_issued_at,
+            }
+        }
+        _ => pdf_generator::DocumentKind::Invoice,
+    };
+
+    let render = InvoiceRender {
+        id: Some(invoice.id),
+        business_info,
+        entries,
+        line_items,
+        period: period_start.zip(period_end),
+        currency: invoice.currency.clone(),
+        client_id: invoice.client_id,
+        language,
+        due_date: invoice.due_date,
+        payment_terms: invoice.payment_terms.clone(),
+        issued_at: invoice.created_at,
+        preview: false,
+        kind,
+        timesheet,
+        // A policy that no longer parses only loses its note.
+        rounding: rounding.and_then(|json| serde_json::from_str(&json).ok()),
+    };
+    Ok((invoice, render))
+}
+
+/// The single row an invoice with neither line items nor linked entries is
+/// drawn with: its stored hours at their average rate, or its total as a
+/// flat fee when no hours were stored.
+fn stored_totals_row(
+    invoice: &Invoice,
+    labels: &invoice_labels::InvoiceLabels,
+) -> pdf_generator::InvoiceLineItem {
+    if invoice.total_hours > 0.0 {
+        pdf_generator::InvoiceLineItem {
+            date: None,
+            description: labels.hours_worked.into(),
+            quantity: invoice.total_hours,
+            unit_price: invoice.total_amount / invoice.total_hours,
+            amount: invoice.total_amount,
+            fixed_fee: false,
+        }
+    } else {
+        let mut label = labels.fixed_fee.chars();
+        let description = label
+            .next()
+            .map(|first| first.to_uppercase().chain(label).collect())
+            .unwrap_or_default();
+        pdf_generator::InvoiceLineItem {
+            date: None,
+            description,
+            quantity: 1.0,
+            unit_price: invoice.total_amount,
+            amount: invoice.total_amount,
+            fixed_fee: true,
+        }
+    }
+}
+
+/// Renders a saved invoice's PDF again from its stored data, overwriting
+/// the file at its stored path. Only the invoice's contents are stored: the
+/// logo, fonts and PDF signing are today's settings, so a regenerated PDF
+/// carries any branding changed since it was issued and a fresh signature.
+#[tauri::command]
+async fn regenerate_invoice_pdf(app_handle: tauri::AppHandle, id: i64) -> Result<Invoice, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    let (invoice, render) = tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        load_stored_invoice(&conn, id)
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    if let Some(parent) = Path::new(&invoice.file_path).parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create invoices directory: {}", e))?;
+    }
+    render_invoice_pdf(&app_handle, render, &invoice.file_path)?;
+    Ok(invoice)
+}
+
 #[tauri::command]
 async fn get_all_invoices(app_handle: tauri::AppHandle) -> Result<Vec<Invoice>, String> {
     let db_path = resolve_db_path(&app_handle)?;
@@ -620,22 +2314,11 @@ async fn get_all_invoices(app_handle: tauri::AppHandle) -> Result<Vec<Invoice>,
         let conn = open_connection(db_path)?;
 
         let mut stmt = conn
-            .prepare("SELECT id, created_at, business_info, bill_to_info, total_hours, total_amount, file_path, entry_count FROM invoices ORDER BY created_at DESC")
+            .prepare(&format!("SELECT {INVOICE_COLUMNS} FROM invoices ORDER BY created_at DESC"))
             .map_err(|e| e.to_string())?;
 
         let invoices = stmt
-            .query_map([], |row| {
-                Ok(Invoice {
-                    id: row.get(0)?,
-                    created_at: row.get(1)?,
-                    business_info: row.get(2)?,
-                    bill_to_info: row.get(3)?,
-                    total_hours: row.get(4)?,
-                    total_amount: row.get(5)?,
-                    file_path: row.get(6)?,
-                    entry_count: row.get(7)?,
-                })
-            })
+            .query_map([], map_invoice)
             .map_err(|e| e.to_string())?
             .collect::<Result<Vec<_>, _>>()
             .map_err(|e| e.to_string())?;
@@ -646,6 +2329,44 @@ async fn get_all_invoices(app_handle: tauri::AppHandle) -> Result<Vec<Invoice>,
     .map_err(|e| e.to_string())?
 }
 
+/// `get_all_invoices` one page at a time.
+#[tauri::command]
+async fn get_invoices_page(
+    app_handle: tauri::AppHandle,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Page<Invoice>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let (limit, offset) = page_bounds(limit, offset);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let total: i64 = conn
+            .query_row("SELECT COUNT(*) FROM invoices", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {INVOICE_COLUMNS} FROM invoices
+                 ORDER BY created_at DESC, id DESC
+                 LIMIT ?1 OFFSET ?2"
+            ))
+            .map_err(|e| e.to_string())?;
+        let items = stmt
+            .query_map(params![limit, offset], map_invoice)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(Page {
+            items,
+            total,
+            limit,
+            offset,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
 async fn get_invoice_pdf_path(
     app_handle: tauri::AppHandle,
@@ -670,25 +2391,90 @@ async fn get_invoice_pdf_path(
     .map_err(|e| e.to_string())?
 }
 
+/// Links the entries to a new invoice. An entry invoiced before stays linked
+/// to the earlier invoice too, including one billed before links were kept.
+fn link_invoice_entries(
+    conn: &Connection,
+    invoice_id: i64,
+    entry_ids: &[i64],
+) -> Result<(), String> {
+    for entry_id in entry_ids {
+        conn.execute(
+            "INSERT OR IGNORE INTO invoice_entries (invoice_id, entry_id)
+             SELECT invoice_id, id FROM time_entries WHERE id = ?1 AND invoice_id IS NOT NULL
+             UNION ALL SELECT ?2, ?1",
+            params![entry_id, invoice_id],
+        )
+        .map_err(|err| err.to_string())?;
+        conn.execute(
+            "UPDATE time_entries SET invoice_id = ?1 WHERE id = ?2",
+            params![invoice_id, entry_id],
+        )
+        .map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+/// Deletes an invoice row in one transaction, so entries are never left
+/// unlinked from an invoice that still exists, and returns its PDF path.
+fn remove_invoice(conn: &mut Connection, id: i64) -> Result<String, String> {
+    let tx = conn.transaction().map_err(|err| err.to_string())?;
+    let file_path: String = tx
+        .query_row("SELECT file_path FROM invoices WHERE id = ?1", params![id], |row| row.get(0))
+        .map_err(|e| format!("Invoice not found: {}", e))?;
+    let credit_notes: i64 = tx
+        .query_row(
+            "SELECT COUNT(*) FROM invoices WHERE credited_invoice_id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .map_err(|err| err.to_string())?;
+    if credit_notes > 0 {
+        return Err("This invoice has credit notes; delete them first".to_string());
+    }
+
+    // Its entries go back to the latest other invoice that billed them,
+    // or become billable again, as does any flat fee it billed.
+    tx.execute("DELETE FROM invoice_entries WHERE invoice_id = ?1", params![id])
+        .map_err(|err| err.to_string())?;
+    tx.execute(
+        "UPDATE time_entries
+         SET invoice_id = (
+             SELECT MAX(invoice_id) FROM invoice_entries WHERE entry_id = time_entries.id
+         )
+         WHERE invoice_id = ?1",
+        params![id],
+    )
+    .map_err(|err| err.to_string())?;
+    tx.execute(
+        "UPDATE projects SET fixed_fee_invoice_id = NULL WHERE fixed_fee_invoice_id = ?1",
+        params![id],
+    )
+    .map_err(|err| err.to_string())?;
+    tx.execute("DELETE FROM invoices WHERE id = ?1", params![id])
+        .map_err(|err| err.to_string())?;
+    tx.commit().map_err(|err| err.to_string())?;
+
+    Ok(file_path)
+}
+
 #[tauri::command]
 async fn delete_invoice(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
     let db_path = resolve_db_path(&app_handle)?;
 
-    // Fetch file path and delete row in a single connection to avoid locking issues.
     let file_path = tauri::async_runtime::spawn_blocking(move || {
-        let conn = open_connection(db_path)?;
-        let file_path: String = conn
-            .query_row("SELECT file_path FROM invoices WHERE id = ?1", params![id], |row| row.get(0))
-            .map_err(|e| format!("Invoice not found: {}", e))?;
-
-        conn.execute("DELETE FROM invoices WHERE id = ?1", params![id])
-            .map_err(|err| err.to_string())?;
-
-        Ok::<String, String>(file_path)
+        let mut conn = open_connection(db_path)?;
+        remove_invoice(&mut conn, id)
     })
     .await
     .map_err(|err| err.to_string())??;
 
+    // Exported e-invoices and spreadsheets and the cached preview go with it.
+    for extension in ["xml", "csv", "xlsx"] {
+        let _ = fs::remove_file(Path::new(&file_path).with_extension(extension));
+    }
+    thumbnails::remove_invoice_thumbnail(&app_handle, id);
+
     // Delete file
     if let Err(e) = fs::remove_file(&file_path) {
         // If we can't delete the PDF (e.g. locked by a viewer), log it but still treat as success
@@ -752,34 +2538,168 @@ pub fn run() {
                 .build(),
         )
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(TimerState::default())
-        .on_window_event(|window, event| {
-            if let WindowEvent::CloseRequested { api, .. } = event {
+        .manage(NotesPromptState::default())
+        .manage(idle::IdleState::default())
+        .manage(reminders::ReminderState::default())
+        .manage(overdue::OverdueState::default())
+        .manage(suspend::SuspendState::default())
+        .manage(recovery::RecoveryState::default())
+        .manage(undo::UndoState::default())
+        .manage(recurring::RecurringState::default())
+        .manage(recurring_invoices::RecurringInvoiceState::default())
+        .manage(backup::BackupState::default())
+        .on_window_event(|window, event| match event {
+            WindowEvent::CloseRequested { api, .. } => {
                 api.prevent_close();
                 if window.hide().is_ok() {
                     let _ = refresh_tray(&window.app_handle());
                 }
             }
+            WindowEvent::Focused(true) => focus_pending_entry(window.app_handle()),
+            _ => {}
         })
         .invoke_handler(tauri::generate_handler![
             initialize_database,
             get_today_entries,
             get_entries_in_range,
+            get_entries_page,
             get_today_total,
             create_time_entry,
             update_time_entry,
             delete_time_entry,
+            find_overlapping_entries,
+            entries::duplicate_time_entry,
+            entries::move_time_entry,
+            quick_add::quick_add,
+            entries::set_entry_starred,
+            entries::get_starred_entries,
+            entries::get_entries_by_project,
+            entries::find_gaps,
+            entries::find_duplicates,
+            entries::bulk_update_entries,
+            entries::delete_entries_matching,
+            undo::undo_last_change,
+            audit::get_entry_audit,
+            templates::list_entry_templates,
+            templates::create_entry_template,
+            templates::update_entry_template,
+            templates::delete_entry_template,
+            templates::create_entry_from_template,
+            recurring::list_recurring_rules,
+            recurring::create_recurring_rule,
+            recurring::update_recurring_rule,
+            recurring::delete_recurring_rule,
+            recurring::run_recurring_entries,
+            recurring_invoices::list_recurring_invoices,
+            recurring_invoices::create_recurring_invoice,
+            recurring_invoices::update_recurring_invoice,
+            recurring_invoices::delete_recurring_invoice,
+            recurring_invoices::generate_pending_invoices,
             get_timer_status,
+            list_running_timers,
             start_timer,
             stop_timer,
+            start_break,
+            update_active_timer,
+            discard_timer,
+            pause_timer,
+            resume_timer,
             start_timer_from_tray,
             stop_timer_from_tray,
             save_invoice,
+            prepare_invoice,
+            finalize_invoice,
+            duplicate_invoice,
+            preview_invoice,
+            regenerate_invoice_pdf,
+            ubl::export_invoice_ubl,
+            credit_notes::create_credit_note,
+            thumbnails::get_invoice_thumbnail,
+            spreadsheet::export_invoice_lines,
+            spreadsheet::export_entries_workbook,
+            timesheets::generate_timesheet_pdf,
+            data_export::export_all_data,
+            data_export::import_all_data,
+            backup::create_backup,
+            backup::restore_backup,
+            backup::get_backup_status,
+            email::send_invoice_email,
+            archive::export_invoices_archive,
+            overdue::list_overdue_invoices,
+            overdue::set_invoice_paid,
+            estimates::create_estimate,
+            estimates::list_estimates,
+            estimates::delete_estimate,
+            estimates::accept_estimate,
             get_all_invoices,
+            get_invoices_page,
             get_invoice_pdf_path,
             delete_invoice,
             export_invoice_to_downloads,
-            open_file_in_default_app
+            open_file_in_default_app,
+            set_entry_notes,
+            settings::get_settings,
+            settings::update_settings,
+            signing::check_pdf_signing,
+            settings::set_invoice_logo,
+            settings::set_invoice_fonts,
+            clients::list_clients,
+            clients::create_client,
+            clients::update_client,
+            clients::delete_client,
+            projects::list_projects,
+            projects::suggest_projects,
+            projects::merge_projects,
+            projects::set_project_client,
+            projects::archive_project,
+            projects::unarchive_project,
+            projects::set_project_appearance,
+            projects::set_project_billing,
+            projects::set_project_rounding,
+            projects::set_project_currency,
+            projects::set_project_estimate,
+            projects::set_project_invoice_terms,
+            budgets::set_project_budget,
+            budgets::get_project_budget_status,
+            budgets::get_budget_burn_report,
+            retainers::create_retainer,
+            retainers::list_retainers,
+            retainers::delete_retainer,
+            retainers::get_retainer_balance,
+            profiles::list_bill_to_profiles,
+            profiles::save_bill_to_profile,
+            profiles::delete_bill_to_profile,
+            profiles::list_business_profiles,
+            profiles::create_business_profile,
+            profiles::update_business_profile,
+            profiles::delete_business_profile,
+            reports::get_client_project_totals,
+            reports::get_earnings_report,
+            reports::get_estimate_report,
+            reports::get_break_report,
+            reports::get_segment_totals,
+            reports::get_daily_summaries,
+            reports::get_daily_totals,
+            reports::get_activity_heatmap,
+            reports::get_time_distribution,
+            reports::get_weekly_summary,
+            reports::generate_monthly_report,
+            reports::compare_periods,
+            reports::run_report,
+            reports::get_personal_stats,
+            reports::get_yearly_summary,
+            reports::generate_yearly_summary_pdf,
+            segments::add_timer_segment,
+            segments::list_timer_segments,
+            segments::get_entry_segments,
+            idle::get_idle_period,
+            idle::resolve_idle,
+            suspend::get_suspend_period,
+            suspend::resolve_suspend,
+            recovery::get_recovered_timers,
+            recovery::resolve_recovered_timer
         ])
         .setup(|app| {
             let assets = TrayAssets::load()?;
@@ -787,122 +2707,556 @@ pub fn run() {
             setup_tray(app)?;
             restore_active_timer(&app.handle()).map_err(to_tauri_error)?;
             refresh_tray(&app.handle())?;
+            recurring::check_due_occurrences(app.handle());
+            recurring_invoices::check_day_rollover(app.handle());
+            overdue::check_day_rollover(app.handle());
+            backup::check_schedule(app.handle());
+            spawn_background_checks(app.handle().clone());
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // A normal exit keeps timers running for the next launch; only a
+            // crash leaves their heartbeats behind.
+            if let RunEvent::Exit = event {
+                suspend::clear_heartbeats(app);
+            }
+        });
 }
 
 fn start_timer_internal(
     app_handle: &AppHandle,
     project_name: String,
     hourly_rate: f64,
+    notes: Option<String>,
+    start_time: Option<i64>,
+    target_seconds: Option<i64>,
 ) -> Result<TimerStatusPayload, String> {
     let timer_state = app_handle.state::<TimerState>();
     let sanitized_name = sanitize_project_name(project_name);
     let sanitized_rate = sanitize_hourly_rate(hourly_rate);
-    let start_time = current_unix_timestamp();
-    let active_timer = ActiveTimer {
-        project_name: sanitized_name.clone(),
+    let now = current_unix_timestamp();
+    let start_time = start_time.unwrap_or(now);
+    if start_time > now {
+        return Err("Start time cannot be in the future".into());
+    }
+    let db_path = resolve_db_path(app_handle)?;
+    let (project_color, project_icon, currency) = {
+        let conn = open_connection(db_path.clone())?;
+        if start_time < now {
+            if let Some(existing) = check_overlapping_entries(&conn, 0, start_time, now)?.first() {
+                return Err(format!(
+                    "Start time overlaps an existing {} entry",
+                    existing.project_name
+                ));
+            }
+        }
+        let (color, icon) = projects::project_appearance(&conn, &sanitized_name)?;
+        (color, icon, projects::project_currency(&conn, &sanitized_name)?)
+    };
+    timer_state.ensure_can_start(&sanitized_name)?;
+    let mut active_timer = ActiveTimer {
+        id: 0,
+        project_name: sanitized_name,
         start_time,
         hourly_rate: sanitized_rate,
+        project_color,
+        project_icon,
+        notes,
+        paused_at: None,
+        paused_seconds: 0,
+        target_seconds,
+        currency,
+        is_break: false,
+        utc_offset: Some(local_utc_offset_minutes(start_time)),
+    };
+    // The row id doubles as the timer id.
+    active_timer.id = insert_active_timer(db_path.clone(), &active_timer)?;
+    let status = match timer_state.start(active_timer.clone()) {
+        Ok(status) => status,
+        Err(err) => {
+            let _ = clear_active_timer(db_path, active_timer.id);
+            return Err(err);
+        }
     };
-    let status = timer_state.start(sanitized_name, start_time, sanitized_rate)?;
-    let db_path = resolve_db_path(app_handle)?;
-    if let Err(err) = persist_active_timer(db_path, &active_timer) {
-        timer_state.clear();
-        return Err(err);
-    }
     refresh_tray(app_handle).map_err(|err| err.to_string())?;
-    emit_timer_status(app_handle, &status);
+    emit_timer_status(app_handle, &timer_state.status());
     Ok(status)
 }
 
-async fn stop_timer_internal(app_handle: &AppHandle) -> Result<Option<TimeEntry>, String> {
+/// Stops `timer_id`, or the primary timer when `None`, and saves its entry.
+/// `stop_at` ends the entry earlier than now, e.g. when the user went idle.
+async fn stop_timer_internal(
+    app_handle: &AppHandle,
+    timer_id: Option<i64>,
+    origin: StopOrigin,
+    stop_at: Option<i64>,
+) -> Result<Option<StoppedTimer>, String> {
     let timer_state = app_handle.state::<TimerState>();
-    let Some(active) = timer_state.take_active() else {
-        return Err("No timer is currently running".into());
+    let Some(active) = timer_state.take(timer_id) else {
+        return Err(match timer_id {
+            Some(_) => "Timer not found".into(),
+            None => "No timer is currently running".into(),
+        });
     };
 
-    let end_time = current_unix_timestamp().max(active.start_time + 1);
+    let end_time = stop_at
+        .unwrap_or_else(current_unix_timestamp)
+        .max(active.start_time + 1);
+    let paused_seconds = active.total_paused_seconds(end_time);
     let db_path = resolve_db_path(app_handle)?;
+    if active.is_break {
+        resume_after_break(app_handle, &active, end_time)?;
+    }
 
-    let entry = persist_time_entry(
-        db_path,
-        active.project_name.clone(),
-        active.start_time,
-        end_time,
-        active.hourly_rate,
-    )
-    .await?;
-    let _ = clear_active_timer(resolve_db_path(app_handle)?);
+    // Accidental start/stops are dropped rather than saved; `None` tells the
+    // caller nothing was recorded.
+    let tracked = end_time - active.start_time - paused_seconds;
+    let min_entry_seconds = current_settings(app_handle)
+        .map(|settings| settings.min_entry_seconds)
+        .unwrap_or(0);
+    if tracked < i64::from(min_entry_seconds) {
+        if let Ok(conn) = open_connection(db_path.clone()) {
+            let _ = segments::clear_timer_segments(&conn, active.id);
+        }
+        let _ = clear_active_timer(db_path, active.id);
+        refresh_tray(app_handle).map_err(|err| err.to_string())?;
+        emit_timer_status(app_handle, &timer_state.status());
+        let _ = app_handle.emit(
+            TIMER_DISCARDED_EVENT,
+            TimerDiscardedPayload {
+                project_name: active.project_name,
+                duration: tracked,
+                min_entry_seconds,
+            },
+        );
+        return Ok(None);
+    }
+
+    // One entry per calendar day so daily totals stay with the right day;
+    // rounding applies once, to the final segment. The entries are saved and
+    // the timer's row removed together, so a failure leaves the timer to
+    // stop again rather than half saved.
+    let segments = split_at_local_midnights(active.start_time, end_time);
+    let paused = share_paused_seconds(&segments, paused_seconds);
+    let last_index = segments.len() - 1;
+    let timer = active.clone();
+    let saved = tauri::async_runtime::spawn_blocking({
+        let db_path = db_path.clone();
+        move || {
+            let mut conn = open_connection(db_path)?;
+            let tx = conn.transaction().map_err(|err| err.to_string())?;
+            let mut saved = Vec::with_capacity(segments.len());
+            for (index, ((start, end), paused)) in segments.into_iter().zip(paused).enumerate() {
+                let is_last = index == last_index;
+                saved.push(insert_time_entry(
+                    &tx,
+                    NewTimeEntry {
+                        project_name: timer.project_name.clone(),
+                        start_time: start,
+                        end_time: end,
+                        hourly_rate: timer.hourly_rate,
+                        notes: timer.notes.clone(),
+                        paused_seconds: paused,
+                        apply_rounding: is_last && !timer.is_break,
+                        is_break: timer.is_break,
+                        billable: true,
+                        utc_offset: timer.utc_offset,
+                        source: match origin {
+                            StopOrigin::Tray => audit::EntrySource::Tray,
+                            StopOrigin::Window => audit::EntrySource::Timer,
+                        },
+                    },
+                )?);
+            }
+            tx.execute("DELETE FROM active_timers WHERE id = ?1", params![timer.id])
+                .map_err(|err| err.to_string())?;
+            tx.commit().map_err(|err| err.to_string())?;
+            Ok::<_, String>(saved)
+        }
+    })
+    .await
+    .map_err(|err| err.to_string())?;
+    let mut earlier_entries = match saved {
+        Ok(saved) => saved,
+        Err(err) => {
+            // Still running, so stopping can be retried.
+            if !active.is_break {
+                let _ = timer_state.start(active);
+            }
+            return Err(err);
+        }
+    };
+    let entry = earlier_entries.pop().expect("a timer spans at least one day");
+    // The entries are already saved; losing segment labels isn't worth failing the stop.
+    if let Ok(conn) = open_connection(resolve_db_path(app_handle)?) {
+        let saved: Vec<&TimeEntry> = earlier_entries.iter().chain([&entry]).collect();
+        let _ = segments::attach_segments(&conn, active.id, end_time, &saved);
+    }
+    if !active.is_break {
+        run_entry_checks(app_handle, &entry.project_name);
+    }
+
+    // The entry is already saved; a settings failure only skips the prompt.
+    let needs_notes = !active.is_break
+        && entry.notes.is_none()
+        && current_settings(app_handle)
+            .map(|settings| settings.prompt_notes_on_stop)
+            .unwrap_or(false);
 
     let status = timer_state.status();
     refresh_tray(app_handle).map_err(|err| err.to_string())?;
     emit_timer_status(app_handle, &status);
+    let _ = app_handle.emit(
+        TIMER_STOPPED_EVENT,
+        TimerStoppedPayload {
+            entry_id: entry.id,
+            needs_notes,
+        },
+    );
+
+    if needs_notes && origin == StopOrigin::Tray {
+        prompt_notes_from_tray(app_handle, &entry);
+    }
+
+    Ok(Some(StoppedTimer {
+        entry,
+        needs_notes,
+        earlier_entries,
+    }))
+}
+
+/// Starts a break. Running work timers are paused for its length and resume
+/// when it ends; the break is saved as a `break` entry with no rate.
+fn start_break_internal(app_handle: &AppHandle) -> Result<TimerStatusPayload, String> {
+    let timer_state = app_handle.state::<TimerState>();
+    if timer_state.is_on_break() {
+        return Err("A break is already running".into());
+    }
+    let db_path = resolve_db_path(app_handle)?;
+    let now = current_unix_timestamp();
+    for timer in timer_state.pause_for_break(now) {
+        persist_active_timer(db_path.clone(), &timer)?;
+    }
+
+    let mut break_timer = ActiveTimer {
+        id: 0,
+        project_name: BREAK_PROJECT.to_string(),
+        start_time: now,
+        hourly_rate: 0.0,
+        project_color: None,
+        project_icon: None,
+        notes: None,
+        paused_at: None,
+        paused_seconds: 0,
+        target_seconds: None,
+        currency: "USD".to_string(),
+        is_break: true,
+        utc_offset: Some(local_utc_offset_minutes(now)),
+    };
+    break_timer.id = insert_active_timer(db_path, &break_timer)?;
+    let status = timer_state.start(break_timer)?;
+    refresh_tray(app_handle).map_err(|err| err.to_string())?;
+    emit_timer_status(app_handle, &timer_state.status());
+    Ok(status)
+}
+
+fn resume_after_break(
+    app_handle: &AppHandle,
+    break_timer: &ActiveTimer,
+    now: i64,
+) -> Result<(), String> {
+    let timer_state = app_handle.state::<TimerState>();
+    for timer in timer_state.resume_after_break(break_timer.start_time, now) {
+        persist_active_timer(resolve_db_path(app_handle)?, &timer)?;
+    }
+    Ok(())
+}
+
+fn set_timer_paused(
+    app_handle: &AppHandle,
+    timer_id: Option<i64>,
+    paused: bool,
+) -> Result<TimerStatusPayload, String> {
+    let timer_state = app_handle.state::<TimerState>();
+    let now = current_unix_timestamp();
+    let timer = if paused {
+        timer_state.pause(timer_id, now)?
+    } else {
+        timer_state.resume(timer_id, now)?
+    };
+    persist_active_timer(resolve_db_path(app_handle)?, &timer)?;
+    refresh_tray(app_handle).map_err(|err| err.to_string())?;
+    emit_timer_status(app_handle, &timer_state.status());
+    Ok(timer.status_payload())
+}
+
+/// Scheduler loop for checks that don't wait on a command: suspend and idle
+/// detection, reminders and scheduled backups. Each check reads its settings on every pass.
+fn spawn_background_checks(app_handle: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(BACKGROUND_CHECK_INTERVAL);
+        report_clock_jump(&app_handle);
+        suspend::check_suspend(&app_handle);
+        suspend::record_heartbeat(&app_handle);
+        idle::check_idle(&app_handle);
+        reminders::check_long_running_timers(&app_handle);
+        reminders::check_forgot_to_start(&app_handle);
+        reminders::check_timer_targets(&app_handle);
+        reminders::check_daily_cap(&app_handle);
+        recurring::check_due_occurrences(&app_handle);
+        recurring_invoices::check_day_rollover(&app_handle);
+        overdue::check_day_rollover(&app_handle);
+        backup::check_schedule(&app_handle);
+        // Keeps elapsed time, earnings and countdowns in the tooltip current.
+        if !app_handle.state::<TimerState>().statuses().is_empty() {
+            let _ = refresh_tray(&app_handle);
+        }
+    });
+}
 
-    Ok(Some(entry))
+/// Persists timers shifted by a clock correction and warns the frontend.
+fn report_clock_jump(app_handle: &AppHandle) {
+    let timer_state = app_handle.state::<TimerState>();
+    let Some((jump, timers)) = timer_state.check_clock() else {
+        return;
+    };
+    let status = timer_state.status();
+    if let Ok(db_path) = resolve_db_path(app_handle) {
+        for timer in &timers {
+            let _ = persist_active_timer(db_path.clone(), timer);
+        }
+    }
+    let _ = refresh_tray(app_handle);
+    emit_timer_status(app_handle, &status);
+    let _ = app_handle.emit(CLOCK_JUMP_EVENT, jump);
 }
 
+/// Emits the primary timer's status, plus the full list for multi-timer views.
 fn emit_timer_status(app_handle: &AppHandle, status: &TimerStatusPayload) {
     let _ = app_handle.emit(TIMER_STATUS_EVENT, status);
+    let _ = app_handle.emit(TIMERS_EVENT, app_handle.state::<TimerState>().statuses());
+}
+
+fn refresh_active_timer_appearance(app_handle: &AppHandle, project: &projects::Project) {
+    let timer_state = app_handle.state::<TimerState>();
+    if timer_state.update_appearance(&project.name, project.color.clone(), project.icon.clone()) {
+        let status = timer_state.status();
+        let _ = refresh_tray(app_handle);
+        emit_timer_status(app_handle, &status);
+    }
+}
+
+fn retarget_active_timer(app_handle: &AppHandle, from: &str, project: &projects::Project) {
+    let timer_state = app_handle.state::<TimerState>();
+    if timer_state.retarget(from, project) {
+        let status = timer_state.status();
+        let _ = refresh_tray(app_handle);
+        emit_timer_status(app_handle, &status);
+    }
+}
+
+fn prompt_notes_from_tray(app_handle: &AppHandle, entry: &TimeEntry) {
+    app_handle.state::<NotesPromptState>().set_pending(entry.id);
+    send_notification(
+        app_handle,
+        "What did you work on?",
+        &format!(
+            "{} ({}) — click to add notes.",
+            entry.project_name,
+            format_duration(entry.duration)
+        ),
+    );
+}
+
+/// Advisory checks that run after an entry is created or changed.
+fn run_entry_checks(app_handle: &AppHandle, project_name: &str) {
+    budgets::check_budget_alerts(app_handle, project_name);
+    retainers::check_retainer_balance(app_handle, project_name);
+}
+
+fn send_notification(app_handle: &AppHandle, title: &str, body: &str) {
+    let _ = app_handle
+        .notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show();
+}
+
+/// Desktop notifications have no click callback, but clicking one activates
+/// the app, so the pending entry is surfaced when the window gains focus.
+fn focus_pending_entry(app_handle: &AppHandle) {
+    if let Some(entry_id) = app_handle.state::<NotesPromptState>().take_pending() {
+        let _ = app_handle.emit(ENTRY_FOCUS_EVENT, EntryFocusPayload { entry_id });
+    }
+}
+
+fn current_settings(app_handle: &AppHandle) -> Result<settings::AppSettings, String> {
+    let conn = open_connection(resolve_db_path(app_handle)?)?;
+    settings::load_settings(&conn)
+}
+
+fn resolve_db_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let mut dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|err| err.to_string())?;
+
+    fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+    dir.push(DB_FILE_NAME);
+    Ok(dir)
 }
 
-fn resolve_db_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+fn resolve_invoices_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
     let mut dir = app_handle
         .path()
         .app_data_dir()
         .map_err(|err| err.to_string())?;
 
+    dir.push("invoices");
     fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
-    dir.push(DB_FILE_NAME);
     Ok(dir)
 }
 
-fn resolve_invoices_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+/// Folder for files that only live while a command runs, such as outgoing
+/// mail; only the current user can open it.
+fn resolve_scratch_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
     let mut dir = app_handle
         .path()
         .app_data_dir()
         .map_err(|err| err.to_string())?;
 
-    dir.push("invoices");
+    dir.push("scratch");
     fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))
+            .map_err(|err| err.to_string())?;
+    }
     Ok(dir)
 }
 
-async fn persist_time_entry(
-    db_path: PathBuf,
+/// A new file only the current user can read, deleted when dropped so it's
+/// cleaned up however the command using it ends.
+struct PrivateFile {
+    path: PathBuf,
+}
+
+impl PrivateFile {
+    fn create(path: PathBuf, contents: &[u8]) -> io::Result<Self> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut handle = options.open(&path)?;
+        // Removed again if writing fails.
+        let file = Self { path };
+        io::Write::write_all(&mut handle, contents)?;
+        Ok(file)
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PrivateFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Already-sanitized fields for a time entry about to be inserted.
+struct NewTimeEntry {
     project_name: String,
     start_time: i64,
     end_time: i64,
     hourly_rate: f64,
-) -> Result<TimeEntry, String> {
-    let duration = end_time - start_time;
-    let amount = calculate_amount(duration, hourly_rate);
+    notes: Option<String>,
+    /// Time between start and end that was paused and is not billed.
+    paused_seconds: i64,
+    /// Timer entries follow the project's rounding rule; manual ones are
+    /// stored as typed.
+    apply_rounding: bool,
+    is_break: bool,
+    billable: bool,
+    /// Defaults to the device's offset at `start_time`.
+    utc_offset: Option<i32>,
+    source: audit::EntrySource,
+}
 
+async fn persist_time_entry(db_path: PathBuf, entry: NewTimeEntry) -> Result<TimeEntry, String> {
     tauri::async_runtime::spawn_blocking(move || {
         let conn = open_connection(db_path)?;
-        conn.execute(
-            "INSERT INTO time_entries (project_name, start_time, end_time, duration, hourly_rate, amount)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![project_name, start_time, end_time, duration, hourly_rate, amount],
-        )
-        .map_err(|err| err.to_string())?;
+        insert_time_entry(&conn, entry)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
 
-        let id = conn.last_insert_rowid();
+fn insert_time_entry(conn: &Connection, entry: NewTimeEntry) -> Result<TimeEntry, String> {
+    if !entry.is_break {
+        projects::ensure_project(conn, &entry.project_name)?;
+    }
+    let entry_type = if entry.is_break {
+        ENTRY_TYPE_BREAK
+    } else {
+        ENTRY_TYPE_WORK
+    };
 
-        Ok::<_, String>(TimeEntry {
-            id,
-            project_name,
-            start_time,
+    let tracked = (entry.end_time - entry.start_time - entry.paused_seconds).max(0);
+    // A project's own rule wins over the default from settings.
+    let rule = if entry.apply_rounding {
+        match projects::rounding_rule_for(conn, &entry.project_name)? {
+            Some(rule) => Some(rule),
+            None => settings::load_settings(conn)?.rounding_rule(),
+        }
+    } else {
+        None
+    };
+    let (duration, raw_duration) = match rule {
+        Some((minutes, direction)) => (
+            projects::round_duration(tracked, minutes, direction),
+            Some(tracked),
+        ),
+        None => (tracked, None),
+    };
+    // Rounding moves the end; pauses keep the real stop time.
+    let end_time = entry.end_time + (duration - tracked);
+    let amount = calculate_amount(duration, entry.hourly_rate);
+    let utc_offset = match entry.utc_offset {
+        Some(minutes) => fixed_offset(minutes).map(|_| minutes)?,
+        None => local_utc_offset_minutes(entry.start_time),
+    };
+
+    conn.execute(
+        "INSERT INTO time_entries (project_name, start_time, end_time, duration, hourly_rate, amount, notes, raw_duration, entry_type, billable, utc_offset)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            entry.project_name,
+            entry.start_time,
             end_time,
             duration,
-            hourly_rate,
+            entry.hourly_rate,
             amount,
-        })
-    })
-    .await
-    .map_err(|err| err.to_string())?
+            entry.notes,
+            raw_duration,
+            entry_type,
+            entry.billable,
+            utc_offset
+        ],
+    )
+    .map_err(|err| err.to_string())?;
+
+    let saved = fetch_time_entry(conn, conn.last_insert_rowid())?;
+    audit::record_change(conn, audit::AuditAction::Create, entry.source, None, Some(&saved))?;
+    Ok(saved)
 }
 
 fn open_connection(db_path: PathBuf) -> Result<Connection, String> {
@@ -911,36 +3265,665 @@ fn open_connection(db_path: PathBuf) -> Result<Connection, String> {
         .map_err(|err| err.to_string())?;
     conn.execute(CREATE_TIME_ENTRIES_TABLE_SQL, [])
         .map_err(|err| err.to_string())?;
-    conn.execute(CREATE_ACTIVE_TIMER_TABLE_SQL, [])
+    conn.execute(CREATE_ACTIVE_TIMERS_TABLE_SQL, [])
         .map_err(|err| err.to_string())?;
     conn.execute(CREATE_INVOICES_TABLE_SQL, [])
         .map_err(|err| err.to_string())?;
-    ensure_rate_columns(&conn)?;
+    conn.execute(CREATE_INVOICE_ENTRIES_TABLE_SQL, [])
+        .map_err(|err| err.to_string())?;
+    conn.execute(settings::CREATE_SETTINGS_TABLE_SQL, [])
+        .map_err(|err| err.to_string())?;
+    conn.execute(clients::CREATE_CLIENTS_TABLE_SQL, [])
+        .map_err(|err| err.to_string())?;
+    conn.execute(projects::CREATE_PROJECTS_TABLE_SQL, [])
+        .map_err(|err| err.to_string())?;
+    conn.execute(retainers::CREATE_RETAINERS_TABLE_SQL, [])
+        .map_err(|err| err.to_string())?;
+    conn.execute(profiles::CREATE_BILL_TO_PROFILES_TABLE_SQL, [])
+        .map_err(|err| err.to_string())?;
+    conn.execute(profiles::CREATE_BUSINESS_PROFILES_TABLE_SQL, [])
+        .map_err(|err| err.to_string())?;
+    conn.execute(segments::CREATE_TIME_ENTRY_SEGMENTS_TABLE_SQL, [])
+        .map_err(|err| err.to_string())?;
+    conn.execute(audit::CREATE_ENTRY_AUDIT_TABLE_SQL, [])
+        .map_err(|err| err.to_string())?;
+    conn.execute(templates::CREATE_ENTRY_TEMPLATES_TABLE_SQL, [])
+        .map_err(|err| err.to_string())?;
+    conn.execute(recurring::CREATE_RECURRING_ENTRIES_TABLE_SQL, [])
+        .map_err(|err| err.to_string())?;
+    conn.execute(recurring::CREATE_RECURRING_ENTRY_RUNS_TABLE_SQL, [])
+        .map_err(|err| err.to_string())?;
+    conn.execute(estimates::CREATE_ESTIMATES_TABLE_SQL, [])
+        .map_err(|err| err.to_string())?;
+    conn.execute(recurring_invoices::CREATE_RECURRING_INVOICES_TABLE_SQL, [])
+        .map_err(|err| err.to_string())?;
+    ensure_time_entry_columns(&conn)?;
+    migrate_legacy_active_timer(&conn)?;
+    projects::ensure_project_columns(&conn)?;
+    ensure_column(&conn, "invoices", "client_id", "INTEGER REFERENCES clients(id)")?;
+    ensure_column(&conn, "invoices", "currency", "TEXT NOT NULL DEFAULT 'USD'")?;
+    ensure_column(&conn, "invoices", "due_date", "INTEGER")?;
+    ensure_column(&conn, "invoices", "payment_terms", "TEXT")?;
+    ensure_column(&conn, "invoices", "line_items", "TEXT")?;
+    ensure_column(&conn, "invoices", "language", "TEXT")?;
+    ensure_column(&conn, "invoices", "period_start", "INTEGER")?;
+    ensure_column(&conn, "invoices", "period_end", "INTEGER")?;
+    ensure_column(&conn, "invoices", "invoice_type", "TEXT NOT NULL DEFAULT 'invoice'")?;
+    ensure_column(&conn, "invoices", "credited_invoice_id", "INTEGER REFERENCES invoices(id)")?;
+    ensure_column(&conn, "invoices", "status", "TEXT NOT NULL DEFAULT 'issued'")?;
+    ensure_column(&conn, "invoices", "sent_at", "INTEGER")?;
+    ensure_column(&conn, "invoices", "paid_at", "INTEGER")?;
+    ensure_column(&conn, "invoices", "timesheet", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(&conn, "invoices", "rounding", "TEXT")?;
+    ensure_column(&conn, "invoices", "line_mode", "TEXT")?;
+    ensure_column(&conn, "invoices", "group_by", "TEXT")?;
+    ensure_column(&conn, "invoices", "entry_filter", "TEXT")?;
+    ensure_column(&conn, "clients", "invoice_terms", "TEXT")?;
+    ensure_column(&conn, "clients", "invoice_language", "TEXT")?;
+    ensure_column(&conn, "active_timers", "target_seconds", "INTEGER")?;
+    ensure_column(&conn, "active_timers", "last_heartbeat", "INTEGER")?;
+    ensure_column(&conn, "active_timers", "is_break", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(&conn, "active_timers", "utc_offset", "INTEGER")?;
     Ok(conn)
 }
 
 fn fetch_time_entry(conn: &Connection, id: i64) -> Result<TimeEntry, String> {
     conn
         .query_row(
-            "SELECT id, project_name, start_time, end_time, duration, hourly_rate, amount
-             FROM time_entries
-             WHERE id = ?1",
+            &format!(
+                "SELECT {TIME_ENTRY_COLUMNS}
+                 FROM time_entries
+                 WHERE id = ?1"
+            ),
             params![id],
-            |row| {
-                Ok(TimeEntry {
-                    id: row.get(0)?,
-                    project_name: row.get(1)?,
-                    start_time: row.get(2)?,
-                    end_time: row.get(3)?,
-                    duration: row.get(4)?,
-                    hourly_rate: row.get(5)?,
-                    amount: row.get(6)?,
-                })
-            },
+            map_time_entry,
         )
         .map_err(|err| err.to_string())
 }
 
+fn map_time_entry(row: &rusqlite::Row) -> rusqlite::Result<TimeEntry> {
+    Ok(TimeEntry {
+        id: row.get(0)?,
+        project_name: row.get(1)?,
+        start_time: row.get(2)?,
+        end_time: row.get(3)?,
+        duration: row.get(4)?,
+        hourly_rate: row.get(5)?,
+        amount: row.get(6)?,
+        notes: row.get(7)?,
+        project_color: row.get(8)?,
+        project_icon: row.get(9)?,
+        raw_duration: row.get(10)?,
+        currency: row.get(11)?,
+        entry_type: row.get(12)?,
+        billable: row.get(13)?,
+        starred: row.get(14)?,
+        utc_offset: row.get(15)?,
+        invoice_id: row.get(16)?,
+    })
+}
+
+/// The configured logo, skipped if its file has since gone missing.
+fn invoice_logo(settings: &settings::AppSettings) -> Option<pdf_generator::InvoiceLogo> {
+    let data = fs::read(settings.invoice_logo_path.as_deref()?).ok()?;
+    Some(pdf_generator::InvoiceLogo {
+        data,
+        width_mm: settings
+            .invoice_logo_width_mm
+            .unwrap_or(settings::DEFAULT_LOGO_WIDTH_MM) as f32,
+    })
+}
+
+/// The configured fonts. Unlike a missing logo, a missing font fails the
+/// invoice rather than quietly dropping characters Helvetica can't print.
+fn invoice_fonts(
+    settings: &settings::AppSettings,
+) -> Result<Option<pdf_generator::InvoiceFonts>, String> {
+    let read = |path: &str| {
+        fs::read(path).map_err(|err| format!("Couldn't read the invoice font {}: {}", path, err))
+    };
+    let Some(regular) = settings.invoice_font_path.as_deref() else {
+        return Ok(None);
+    };
+    Ok(Some(pdf_generator::InvoiceFonts {
+        regular: read(regular)?,
+        bold: settings.invoice_bold_font_path.as_deref().map(read).transpose()?,
+    }))
+}
+
+/// Cleans up a draft handed back by the frontend and loads the entries it
+/// bills, sorted by start. Entries deleted or invoiced elsewhere since the
+/// draft was prepared are refused. The rows are built again from those
+/// entries in the draft's layout and rounding, so a dropped entry is no
+/// longer billed; only the draft's descriptions are kept.
+async fn checked_draft(
+    db_path: PathBuf,
+    mut draft: InvoiceDraft,
+) -> Result<(InvoiceDraft, Vec<TimeEntry>), String> {
+    draft.entry_ids.sort_unstable();
+    draft.entry_ids.dedup();
+    draft.line_items = sanitize_line_items(draft.line_items)?;
+    draft.business_info.bank_account = bank_account::sanitize(draft.business_info.bank_account)?;
+    draft.business_info.notes = sanitize_notes(draft.business_info.notes)?;
+    draft.business_info.terms = clients::non_empty(draft.business_info.terms);
+    draft.payment_terms = clients::non_empty(draft.payment_terms);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut entries = Vec::with_capacity(draft.entry_ids.len());
+        for &id in &draft.entry_ids {
+            let entry = fetch_time_entry(&conn, id)?;
+            if entry.entry_type != ENTRY_TYPE_WORK || !entry.billable {
+                return Err(format!("Entry {} isn't billable work", id));
+            }
+            if entry.invoice_id.is_some() && !draft.include_invoiced {
+                return Err(format!("Entry {} has been invoiced since this draft was prepared", id));
+            }
+            if entry.currency != draft.currency {
+                return Err(format!("Entry {} isn't billed in {}", id, draft.currency));
+            }
+            entries.push(entry);
+        }
+        entries.sort_by_key(|entry| entry.start_time);
+
+        let labels = draft.language.labels();
+        let mut line_items = build_invoice_line_items(
+            &conn,
+            &entries,
+            draft.line_mode,
+            draft.group_by,
+            labels,
+            None,
+        )?;
+        if let Some(rounding) = draft.rounding.as_ref() {
+            rounding.validate()?;
+            rounding.apply(&mut line_items, labels);
+        }
+        if line_items.is_empty() {
+            return Err("The draft lists no billable time left to invoice".into());
+        }
+        draft.line_items = carry_line_wording(line_items, &draft.line_items);
+        Ok((draft, entries))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Trims descriptions and rejects rows that can't be printed or totalled.
+fn sanitize_line_items(
+    line_items: Vec<pdf_generator::InvoiceLineItem>,
+) -> Result<Vec<pdf_generator::InvoiceLineItem>, String> {
+    if line_items.is_empty() {
+        return Err("An invoice needs at least one line item".into());
+    }
+    line_items
+        .into_iter()
+        .map(|mut item| {
+            item.description = item.description.trim().to_string();
+            if item.description.is_empty() {
+                return Err("Every line item needs a description".to_string());
+            }
+            if ![item.quantity, item.unit_price, item.amount]
+                .iter()
+                .all(|value| value.is_finite())
+            {
+                return Err(format!("\"{}\" has an invalid quantity, price or amount", item.description));
+            }
+            item.date = clients::non_empty(item.date);
+            Ok(item)
+        })
+        .collect()
+}
+
+/// Gives rebuilt rows the descriptions the draft's rows were reworded to. A
+/// row takes the wording of the draft row with the same figures, or of the
+/// row in its place when the number of rows is unchanged, as when a summary
+/// row bills one entry fewer.
+fn carry_line_wording(
+    rebuilt: Vec<pdf_generator::InvoiceLineItem>,
+    edited: &[pdf_generator::InvoiceLineItem],
+) -> Vec<pdf_generator::InvoiceLineItem> {
+    let same_figures = |a: &pdf_generator::InvoiceLineItem, b: &pdf_generator::InvoiceLineItem| {
+        a.fixed_fee == b.fixed_fee
+            && a.date == b.date
+            && (a.quantity - b.quantity).abs() < 1e-6
+            && (a.amount - b.amount).abs() < 0.005
+    };
+    let in_place = rebuilt.len() == edited.len();
+    let mut used = vec![false; edited.len()];
+    rebuilt
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut item)| {
+            let wording = (0..edited.len())
+                .find(|&other| !used[other] && same_figures(&item, &edited[other]))
+                .or_else(|| {
+                    (in_place && !used[index] && edited[index].fixed_fee == item.fixed_fee)
+                        .then_some(index)
+                });
+            if let Some(other) = wording {
+                used[other] = true;
+                item.description = edited[other].description.clone();
+            }
+            item
+        })
+        .collect()
+}
+
+/// Draws the invoice with the current logo and fonts, the terms of the billed
+/// client and projects, and the client's retainer balance as of issue. Credit
+/// notes and estimates only get their own terms.
+fn render_invoice_pdf(
+    app_handle: &AppHandle,
+    render: InvoiceRender,
+    output_path: &str,
+) -> Result<(), String> {
+    let InvoiceRender {
+        id,
+        business_info,
+        entries,
+        line_items,
+        period,
+        currency,
+        client_id,
+        language,
+        due_date,
+        payment_terms,
+        issued_at,
+        preview,
+        kind,
+        timesheet,
+        rounding,
+    } = render;
+
+    let (retainer, terms) = if kind != pdf_generator::DocumentKind::Invoice {
+        (None, business_info.terms.clone().into_iter().collect())
+    } else {
+        let conn = open_connection(resolve_db_path(app_handle)?)?;
+        let retainer = match client_id {
+            Some(id) => retainers::active_retainer_balance(&conn, id, issued_at)?,
+            None => None,
+        };
+        let terms = business_info
+            .terms
+            .clone()
+            .into_iter()
+            .chain(collect_invoice_terms(&conn, client_id, &entries)?)
+            .collect::<Vec<_>>();
+        (retainer, terms)
+    };
+    let settings = current_settings(app_handle)?;
+    let logo = invoice_logo(&settings);
+    let fonts = invoice_fonts(&settings)?;
+
+    let entries = entries.into_iter().map(pdf_generator::TimeEntry::from).collect();
+    let business_info = pdf_generator::BusinessInfo {
+        name: business_info.name,
+        address: business_info.address,
+        email: business_info.email,
+        phone: business_info.phone,
+        tax_id: business_info.tax_id,
+        bank_details: business_info.bank_details,
+        client_name: business_info.client_name,
+        client_address: business_info.client_address,
+        client_email: business_info.client_email,
+        client_phone: business_info.client_phone,
+        bank_account: business_info.bank_account,
+        notes: business_info.notes,
+    };
+
+    let document = pdf_generator::InvoiceDocument {
+        number: id.map(pdf_generator::invoice_number),
+        entries,
+        line_items,
+        business_info,
+        period: period.map(|(start_time, end_time)| pdf_generator::InvoicePeriod {
+            start_time,
+            end_time,
+        }),
+        currency,
+        terms,
+        retainer: retainer.map(|balance| pdf_generator::RetainerSummary {
+            purchased_hours: balance.retainer.hours,
+            used_hours: balance.used_hours,
+            remaining_hours: balance.remaining_hours,
+        }),
+        logo,
+        fonts,
+        language,
+        due_date,
+        payment_terms,
+        issued_at,
+        preview,
+        kind,
+        timesheet,
+        rounding_note: rounding.map(|rounding| rounding.note(language.labels())),
+    };
+    pdf_generator::generate_invoice(document, output_path)?;
+    match settings.pdf_signing.as_ref() {
+        Some(signing) if !preview => {
+            signing::sign_pdf(output_path, signing, &resolve_scratch_dir(app_handle)?)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Hourly work is summarised as one "Hours worked" row, listed entry by
+/// entry in detailed mode, or aggregated by `group_by`, which wins over the
+/// mode when set. Every hourly row carries the notes of the entries it bills.
+/// Each fixed-fee project gets its own row billed at the flat
+/// fee, with tracked hours noted for reference only; once an invoice other
+/// than `invoice_id` has billed the fee, the row is listed at no charge.
+fn build_invoice_line_items(
+    conn: &Connection,
+    entries: &[TimeEntry],
+    mode: InvoiceLineMode,
+    group_by: Option<InvoiceGrouping>,
+    labels: &invoice_labels::InvoiceLabels,
+    invoice_id: Option<i64>,
+) -> Result<Vec<pdf_generator::InvoiceLineItem>, String> {
+    let mut hourly: Vec<&TimeEntry> = Vec::new();
+    let mut fixed: Vec<(String, f64, bool, f64)> = Vec::new();
+
+    for entry in entries {
+        let hours = entry.duration as f64 / 3600.0;
+        if let Some(index) = fixed.iter().position(|(name, ..)| *name == entry.project_name) {
+            fixed[index].3 += hours;
+            continue;
+        }
+        match projects::fixed_fee_billing(conn, &entry.project_name, invoice_id)? {
+            Some((fee, billed)) => fixed.push((entry.project_name.clone(), fee, billed, hours)),
+            None => hourly.push(entry),
+        }
+    }
+
+    let mut items = Vec::new();
+    match (group_by, mode) {
+        (Some(grouping), _) => items.extend(group_line_items(&hourly, grouping, labels)),
+        (None, InvoiceLineMode::Summary) => {
+            let hourly_hours: f64 = hourly.iter().map(|entry| entry.duration as f64 / 3600.0).sum();
+            let hourly_amount: f64 = hourly.iter().map(|entry| entry.amount).sum();
+            if hourly_hours > 0.0 {
+                let notes = hourly.iter().filter_map(|entry| entry.notes.as_deref());
+                items.push(pdf_generator::InvoiceLineItem {
+                    date: None,
+                    description: with_entry_notes(labels.hours_worked.into(), notes),
+                    quantity: hourly_hours,
+                    unit_price: hourly_amount / hourly_hours,
+                    amount: hourly_amount,
+                    fixed_fee: false,
+                });
+            }
+        }
+        (None, InvoiceLineMode::Detailed) => {
+            let mut hourly = hourly;
+            hourly.sort_by_key(|entry| (entry.start_time, entry.id));
+            for entry in hourly.into_iter().filter(|entry| entry.duration > 0) {
+                items.push(pdf_generator::InvoiceLineItem {
+                    date: Some(
+                        entry_local_date(entry)
+                            .format(labels.date_format)
+                            .to_string(),
+                    ),
+                    description: match entry.notes.as_deref() {
+                        Some(notes) => format!("{}: {}", entry.project_name, notes),
+                        None => entry.project_name.clone(),
+                    },
+                    quantity: entry.duration as f64 / 3600.0,
+                    unit_price: entry.hourly_rate,
+                    amount: entry.amount,
+                    fixed_fee: false,
+                });
+            }
+        }
+    }
+    for (name, fee, billed, hours) in fixed {
+        let (label, fee) = if billed {
+            (labels.fixed_fee_billed, 0.0)
+        } else {
+            (labels.fixed_fee, fee)
+        };
+        items.push(pdf_generator::InvoiceLineItem {
+            date: None,
+            description: format!("{} ({}, {:.2} h {})", name, label, hours, labels.tracked),
+            quantity: 1.0,
+            unit_price: fee,
+            amount: fee,
+            fixed_fee: true,
+        });
+    }
+    Ok(items)
+}
+
+/// One row per group and rate, in date order for days and weeks and by
+/// project name otherwise.
+fn group_line_items(
+    entries: &[&TimeEntry],
+    grouping: InvoiceGrouping,
+    labels: &invoice_labels::InvoiceLabels,
+) -> Vec<pdf_generator::InvoiceLineItem> {
+    struct Group {
+        key: String,
+        date: Option<NaiveDate>,
+        rate: f64,
+        projects: Vec<String>,
+        notes: Vec<String>,
+        seconds: i64,
+        amount: f64,
+    }
+
+    let mut groups: Vec<Group> = Vec::new();
+    for entry in entries.iter().filter(|entry| entry.duration > 0) {
+        let day = entry_local_date(entry);
+        let (key, date) = match grouping {
+            InvoiceGrouping::Project => (entry.project_name.clone(), None),
+            InvoiceGrouping::Day => (day.to_string(), Some(day)),
+            InvoiceGrouping::Week => {
+                let monday = day - Duration::days(i64::from(day.weekday().num_days_from_monday()));
+                (monday.to_string(), Some(monday))
+            }
+        };
+        let index = match groups
+            .iter()
+            .position(|group| group.key == key && group.rate == entry.hourly_rate)
+        {
+            Some(index) => index,
+            None => {
+                groups.push(Group {
+                    key,
+                    date,
+                    rate: entry.hourly_rate,
+                    projects: Vec::new(),
+                    notes: Vec::new(),
+                    seconds: 0,
+                    amount: 0.0,
+                });
+                groups.len() - 1
+            }
+        };
+        let group = &mut groups[index];
+        if !group.projects.contains(&entry.project_name) {
+            group.projects.push(entry.project_name.clone());
+        }
+        group.notes.extend(entry.notes.clone());
+        group.seconds += entry.duration;
+        group.amount += entry.amount;
+    }
+
+    groups.sort_by(|a, b| {
+        a.key
+            .to_lowercase()
+            .cmp(&b.key.to_lowercase())
+            .then(a.rate.total_cmp(&b.rate))
+    });
+    groups
+        .into_iter()
+        .map(|group| pdf_generator::InvoiceLineItem {
+            date: group
+                .date
+                .map(|date| date.format(labels.date_format).to_string()),
+            description: with_entry_notes(
+                match grouping {
+                    InvoiceGrouping::Project => group.key,
+                    InvoiceGrouping::Day | InvoiceGrouping::Week => group.projects.join(", "),
+                },
+                group.notes.iter().map(String::as_str),
+            ),
+            quantity: group.seconds as f64 / 3600.0,
+            unit_price: group.rate,
+            amount: group.amount,
+            fixed_fee: false,
+        })
+        .collect()
+}
+
+/// `description` followed by each distinct note once, the way a detailed row
+/// follows its project with the entry's notes.
+fn with_entry_notes<'a>(description: String, notes: impl IntoIterator<Item = &'a str>) -> String {
+    let mut distinct: Vec<&str> = Vec::new();
+    for note in notes {
+        if !distinct.contains(&note) {
+            distinct.push(note);
+        }
+    }
+    if distinct.is_empty() {
+        description
+    } else {
+        format!("{}: {}", description, distinct.join("; "))
+    }
+}
+
+/// The day an entry started where it was tracked.
+fn entry_local_date(entry: &TimeEntry) -> NaiveDate {
+    let start = Utc
+        .timestamp_opt(entry.start_time, 0)
+        .single()
+        .unwrap_or_default();
+    match entry.utc_offset.and_then(|minutes| fixed_offset(minutes).ok()) {
+        Some(offset) => start.with_timezone(&offset).date_naive(),
+        None => start.with_timezone(&Local).date_naive(),
+    }
+}
+
+
+fn query_invoice_entries(
+    conn: &Connection,
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+    filter: &InvoiceEntryFilter,
+) -> Result<Vec<TimeEntry>, String> {
+    // Breaks and non-billable work are never billed.
+    let mut clauses: Vec<String> = vec![
+        format!("entry_type = '{ENTRY_TYPE_WORK}'"),
+        "billable = 1".into(),
+    ];
+    if !filter.include_invoiced.unwrap_or(false) {
+        clauses.push("invoice_id IS NULL".into());
+    }
+    let mut values: Vec<SqlValue> = Vec::new();
+
+    if let (Some(start), Some(end)) = (start_time, end_time) {
+        clauses.push("start_time >= ? AND start_time < ?".into());
+        values.push(start.into());
+        values.push(end.into());
+    }
+    if let Some(client_id) = filter.client_id {
+        clauses.push("project_name IN (SELECT name FROM projects WHERE client_id = ?)".into());
+        values.push(client_id.into());
+    }
+    if let Some(names) = filter.project_names.as_ref().filter(|names| !names.is_empty()) {
+        let placeholders = vec!["?"; names.len()].join(", ");
+        clauses.push(format!("project_name IN ({placeholders})"));
+        values.extend(names.iter().map(|name| SqlValue::from(name.trim().to_string())));
+    }
+
+    let where_sql = clauses.join(" AND ");
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {TIME_ENTRY_COLUMNS}
+             FROM time_entries
+             WHERE {where_sql}
+             ORDER BY start_time ASC"
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values), map_time_entry)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Terms printed on the invoice: the billed client's first, then those of
+/// each invoiced project, skipping repeats.
+fn collect_invoice_terms(
+    conn: &Connection,
+    client_id: Option<i64>,
+    entries: &[TimeEntry],
+) -> Result<Vec<String>, String> {
+    let mut terms: Vec<String> = Vec::new();
+    if let Some(id) = client_id {
+        terms.extend(clients::fetch_client(conn, id)?.invoice_terms);
+    }
+    let mut seen_projects: Vec<&str> = Vec::new();
+    for entry in entries {
+        if seen_projects.contains(&entry.project_name.as_str()) {
+            continue;
+        }
+        seen_projects.push(&entry.project_name);
+        let project_terms = projects::fetch_project(conn, &entry.project_name)
+            .ok()
+            .and_then(|project| project.invoice_terms);
+        if let Some(text) = project_terms {
+            if !terms.contains(&text) {
+                terms.push(text);
+            }
+        }
+    }
+    Ok(terms)
+}
+
+fn map_invoice(row: &rusqlite::Row) -> rusqlite::Result<Invoice> {
+    Ok(Invoice {
+        id: row.get(0)?,
+        created_at: row.get(1)?,
+        business_info: row.get(2)?,
+        bill_to_info: row.get(3)?,
+        total_hours: row.get(4)?,
+        total_amount: row.get(5)?,
+        file_path: row.get(6)?,
+        entry_count: row.get(7)?,
+        client_id: row.get(8)?,
+        currency: row.get(9)?,
+        due_date: row.get(10)?,
+        payment_terms: row.get(11)?,
+        invoice_type: row.get(12)?,
+        credited_invoice_id: row.get(13)?,
+        status: row.get(14)?,
+        sent_at: row.get(15)?,
+        paid_at: row.get(16)?,
+    })
+}
+
+fn prefill_bill_to(
+    business_info: &mut BusinessInfo,
+    name: &str,
+    address: Option<&str>,
+    email: Option<&str>,
+) {
+    fill_if_blank(&mut business_info.client_name, Some(name));
+    fill_if_blank(&mut business_info.client_address, address);
+    fill_if_blank(&mut business_info.client_email, email);
+}
+
+fn fill_if_blank(field: &mut Option<String>, value: Option<&str>) {
+    let is_blank = field.as_deref().map(str::trim).unwrap_or("").is_empty();
+    if is_blank {
+        if let Some(value) = value {
+            *field = Some(value.to_string());
+        }
+    }
+}
+
 fn database_migrations() -> Vec<Migration> {
     vec![
         Migration {
@@ -961,40 +3944,195 @@ fn database_migrations() -> Vec<Migration> {
             sql: CREATE_ACTIVE_TIMER_TABLE_SQL,
             kind: MigrationKind::Up,
         },
+        Migration {
+            version: 4,
+            description: "create_settings",
+            sql: settings::CREATE_SETTINGS_TABLE_SQL,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 5,
+            description: "create_clients",
+            sql: clients::CREATE_CLIENTS_TABLE_SQL,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 6,
+            description: "create_projects",
+            sql: projects::CREATE_PROJECTS_TABLE_SQL,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 7,
+            description: "create_retainers",
+            sql: retainers::CREATE_RETAINERS_TABLE_SQL,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 8,
+            description: "create_bill_to_profiles",
+            sql: profiles::CREATE_BILL_TO_PROFILES_TABLE_SQL,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 9,
+            description: "create_business_profiles",
+            sql: profiles::CREATE_BUSINESS_PROFILES_TABLE_SQL,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 10,
+            description: "create_active_timers",
+            sql: CREATE_ACTIVE_TIMERS_TABLE_SQL,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 11,
+            description: "create_time_entry_segments",
+            sql: segments::CREATE_TIME_ENTRY_SEGMENTS_TABLE_SQL,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 12,
+            description: "create_entry_audit",
+            sql: audit::CREATE_ENTRY_AUDIT_TABLE_SQL,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 13,
+            description: "create_entry_templates",
+            sql: templates::CREATE_ENTRY_TEMPLATES_TABLE_SQL,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 14,
+            description: "create_recurring_entries",
+            sql: recurring::CREATE_RECURRING_ENTRIES_TABLE_SQL,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 15,
+            description: "create_recurring_entry_runs",
+            sql: recurring::CREATE_RECURRING_ENTRY_RUNS_TABLE_SQL,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 16,
+            description: "create_estimates",
+            sql: estimates::CREATE_ESTIMATES_TABLE_SQL,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 17,
+            description: "create_recurring_invoices",
+            sql: recurring_invoices::CREATE_RECURRING_INVOICES_TABLE_SQL,
+            kind: MigrationKind::Up,
+        },
     ]
 }
 
-fn day_bounds_timestamps() -> Result<(i64, i64), String> {
-    let now = Local::now();
+/// Today's bounds at `utc_offset` minutes east of UTC, or in the device's
+/// zone when it is `None`.
+fn day_bounds_timestamps(utc_offset: Option<i32>) -> Result<(i64, i64), String> {
+    match utc_offset {
+        Some(minutes) => day_bounds_in(&fixed_offset(minutes)?),
+        None => day_bounds_in(&Local),
+    }
+}
+
+fn day_bounds_in<Tz: TimeZone>(tz: &Tz) -> Result<(i64, i64), String> {
+    let now = Utc::now().with_timezone(tz);
     let start_local = now
         .date_naive()
         .and_hms_opt(0, 0, 0)
         .ok_or_else(|| "Failed to compute start of day".to_string())?;
 
-    let start_local_dt = match Local.from_local_datetime(&start_local) {
-        LocalResult::Single(dt) => dt,
-        _ => return Err("Unable to resolve local time".into()),
-    };
+    let start_local_dt = match tz.from_local_datetime(&start_local) {
+        LocalResult::Single(dt) => dt,
+        _ => return Err("Unable to resolve local time".into()),
+    };
+
+    let end_local_dt = start_local_dt.clone() + Duration::days(1);
+
+    let start_ts = start_local_dt.with_timezone(&Utc).timestamp();
+    let end_ts = end_local_dt.with_timezone(&Utc).timestamp();
+
+    Ok((start_ts, end_ts))
+}
+
+/// A UTC offset in minutes, as stored on entries and accepted by commands.
+fn fixed_offset(minutes: i32) -> Result<FixedOffset, String> {
+    if minutes.abs() > MAX_UTC_OFFSET_MINUTES {
+        return Err(format!(
+            "UTC offsets must be within ±{} hours",
+            MAX_UTC_OFFSET_MINUTES / 60
+        ));
+    }
+    FixedOffset::east_opt(minutes * 60).ok_or_else(|| "Invalid UTC offset".to_string())
+}
+
+/// The device's offset from UTC at `timestamp`, in minutes.
+fn local_utc_offset_minutes(timestamp: i64) -> i32 {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .earliest()
+        .map(|dt| dt.offset().fix().local_minus_utc() / 60)
+        .unwrap_or(0)
+}
 
-    let end_local_dt = start_local_dt + Duration::days(1);
+/// Splits `start..end` at each local midnight so every piece falls on a
+/// single calendar day.
+fn split_at_local_midnights(start: i64, end: i64) -> Vec<(i64, i64)> {
+    let mut segments = Vec::new();
+    let mut cursor = start;
+    while let Some(midnight) = next_local_midnight(cursor).filter(|midnight| *midnight < end) {
+        segments.push((cursor, midnight));
+        cursor = midnight;
+    }
+    segments.push((cursor, end));
+    segments
+}
 
-    let start_ts = start_local_dt.with_timezone(&Utc).timestamp();
-    let end_ts = end_local_dt.with_timezone(&Utc).timestamp();
+/// Shares pauses, which aren't timestamped, out over `segments` by their
+/// length. The last segment takes what's left so the shares add up.
+fn share_paused_seconds(segments: &[(i64, i64)], paused_seconds: i64) -> Vec<i64> {
+    let (Some(&(start, _)), Some(&(_, end))) = (segments.first(), segments.last()) else {
+        return Vec::new();
+    };
+    let span = (end - start).max(1);
+    let mut remaining = paused_seconds;
+    let mut shares: Vec<i64> = segments[..segments.len() - 1]
+        .iter()
+        .map(|(start, end)| {
+            let share = paused_seconds * (end - start) / span;
+            remaining -= share;
+            share
+        })
+        .collect();
+    shares.push(remaining);
+    shares
+}
 
-    Ok((start_ts, end_ts))
+fn next_local_midnight(ts: i64) -> Option<i64> {
+    let local = Local.timestamp_opt(ts, 0).single()?;
+    let midnight = local.date_naive().succ_opt()?.and_hms_opt(0, 0, 0)?;
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.timestamp())
 }
 
 fn setup_tray(app: &mut tauri::App) -> tauri::Result<()> {
     let assets = app.state::<TrayAssets>();
-    let initial_status = {
+    let initial_statuses = {
         let timer_state = app.state::<TimerState>();
-        timer_state.status()
+        timer_state.statuses()
     };
     let app_handle = app.handle();
     let today_total = current_today_totals(&app_handle).map_err(to_tauri_error)?;
     let initial_menu = build_tray_menu(
         &app_handle,
-        &initial_status,
+        &initial_statuses,
         today_total.total_seconds,
         is_main_window_visible(&app_handle),
     )?;
@@ -1005,19 +4143,34 @@ fn setup_tray(app: &mut tauri::App) -> tauri::Result<()> {
         .menu(&initial_menu)
         .show_menu_on_left_click(true)
         .on_menu_event(|app, event| match event.id().as_ref() {
-            MENU_STATUS_ID => {}
             MENU_START_ID => {
-                let _ = start_timer_internal(app, "Quick Task".to_string(), 0.0);
-            }
-            MENU_STOP_ID => {
                 let app_handle = app.clone();
                 tauri::async_runtime::spawn(async move {
-                    let _ = stop_timer_internal(&app_handle).await;
+                    let _ = start_timer_from_tray(app_handle, String::new()).await;
                 });
             }
+            MENU_BREAK_ID => {
+                let _ = start_break_internal(app);
+            }
             MENU_TOGGLE_WINDOW_ID => toggle_main_window(app),
             MENU_QUIT_ID => app.exit(0),
-            _ => {}
+            id => {
+                if let Some(timer_id) = parse_timer_menu_id(id, MENU_STOP_ID) {
+                    let app_handle = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let _ =
+                            stop_timer_internal(&app_handle, Some(timer_id), StopOrigin::Tray, None)
+                                .await;
+                    });
+                } else if let Some(timer_id) = parse_timer_menu_id(id, MENU_PAUSE_ID) {
+                    let paused = app
+                        .state::<TimerState>()
+                        .statuses()
+                        .iter()
+                        .any(|status| status.timer_id == Some(timer_id) && status.is_paused);
+                    let _ = set_timer_paused(app, Some(timer_id), !paused);
+                }
+            }
         })
         .on_tray_icon_event(|icon, event| {
             if let TrayIconEvent::Click { .. } = event {
@@ -1043,30 +4196,30 @@ fn toggle_main_window(app: &AppHandle) {
 }
 
 fn refresh_tray(app: &AppHandle) -> tauri::Result<()> {
-    let status = {
+    let statuses = {
         let timer_state = app.state::<TimerState>();
-        timer_state.status()
+        timer_state.statuses()
     };
     let totals = current_today_totals(app).map_err(to_tauri_error)?;
 
-    apply_tray_updates(app, &status, totals.total_seconds)
+    apply_tray_updates(app, &statuses, totals.total_seconds)
 }
 
 fn apply_tray_updates(
     app: &AppHandle,
-    status: &TimerStatusPayload,
+    statuses: &[TimerStatusPayload],
     today_total_seconds: i64,
 ) -> tauri::Result<()> {
     let tray_assets = app.state::<TrayAssets>();
     if let Some(tray) = app.tray_by_id(TRAY_ID) {
-        let icon = if status.is_running {
-            tray_assets.running_icon.clone()
-        } else {
+        let icon = if statuses.is_empty() {
             tray_assets.idle_icon.clone()
+        } else {
+            tray_assets.running_icon.clone()
         };
         tray.set_icon(Some(icon))?;
 
-        let tooltip = build_status_text(status);
+        let tooltip = build_tray_tooltip(statuses);
         if tooltip.is_empty() {
             tray.set_tooltip(None::<&str>)?;
         } else {
@@ -1074,7 +4227,7 @@ fn apply_tray_updates(
         }
         let menu = build_tray_menu(
             app,
-            status,
+            statuses,
             today_total_seconds,
             is_main_window_visible(app),
         )?;
@@ -1084,27 +4237,100 @@ fn apply_tray_updates(
     Ok(())
 }
 
+fn build_tray_tooltip(statuses: &[TimerStatusPayload]) -> String {
+    if statuses.is_empty() {
+        return build_status_text(&TimerStatusPayload::idle());
+    }
+    statuses
+        .iter()
+        .map(build_status_text)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Menu ids for per-timer actions are `<action>:<timer id>`.
+fn timer_menu_id(action: &str, timer_id: Option<i64>) -> String {
+    format!("{}:{}", action, timer_id.unwrap_or_default())
+}
+
+fn parse_timer_menu_id(id: &str, action: &str) -> Option<i64> {
+    id.strip_prefix(action)?.strip_prefix(':')?.parse().ok()
+}
+
 fn build_tray_menu<R: Runtime>(
     app: &AppHandle<R>,
-    status: &TimerStatusPayload,
+    statuses: &[TimerStatusPayload],
     today_total_seconds: i64,
     window_visible: bool,
 ) -> tauri::Result<tauri::menu::Menu<R>> {
-    let status_item = MenuItemBuilder::with_id(MENU_STATUS_ID, build_status_text(status))
-        .enabled(false)
-        .build(app)?;
+    let mut status_items = Vec::new();
+    if statuses.is_empty() {
+        status_items.push(
+            MenuItemBuilder::with_id(MENU_STATUS_ID, build_status_text(&TimerStatusPayload::idle()))
+                .enabled(false)
+                .build(app)?,
+        );
+    }
+    for status in statuses {
+        status_items.push(
+            MenuItemBuilder::with_id(
+                timer_menu_id(MENU_STATUS_ID, status.timer_id),
+                build_status_text(status),
+            )
+            .enabled(false)
+            .build(app)?,
+        );
+    }
     let total_item = MenuItemBuilder::with_id(
         MENU_TOTAL_ID,
         format!("Total Today: {}", format_duration(today_total_seconds)),
     )
     .enabled(false)
     .build(app)?;
-    let start_item = MenuItemBuilder::with_id(MENU_START_ID, "Start Timer")
-        .enabled(!status.is_running)
-        .build(app)?;
-    let stop_item = MenuItemBuilder::with_id(MENU_STOP_ID, "Stop Timer")
-        .enabled(status.is_running)
+    // Starting another timer is allowed while others run.
+    let start_item = MenuItemBuilder::with_id(MENU_START_ID, "Start Timer").build(app)?;
+    let break_item = MenuItemBuilder::with_id(MENU_BREAK_ID, "Start Break")
+        .enabled(!statuses.iter().any(|status| status.is_break))
         .build(app)?;
+
+    // With one timer the actions keep their plain labels; with several each
+    // one names its project.
+    let mut timer_items = Vec::new();
+    if statuses.is_empty() {
+        timer_items.push(
+            MenuItemBuilder::with_id(MENU_STOP_ID, "Stop Timer")
+                .enabled(false)
+                .build(app)?,
+        );
+    }
+    for status in statuses {
+        if status.is_break {
+            timer_items.push(
+                MenuItemBuilder::with_id(timer_menu_id(MENU_STOP_ID, status.timer_id), "End Break")
+                    .build(app)?,
+            );
+            continue;
+        }
+        let target = match (&status.project_name, statuses.len()) {
+            (Some(name), count) if count > 1 => name.clone(),
+            _ => "Timer".to_string(),
+        };
+        let pause_action = if status.is_paused { "Resume" } else { "Pause" };
+        timer_items.push(
+            MenuItemBuilder::with_id(
+                timer_menu_id(MENU_PAUSE_ID, status.timer_id),
+                format!("{} {}", pause_action, target),
+            )
+            .build(app)?,
+        );
+        timer_items.push(
+            MenuItemBuilder::with_id(
+                timer_menu_id(MENU_STOP_ID, status.timer_id),
+                format!("Stop {}", target),
+            )
+            .build(app)?,
+        );
+    }
     let toggle_label = if window_visible {
         "Hide Window"
     } else {
@@ -1113,12 +4339,19 @@ fn build_tray_menu<R: Runtime>(
     let toggle_item = MenuItemBuilder::with_id(MENU_TOGGLE_WINDOW_ID, toggle_label).build(app)?;
     let quit_item = MenuItemBuilder::with_id(MENU_QUIT_ID, "Quit").build(app)?;
 
-    MenuBuilder::new(app)
-        .item(&status_item)
+    let mut builder = MenuBuilder::new(app);
+    for item in &status_items {
+        builder = builder.item(item);
+    }
+    builder = builder
         .item(&total_item)
         .separator()
         .item(&start_item)
-        .item(&stop_item)
+        .item(&break_item);
+    for item in &timer_items {
+        builder = builder.item(item);
+    }
+    builder
         .separator()
         .item(&toggle_item)
         .item(&quit_item)
@@ -1205,53 +4438,180 @@ fn sanitize_hourly_rate(rate: f64) -> f64 {
     }
 }
 
+fn sanitize_notes(notes: Option<String>) -> Result<Option<String>, String> {
+    let Some(notes) = notes else {
+        return Ok(None);
+    };
+    let trimmed = notes.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+    if trimmed.chars().count() > MAX_NOTES_LENGTH {
+        return Err(format!("Notes must be at most {} characters", MAX_NOTES_LENGTH));
+    }
+    Ok(Some(trimmed.to_string()))
+}
+
 fn current_unix_timestamp() -> i64 {
     Utc::now().timestamp()
 }
 
+/// Seconds on a clock that can't be set and keeps counting while the machine
+/// sleeps, so any other change in the wall clock is the clock being set.
+/// `Instant` stops during sleep on Linux and macOS, so there the clock is
+/// read directly.
+fn continuous_seconds() -> i64 {
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios"))]
+    {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let clock = libc::CLOCK_BOOTTIME;
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        let clock = libc::CLOCK_MONOTONIC;
+        let mut time = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: `time` is a valid timespec for the call to fill in.
+        if unsafe { libc::clock_gettime(clock, &mut time) } == 0 {
+            // `time_t` is 32 bits on some targets.
+            #[allow(clippy::unnecessary_cast)]
+            return time.tv_sec as i64;
+        }
+    }
+    // On Windows `Instant` counts time asleep.
+    static FIRST_READING: OnceLock<Instant> = OnceLock::new();
+    FIRST_READING.get_or_init(Instant::now).elapsed().as_secs() as i64
+}
+
+/// Saves a new running timer and returns its id.
+fn insert_active_timer(db_path: PathBuf, timer: &ActiveTimer) -> Result<i64, String> {
+    let conn = open_connection(db_path)?;
+    conn.execute(
+        "INSERT INTO active_timers
+             (project_name, start_time, hourly_rate, notes, paused_at, paused_seconds, target_seconds,
+              is_break, utc_offset, last_heartbeat)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            timer.project_name,
+            timer.start_time,
+            timer.hourly_rate,
+            timer.notes,
+            timer.paused_at,
+            timer.paused_seconds,
+            timer.target_seconds,
+            timer.is_break,
+            timer.utc_offset,
+            current_unix_timestamp()
+        ],
+    )
+    .map_err(|err| err.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
 fn persist_active_timer(db_path: PathBuf, timer: &ActiveTimer) -> Result<(), String> {
     let conn = open_connection(db_path)?;
     conn.execute(
-        "INSERT OR REPLACE INTO active_timer (id, project_name, start_time, hourly_rate)
-         VALUES (1, ?1, ?2, ?3)",
-        params![timer.project_name, timer.start_time, timer.hourly_rate],
+        "UPDATE active_timers
+         SET project_name = ?1, start_time = ?2, hourly_rate = ?3, notes = ?4,
+             paused_at = ?5, paused_seconds = ?6, target_seconds = ?7, last_heartbeat = ?8
+         WHERE id = ?9",
+        params![
+            timer.project_name,
+            timer.start_time,
+            timer.hourly_rate,
+            timer.notes,
+            timer.paused_at,
+            timer.paused_seconds,
+            timer.target_seconds,
+            current_unix_timestamp(),
+            timer.id
+        ],
     )
     .map_err(|err| err.to_string())?;
     Ok(())
 }
 
-fn clear_active_timer(db_path: PathBuf) -> Result<(), String> {
+fn clear_active_timer(db_path: PathBuf, timer_id: i64) -> Result<(), String> {
     let conn = open_connection(db_path)?;
-    conn.execute("DELETE FROM active_timer WHERE id = 1", [])
+    conn.execute("DELETE FROM active_timers WHERE id = ?1", params![timer_id])
         .map_err(|err| err.to_string())?;
     Ok(())
 }
 
-fn load_active_timer(db_path: PathBuf) -> Result<Option<ActiveTimer>, String> {
+fn load_active_timers(db_path: PathBuf) -> Result<Vec<ActiveTimer>, String> {
     let conn = open_connection(db_path)?;
-    let result = conn.query_row(
-        "SELECT project_name, start_time, hourly_rate FROM active_timer WHERE id = 1",
-        [],
-        |row| {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, project_name, start_time, hourly_rate, notes, paused_at, paused_seconds,
+                    target_seconds, is_break, utc_offset
+             FROM active_timers
+             ORDER BY start_time ASC, id ASC",
+        )
+        .map_err(|err| err.to_string())?;
+    let mut timers = stmt
+        .query_map([], |row| {
             Ok(ActiveTimer {
-                project_name: row.get(0)?,
-                start_time: row.get(1)?,
-                hourly_rate: row.get(2)?,
+                id: row.get(0)?,
+                project_name: row.get(1)?,
+                start_time: row.get(2)?,
+                hourly_rate: row.get(3)?,
+                project_color: None,
+                project_icon: None,
+                notes: row.get(4)?,
+                paused_at: row.get(5)?,
+                paused_seconds: row.get(6)?,
+                target_seconds: row.get(7)?,
+                currency: String::new(),
+                is_break: row.get(8)?,
+                utc_offset: row.get(9)?,
             })
-        },
-    );
-    match result {
-        Ok(timer) => Ok(Some(timer)),
-        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(err) => Err(err.to_string()),
+        })
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+    for timer in &mut timers {
+        let (color, icon) = projects::project_appearance(&conn, &timer.project_name)?;
+        timer.project_color = color;
+        timer.project_icon = icon;
+        timer.currency = projects::project_currency(&conn, &timer.project_name)?;
+    }
+    Ok(timers)
+}
+
+/// Databases from before concurrent timers kept a single timer in
+/// `active_timer`; move it into `active_timers` and drop the old table.
+fn migrate_legacy_active_timer(conn: &Connection) -> Result<(), String> {
+    let has_legacy: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'active_timer')",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|err| err.to_string())?;
+    if !has_legacy {
+        return Ok(());
     }
+    ensure_column(conn, "active_timer", "notes", "TEXT")?;
+    ensure_column(conn, "active_timer", "paused_at", "INTEGER")?;
+    ensure_column(conn, "active_timer", "paused_seconds", "INTEGER NOT NULL DEFAULT 0")?;
+    conn.execute_batch(
+        "BEGIN;
+         INSERT INTO active_timers
+             (project_name, start_time, hourly_rate, notes, paused_at, paused_seconds)
+         SELECT project_name, start_time, hourly_rate, notes, paused_at, paused_seconds
+         FROM active_timer;
+         DROP TABLE active_timer;
+         COMMIT;",
+    )
+    .map_err(|err| err.to_string())
 }
 
 fn last_used_hourly_rate(db_path: PathBuf) -> Result<f64, String> {
     let conn = open_connection(db_path)?;
     let result = conn.query_row(
-        "SELECT hourly_rate FROM time_entries ORDER BY start_time DESC LIMIT 1",
-        [],
+        "SELECT hourly_rate FROM time_entries WHERE entry_type = ?1
+         ORDER BY start_time DESC LIMIT 1",
+        params![ENTRY_TYPE_WORK],
         |row| row.get::<_, f64>(0),
     );
     match result {
@@ -1263,16 +4623,32 @@ fn last_used_hourly_rate(db_path: PathBuf) -> Result<f64, String> {
 
 fn restore_active_timer(app: &AppHandle) -> Result<(), String> {
     let db_path = resolve_db_path(app)?;
-    if let Some(timer) = load_active_timer(db_path)? {
+    let timers = load_active_timers(db_path.clone())?;
+    if !timers.is_empty() {
         let timer_state = app.state::<TimerState>();
-        timer_state.restore(timer);
+        timer_state.restore(timers);
         let status = timer_state.status();
         let _ = refresh_tray(app);
         emit_timer_status(app, &status);
+        let conn = open_connection(db_path)?;
+        let prompted = suspend::check_restored_timers(app, &conn)?;
+        recovery::check_recovered_timers(app, &conn, &prompted)?;
     }
     Ok(())
 }
 
+/// Replaces the timers in memory with those in the database, e.g. after it
+/// was restored from a backup, and updates the tray and window to match.
+fn reload_active_timers(app: &AppHandle) -> Result<(), String> {
+    let timers = load_active_timers(resolve_db_path(app)?)?;
+    let timer_state = app.state::<TimerState>();
+    timer_state.restore(timers);
+    let status = timer_state.status();
+    let _ = refresh_tray(app);
+    emit_timer_status(app, &status);
+    Ok(())
+}
+
 fn is_main_window_visible(app: &AppHandle) -> bool {
     app.get_webview_window("main")
         .and_then(|window| window.is_visible().ok())
@@ -1281,7 +4657,37 @@ fn is_main_window_visible(app: &AppHandle) -> bool {
 
 fn build_status_text(status: &TimerStatusPayload) -> String {
     if let (Some(name), Some(elapsed)) = (&status.project_name, status.elapsed_seconds) {
-        format!("Running: {} ({})", name, format_duration(elapsed))
+        if status.is_break {
+            return format!("On break ({})", format_duration(elapsed));
+        }
+        let label = match &status.project_icon {
+            Some(icon) => format!("{} {}", icon, name),
+            None => name.clone(),
+        };
+        let state = if status.is_paused { "Paused" } else { "Running" };
+        // Unbilled timers have nothing worth showing.
+        let label = match (status.earned_amount, &status.currency) {
+            (Some(amount), Some(currency)) if status.hourly_rate.unwrap_or(0.0) > 0.0 => {
+                format!("{} · {:.2} {}", label, amount, currency)
+            }
+            _ => label,
+        };
+        match status.remaining_seconds {
+            Some(0) => format!(
+                "{}: {} ({}, target reached)",
+                state,
+                label,
+                format_duration(elapsed)
+            ),
+            Some(remaining) => format!(
+                "{}: {} ({}, {} left)",
+                state,
+                label,
+                format_duration(elapsed),
+                format_duration(remaining)
+            ),
+            None => format!("{}: {} ({})", state, label, format_duration(elapsed)),
+        }
     } else {
         "Status: No timer running".to_string()
     }
@@ -1302,7 +4708,7 @@ fn calculate_amount(duration_seconds: i64, hourly_rate: f64) -> f64 {
 
 fn current_today_totals(app: &AppHandle) -> Result<TodayTotals, String> {
     let db_path = resolve_db_path(app)?;
-    let (start_ts, end_ts) = day_bounds_timestamps()?;
+    let (start_ts, end_ts) = day_bounds_timestamps(None)?;
     let conn = open_connection(db_path)?;
     query_totals_between(&conn, start_ts, end_ts)
 }
@@ -1316,29 +4722,72 @@ fn query_totals_between(
     start_ts: i64,
     end_ts: i64,
 ) -> Result<TodayTotals, String> {
-    conn.query_row(
-        "SELECT
-                COALESCE(SUM(duration), 0) as total_duration,
-                COALESCE(SUM(amount), 0) as total_amount
+    let (total_seconds, total_amount, break_seconds) = conn
+        .query_row(
+            "SELECT
+                COALESCE(SUM(CASE WHEN entry_type = 'work' THEN duration END), 0) as total_duration,
+                COALESCE(SUM(CASE WHEN entry_type = 'work' THEN amount END), 0) as total_amount,
+                COALESCE(SUM(CASE WHEN entry_type = 'break' THEN duration END), 0) as break_duration
              FROM time_entries
              WHERE start_time >= ?1 AND start_time < ?2",
-        params![start_ts, end_ts],
-        |row| {
-            Ok(TodayTotals {
-                total_seconds: row.get(0)?,
-                total_amount: row.get(1)?,
+            params![start_ts, end_ts],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|err| err.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(p.currency, c.currency, 'USD'), SUM(e.amount)
+             FROM time_entries e
+             LEFT JOIN projects p ON p.name = e.project_name
+             LEFT JOIN clients c ON c.id = p.client_id
+             WHERE e.start_time >= ?1 AND e.start_time < ?2 AND e.entry_type = 'work'
+             GROUP BY 1
+             ORDER BY 1",
+        )
+        .map_err(|err| err.to_string())?;
+    let currency_totals = stmt
+        .query_map(params![start_ts, end_ts], |row| {
+            Ok(CurrencyTotal {
+                currency: row.get(0)?,
+                amount: row.get(1)?,
             })
-        },
-    )
-    .map_err(|err| err.to_string())
+        })
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    Ok(TodayTotals {
+        total_seconds,
+        total_amount,
+        currency_totals,
+        break_seconds,
+    })
 }
 
-fn ensure_rate_columns(conn: &Connection) -> Result<(), String> {
+fn ensure_time_entry_columns(conn: &Connection) -> Result<(), String> {
+    ensure_column(conn, "time_entries", "hourly_rate", "REAL NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "time_entries", "amount", "REAL NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "time_entries", "notes", "TEXT")?;
+    ensure_column(conn, "time_entries", "raw_duration", "INTEGER")?;
+    ensure_column(conn, "time_entries", "entry_type", "TEXT NOT NULL DEFAULT 'work'")?;
+    ensure_column(conn, "time_entries", "billable", "INTEGER NOT NULL DEFAULT 1")?;
+    ensure_column(conn, "time_entries", "starred", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "time_entries", "utc_offset", "INTEGER")?;
+    ensure_column(conn, "time_entries", "invoice_id", "INTEGER REFERENCES invoices(id)")?;
+    Ok(())
+}
+
+/// Adds `column` to `table` when an older database predates it.
+fn ensure_column(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), String> {
     let mut stmt = conn
-        .prepare("PRAGMA table_info(time_entries)")
+        .prepare(&format!("PRAGMA table_info({table})"))
         .map_err(|err| err.to_string())?;
-    let mut has_hourly_rate = false;
-    let mut has_amount = false;
     let rows = stmt
         .query_map([], |row| {
             let name: String = row.get(1)?;
@@ -1347,32 +4796,131 @@ fn ensure_rate_columns(conn: &Connection) -> Result<(), String> {
         .map_err(|err| err.to_string())?;
 
     for col in rows {
-        let name = col.map_err(|err| err.to_string())?;
-        if name == "hourly_rate" {
-            has_hourly_rate = true;
-        }
-        if name == "amount" {
-            has_amount = true;
+        if col.map_err(|err| err.to_string())? == column {
+            return Ok(());
         }
     }
 
-    if !has_hourly_rate {
-        conn.execute(
-            "ALTER TABLE time_entries ADD COLUMN hourly_rate REAL NOT NULL DEFAULT 0",
-            [],
+    conn.execute(
+        &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+        [],
+    )
+    .map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+fn update_entry_notes(conn: &Connection, id: i64, notes: Option<&str>) -> Result<(), String> {
+    let changed = conn
+        .execute(
+            "UPDATE time_entries SET notes = ?1 WHERE id = ?2",
+            params![notes, id],
         )
         .map_err(|err| err.to_string())?;
+    if changed == 0 {
+        return Err("Time entry not found".into());
     }
+    Ok(())
+}
 
-    if !has_amount {
-        conn.execute(
-            "ALTER TABLE time_entries ADD COLUMN amount REAL NOT NULL DEFAULT 0",
-            [],
-        )
-        .map_err(|err| err.to_string())?;
+/// The entries `start_time..end_time` would overlap, or an error carrying them
+/// as its `conflicts` when the `block_overlapping_entries` setting is on.
+fn enforce_no_overlap(
+    conn: &Connection,
+    current_id: i64,
+    start_time: i64,
+    end_time: i64,
+) -> Result<Vec<TimeEntry>, EntrySaveError> {
+    let overlapping = check_overlapping_entries(conn, current_id, start_time, end_time)?;
+    if overlapping.is_empty() || !settings::load_settings(conn)?.block_overlapping_entries {
+        return Ok(overlapping);
     }
+    let described: Vec<String> = overlapping
+        .iter()
+        .map(|entry| {
+            let at = |ts: i64| {
+                Local
+                    .timestamp_opt(ts, 0)
+                    .single()
+                    .map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default()
+            };
+            format!(
+                "#{} {} ({} – {})",
+                entry.id,
+                entry.project_name,
+                at(entry.start_time),
+                at(entry.end_time)
+            )
+        })
+        .collect();
+    Err(EntrySaveError {
+        message: format!(
+            "This entry overlaps {}: {}",
+            if described.len() == 1 {
+                "an existing entry".to_string()
+            } else {
+                format!("{} existing entries", described.len())
+            },
+            described.join("; ")
+        ),
+        conflicts: overlapping
+            .into_iter()
+            .map(|entry| EntryConflict {
+                id: entry.id,
+                project_name: entry.project_name,
+                start_time: entry.start_time,
+                end_time: entry.end_time,
+            })
+            .collect(),
+    })
+}
 
-    Ok(())
+/// Entries overlapping `start_time..end_time`, other than `exclude_id`; lets
+/// the window show what blocked a save.
+#[tauri::command]
+async fn find_overlapping_entries(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+    exclude_id: Option<i64>,
+) -> Result<Vec<TimeEntry>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        check_overlapping_entries(&conn, exclude_id.unwrap_or(0), start_time, end_time)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Flags entries longer than `max_entry_hours` or ending in the future.
+fn suspicious_entry_warnings(
+    conn: &Connection,
+    start_time: i64,
+    end_time: i64,
+) -> Result<Vec<EntryWarning>, String> {
+    let max_hours = settings::load_settings(conn)?
+        .max_entry_hours
+        .unwrap_or(DEFAULT_MAX_ENTRY_HOURS);
+    let mut warnings = Vec::new();
+    if max_hours > 0.0 && (end_time - start_time) as f64 > max_hours * 3600.0 {
+        warnings.push(EntryWarning {
+            kind: EntryWarningKind::TooLong,
+            message: format!(
+                "This entry is {} long, more than {} hours",
+                format_duration(end_time - start_time),
+                max_hours
+            ),
+        });
+    }
+    if end_time > current_unix_timestamp() {
+        warnings.push(EntryWarning {
+            kind: EntryWarningKind::EndsInFuture,
+            message: "This entry ends in the future".into(),
+        });
+    }
+    Ok(warnings)
 }
 
 fn check_overlapping_entries(
@@ -1382,27 +4930,17 @@ fn check_overlapping_entries(
     end_time: i64,
 ) -> Result<Vec<TimeEntry>, String> {
     let mut stmt = conn
-        .prepare(
-            "SELECT id, project_name, start_time, end_time, duration, hourly_rate, amount
+        .prepare(&format!(
+            "SELECT {TIME_ENTRY_COLUMNS}
              FROM time_entries
              WHERE id != ?1
              AND NOT (end_time <= ?2 OR start_time >= ?3)
-             ORDER BY start_time ASC",
-        )
+             ORDER BY start_time ASC"
+        ))
         .map_err(|err| err.to_string())?;
 
     let rows = stmt
-        .query_map(params![current_id, start_time, end_time], |row| {
-            Ok(TimeEntry {
-                id: row.get(0)?,
-                project_name: row.get(1)?,
-                start_time: row.get(2)?,
-                end_time: row.get(3)?,
-                duration: row.get(4)?,
-                hourly_rate: row.get(5)?,
-                amount: row.get(6)?,
-            })
-        })
+        .query_map(params![current_id, start_time, end_time], map_time_entry)
         .map_err(|err| err.to_string())?;
 
     let mut entries = Vec::new();
@@ -1412,3 +4950,334 @@ fn check_overlapping_entries(
 
     Ok(entries)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(day: u32, hour: u32, minute: u32) -> i64 {
+        Local
+            .with_ymd_and_hms(2025, 6, day, hour, minute, 0)
+            .single()
+            .expect("unambiguous local time")
+            .timestamp()
+    }
+
+    #[test]
+    fn split_keeps_a_same_day_span_whole() {
+        let (start, end) = (local(10, 9, 0), local(10, 17, 30));
+        assert_eq!(split_at_local_midnights(start, end), vec![(start, end)]);
+    }
+
+    #[test]
+    fn split_cuts_at_each_local_midnight() {
+        let (start, end) = (local(10, 22, 0), local(12, 1, 30));
+        assert_eq!(
+            split_at_local_midnights(start, end),
+            vec![
+                (start, local(11, 0, 0)),
+                (local(11, 0, 0), local(12, 0, 0)),
+                (local(12, 0, 0), end),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_ending_at_midnight_stays_on_one_day() {
+        let (start, end) = (local(10, 20, 0), local(11, 0, 0));
+        assert_eq!(split_at_local_midnights(start, end), vec![(start, end)]);
+    }
+
+    #[test]
+    fn pauses_are_shared_by_segment_length() {
+        let segments = [(0, 3_600), (3_600, 10_800)];
+        assert_eq!(share_paused_seconds(&segments, 900), vec![300, 600]);
+    }
+
+    #[test]
+    fn the_last_segment_takes_the_rounding_remainder() {
+        let segments = [(0, 1), (1, 2), (2, 3)];
+        let shares = share_paused_seconds(&segments, 2);
+        assert_eq!(shares, vec![0, 0, 2]);
+        assert_eq!(shares.iter().sum::<i64>(), 2);
+    }
+
+    #[test]
+    fn a_single_segment_takes_every_pause() {
+        assert_eq!(share_paused_seconds(&[(100, 4_000)], 1_234), vec![1_234]);
+    }
+
+    fn test_connection(name: &str) -> Connection {
+        let path = std::env::temp_dir().join(format!("tt-{}-{}.db", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        open_connection(path).expect("test database")
+    }
+
+    fn work_entry(id: i64, start_time: i64, seconds: i64, hourly_rate: f64) -> TimeEntry {
+        TimeEntry {
+            id,
+            project_name: "Website".into(),
+            start_time,
+            end_time: start_time + seconds,
+            duration: seconds,
+            hourly_rate,
+            amount: seconds as f64 / 3600.0 * hourly_rate,
+            notes: Some(format!("Task {}", id)),
+            project_color: None,
+            project_icon: None,
+            raw_duration: None,
+            currency: "USD".into(),
+            entry_type: ENTRY_TYPE_WORK.into(),
+            billable: true,
+            starred: false,
+            utc_offset: Some(0),
+            invoice_id: None,
+        }
+    }
+
+    fn rows(
+        conn: &Connection,
+        entries: &[TimeEntry],
+        mode: InvoiceLineMode,
+    ) -> Vec<pdf_generator::InvoiceLineItem> {
+        let labels = invoice_labels::InvoiceLanguage::default().labels();
+        build_invoice_line_items(conn, entries, mode, None, labels, None).expect("line items")
+    }
+
+    #[test]
+    fn summary_and_grouped_rows_carry_the_entries_notes() {
+        let conn = test_connection("row-notes");
+        let mut entries = [
+            work_entry(1, 0, 3_600, 100.0),
+            work_entry(2, 3_600, 3_600, 100.0),
+            work_entry(3, 7_200, 3_600, 100.0),
+        ];
+        entries[1].notes = Some("Task 1".into());
+        entries[2].notes = None;
+
+        let summary = rows(&conn, &entries, InvoiceLineMode::Summary);
+        assert_eq!(summary[0].description, "Hours worked: Task 1");
+
+        let labels = invoice_labels::InvoiceLanguage::default().labels();
+        let by_project: Vec<&TimeEntry> = entries.iter().collect();
+        let grouped = group_line_items(&by_project, InvoiceGrouping::Project, labels);
+        assert_eq!(grouped[0].description, "Website: Task 1");
+    }
+
+    #[test]
+    fn a_dropped_entry_leaves_the_summary_row() {
+        let conn = test_connection("summary-drop");
+        let entries = [work_entry(1, 0, 3_600, 100.0), work_entry(2, 7_200, 7_200, 100.0)];
+        let mut prepared = rows(&conn, &entries, InvoiceLineMode::Summary);
+        prepared[0].description = "Consulting, June".into();
+
+        let kept = rows(&conn, &entries[..1], InvoiceLineMode::Summary);
+        let billed = carry_line_wording(kept, &prepared);
+
+        assert_eq!(billed.len(), 1);
+        assert_eq!(billed[0].description, "Consulting, June");
+        assert!((billed[0].quantity - 1.0).abs() < 1e-9);
+        assert!((billed[0].amount - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn a_dropped_entry_leaves_the_detailed_rows() {
+        let conn = test_connection("detailed-drop");
+        let entries = [
+            work_entry(1, 0, 3_600, 100.0),
+            work_entry(2, 86_400, 5_400, 100.0),
+            work_entry(3, 172_800, 1_800, 100.0),
+        ];
+        let mut prepared = rows(&conn, &entries, InvoiceLineMode::Detailed);
+        prepared[1].description = "Design review".into();
+
+        let kept = rows(&conn, &entries[1..], InvoiceLineMode::Detailed);
+        let billed = carry_line_wording(kept, &prepared);
+
+        let descriptions: Vec<_> = billed.iter().map(|item| item.description.as_str()).collect();
+        assert_eq!(descriptions, ["Design review", "Website: Task 3"]);
+        let total: f64 = billed.iter().map(|item| item.amount).sum();
+        assert!((total - 200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn edited_figures_are_not_billed() {
+        let conn = test_connection("edited-figures");
+        let entries = [work_entry(1, 0, 3_600, 100.0)];
+        let mut prepared = rows(&conn, &entries, InvoiceLineMode::Summary);
+        prepared[0].quantity = 10.0;
+        prepared[0].amount = 9_999.0;
+        prepared[0].description = "Retainer work".into();
+
+        let billed = carry_line_wording(rows(&conn, &entries, InvoiceLineMode::Summary), &prepared);
+
+        assert_eq!(billed[0].description, "Retainer work");
+        assert!((billed[0].amount - 100.0).abs() < 1e-9);
+    }
+
+    fn insert_invoice(conn: &Connection, file_path: &str) -> i64 {
+        conn.execute(
+            "INSERT INTO invoices (created_at, business_info, bill_to_info, total_hours,
+                                   total_amount, file_path, entry_count)
+             VALUES (0, '{}', '{}', 1, 100, ?1, 1)",
+            params![file_path],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn entry_invoice(conn: &Connection) -> Option<i64> {
+        conn.query_row("SELECT invoice_id FROM time_entries WHERE id = 1", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn deleting_a_reinvoice_links_the_entry_back_to_its_first_invoice() {
+        let mut conn = test_connection("reinvoiced-entry");
+        conn.execute(
+            "INSERT INTO time_entries (id, project_name, start_time, end_time, duration)
+             VALUES (1, 'Website', 0, 3600, 3600)",
+            [],
+        )
+        .unwrap();
+        let first = insert_invoice(&conn, "first.pdf");
+        link_invoice_entries(&conn, first, &[1]).unwrap();
+        let second = insert_invoice(&conn, "second.pdf");
+        link_invoice_entries(&conn, second, &[1]).unwrap();
+        assert_eq!(entry_invoice(&conn), Some(second));
+
+        assert_eq!(remove_invoice(&mut conn, second).unwrap(), "second.pdf");
+        assert_eq!(entry_invoice(&conn), Some(first));
+
+        remove_invoice(&mut conn, first).unwrap();
+        assert_eq!(entry_invoice(&conn), None);
+    }
+
+    #[test]
+    fn an_invoice_with_credit_notes_is_kept_whole() {
+        let mut conn = test_connection("credited-invoice");
+        let invoice = insert_invoice(&conn, "invoice.pdf");
+        let credit_note = insert_invoice(&conn, "credit.pdf");
+        conn.execute(
+            "UPDATE invoices SET credited_invoice_id = ?1 WHERE id = ?2",
+            params![invoice, credit_note],
+        )
+        .unwrap();
+
+        assert!(remove_invoice(&mut conn, invoice).is_err());
+        let left: i64 = conn
+            .query_row("SELECT COUNT(*) FROM invoices", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(left, 2);
+    }
+
+    #[test]
+    fn an_invoice_from_before_line_items_and_links_is_drawn_from_its_totals() {
+        let conn = test_connection("stored-totals");
+        conn.execute(
+            "INSERT INTO invoices (created_at, business_info, bill_to_info, total_hours,
+                                   total_amount, file_path, entry_count)
+             VALUES (0, '{\"name\":\"Studio\"}', '{}', 2.5, 200, 'old.pdf', 3)",
+            [],
+        )
+        .unwrap();
+        let id = conn.last_insert_rowid();
+
+        let (_, render) = load_stored_invoice(&conn, id).unwrap();
+
+        assert!(render.entries.is_empty());
+        assert_eq!(render.line_items.len(), 1);
+        let row = &render.line_items[0];
+        assert!(!row.fixed_fee);
+        assert!((row.quantity - 2.5).abs() < 1e-9);
+        assert!((row.unit_price - 80.0).abs() < 1e-9);
+        assert!((row.amount - 200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn a_blocked_overlap_returns_the_entries_it_ran_into() {
+        let conn = test_connection("blocked-overlap");
+        conn.execute(
+            "INSERT INTO time_entries (id, project_name, start_time, end_time, duration)
+             VALUES (1, 'Website', 1000, 4600, 3600), (2, 'Admin', 5000, 6000, 1000)",
+            [],
+        )
+        .unwrap();
+        assert_eq!(enforce_no_overlap(&conn, 0, 4000, 5500).unwrap().len(), 2);
+
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES ('block_overlapping_entries', 'true')",
+            [],
+        )
+        .unwrap();
+        let error = enforce_no_overlap(&conn, 0, 4000, 5500).unwrap_err();
+        assert!(error.message.starts_with("This entry overlaps 2 existing entries"));
+        assert_eq!(
+            error.conflicts,
+            vec![
+                EntryConflict {
+                    id: 1,
+                    project_name: "Website".into(),
+                    start_time: 1000,
+                    end_time: 4600,
+                },
+                EntryConflict {
+                    id: 2,
+                    project_name: "Admin".into(),
+                    start_time: 5000,
+                    end_time: 6000,
+                },
+            ]
+        );
+        assert!(enforce_no_overlap(&conn, 1, 900, 4000).unwrap().is_empty());
+    }
+
+    fn running_timer(id: i64, start_time: i64) -> ActiveTimer {
+        ActiveTimer {
+            id,
+            project_name: "Website".into(),
+            start_time,
+            hourly_rate: 0.0,
+            project_color: None,
+            project_icon: None,
+            notes: None,
+            paused_at: None,
+            paused_seconds: 0,
+            target_seconds: None,
+            currency: "EUR".into(),
+            is_break: false,
+            utc_offset: Some(0),
+        }
+    }
+
+    #[test]
+    fn setting_the_clock_either_way_keeps_elapsed_time() {
+        let mut inner = TimerInner {
+            timers: vec![running_timer(1, 10_000)],
+            last_clock: None,
+        };
+        assert_eq!(inner.correct_clock(10_600, 500), None);
+
+        // Set an hour forwards while 30 seconds passed.
+        let jump = inner.correct_clock(14_230, 530).unwrap();
+        assert_eq!(jump.jump_seconds, 3_600);
+        assert_eq!(inner.timers[0].start_time, 13_600);
+        assert_eq!(inner.timers[0].worked_seconds(14_230), 630);
+
+        // And back two hours.
+        let jump = inner.correct_clock(7_060, 560).unwrap();
+        assert_eq!(jump.jump_seconds, -7_200);
+        assert_eq!(jump.timer_ids, vec![1]);
+        assert_eq!(inner.timers[0].worked_seconds(7_060), 660);
+    }
+
+    #[test]
+    fn time_asleep_is_not_a_clock_change() {
+        let mut inner = TimerInner {
+            timers: vec![running_timer(1, 10_000)],
+            last_clock: Some((500, 10_600)),
+        };
+        assert_eq!(inner.correct_clock(14_200, 4_100), None);
+        assert_eq!(inner.timers[0].start_time, 10_000);
+    }
+}
@@ -0,0 +1,194 @@
+use std::sync::Mutex;
+
+use chrono::{Local, NaiveDate, TimeZone};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    current_settings, current_unix_timestamp, map_invoice, open_connection, resolve_db_path,
+    send_notification, Invoice, INVOICE_COLUMNS, INVOICE_STATUS_ISSUED, INVOICE_STATUS_PAID,
+    INVOICE_STATUS_SENT, INVOICE_TYPE_INVOICE,
+};
+
+pub const OVERDUE_INVOICES_EVENT: &str = "invoices://overdue";
+
+/// An unpaid invoice past its due date.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverdueInvoice {
+    #[serde(flatten)]
+    pub invoice: Invoice,
+    /// Total less any credit notes.
+    pub balance: f64,
+    /// Whole local days since the due date.
+    pub days_overdue: i64,
+}
+
+/// The local day overdue invoices were last checked for.
+#[derive(Default)]
+pub struct OverdueState {
+    last_day: Mutex<Option<NaiveDate>>,
+}
+
+/// Unpaid invoices whose due date has passed, longest overdue first.
+#[tauri::command]
+pub async fn list_overdue_invoices(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<OverdueInvoice>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        load_overdue(&conn)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Marks an invoice paid, or back to issued or sent (if it was emailed).
+/// Paid invoices are never overdue.
+#[tauri::command]
+pub async fn set_invoice_paid(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    paid: bool,
+) -> Result<Invoice, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let changed = if paid {
+            conn.execute(
+                "UPDATE invoices SET status = ?1, paid_at = COALESCE(paid_at, ?2)
+                 WHERE id = ?3 AND invoice_type = ?4",
+                params![
+                    INVOICE_STATUS_PAID,
+                    current_unix_timestamp(),
+                    id,
+                    INVOICE_TYPE_INVOICE
+                ],
+            )
+        } else {
+            conn.execute(
+                "UPDATE invoices
+                 SET status = CASE WHEN sent_at IS NULL THEN ?1 ELSE ?2 END, paid_at = NULL
+                 WHERE id = ?3 AND invoice_type = ?4",
+                params![
+                    INVOICE_STATUS_ISSUED,
+                    INVOICE_STATUS_SENT,
+                    id,
+                    INVOICE_TYPE_INVOICE
+                ],
+            )
+        }
+        .map_err(|err| err.to_string())?;
+        if changed == 0 {
+            return Err("Invoice not found".into());
+        }
+        conn.query_row(
+            &format!("SELECT {INVOICE_COLUMNS} FROM invoices WHERE id = ?1"),
+            params![id],
+            map_invoice,
+        )
+        .map_err(|err| err.to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Called at launch and on every scheduler tick; once per local day, emits
+/// the overdue invoices and, if enabled in settings, notifies about them.
+pub fn check_day_rollover(app: &AppHandle) {
+    let today = Local::now().date_naive();
+    let state = app.state::<OverdueState>();
+    let mut last_day = state.last_day.lock().expect("overdue state poisoned");
+    if *last_day == Some(today) {
+        return;
+    }
+    *last_day = Some(today);
+    drop(last_day);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match list_overdue_invoices(app.clone()).await {
+            Ok(overdue) => notify_overdue(&app, &overdue),
+            Err(err) => eprintln!("Failed to check for overdue invoices: {}", err),
+        }
+    });
+}
+
+fn notify_overdue(app: &AppHandle, overdue: &[OverdueInvoice]) {
+    if overdue.is_empty() {
+        return;
+    }
+    if current_settings(app).is_ok_and(|settings| settings.overdue_notifications) {
+        let body = match overdue {
+            [only] => format!(
+                "Invoice #{} ({:.2} {}) is {} overdue.",
+                only.invoice.id,
+                only.balance,
+                only.invoice.currency,
+                days(only.days_overdue)
+            ),
+            _ => format!(
+                "{} invoices are overdue, the oldest by {}.",
+                overdue.len(),
+                days(overdue[0].days_overdue)
+            ),
+        };
+        send_notification(app, "Overdue invoices", &body);
+    }
+    let _ = app.emit(OVERDUE_INVOICES_EVENT, overdue);
+}
+
+fn days(count: i64) -> String {
+    if count == 1 {
+        "1 day".into()
+    } else {
+        format!("{} days", count)
+    }
+}
+
+/// Invoices are due through their due date and overdue from the next local
+/// day. Fully credited invoices have nothing left to pay.
+fn load_overdue(conn: &Connection) -> Result<Vec<OverdueInvoice>, String> {
+    let today = Local::now().date_naive();
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {INVOICE_COLUMNS},
+                 (SELECT COALESCE(SUM(credit.total_amount), 0) FROM invoices credit
+                  WHERE credit.credited_invoice_id = invoices.id)
+             FROM invoices
+             WHERE invoice_type = ?1 AND status != ?2 AND due_date IS NOT NULL
+             ORDER BY due_date ASC, id ASC"
+        ))
+        .map_err(|err| err.to_string())?;
+    let invoices = stmt
+        .query_map(params![INVOICE_TYPE_INVOICE, INVOICE_STATUS_PAID], |row| {
+            let invoice = map_invoice(row)?;
+            let credited: f64 = row.get(17)?;
+            Ok((invoice, credited))
+        })
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    Ok(invoices
+        .into_iter()
+        .filter_map(|(invoice, credited)| {
+            let due_day = Local
+                .timestamp_opt(invoice.due_date?, 0)
+                .single()?
+                .date_naive();
+            let days_overdue = (today - due_day).num_days();
+            // Credit notes are stored with negative totals.
+            let balance = invoice.total_amount + credited;
+            (days_overdue > 0 && balance > 0.005).then_some(OverdueInvoice {
+                invoice,
+                balance,
+                days_overdue,
+            })
+        })
+        .collect())
+}
@@ -1,18 +1,41 @@
 use printpdf::*;
 use std::fs::File;
 use std::io::BufWriter;
-use chrono::Local;
+use chrono::{Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::bank_account::{self, BankAccount};
+use crate::invoice_labels::{InvoiceLabels, InvoiceLanguage};
+use crate::qr_code::QrCode;
+
+const PAGE_WIDTH_MM: f32 = 210.0;
+const PAGE_HEIGHT_MM: f32 = 297.0;
+/// Where content starts on pages after the first.
+const CONTINUATION_TOP_MM: f32 = 275.0;
+/// Content stops above this; page numbers sit below it.
+const BOTTOM_MARGIN_MM: f32 = 25.0;
+const CARRY_ROW_HEIGHT_MM: f32 = 10.0;
+const TOTALS_HEIGHT_MM: f32 = 24.0;
+/// A heading plus its first line, so headings aren't left alone at the bottom.
+const SECTION_HEADING_HEIGHT_MM: f32 = 12.0;
+/// Side of the payment QR code, which sits at the right of the payment
+/// details with its caption below.
+const PAYMENT_QR_SIZE_MM: f32 = 30.0;
+const PAYMENT_QR_X_MM: f32 = 160.0;
+const PAYMENT_QR_HEIGHT_MM: f32 = PAYMENT_QR_SIZE_MM + 12.0;
 
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct TimeEntry {
     pub id: i64,
     pub project_name: String,
+    pub currency: String,
     pub start_time: i64,
     pub end_time: i64,
     pub duration: i64,
     pub hourly_rate: f64,
     pub amount: f64,
+    pub notes: Option<String>,
 }
 
 #[allow(dead_code)]
@@ -22,10 +45,14 @@ pub struct BusinessInfo {
     pub address: Option<String>,
     pub email: Option<String>,
     pub phone: Option<String>,
+    pub tax_id: Option<String>,
+    pub bank_details: Option<String>,
     pub client_name: Option<String>,
     pub client_address: Option<String>,
     pub client_email: Option<String>,
     pub client_phone: Option<String>,
+    pub bank_account: Option<BankAccount>,
+    pub notes: Option<String>,
 }
 
 #[allow(dead_code)]
@@ -35,29 +62,217 @@ pub struct InvoicePeriod {
     pub end_time: i64,
 }
 
-pub fn generate_invoice(
-    entries: Vec<TimeEntry>,
-    business_info: BusinessInfo,
-    output_path: &str,
-    _period: Option<InvoicePeriod>,
-) -> Result<(), String> {
+/// One row of the invoice table. Amounts are computed by the caller so the
+/// PDF and the stored invoice totals always agree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceLineItem {
+    /// Shown in a Date column, which only appears when some row has one.
+    pub date: Option<String>,
+    pub description: String,
+    pub quantity: f64,
+    pub unit_price: f64,
+    pub amount: f64,
+    /// A flat fee (quantity 1) rather than hours.
+    #[serde(default)]
+    pub fixed_fee: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct RetainerSummary {
+    pub purchased_hours: f64,
+    pub used_hours: f64,
+    pub remaining_hours: f64,
+}
+
+/// A PNG or JPEG printed in the top-right corner of the first page.
+#[derive(Debug, Clone)]
+pub struct InvoiceLogo {
+    pub data: Vec<u8>,
+    pub width_mm: f32,
+}
+
+/// TrueType fonts used instead of Helvetica, for names and scripts outside
+/// Latin-1. Without a bold face the regular one is used for headings too.
+#[derive(Debug, Clone)]
+pub struct InvoiceFonts {
+    pub regular: Vec<u8>,
+    pub bold: Option<Vec<u8>>,
+}
+
+/// The number an invoice is known by, as the invoice list shows it: its id
+/// padded to three digits.
+pub fn invoice_number(id: i64) -> String {
+    format!("{:03}", id)
+}
+
+/// What the PDF is issued as. Every kind shares the invoice layout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DocumentKind {
+    #[default]
+    Invoice,
+    /// Credits an earlier invoice, which it refers to by number and date.
+    /// There is nothing to pay, so no payment details are printed.
+    CreditNote {
+        invoice_id: i64,
+        invoice_issued_at: i64,
+    },
+    /// A quote for work not yet done, open for acceptance until
+    /// `valid_until`. Also printed without payment details.
+    Estimate { valid_until: i64 },
+}
+
+/// Everything rendered onto an invoice PDF.
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct InvoiceDocument {
+    /// The invoice's number; none for a draft preview.
+    pub number: Option<String>,
+    pub entries: Vec<TimeEntry>,
+    pub line_items: Vec<InvoiceLineItem>,
+    pub business_info: BusinessInfo,
+    pub period: Option<InvoicePeriod>,
+    /// ISO code every amount on the invoice is expressed in.
+    pub currency: String,
+    /// Client and project terms, each printed as its own paragraph.
+    pub terms: Vec<String>,
+    pub retainer: Option<RetainerSummary>,
+    pub logo: Option<InvoiceLogo>,
+    pub fonts: Option<InvoiceFonts>,
+    pub language: InvoiceLanguage,
+    pub due_date: Option<i64>,
+    /// Printed ahead of the client and project terms.
+    pub payment_terms: Option<String>,
+    /// Printed as the issue date; the original date when regenerating.
+    pub issued_at: i64,
+    /// Draws a diagonal "DRAFT" watermark on every page.
+    pub preview: bool,
+    pub kind: DocumentKind,
+    /// Appends `entries` as a per-day timesheet on pages of their own.
+    pub timesheet: bool,
+    /// How billed hours were rounded, printed under the totals.
+    pub rounding_note: Option<String>,
+}
+
+/// Everything rendered onto a standalone timesheet PDF.
+#[derive(Debug, Clone)]
+pub struct TimesheetDocument {
+    pub entries: Vec<TimeEntry>,
+    pub period: InvoicePeriod,
+    /// Printed under the period.
+    pub client_name: Option<String>,
+    pub fonts: Option<InvoiceFonts>,
+    pub language: InvoiceLanguage,
+}
+
+/// One project's row in a monthly report.
+#[derive(Debug, Clone)]
+pub struct ReportProject {
+    pub name: String,
+    pub currency: String,
+    pub hours: f64,
+    pub amount: f64,
+    pub previous_hours: f64,
+}
+
+/// Earnings in one currency this month and the month before.
+#[derive(Debug, Clone)]
+pub struct ReportEarnings {
+    pub currency: String,
+    pub amount: f64,
+    pub previous_amount: f64,
+}
+
+/// Everything rendered onto a monthly report PDF.
+#[derive(Debug, Clone)]
+pub struct MonthlyReportDocument {
+    /// The month reported on and the one compared with, e.g. "March 2025".
+    pub month: String,
+    pub previous_month: String,
+    pub hours: f64,
+    pub previous_hours: f64,
+    pub entry_count: i64,
+    pub previous_entry_count: i64,
+    pub earnings: Vec<ReportEarnings>,
+    /// Most time first; projects only worked on last month have no hours.
+    pub projects: Vec<ReportProject>,
+    /// Hours on every day of the month, including days without work.
+    pub days: Vec<(NaiveDate, f64)>,
+    pub fonts: Option<InvoiceFonts>,
+    pub generated_at: i64,
+}
+
+/// A titled table in a tabular report. Each column starts at its x position
+/// in millimetres; cells are printed as given.
+#[derive(Debug, Clone)]
+pub struct ReportTable {
+    pub heading: String,
+    pub columns: Vec<(f32, String)>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// A report made only of tables, such as the yearly summary.
+#[derive(Debug, Clone)]
+pub struct TableReportDocument {
+    pub title: String,
+    pub subtitle: String,
+    pub tables: Vec<ReportTable>,
+    pub fonts: Option<InvoiceFonts>,
+    pub generated_at: i64,
+}
+
+pub fn generate_invoice(document: InvoiceDocument, output_path: &str) -> Result<(), String> {
+    let InvoiceDocument {
+        number,
+        entries,
+        line_items,
+        business_info,
+        currency,
+        terms,
+        retainer,
+        logo,
+        fonts,
+        language,
+        due_date,
+        payment_terms,
+        issued_at,
+        preview,
+        kind,
+        timesheet,
+        period,
+        rounding_note,
+    } = document;
+    let labels = language.labels();
+
     // Create PDF document
-    let title_text = "Invoice".to_string();
+    let title_text = match kind {
+        DocumentKind::Invoice => labels.invoice,
+        DocumentKind::CreditNote { .. } => labels.credit_note,
+        DocumentKind::Estimate { .. } => labels.estimate,
+    }
+    .to_string();
 
     let (doc, page1, layer1) = PdfDocument::new(
         &title_text,
-        Mm(210.0), // A4 width
-        Mm(297.0), // A4 height
+        Mm(PAGE_WIDTH_MM), // A4 width
+        Mm(PAGE_HEIGHT_MM), // A4 height
         "Layer 1",
     );
 
-    let font_bold = doc.add_builtin_font(BuiltinFont::HelveticaBold)
-        .map_err(|e| format!("Failed to load font: {}", e))?;
-    let font_regular = doc.add_builtin_font(BuiltinFont::Helvetica)
-        .map_err(|e| format!("Failed to load font: {}", e))?;
+    let (font_regular, font_bold) = load_fonts(&doc, fonts)?;
 
     let current_layer = doc.get_page(page1).get_layer(layer1);
 
+    // Drawn first so the content sits on top of it.
+    let watermark = preview.then_some((&font_bold, labels.draft));
+    if let Some((font, text)) = watermark {
+        draw_watermark(&current_layer, font, text);
+    }
+
+    if let Some(logo) = logo {
+        draw_logo(&current_layer, &logo)?;
+    }
+
     let mut y_position: f32 = 260.0;
 
     // Header
@@ -70,14 +285,64 @@ pub fn generate_invoice(
     );
     y_position -= 12.0_f32;
 
-    let issue_date = Local::now();
+    let issue_date = Local
+        .timestamp_opt(issued_at, 0)
+        .single()
+        .unwrap_or_else(Local::now);
     current_layer.use_text(
-        &format!("Issue date: {}", issue_date.format("%d/%m/%Y")),
+        &format!("{}: {}", labels.issue_date, issue_date.format(labels.date_format)),
         11.0,
         Mm(20.0),
         Mm(y_position),
         &font_regular,
     );
+    if let Some(due_date) = due_date.and_then(|due| Local.timestamp_opt(due, 0).single()) {
+        y_position -= 6.0_f32;
+        current_layer.use_text(
+            format!("{}: {}", labels.due_date, due_date.format(labels.date_format)),
+            11.0,
+            Mm(20.0),
+            Mm(y_position),
+            &font_bold,
+        );
+    }
+    if let DocumentKind::Estimate { valid_until } = kind {
+        let valid_until = Local
+            .timestamp_opt(valid_until, 0)
+            .single()
+            .unwrap_or_else(Local::now);
+        y_position -= 6.0_f32;
+        current_layer.use_text(
+            format!("{}: {}", labels.valid_until, valid_until.format(labels.date_format)),
+            11.0,
+            Mm(20.0),
+            Mm(y_position),
+            &font_bold,
+        );
+    }
+    if let DocumentKind::CreditNote {
+        invoice_id,
+        invoice_issued_at,
+    } = kind
+    {
+        let invoice_date = Local
+            .timestamp_opt(invoice_issued_at, 0)
+            .single()
+            .unwrap_or_else(Local::now);
+        y_position -= 6.0_f32;
+        current_layer.use_text(
+            format!(
+                "{} #{}, {}",
+                labels.credit_for,
+                invoice_number(invoice_id),
+                invoice_date.format(labels.date_format)
+            ),
+            11.0,
+            Mm(20.0),
+            Mm(y_position),
+            &font_regular,
+        );
+    }
     y_position -= 10.0_f32;
     y_position -= 8.0_f32;
 
@@ -86,11 +351,12 @@ pub fn generate_invoice(
     y_position -= 14.0_f32;
 
     // Two columns: Bill from / Bill to
-    let from_y = write_contact_block(
+    let mut from_y = write_contact_block(
         &current_layer,
         &font_bold,
         &font_regular,
-        "Bill from",
+        labels,
+        labels.bill_from,
         20.0,
         85.0,
         y_position,
@@ -99,12 +365,24 @@ pub fn generate_invoice(
         &business_info.email,
         &business_info.phone,
     );
+    if let Some(tax_id) = business_info.tax_id.as_deref() {
+        from_y = write_wrapped_text(
+            &current_layer,
+            &font_regular,
+            &format!("{}: {}", labels.tax_id, tax_id.trim()),
+            10.0,
+            20.0,
+            from_y,
+            85.0,
+        );
+    }
 
     let to_y = write_contact_block(
         &current_layer,
         &font_bold,
         &font_regular,
-        "Bill to",
+        labels,
+        labels.bill_to,
         120.0,
         70.0,
         y_position,
@@ -120,80 +398,348 @@ pub fn generate_invoice(
 
     y_position = from_y.min(to_y) - 18.0_f32;
 
-    // Table Header
-    current_layer.use_text("Description", 10.0, Mm(20.0), Mm(y_position), &font_regular);
-    current_layer.use_text("Quantity", 10.0, Mm(110.0), Mm(y_position), &font_regular);
-    current_layer.use_text("Unit Price", 10.0, Mm(140.0), Mm(y_position), &font_regular);
-    current_layer.use_text("Amount", 10.0, Mm(175.0), Mm(y_position), &font_regular);
-    y_position -= 6.0_f32;
-    draw_line(&current_layer, 20.0, y_position, 190.0, y_position, 0.4);
-    y_position -= 10.0_f32;
+    // Table; rows that would run into the bottom margin move to a new page
+    // under a repeated header, with the running subtotal carried forward.
+    let has_dates = line_items.iter().any(|item| item.date.is_some());
+    let (description_x, description_width) = if has_dates {
+        (45.0, 60.0)
+    } else {
+        (20.0, 85.0)
+    };
+    let mut cursor = PageCursor::new(&doc, current_layer, y_position, watermark);
+    write_table_header(&mut cursor, &font_regular, labels, has_dates, description_x);
 
-    // Aggregate totals
-    let total_hours: f64 = entries.iter().map(|e| e.duration as f64 / 3600.0).sum();
-    let total_amount: f64 = entries.iter().map(|e| e.amount).sum();
-    let unit_price = if total_hours > 0.0 { total_amount / total_hours } else { 0.0 };
+    let total_amount: f64 = line_items.iter().map(|item| item.amount).sum();
+    let description_chars = max_characters_for_width(description_width, 10.0);
+    let mut running_total = 0.0;
 
-    // Single row summary
-    current_layer.use_text("Hours worked", 10.0, Mm(20.0), Mm(y_position), &font_regular);
-    current_layer.use_text(
-        &format!("{:.2}", total_hours),
-        10.0,
-        Mm(110.0),
-        Mm(y_position),
-        &font_regular,
-    );
-    current_layer.use_text(
-        &format_money(unit_price),
-        10.0,
-        Mm(140.0),
-        Mm(y_position),
-        &font_regular,
-    );
-    current_layer.use_text(
-        &format_money(total_amount),
-        10.0,
-        Mm(175.0),
-        Mm(y_position),
-        &font_regular,
-    );
-    y_position -= 12.0_f32;
+    for item in &line_items {
+        let row_height = wrap_text(&item.description, description_chars).len() as f32 * 5.0 + 3.0;
+        if !cursor.fits(row_height + CARRY_ROW_HEIGHT_MM) {
+            write_carry_row(
+                &cursor,
+                &font_regular,
+                labels.carried_forward,
+                running_total,
+                &currency,
+            );
+            cursor.new_page();
+            write_table_header(&mut cursor, &font_regular, labels, has_dates, description_x);
+            write_carry_row(
+                &cursor,
+                &font_regular,
+                labels.brought_forward,
+                running_total,
+                &currency,
+            );
+            cursor.y -= CARRY_ROW_HEIGHT_MM;
+        }
 
-    draw_line(&current_layer, 20.0, y_position, 190.0, y_position, 0.3);
-    y_position -= 12.0_f32;
+        let row_y = cursor.y;
+        let layer = cursor.layer.clone();
+        if let Some(date) = item.date.as_deref() {
+            layer.use_text(date, 10.0, Mm(20.0), Mm(row_y), &font_regular);
+        }
+        layer.use_text(
+            format!("{:.2}", item.quantity),
+            10.0,
+            Mm(110.0),
+            Mm(row_y),
+            &font_regular,
+        );
+        layer.use_text(
+            format_money(item.unit_price, &currency),
+            10.0,
+            Mm(140.0),
+            Mm(row_y),
+            &font_regular,
+        );
+        layer.use_text(
+            format_money(item.amount, &currency),
+            10.0,
+            Mm(175.0),
+            Mm(row_y),
+            &font_regular,
+        );
+        let desc_y = write_wrapped_text(
+            &layer,
+            &font_regular,
+            &item.description,
+            10.0,
+            description_x,
+            row_y,
+            description_width,
+        );
+        cursor.y = desc_y - 3.0_f32;
+        running_total += item.amount;
+    }
+    cursor.y -= 4.0_f32;
 
-    // Totals
-    current_layer.use_text(
-        "SUBTOTAL",
+    // Totals stay together with the line above them.
+    cursor.ensure_space(TOTALS_HEIGHT_MM);
+    draw_line(&cursor.layer, 20.0, cursor.y, 190.0, cursor.y, 0.3);
+    cursor.y -= 12.0_f32;
+
+    cursor.layer.use_text(
+        labels.subtotal,
         10.0,
         Mm(140.0),
-        Mm(y_position),
+        Mm(cursor.y),
         &font_regular,
     );
-    current_layer.use_text(
-        &format_money(total_amount),
+    cursor.layer.use_text(
+        &format_money(total_amount, &currency),
         10.0,
         Mm(175.0),
-        Mm(y_position),
+        Mm(cursor.y),
         &font_regular,
     );
-    y_position -= 10.0_f32;
-    current_layer.use_text(
-        "TOTAL",
+    cursor.y -= 10.0_f32;
+    cursor.layer.use_text(
+        labels.total,
         12.0,
         Mm(140.0),
-        Mm(y_position),
+        Mm(cursor.y),
         &font_bold,
     );
-    current_layer.use_text(
-        &format_money(total_amount),
+    cursor.layer.use_text(
+        &format_money(total_amount, &currency),
         12.0,
         Mm(175.0),
-        Mm(y_position),
+        Mm(cursor.y),
         &font_bold,
     );
+    if let Some(note) = rounding_note.as_deref() {
+        cursor.y -= 8.0_f32;
+        cursor.write_wrapped(&font_regular, note, 9.0, 20.0, 170.0);
+    }
+
+    if let Some(retainer) = retainer {
+        cursor.y -= 16.0_f32;
+        cursor.ensure_space(SECTION_HEADING_HEIGHT_MM);
+        cursor.layer.use_text(labels.retainer, 11.0, Mm(20.0), Mm(cursor.y), &font_bold);
+        cursor.y -= 6.0_f32;
+        cursor.layer.use_text(
+            format!(
+                "{}: {:.2} h   {}: {:.2} h   {}: {:.2} h",
+                labels.purchased,
+                retainer.purchased_hours,
+                labels.used,
+                retainer.used_hours,
+                labels.remaining,
+                retainer.remaining_hours
+            ),
+            10.0,
+            Mm(20.0),
+            Mm(cursor.y),
+            &font_regular,
+        );
+    }
 
-    // Save PDF
+    let is_invoice = kind == DocumentKind::Invoice;
+    let account_lines = business_info
+        .bank_account
+        .as_ref()
+        .filter(|_| is_invoice)
+        .map(|account| bank_account_lines(account, labels))
+        .unwrap_or_default();
+    // Scanning the code fills in a SEPA transfer of the invoice total,
+    // referenced by the invoice's number, or its date on a preview.
+    let payment_qr = business_info.bank_account.as_ref().filter(|_| is_invoice).and_then(|account| {
+        let remittance = match number.as_deref() {
+            Some(number) => format!("{} {}", labels.invoice, number),
+            None => format!("{} {}", labels.invoice, issue_date.format(labels.date_format)),
+        };
+        let payload = bank_account::epc_qr_payload(
+            account,
+            &business_info.name,
+            &currency,
+            total_amount,
+            &remittance,
+        )?;
+        QrCode::encode(payload.as_bytes()).ok()
+    });
+    if !account_lines.is_empty() || (is_invoice && business_info.bank_details.is_some()) {
+        cursor.y -= 16.0_f32;
+        cursor.ensure_space(if payment_qr.is_some() {
+            PAYMENT_QR_HEIGHT_MM
+        } else {
+            SECTION_HEADING_HEIGHT_MM
+        });
+        cursor.layer.use_text(
+            labels.payment_details,
+            11.0,
+            Mm(20.0),
+            Mm(cursor.y),
+            &font_bold,
+        );
+        let qr_page = cursor.pages.len();
+        let qr_bottom = cursor.y + 4.0 - PAYMENT_QR_SIZE_MM;
+        if let Some(qr) = payment_qr.as_ref() {
+            draw_qr_code(&cursor.layer, qr, PAYMENT_QR_X_MM, qr_bottom, PAYMENT_QR_SIZE_MM);
+            cursor.layer.use_text(
+                labels.scan_to_pay,
+                8.0,
+                Mm(PAYMENT_QR_X_MM),
+                Mm(qr_bottom - 4.0),
+                &font_regular,
+            );
+        }
+        // Text stops short of the code.
+        let text_width = if payment_qr.is_some() { 130.0 } else { 170.0 };
+        cursor.y -= 6.0_f32;
+        for line in &account_lines {
+            cursor.write_wrapped(&font_regular, line, 10.0, 20.0, text_width);
+        }
+        let free_text = business_info.bank_details.as_deref().unwrap_or_default();
+        for line in free_text.lines().filter(|line| !line.trim().is_empty()) {
+            cursor.write_wrapped(&font_regular, line.trim(), 10.0, 20.0, text_width);
+        }
+        if payment_qr.is_some() && cursor.pages.len() == qr_page {
+            cursor.y = cursor.y.min(qr_bottom - 6.0);
+        }
+    }
+
+    let terms: Vec<String> = payment_terms.into_iter().chain(terms).collect();
+    if !terms.is_empty() {
+        cursor.y -= 16.0_f32;
+        cursor.ensure_space(SECTION_HEADING_HEIGHT_MM);
+        cursor.layer.use_text(labels.terms, 11.0, Mm(20.0), Mm(cursor.y), &font_bold);
+        cursor.y -= 6.0_f32;
+        for paragraph in &terms {
+            for line in paragraph.lines().filter(|line| !line.trim().is_empty()) {
+                cursor.write_wrapped(&font_regular, line.trim(), 10.0, 20.0, 170.0);
+            }
+            cursor.y -= 3.0_f32;
+        }
+    }
+
+    if let Some(notes) = business_info.notes.as_deref() {
+        cursor.y -= 16.0_f32;
+        cursor.ensure_space(SECTION_HEADING_HEIGHT_MM);
+        cursor.layer.use_text(labels.notes, 11.0, Mm(20.0), Mm(cursor.y), &font_bold);
+        cursor.y -= 6.0_f32;
+        for line in notes.lines().filter(|line| !line.trim().is_empty()) {
+            cursor.write_wrapped(&font_regular, line.trim(), 10.0, 20.0, 170.0);
+        }
+    }
+
+    if timesheet && !entries.is_empty() {
+        cursor.new_page();
+        write_timesheet(
+            &mut cursor,
+            &font_regular,
+            &font_bold,
+            labels,
+            &entries,
+            period.as_ref(),
+            None,
+        );
+    }
+
+    number_pages(&cursor.pages, &font_regular, labels.page, labels.page_of);
+    save_pdf(doc, output_path)
+}
+
+/// A timesheet on its own, with sign-off lines for whoever submits the
+/// hours and whoever approves them.
+pub fn generate_timesheet(document: TimesheetDocument, output_path: &str) -> Result<(), String> {
+    let labels = document.language.labels();
+    let (doc, page1, layer1) = PdfDocument::new(
+        labels.timesheet,
+        Mm(PAGE_WIDTH_MM),
+        Mm(PAGE_HEIGHT_MM),
+        "Layer 1",
+    );
+    let (font_regular, font_bold) = load_fonts(&doc, document.fonts)?;
+    let layer = doc.get_page(page1).get_layer(layer1);
+    let mut cursor = PageCursor::new(&doc, layer, 265.0, None);
+
+    write_timesheet(
+        &mut cursor,
+        &font_regular,
+        &font_bold,
+        labels,
+        &document.entries,
+        Some(&document.period),
+        document.client_name.as_deref(),
+    );
+    write_sign_off(&mut cursor, &font_regular, &font_bold, labels);
+
+    number_pages(&cursor.pages, &font_regular, labels.page, labels.page_of);
+    save_pdf(doc, output_path)
+}
+
+/// Name, signature and date lines side by side for the submitter and the
+/// approver, kept together on one page.
+fn write_sign_off(
+    cursor: &mut PageCursor,
+    font_regular: &IndirectFontRef,
+    font_bold: &IndirectFontRef,
+    labels: &InvoiceLabels,
+) {
+    cursor.y -= 16.0_f32;
+    cursor.ensure_space(50.0);
+    for (x, heading) in [(20.0, labels.submitted_by), (110.0, labels.approved_by)] {
+        cursor
+            .layer
+            .use_text(heading, 11.0, Mm(x), Mm(cursor.y), font_bold);
+    }
+    for caption in [labels.name, labels.signature, labels.date] {
+        cursor.y -= 13.0_f32;
+        for x in [20.0, 110.0] {
+            draw_line(&cursor.layer, x, cursor.y, x + 75.0, cursor.y, 0.3);
+            cursor
+                .layer
+                .use_text(caption, 8.0, Mm(x), Mm(cursor.y - 4.0), font_regular);
+        }
+    }
+}
+
+/// The regular and bold fonts, Helvetica unless custom ones are set.
+fn load_fonts(
+    doc: &PdfDocumentReference,
+    fonts: Option<InvoiceFonts>,
+) -> Result<(IndirectFontRef, IndirectFontRef), String> {
+    match fonts {
+        Some(fonts) => {
+            let regular = doc
+                .add_external_font(fonts.regular.as_slice())
+                .map_err(|e| format!("Failed to load font: {}", e))?;
+            let bold = match fonts.bold {
+                Some(bold) => doc
+                    .add_external_font(bold.as_slice())
+                    .map_err(|e| format!("Failed to load font: {}", e))?,
+                None => regular.clone(),
+            };
+            Ok((regular, bold))
+        }
+        None => Ok((
+            doc.add_builtin_font(BuiltinFont::Helvetica)
+                .map_err(|e| format!("Failed to load font: {}", e))?,
+            doc.add_builtin_font(BuiltinFont::HelveticaBold)
+                .map_err(|e| format!("Failed to load font: {}", e))?,
+        )),
+    }
+}
+
+/// "Page 1 of 3" at the foot of every page, unless there is only one.
+fn number_pages(pages: &[PdfLayerReference], font: &IndirectFontRef, page: &str, of: &str) {
+    let page_count = pages.len();
+    if page_count > 1 {
+        for (index, layer) in pages.iter().enumerate() {
+            layer.use_text(
+                format!("{} {} {} {}", page, index + 1, of, page_count),
+                9.0,
+                Mm(170.0),
+                Mm(12.0),
+                font,
+            );
+        }
+    }
+}
+
+fn save_pdf(doc: PdfDocumentReference, output_path: &str) -> Result<(), String> {
     let file = File::create(output_path)
         .map_err(|e| format!("Failed to create PDF file: {}", e))?;
     let mut buf_writer = BufWriter::new(file);
@@ -204,14 +750,746 @@ pub fn generate_invoice(
     Ok(())
 }
 
-fn format_money(amount: f64) -> String {
-    format!("{:.2} USD", amount)
+/// Each table under its heading, the header repeated on every page a table
+/// runs onto.
+pub fn generate_table_report(
+    document: TableReportDocument,
+    output_path: &str,
+) -> Result<(), String> {
+    let (doc, page1, layer1) = PdfDocument::new(
+        &document.title,
+        Mm(PAGE_WIDTH_MM),
+        Mm(PAGE_HEIGHT_MM),
+        "Layer 1",
+    );
+    let (font_regular, font_bold) = load_fonts(&doc, document.fonts)?;
+    let layer = doc.get_page(page1).get_layer(layer1);
+    let mut cursor = write_report_title(
+        &doc,
+        layer,
+        &font_regular,
+        &font_bold,
+        &document.title,
+        &document.subtitle,
+        document.generated_at,
+    );
+
+    for (index, table) in document.tables.iter().enumerate() {
+        if index > 0 {
+            cursor.y -= 10.0_f32;
+        }
+        write_report_heading(&mut cursor, &font_bold, &table.heading);
+        let header: Vec<(f32, &str)> = table
+            .columns
+            .iter()
+            .map(|(x, name)| (*x, name.as_str()))
+            .collect();
+        write_report_table_header(&mut cursor, &font_bold, &header);
+        for row in &table.rows {
+            if !cursor.fits(5.0) {
+                cursor.new_page();
+                write_report_table_header(&mut cursor, &font_bold, &header);
+            }
+            let cells: Vec<(f32, &str)> = table
+                .columns
+                .iter()
+                .zip(row)
+                .map(|((x, _), cell)| (*x, cell.as_str()))
+                .collect();
+            write_report_row(&cursor, &font_regular, 9.0, &cells);
+            cursor.y -= 5.0_f32;
+        }
+    }
+
+    number_pages(&cursor.pages, &font_regular, "Page", "of");
+    save_pdf(doc, output_path)
+}
+
+const REPORT_BAR_X_MM: f32 = 62.0;
+const REPORT_BAR_WIDTH_MM: f32 = 128.0;
+
+/// A summary of the month against the one before, hours and earnings per
+/// project, and a bar per day scaled to the busiest one.
+pub fn generate_monthly_report(
+    document: MonthlyReportDocument,
+    output_path: &str,
+) -> Result<(), String> {
+    let title = format!("Monthly report – {}", document.month);
+    let (doc, page1, layer1) =
+        PdfDocument::new(&title, Mm(PAGE_WIDTH_MM), Mm(PAGE_HEIGHT_MM), "Layer 1");
+    let (font_regular, font_bold) = load_fonts(&doc, document.fonts)?;
+    let layer = doc.get_page(page1).get_layer(layer1);
+    let mut cursor = write_report_title(
+        &doc,
+        layer,
+        &font_regular,
+        &font_bold,
+        "Monthly report",
+        &document.month,
+        document.generated_at,
+    );
+
+    // Summary against last month
+    write_report_heading(&mut cursor, &font_bold, "Summary");
+    write_report_row(
+        &cursor,
+        &font_bold,
+        9.0,
+        &[
+            (20.0, ""),
+            (80.0, &document.month),
+            (120.0, &document.previous_month),
+            (165.0, "Change"),
+        ],
+    );
+    cursor.y -= 3.0_f32;
+    draw_line(&cursor.layer, 20.0, cursor.y, 190.0, cursor.y, 0.4);
+    cursor.y -= 6.0_f32;
+    let mut summary = vec![
+        (
+            "Hours tracked".to_string(),
+            format!("{:.2}", document.hours),
+            format!("{:.2}", document.previous_hours),
+            percent_change(document.hours, document.previous_hours),
+        ),
+        (
+            "Entries".to_string(),
+            document.entry_count.to_string(),
+            document.previous_entry_count.to_string(),
+            percent_change(
+                document.entry_count as f64,
+                document.previous_entry_count as f64,
+            ),
+        ),
+    ];
+    for earnings in &document.earnings {
+        summary.push((
+            format!("Earnings ({})", earnings.currency),
+            format_money(earnings.amount, &earnings.currency),
+            format_money(earnings.previous_amount, &earnings.currency),
+            percent_change(earnings.amount, earnings.previous_amount),
+        ));
+    }
+    for (label, current, previous, change) in &summary {
+        write_report_row(
+            &cursor,
+            &font_regular,
+            10.0,
+            &[(20.0, label), (80.0, current), (120.0, previous), (165.0, change)],
+        );
+        cursor.y -= 6.0_f32;
+    }
+
+    // Projects
+    cursor.y -= 10.0_f32;
+    write_report_heading(&mut cursor, &font_bold, "Projects");
+    if document.projects.is_empty() {
+        cursor.write_wrapped(&font_regular, "No time tracked.", 10.0, 20.0, 170.0);
+    } else {
+        write_report_project_header(&mut cursor, &font_bold);
+    }
+    let name_chars = max_characters_for_width(75.0, 9.0);
+    for project in &document.projects {
+        let name_lines = wrap_text(&project.name, name_chars);
+        let row_height = name_lines.len() as f32 * 4.5 + 2.0;
+        if !cursor.fits(row_height) {
+            cursor.new_page();
+            write_report_project_header(&mut cursor, &font_bold);
+        }
+        let share = if document.hours > 0.0 {
+            format!("{:.0}%", project.hours / document.hours * 100.0)
+        } else {
+            String::new()
+        };
+        write_report_row(
+            &cursor,
+            &font_regular,
+            9.0,
+            &[
+                (100.0, &format!("{:.2}", project.hours)),
+                (120.0, &format!("{:.2}", project.previous_hours)),
+                (142.0, &share),
+                (160.0, &format_money(project.amount, &project.currency)),
+            ],
+        );
+        for (index, line) in name_lines.iter().enumerate() {
+            cursor.layer.use_text(
+                line,
+                9.0,
+                Mm(20.0),
+                Mm(cursor.y - index as f32 * 4.5),
+                &font_regular,
+            );
+        }
+        cursor.y -= row_height;
+    }
+
+    // Days
+    cursor.y -= 10.0_f32;
+    write_report_heading(&mut cursor, &font_bold, "Hours per day");
+    write_report_day_header(&mut cursor, &font_bold);
+    let busiest = document
+        .days
+        .iter()
+        .map(|(_, hours)| *hours)
+        .fold(0.0, f64::max);
+    for (date, hours) in &document.days {
+        if !cursor.fits(5.0) {
+            cursor.new_page();
+            write_report_day_header(&mut cursor, &font_bold);
+        }
+        let y = cursor.y;
+        write_report_row(
+            &cursor,
+            &font_regular,
+            9.0,
+            &[
+                (20.0, &date.format("%a %d").to_string()),
+                (40.0, &format!("{:.2}", hours)),
+            ],
+        );
+        if busiest > 0.0 && *hours > 0.0 {
+            let width = (hours / busiest) as f32 * REPORT_BAR_WIDTH_MM;
+            cursor.layer.save_graphics_state();
+            cursor
+                .layer
+                .set_fill_color(Color::Greyscale(Greyscale::new(0.55, None)));
+            cursor.layer.add_rect(Rect::new(
+                Mm(REPORT_BAR_X_MM),
+                Mm(y - 0.5),
+                Mm(REPORT_BAR_X_MM + width),
+                Mm(y + 2.5),
+            ));
+            cursor.layer.restore_graphics_state();
+        }
+        cursor.y -= 5.0_f32;
+    }
+
+    number_pages(&cursor.pages, &font_regular, "Page", "of");
+    save_pdf(doc, output_path)
+}
+
+fn write_report_heading(cursor: &mut PageCursor, font: &IndirectFontRef, text: &str) {
+    cursor.ensure_space(SECTION_HEADING_HEIGHT_MM + 10.0);
+    cursor
+        .layer
+        .use_text(text, 12.0, Mm(20.0), Mm(cursor.y), font);
+    cursor.y -= 8.0_f32;
+}
+
+/// The title, subtitle and generation time atop a report's first page,
+/// returning a cursor below them.
+fn write_report_title<'a>(
+    doc: &'a PdfDocumentReference,
+    layer: PdfLayerReference,
+    font_regular: &IndirectFontRef,
+    font_bold: &IndirectFontRef,
+    title: &str,
+    subtitle: &str,
+    generated_at: i64,
+) -> PageCursor<'a> {
+    layer.use_text(title, 22.0, Mm(20.0), Mm(265.0), font_bold);
+    layer.use_text(subtitle, 14.0, Mm(20.0), Mm(255.0), font_regular);
+    let generated_at = Local
+        .timestamp_opt(generated_at, 0)
+        .single()
+        .unwrap_or_else(Local::now);
+    layer.use_text(
+        format!("Generated {}", generated_at.format("%Y-%m-%d %H:%M")),
+        9.0,
+        Mm(20.0),
+        Mm(248.0),
+        font_regular,
+    );
+    draw_line(&layer, 20.0, 242.0, 190.0, 242.0, 0.3);
+    PageCursor::new(doc, layer, 230.0, None)
+}
+
+fn write_report_project_header(cursor: &mut PageCursor, font: &IndirectFontRef) {
+    write_report_table_header(
+        cursor,
+        font,
+        &[
+            (20.0, "Project"),
+            (100.0, "Hours"),
+            (120.0, "Last month"),
+            (142.0, "Share"),
+            (160.0, "Earnings"),
+        ],
+    );
+}
+
+fn write_report_day_header(cursor: &mut PageCursor, font: &IndirectFontRef) {
+    write_report_table_header(cursor, font, &[(20.0, "Day"), (40.0, "Hours")]);
+}
+
+fn write_report_table_header(
+    cursor: &mut PageCursor,
+    font: &IndirectFontRef,
+    columns: &[(f32, &str)],
+) {
+    write_report_row(cursor, font, 9.0, columns);
+    cursor.y -= 3.0_f32;
+    draw_line(&cursor.layer, 20.0, cursor.y, 190.0, cursor.y, 0.4);
+    cursor.y -= 6.0_f32;
+}
+
+/// Writes each text at its x position on the cursor's baseline.
+fn write_report_row(
+    cursor: &PageCursor,
+    font: &IndirectFontRef,
+    font_size: f32,
+    columns: &[(f32, &str)],
+) {
+    for (x, text) in columns {
+        cursor
+            .layer
+            .use_text(*text, font_size, Mm(*x), Mm(cursor.y), font);
+    }
+}
+
+/// "+12%", "-5%", or "–" when there is nothing to compare with.
+fn percent_change(current: f64, previous: f64) -> String {
+    if previous.abs() < 0.005 {
+        if current.abs() < 0.005 {
+            "0%".into()
+        } else {
+            "–".into()
+        }
+    } else {
+        format!("{:+.0}%", (current - previous) / previous.abs() * 100.0)
+    }
+}
+
+/// Tracks the page being written and the baseline on it, adding pages as
+/// content reaches the bottom margin.
+struct PageCursor<'a> {
+    doc: &'a PdfDocumentReference,
+    layer: PdfLayerReference,
+    pages: Vec<PdfLayerReference>,
+    y: f32,
+    /// Font and text of the watermark drawn on each new page.
+    watermark: Option<(&'a IndirectFontRef, &'static str)>,
+}
+
+impl<'a> PageCursor<'a> {
+    fn new(
+        doc: &'a PdfDocumentReference,
+        layer: PdfLayerReference,
+        y: f32,
+        watermark: Option<(&'a IndirectFontRef, &'static str)>,
+    ) -> Self {
+        Self {
+            doc,
+            pages: vec![layer.clone()],
+            layer,
+            y,
+            watermark,
+        }
+    }
+
+    fn fits(&self, height: f32) -> bool {
+        self.y - height >= BOTTOM_MARGIN_MM
+    }
+
+    fn new_page(&mut self) {
+        let (page, layer) = self
+            .doc
+            .add_page(Mm(PAGE_WIDTH_MM), Mm(PAGE_HEIGHT_MM), "Layer 1");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        if let Some((font, text)) = self.watermark {
+            draw_watermark(&self.layer, font, text);
+        }
+        self.pages.push(self.layer.clone());
+        self.y = CONTINUATION_TOP_MM;
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if !self.fits(height) {
+            self.new_page();
+        }
+    }
+
+    /// Like `write_wrapped_text`, but long text continues on a new page.
+    fn write_wrapped(
+        &mut self,
+        font: &IndirectFontRef,
+        text: &str,
+        font_size: f32,
+        x: f32,
+        max_width_mm: f32,
+    ) {
+        let max_chars = max_characters_for_width(max_width_mm, font_size);
+        for line in wrap_text(text, max_chars) {
+            self.ensure_space(5.0);
+            self.layer
+                .use_text(line, font_size, Mm(x), Mm(self.y), font);
+            self.y -= 5.0_f32;
+        }
+    }
+}
+
+const TIMESHEET_TIME_X_MM: f32 = 42.0;
+const TIMESHEET_PROJECT_X_MM: f32 = 65.0;
+const TIMESHEET_PROJECT_WIDTH_MM: f32 = 38.0;
+const TIMESHEET_NOTES_X_MM: f32 = 106.0;
+const TIMESHEET_NOTES_WIDTH_MM: f32 = 62.0;
+const TIMESHEET_HOURS_X_MM: f32 = 175.0;
+
+/// Every entry with its times and notes, grouped by day with a total per
+/// day and for the whole sheet.
+fn write_timesheet(
+    cursor: &mut PageCursor,
+    font_regular: &IndirectFontRef,
+    font_bold: &IndirectFontRef,
+    labels: &InvoiceLabels,
+    entries: &[TimeEntry],
+    period: Option<&InvoicePeriod>,
+    client_name: Option<&str>,
+) {
+    let local = |timestamp: i64| {
+        Local
+            .timestamp_opt(timestamp, 0)
+            .single()
+            .unwrap_or_else(Local::now)
+    };
+    let mut entries: Vec<&TimeEntry> = entries.iter().collect();
+    entries.sort_by_key(|entry| (entry.start_time, entry.id));
+
+    cursor
+        .layer
+        .use_text(labels.timesheet, 18.0, Mm(20.0), Mm(cursor.y), font_bold);
+    cursor.y -= 8.0_f32;
+    if let Some(period) = period {
+        cursor.layer.use_text(
+            format!(
+                "{} – {}",
+                local(period.start_time).format(labels.date_format),
+                local(period.end_time).format(labels.date_format)
+            ),
+            10.0,
+            Mm(20.0),
+            Mm(cursor.y),
+            font_regular,
+        );
+        cursor.y -= 6.0_f32;
+    }
+    if let Some(client_name) = client_name {
+        cursor
+            .layer
+            .use_text(client_name, 10.0, Mm(20.0), Mm(cursor.y), font_regular);
+        cursor.y -= 6.0_f32;
+    }
+    cursor.y -= 6.0_f32;
+    write_timesheet_header(cursor, font_regular, labels);
+
+    let project_chars = max_characters_for_width(TIMESHEET_PROJECT_WIDTH_MM, 9.0);
+    let notes_chars = max_characters_for_width(TIMESHEET_NOTES_WIDTH_MM, 9.0);
+    let mut total_seconds = 0;
+    let mut index = 0;
+    while index < entries.len() {
+        let day = local(entries[index].start_time).date_naive();
+        let day_len = entries[index..]
+            .iter()
+            .take_while(|entry| local(entry.start_time).date_naive() == day)
+            .count();
+        let mut day_seconds = 0;
+        // The date heads the day's first row and its first on a new page.
+        let mut show_date = true;
+        for entry in &entries[index..index + day_len] {
+            let project_lines = wrap_text(&entry.project_name, project_chars);
+            let notes_lines = entry
+                .notes
+                .as_deref()
+                .map(|notes| wrap_text(notes, notes_chars))
+                .unwrap_or_default();
+            let row_height = project_lines.len().max(notes_lines.len()).max(1) as f32 * 4.5 + 2.0;
+            if !cursor.fits(row_height) {
+                cursor.new_page();
+                write_timesheet_header(cursor, font_regular, labels);
+                show_date = true;
+            }
+
+            let y = cursor.y;
+            let layer = cursor.layer.clone();
+            if std::mem::take(&mut show_date) {
+                layer.use_text(
+                    day.format(labels.date_format).to_string(),
+                    9.0,
+                    Mm(20.0),
+                    Mm(y),
+                    font_regular,
+                );
+            }
+            layer.use_text(
+                format!(
+                    "{}–{}",
+                    local(entry.start_time).format("%H:%M"),
+                    local(entry.end_time).format("%H:%M")
+                ),
+                9.0,
+                Mm(TIMESHEET_TIME_X_MM),
+                Mm(y),
+                font_regular,
+            );
+            for (line_index, line) in project_lines.iter().enumerate() {
+                layer.use_text(
+                    line,
+                    9.0,
+                    Mm(TIMESHEET_PROJECT_X_MM),
+                    Mm(y - line_index as f32 * 4.5),
+                    font_regular,
+                );
+            }
+            for (line_index, line) in notes_lines.iter().enumerate() {
+                layer.use_text(
+                    line,
+                    9.0,
+                    Mm(TIMESHEET_NOTES_X_MM),
+                    Mm(y - line_index as f32 * 4.5),
+                    font_regular,
+                );
+            }
+            layer.use_text(
+                format!("{:.2}", entry.duration as f64 / 3600.0),
+                9.0,
+                Mm(TIMESHEET_HOURS_X_MM),
+                Mm(y),
+                font_regular,
+            );
+            cursor.y -= row_height;
+            day_seconds += entry.duration;
+        }
+
+        cursor.ensure_space(8.0);
+        cursor.layer.use_text(
+            labels.day_total,
+            9.0,
+            Mm(TIMESHEET_NOTES_X_MM),
+            Mm(cursor.y),
+            font_bold,
+        );
+        cursor.layer.use_text(
+            format!("{:.2}", day_seconds as f64 / 3600.0),
+            9.0,
+            Mm(TIMESHEET_HOURS_X_MM),
+            Mm(cursor.y),
+            font_bold,
+        );
+        cursor.y -= 3.0_f32;
+        draw_line(&cursor.layer, 20.0, cursor.y, 190.0, cursor.y, 0.2);
+        cursor.y -= 5.0_f32;
+        total_seconds += day_seconds;
+        index += day_len;
+    }
+
+    cursor.ensure_space(10.0);
+    cursor.y -= 2.0_f32;
+    cursor.layer.use_text(
+        format!("{} {}", labels.total, labels.hours),
+        11.0,
+        Mm(TIMESHEET_NOTES_X_MM),
+        Mm(cursor.y),
+        font_bold,
+    );
+    cursor.layer.use_text(
+        format!("{:.2}", total_seconds as f64 / 3600.0),
+        11.0,
+        Mm(TIMESHEET_HOURS_X_MM),
+        Mm(cursor.y),
+        font_bold,
+    );
+}
+
+fn write_timesheet_header(cursor: &mut PageCursor, font: &IndirectFontRef, labels: &InvoiceLabels) {
+    let y = cursor.y;
+    cursor.layer.use_text(labels.date, 9.0, Mm(20.0), Mm(y), font);
+    cursor
+        .layer
+        .use_text(labels.time, 9.0, Mm(TIMESHEET_TIME_X_MM), Mm(y), font);
+    cursor
+        .layer
+        .use_text(labels.project, 9.0, Mm(TIMESHEET_PROJECT_X_MM), Mm(y), font);
+    cursor
+        .layer
+        .use_text(labels.description, 9.0, Mm(TIMESHEET_NOTES_X_MM), Mm(y), font);
+    cursor
+        .layer
+        .use_text(labels.hours, 9.0, Mm(TIMESHEET_HOURS_X_MM), Mm(y), font);
+    cursor.y -= 4.0_f32;
+    draw_line(&cursor.layer, 20.0, cursor.y, 190.0, cursor.y, 0.4);
+    cursor.y -= 6.0_f32;
+}
+
+/// One "Label: value" line per filled-in account field.
+fn bank_account_lines(account: &BankAccount, labels: &InvoiceLabels) -> Vec<String> {
+    [
+        (labels.account_holder, account.account_holder.clone()),
+        (labels.bank, account.bank_name.clone()),
+        ("IBAN", account.formatted_iban()),
+        ("BIC", account.bic.clone()),
+        (labels.account_number, account.account_number.clone()),
+    ]
+    .into_iter()
+    .filter_map(|(label, value)| value.map(|value| format!("{}: {}", label, value)))
+    .collect()
+}
+
+fn write_table_header(
+    cursor: &mut PageCursor,
+    font: &IndirectFontRef,
+    labels: &InvoiceLabels,
+    has_dates: bool,
+    description_x: f32,
+) {
+    let y = cursor.y;
+    if has_dates {
+        cursor.layer.use_text(labels.date, 10.0, Mm(20.0), Mm(y), font);
+    }
+    cursor
+        .layer
+        .use_text(labels.description, 10.0, Mm(description_x), Mm(y), font);
+    cursor.layer.use_text(labels.quantity, 10.0, Mm(110.0), Mm(y), font);
+    cursor.layer.use_text(labels.unit_price, 10.0, Mm(140.0), Mm(y), font);
+    cursor.layer.use_text(labels.amount, 10.0, Mm(175.0), Mm(y), font);
+    cursor.y -= 6.0_f32;
+    draw_line(&cursor.layer, 20.0, cursor.y, 190.0, cursor.y, 0.4);
+    cursor.y -= 10.0_f32;
+}
+
+/// The running subtotal at a page break, e.g. "Carried forward 1200.00 EUR".
+fn write_carry_row(
+    cursor: &PageCursor,
+    font: &IndirectFontRef,
+    label: &str,
+    amount: f64,
+    currency: &str,
+) {
+    cursor.layer.use_text(label, 10.0, Mm(140.0), Mm(cursor.y), font);
+    cursor.layer.use_text(
+        format_money(amount, currency),
+        10.0,
+        Mm(175.0),
+        Mm(cursor.y),
+        font,
+    );
+}
+
+/// Tallest a logo may be; wider logos are scaled down to fit.
+const MAX_LOGO_HEIGHT_MM: f32 = 28.0;
+/// Bottom edge of the logo, level with the issue date.
+const LOGO_BOTTOM_MM: f32 = 250.0;
+
+/// Checks that `data` is a TrueType font the invoice can embed.
+pub fn validate_font(data: &[u8]) -> Result<(), String> {
+    PdfDocument::empty("Font check")
+        .add_external_font(data)
+        .map(|_| ())
+        .map_err(|_| "The font must be a TrueType (.ttf) or OpenType (.otf) file".to_string())
+}
+
+/// Checks that `data` is a PNG or JPEG the invoice can embed.
+pub fn validate_logo(data: &[u8]) -> Result<(), String> {
+    decode_logo(data).map(|_| ())
+}
+
+fn draw_logo(layer: &PdfLayerReference, logo: &InvoiceLogo) -> Result<(), String> {
+    let image = decode_logo(&logo.data)?;
+    let (width_px, height_px) = (image.width.0 as f32, image.height.0 as f32);
+    let width_mm = logo
+        .width_mm
+        .min(MAX_LOGO_HEIGHT_MM * width_px / height_px);
+    // One pixel per point at `dpi`, so choosing it sets the printed size.
+    let dpi = width_px * 25.4 / width_mm;
+    Image::from(image).add_to_layer(
+        layer.clone(),
+        ImageTransform {
+            translate_x: Some(Mm(190.0 - width_mm)),
+            translate_y: Some(Mm(LOGO_BOTTOM_MM)),
+            dpi: Some(dpi),
+            ..Default::default()
+        },
+    );
+    Ok(())
+}
+
+/// JPEGs are embedded as they are; PNGs are decoded and flattened onto white,
+/// since the page is white anyway.
+fn decode_logo(data: &[u8]) -> Result<ImageXObject, String> {
+    if data.starts_with(&[0xFF, 0xD8]) {
+        let (width, height, components) =
+            jpeg_dimensions(data).ok_or("The logo isn't a readable JPEG")?;
+        let color_space = match components {
+            1 => ColorSpace::Greyscale,
+            3 => ColorSpace::Rgb,
+            4 => ColorSpace::Cmyk,
+            _ => return Err("The logo uses an unsupported JPEG color format".into()),
+        };
+        return Ok(ImageXObject {
+            width: Px(width),
+            height: Px(height),
+            color_space,
+            bits_per_component: ColorBits::Bit8,
+            interpolate: true,
+            image_data: data.to_vec(),
+            image_filter: Some(ImageFilter::DCT),
+            smask: None,
+            clipping_bbox: None,
+        });
+    }
+
+    let image = tauri::image::Image::from_bytes(data)
+        .map_err(|_| "The logo must be a PNG or JPEG image".to_string())?;
+    let mut rgb = Vec::with_capacity(image.rgba().len() / 4 * 3);
+    for pixel in image.rgba().chunks_exact(4) {
+        let alpha = u16::from(pixel[3]);
+        for channel in &pixel[..3] {
+            rgb.push(((u16::from(*channel) * alpha + 255 * (255 - alpha)) / 255) as u8);
+        }
+    }
+    Ok(ImageXObject {
+        width: Px(image.width() as usize),
+        height: Px(image.height() as usize),
+        color_space: ColorSpace::Rgb,
+        bits_per_component: ColorBits::Bit8,
+        interpolate: true,
+        image_data: rgb,
+        image_filter: None,
+        smask: None,
+        clipping_bbox: None,
+    })
+}
+
+/// Width, height and component count from a JPEG's start-of-frame marker.
+fn jpeg_dimensions(data: &[u8]) -> Option<(usize, usize, u8)> {
+    let mut index = 2;
+    while index + 4 <= data.len() {
+        if data[index] != 0xFF {
+            return None;
+        }
+        let marker = data[index + 1];
+        let length = usize::from(u16::from_be_bytes([data[index + 2], data[index + 3]]));
+        // SOF0–SOF15, except DHT (C4), JPG (C8) and DAC (CC).
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let frame = data.get(index + 4..index + 10)?;
+            let height = usize::from(u16::from_be_bytes([frame[1], frame[2]]));
+            let width = usize::from(u16::from_be_bytes([frame[3], frame[4]]));
+            return (width > 0 && height > 0).then_some((width, height, frame[5]));
+        }
+        index += 2 + length;
+    }
+    None
+}
+
+fn format_money(amount: f64, currency: &str) -> String {
+    format!("{:.2} {}", amount, currency)
 }
 
 fn write_contact_block(
     layer: &PdfLayerReference,
     font_bold: &IndirectFontRef,
     font_regular: &IndirectFontRef,
+    labels: &InvoiceLabels,
     label: &str,
     x: f32,
     max_width_mm: f32,
@@ -239,7 +1517,7 @@ fn write_contact_block(
             y = write_wrapped_text(
                 layer,
                 font_regular,
-                &format!("Email: {}", value.trim()),
+                &format!("{}: {}", labels.email, value.trim()),
                 10.0,
                 x,
                 y,
@@ -253,7 +1531,7 @@ fn write_contact_block(
             y = write_wrapped_text(
                 layer,
                 font_regular,
-                &format!("Phone: {}", value.trim()),
+                &format!("{}: {}", labels.phone, value.trim()),
                 10.0,
                 x,
                 y,
@@ -365,6 +1643,44 @@ fn max_characters_for_width(max_width_mm: f32, font_size: f32) -> usize {
     estimated.max(8)
 }
 
+/// Draws `qr` as a `size`-millimetre square with its bottom left corner at
+/// `(x, y)`. The page around it is left blank as the quiet zone.
+fn draw_qr_code(layer: &PdfLayerReference, qr: &QrCode, x: f32, y: f32, size: f32) {
+    let module = size / qr.size() as f32;
+    for row in 0..qr.size() {
+        for column in 0..qr.size() {
+            if qr.is_dark(column, row) {
+                let left = x + column as f32 * module;
+                let top = y + size - row as f32 * module;
+                layer.add_rect(Rect::new(
+                    Mm(left),
+                    Mm(top - module),
+                    Mm(left + module),
+                    Mm(top),
+                ));
+            }
+        }
+    }
+}
+
+/// Large light-grey text across the page from bottom left to top right,
+/// sized down for longer words so it stays on the page.
+fn draw_watermark(layer: &PdfLayerReference, font: &IndirectFontRef, text: &str) {
+    let font_size = (600.0 / text.chars().count().max(1) as f32).min(110.0);
+    layer.save_graphics_state();
+    layer.set_fill_color(Color::Greyscale(Greyscale::new(0.88, None)));
+    layer.begin_text_section();
+    layer.set_font(font, font_size);
+    layer.set_text_matrix(TextMatrix::TranslateRotate(
+        Mm(45.0).into(),
+        Mm(75.0).into(),
+        45.0,
+    ));
+    layer.write_text(text, font);
+    layer.end_text_section();
+    layer.restore_graphics_state();
+}
+
 fn draw_line(layer: &PdfLayerReference, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32) {
     let line = Line {
         points: vec![
@@ -0,0 +1,1027 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+use printpdf::lopdf::{content::Operation, Dictionary, Document, Object, ObjectId, Stream};
+use ttf_parser::{Face, GlyphId, OutlineBuilder};
+
+/// A4 in points, used when a page has no readable MediaBox.
+const DEFAULT_PAGE_SIZE_PT: (f32, f32) = (595.0, 842.0);
+/// Pages are drawn at this multiple of the output size and averaged down,
+/// which smooths edges without a real anti-aliasing rasterizer. Four keeps
+/// small text readable as glyphs rather than noise.
+const SUPERSAMPLE: usize = 4;
+/// Average glyph advance as a share of the font size, for characters no
+/// font describes.
+const GLYPH_ADVANCE_EM: f32 = 0.5;
+/// Height of the bar drawn for a character without a glyph, as a share of
+/// the font size: about the x-height plus a little.
+const TEXT_BAR_EM: f32 = 0.55;
+/// How far those bars are lightened towards white so they read as text.
+const TEXT_LIGHTEN: f32 = 0.45;
+/// Segments each curve of a glyph outline is flattened into.
+const CURVE_SEGMENTS: usize = 6;
+/// Size of a bitmap font pixel as a share of the font size, so capitals
+/// stand about as tall as Helvetica's.
+const BITMAP_PIXEL_EM: f32 = 0.1;
+
+/// Helvetica's advances in thousandths of an em for printable ASCII, from
+/// its AFM metrics. Helvetica-Bold's are close enough to share them.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// A 5x8 bitmap font for printable ASCII, one byte per column with the top
+/// row in the lowest bit. Rows 0 to 6 stand on the baseline and row 7 hangs
+/// below it. The built-in PDF fonts carry no outlines, so their text is
+/// drawn with these at Helvetica's advances.
+#[rustfmt::skip]
+const BITMAP_FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x5F, 0x00, 0x00], // space !
+    [0x00, 0x07, 0x00, 0x07, 0x00], [0x14, 0x7F, 0x14, 0x7F, 0x14], // " #
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12], [0x23, 0x13, 0x08, 0x64, 0x62], // $ %
+    [0x36, 0x49, 0x56, 0x20, 0x50], [0x00, 0x05, 0x03, 0x00, 0x00], // & '
+    [0x00, 0x1C, 0x22, 0x41, 0x00], [0x00, 0x41, 0x22, 0x1C, 0x00], // ( )
+    [0x2A, 0x1C, 0x7F, 0x1C, 0x2A], [0x08, 0x08, 0x3E, 0x08, 0x08], // * +
+    [0x00, 0xA0, 0x60, 0x00, 0x00], [0x08, 0x08, 0x08, 0x08, 0x08], // , -
+    [0x00, 0x60, 0x60, 0x00, 0x00], [0x20, 0x10, 0x08, 0x04, 0x02], // . /
+    [0x3E, 0x51, 0x49, 0x45, 0x3E], [0x00, 0x42, 0x7F, 0x40, 0x00], // 0 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], [0x21, 0x41, 0x45, 0x4B, 0x31], // 2 3
+    [0x18, 0x14, 0x12, 0x7F, 0x10], [0x27, 0x45, 0x45, 0x45, 0x39], // 4 5
+    [0x3C, 0x4A, 0x49, 0x49, 0x30], [0x01, 0x71, 0x09, 0x05, 0x03], // 6 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], [0x06, 0x49, 0x49, 0x29, 0x1E], // 8 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], [0x00, 0x56, 0x36, 0x00, 0x00], // : ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], [0x14, 0x14, 0x14, 0x14, 0x14], // < =
+    [0x00, 0x41, 0x22, 0x14, 0x08], [0x02, 0x01, 0x51, 0x09, 0x06], // > ?
+    [0x32, 0x49, 0x79, 0x41, 0x3E], [0x7E, 0x11, 0x11, 0x11, 0x7E], // @ A
+    [0x7F, 0x49, 0x49, 0x49, 0x36], [0x3E, 0x41, 0x41, 0x41, 0x22], // B C
+    [0x7F, 0x41, 0x41, 0x22, 0x1C], [0x7F, 0x49, 0x49, 0x49, 0x41], // D E
+    [0x7F, 0x09, 0x09, 0x09, 0x01], [0x3E, 0x41, 0x49, 0x49, 0x7A], // F G
+    [0x7F, 0x08, 0x08, 0x08, 0x7F], [0x00, 0x41, 0x7F, 0x41, 0x00], // H I
+    [0x20, 0x40, 0x41, 0x3F, 0x01], [0x7F, 0x08, 0x14, 0x22, 0x41], // J K
+    [0x7F, 0x40, 0x40, 0x40, 0x40], [0x7F, 0x02, 0x0C, 0x02, 0x7F], // L M
+    [0x7F, 0x04, 0x08, 0x10, 0x7F], [0x3E, 0x41, 0x41, 0x41, 0x3E], // N O
+    [0x7F, 0x09, 0x09, 0x09, 0x06], [0x3E, 0x41, 0x51, 0x21, 0x5E], // P Q
+    [0x7F, 0x09, 0x19, 0x29, 0x46], [0x46, 0x49, 0x49, 0x49, 0x31], // R S
+    [0x01, 0x01, 0x7F, 0x01, 0x01], [0x3F, 0x40, 0x40, 0x40, 0x3F], // T U
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], [0x3F, 0x40, 0x38, 0x40, 0x3F], // V W
+    [0x63, 0x14, 0x08, 0x14, 0x63], [0x07, 0x08, 0x70, 0x08, 0x07], // X Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], [0x00, 0x7F, 0x41, 0x41, 0x00], // Z [
+    [0x02, 0x04, 0x08, 0x10, 0x20], [0x00, 0x41, 0x41, 0x7F, 0x00], // \ ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], [0x80, 0x80, 0x80, 0x80, 0x80], // ^ _
+    [0x00, 0x01, 0x02, 0x04, 0x00], [0x20, 0x54, 0x54, 0x54, 0x78], // ` a
+    [0x7F, 0x48, 0x44, 0x44, 0x38], [0x38, 0x44, 0x44, 0x44, 0x20], // b c
+    [0x38, 0x44, 0x44, 0x48, 0x7F], [0x38, 0x54, 0x54, 0x54, 0x18], // d e
+    [0x08, 0x7E, 0x09, 0x01, 0x02], [0x18, 0xA4, 0xA4, 0xA4, 0x7C], // f g
+    [0x7F, 0x08, 0x04, 0x04, 0x78], [0x00, 0x44, 0x7D, 0x40, 0x00], // h i
+    [0x40, 0x80, 0x84, 0x7D, 0x00], [0x7F, 0x10, 0x28, 0x44, 0x00], // j k
+    [0x00, 0x41, 0x7F, 0x40, 0x00], [0x7C, 0x04, 0x18, 0x04, 0x78], // l m
+    [0x7C, 0x08, 0x04, 0x04, 0x78], [0x38, 0x44, 0x44, 0x44, 0x38], // n o
+    [0xFC, 0x24, 0x24, 0x24, 0x18], [0x18, 0x24, 0x24, 0x24, 0xFC], // p q
+    [0x7C, 0x08, 0x04, 0x04, 0x08], [0x48, 0x54, 0x54, 0x54, 0x20], // r s
+    [0x04, 0x3F, 0x44, 0x40, 0x20], [0x3C, 0x40, 0x40, 0x20, 0x7C], // t u
+    [0x1C, 0x20, 0x40, 0x20, 0x1C], [0x3C, 0x40, 0x30, 0x40, 0x3C], // v w
+    [0x44, 0x28, 0x10, 0x28, 0x44], [0x1C, 0xA0, 0xA0, 0xA0, 0x7C], // x y
+    [0x44, 0x64, 0x54, 0x4C, 0x44], [0x00, 0x08, 0x36, 0x41, 0x00], // z {
+    [0x00, 0x00, 0x7F, 0x00, 0x00], [0x00, 0x41, 0x36, 0x08, 0x00], // | }
+    [0x08, 0x04, 0x08, 0x10, 0x08],                                 // ~
+];
+/// Images that can't be decoded (JPEG logos) are shown as a box of this grey.
+const IMAGE_PLACEHOLDER: [u8; 3] = [215, 215, 215];
+
+/// Renders the first page of a PDF to a PNG `width_px` wide.
+///
+/// This is a preview renderer for the PDFs this app writes, not a general
+/// one: page curves are flattened to straight lines, clipping is ignored and
+/// JPEG images become grey boxes. Text in embedded TrueType fonts is drawn
+/// from the fonts' outlines and text in the built-in fonts with a bitmap
+/// font. Lines, filled rectangles (such as the payment QR code) and
+/// uncompressed or Flate-compressed RGB and greyscale images are drawn as
+/// they are.
+pub fn render_first_page(pdf_path: &Path, width_px: u32) -> Result<Vec<u8>, String> {
+    let doc = Document::load(pdf_path).map_err(|e| format!("Failed to read PDF: {}", e))?;
+    let page_id = *doc
+        .get_pages()
+        .values()
+        .next()
+        .ok_or("The PDF has no pages")?;
+    let (page_width, page_height) = page_size(&doc, page_id);
+
+    let scale = width_px as f32 * SUPERSAMPLE as f32 / page_width;
+    let width = width_px as usize * SUPERSAMPLE;
+    let height = ((page_height * scale).round() as usize).max(1);
+    let mut canvas = Canvas::new(width, height);
+    let device = Matrix([scale, 0.0, 0.0, -scale, 0.0, page_height * scale]);
+
+    let content = doc
+        .get_and_decode_page_content(page_id)
+        .map_err(|e| format!("Failed to read the PDF page: {}", e))?;
+    let fonts = page_fonts(&doc, page_id);
+    let font_files: BTreeMap<&[u8], Vec<u8>> = fonts
+        .iter()
+        .filter_map(|(name, font)| Some((name.as_slice(), embedded_font_file(&doc, font)?)))
+        .collect();
+    let faces: BTreeMap<&[u8], Face> = font_files
+        .iter()
+        .filter_map(|(name, file)| Some((*name, Face::parse(file, 0).ok()?)))
+        .collect();
+    let mut painter = Painter {
+        doc: &doc,
+        page_id,
+        fonts: &fonts,
+        faces: &faces,
+        face: None,
+        device,
+        state: GraphicsState::default(),
+        stack: Vec::new(),
+        path: Vec::new(),
+        text: TextState::default(),
+    };
+    for operation in &content.operations {
+        painter.apply(operation, &mut canvas);
+    }
+
+    let image = canvas.downsample(SUPERSAMPLE);
+    encode_png(width_px, (height / SUPERSAMPLE).max(1) as u32, &image)
+}
+
+/// The page's fonts by resource name. Unlike lopdf's own lookup, this
+/// follows a font dictionary given by reference, as printpdf writes it.
+fn page_fonts(doc: &Document, page_id: ObjectId) -> BTreeMap<Vec<u8>, &Dictionary> {
+    let (resources, resource_ids) = doc.get_page_resources(page_id);
+    let mut fonts = BTreeMap::new();
+    for resources in resources.into_iter().chain(
+        resource_ids
+            .into_iter()
+            .filter_map(|id| doc.get_dictionary(id).ok()),
+    ) {
+        let Some(dict) = resources
+            .get_deref(b"Font", doc)
+            .ok()
+            .and_then(|fonts| fonts.as_dict().ok())
+        else {
+            continue;
+        };
+        for (name, font) in dict.iter() {
+            let font = match font {
+                Object::Reference(id) => doc.get_dictionary(*id).ok(),
+                object => object.as_dict().ok(),
+            };
+            if let Some(font) = font {
+                fonts.entry(name.clone()).or_insert(font);
+            }
+        }
+    }
+    fonts
+}
+
+/// The TrueType program of a Type0 font that embeds one.
+fn embedded_font_file(doc: &Document, font: &Dictionary) -> Option<Vec<u8>> {
+    let descendant = font
+        .get_deref(b"DescendantFonts", doc)
+        .ok()?
+        .as_array()
+        .ok()?
+        .first()?;
+    let descendant = match descendant {
+        Object::Reference(id) => doc.get_dictionary(*id).ok()?,
+        object => object.as_dict().ok()?,
+    };
+    let descriptor = descendant
+        .get_deref(b"FontDescriptor", doc)
+        .ok()?
+        .as_dict()
+        .ok()?;
+    let file = descriptor
+        .get_deref(b"FontFile2", doc)
+        .ok()?
+        .as_stream()
+        .ok()?;
+    file.decompressed_content()
+        .ok()
+        .or_else(|| Some(file.content.clone()))
+}
+
+fn page_size(doc: &Document, page_id: ObjectId) -> (f32, f32) {
+    let media_box = doc
+        .get_dictionary(page_id)
+        .ok()
+        .and_then(|page| page.get(b"MediaBox").ok())
+        .and_then(|object| object.as_array().ok())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_float().ok())
+                .collect::<Vec<_>>()
+        });
+    match media_box.as_deref() {
+        Some([x0, y0, x1, y1]) if x1 > x0 && y1 > y0 => (x1 - x0, y1 - y0),
+        _ => DEFAULT_PAGE_SIZE_PT,
+    }
+}
+
+/// A PDF transformation matrix `[a b c d e f]`.
+#[derive(Debug, Clone, Copy)]
+struct Matrix([f32; 6]);
+
+impl Matrix {
+    const IDENTITY: Matrix = Matrix([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    /// `self` applied first, then `other`.
+    fn then(self, other: Matrix) -> Matrix {
+        let [a, b, c, d, e, f] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = other.0;
+        Matrix([
+            a * a2 + b * c2,
+            a * b2 + b * d2,
+            c * a2 + d * c2,
+            c * b2 + d * d2,
+            e * a2 + f * c2 + e2,
+            e * b2 + f * d2 + f2,
+        ])
+    }
+
+    fn apply(self, (x, y): (f32, f32)) -> (f32, f32) {
+        let [a, b, c, d, e, f] = self.0;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+
+    fn invert(self) -> Option<Matrix> {
+        let [a, b, c, d, e, f] = self.0;
+        let det = a * d - b * c;
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        Some(Matrix([
+            d / det,
+            -b / det,
+            -c / det,
+            a / det,
+            (c * f - d * e) / det,
+            (b * e - a * f) / det,
+        ]))
+    }
+
+    /// How much the matrix scales lengths, on average.
+    fn scale(self) -> f32 {
+        let [a, b, c, d, _, _] = self.0;
+        (a * d - b * c).abs().sqrt()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GraphicsState {
+    ctm: Matrix,
+    fill: [u8; 3],
+    stroke: [u8; 3],
+    line_width: f32,
+}
+
+impl Default for GraphicsState {
+    fn default() -> Self {
+        Self {
+            ctm: Matrix::IDENTITY,
+            fill: [0, 0, 0],
+            stroke: [0, 0, 0],
+            line_width: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TextState {
+    matrix: Matrix,
+    line_matrix: Matrix,
+    font_size: f32,
+    /// Bytes per character in the current font: 2 for the glyph ids of an
+    /// embedded (Type0) font, 1 for the built-in fonts.
+    bytes_per_char: usize,
+}
+
+impl Default for TextState {
+    fn default() -> Self {
+        Self {
+            matrix: Matrix::IDENTITY,
+            line_matrix: Matrix::IDENTITY,
+            font_size: 12.0,
+            bytes_per_char: 1,
+        }
+    }
+}
+
+struct Painter<'a> {
+    doc: &'a Document,
+    page_id: ObjectId,
+    fonts: &'a BTreeMap<Vec<u8>, &'a Dictionary>,
+    /// Parsed embedded fonts by resource name.
+    faces: &'a BTreeMap<&'a [u8], Face<'a>>,
+    /// The current font's outlines, when it embeds them.
+    face: Option<&'a Face<'a>>,
+    /// User space to canvas pixels.
+    device: Matrix,
+    state: GraphicsState,
+    stack: Vec<GraphicsState>,
+    /// Subpaths of the path being built, in user space.
+    path: Vec<Vec<(f32, f32)>>,
+    text: TextState,
+}
+
+impl Painter<'_> {
+    fn apply(&mut self, operation: &Operation, canvas: &mut Canvas) {
+        let numbers: Vec<f32> = operation
+            .operands
+            .iter()
+            .filter_map(|operand| operand.as_float().ok())
+            .collect();
+        match (operation.operator.as_str(), numbers.as_slice()) {
+            ("q", _) => self.stack.push(self.state),
+            ("Q", _) => {
+                if let Some(state) = self.stack.pop() {
+                    self.state = state;
+                }
+            }
+            ("cm", &[a, b, c, d, e, f]) => {
+                self.state.ctm = Matrix([a, b, c, d, e, f]).then(self.state.ctm);
+            }
+            ("w", &[width]) => self.state.line_width = width,
+            ("g", &[grey]) => self.state.fill = grey_color(grey),
+            ("G", &[grey]) => self.state.stroke = grey_color(grey),
+            ("rg", &[r, g, b]) => self.state.fill = rgb_color(r, g, b),
+            ("RG", &[r, g, b]) => self.state.stroke = rgb_color(r, g, b),
+            ("k", &[c, m, y, k]) => self.state.fill = cmyk_color(c, m, y, k),
+            ("K", &[c, m, y, k]) => self.state.stroke = cmyk_color(c, m, y, k),
+
+            ("m", &[x, y]) => self.path.push(vec![(x, y)]),
+            ("l", &[x, y]) => self.line_to((x, y)),
+            // Curves are flattened to their end points.
+            ("c", &[_, _, _, _, x, y]) | ("v" | "y", &[_, _, x, y]) => self.line_to((x, y)),
+            ("h", _) => {
+                if let Some(first) = self
+                    .path
+                    .last()
+                    .and_then(|subpath| subpath.first().copied())
+                {
+                    self.line_to(first);
+                }
+            }
+            ("re", &[x, y, w, h]) => {
+                self.path
+                    .push(vec![(x, y), (x + w, y), (x + w, y + h), (x, y + h), (x, y)])
+            }
+            ("f" | "F" | "f*", _) => self.fill_path(canvas),
+            ("S" | "s", _) => self.stroke_path(canvas),
+            ("B" | "B*" | "b" | "b*", _) => {
+                self.fill_path(canvas);
+                self.stroke_path(canvas);
+            }
+            ("n", _) => self.path.clear(),
+
+            ("BT", _) => {
+                self.text.matrix = Matrix::IDENTITY;
+                self.text.line_matrix = Matrix::IDENTITY;
+            }
+            ("Tf", &[size]) => {
+                self.text.font_size = size;
+                let name = operation
+                    .operands
+                    .first()
+                    .and_then(|name| name.as_name().ok());
+                self.face = name.and_then(|name| self.faces.get(name));
+                let embedded = name
+                    .and_then(|name| self.fonts.get(name))
+                    .and_then(|font| font.get(b"Subtype").ok())
+                    .and_then(|subtype| subtype.as_name().ok())
+                    == Some(b"Type0".as_slice());
+                self.text.bytes_per_char = if embedded { 2 } else { 1 };
+            }
+            ("Td", &[tx, ty]) => {
+                self.text.line_matrix =
+                    Matrix([1.0, 0.0, 0.0, 1.0, tx, ty]).then(self.text.line_matrix);
+                self.text.matrix = self.text.line_matrix;
+            }
+            ("Tm", &[a, b, c, d, e, f]) => {
+                self.text.line_matrix = Matrix([a, b, c, d, e, f]);
+                self.text.matrix = self.text.line_matrix;
+            }
+            ("Tj" | "'" | "\"", _) => {
+                if let Some(Object::String(bytes, _)) = operation.operands.last() {
+                    self.show_text(bytes, canvas);
+                }
+            }
+            ("TJ", _) => {
+                if let Some(Ok(items)) = operation.operands.first().map(Object::as_array) {
+                    for item in items {
+                        match item {
+                            Object::String(bytes, _) => self.show_text(bytes, canvas),
+                            // Adjustments are in thousandths of an em, backwards.
+                            item => {
+                                if let Ok(adjustment) = item.as_float() {
+                                    self.advance(-adjustment / 1000.0 * self.text.font_size);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            ("Do", _) => {
+                if let Some(Ok(name)) = operation.operands.first().map(Object::as_name) {
+                    self.draw_image(name, canvas);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn line_to(&mut self, point: (f32, f32)) {
+        match self.path.last_mut() {
+            Some(subpath) => subpath.push(point),
+            None => self.path.push(vec![point]),
+        }
+    }
+
+    fn to_device(&self, point: (f32, f32)) -> (f32, f32) {
+        self.state.ctm.then(self.device).apply(point)
+    }
+
+    fn fill_path(&mut self, canvas: &mut Canvas) {
+        let polygons: Vec<Vec<(f32, f32)>> = self
+            .path
+            .iter()
+            .map(|subpath| subpath.iter().map(|point| self.to_device(*point)).collect())
+            .collect();
+        canvas.fill(&polygons, self.state.fill);
+        self.path.clear();
+    }
+
+    /// Each segment becomes a quad at least a canvas pixel wide, so hairlines
+    /// still show at thumbnail size.
+    fn stroke_path(&mut self, canvas: &mut Canvas) {
+        let to_device = self.state.ctm.then(self.device);
+        let half_width = (self.state.line_width * to_device.scale()).max(1.0) / 2.0;
+        for subpath in &self.path {
+            for segment in subpath.windows(2) {
+                let (x0, y0) = to_device.apply(segment[0]);
+                let (x1, y1) = to_device.apply(segment[1]);
+                let length = ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt();
+                if length < f32::EPSILON {
+                    continue;
+                }
+                let (nx, ny) = (
+                    -(y1 - y0) / length * half_width,
+                    (x1 - x0) / length * half_width,
+                );
+                let quad = vec![
+                    (x0 + nx, y0 + ny),
+                    (x1 + nx, y1 + ny),
+                    (x1 - nx, y1 - ny),
+                    (x0 - nx, y0 - ny),
+                ];
+                canvas.fill(&[quad], self.state.stroke);
+            }
+        }
+        self.path.clear();
+    }
+
+    /// Draws the glyphs of a text string from the current text position and
+    /// moves the position past each one.
+    fn show_text(&mut self, bytes: &[u8], canvas: &mut Canvas) {
+        let size = self.text.font_size;
+        if self.text.bytes_per_char == 2 {
+            for code in bytes.chunks_exact(2) {
+                // Embedded fonts are written with glyph ids as character codes.
+                let glyph = GlyphId(u16::from_be_bytes([code[0], code[1]]));
+                let drawn = self.face.and_then(|face| {
+                    let scale = size / f32::from(face.units_per_em());
+                    let mut outline = Outline::new(self.glyph_to_device(scale));
+                    face.outline_glyph(glyph, &mut outline);
+                    canvas.fill(&outline.polygons, self.state.fill);
+                    Some(f32::from(face.glyph_hor_advance(glyph)?) * scale)
+                });
+                match drawn {
+                    Some(advance) => self.advance(advance),
+                    None => self.draw_missing_glyph(canvas),
+                }
+            }
+        } else {
+            for &byte in bytes {
+                match byte.checked_sub(b' ').map(usize::from) {
+                    Some(index) if index < BITMAP_FONT.len() => {
+                        let advance = f32::from(HELVETICA_WIDTHS[index]) / 1000.0 * size;
+                        self.draw_bitmap_glyph(&BITMAP_FONT[index], advance, canvas);
+                        self.advance(advance);
+                    }
+                    _ => self.draw_missing_glyph(canvas),
+                }
+            }
+        }
+    }
+
+    /// Glyph space, at `scale` text units per unit, to canvas pixels at the
+    /// current text position.
+    fn glyph_to_device(&self, scale: f32) -> Matrix {
+        Matrix([scale, 0.0, 0.0, scale, 0.0, 0.0])
+            .then(self.text.matrix)
+            .then(self.state.ctm)
+            .then(self.device)
+    }
+
+    /// A bitmap glyph centred in its advance, one rectangle per set bit,
+    /// narrowed to fit characters such as "i" between their neighbours.
+    fn draw_bitmap_glyph(&self, columns: &[u8; 5], advance: f32, canvas: &mut Canvas) {
+        let height = self.text.font_size * BITMAP_PIXEL_EM;
+        let width = height.min(advance / 6.0);
+        let left = (advance - width * 5.0) / 2.0;
+        let to_device = self.glyph_to_device(1.0);
+        let mut rectangles = Vec::new();
+        for (column, bits) in columns.iter().enumerate() {
+            for row in (0..8).filter(|row| bits >> row & 1 == 1) {
+                let (x, y) = (left + column as f32 * width, (6 - row) as f32 * height);
+                rectangles.push(
+                    [
+                        (x, y),
+                        (x + width, y),
+                        (x + width, y + height),
+                        (x, y + height),
+                    ]
+                    .iter()
+                    .map(|point| to_device.apply(*point))
+                    .collect(),
+                );
+            }
+        }
+        canvas.fill(&rectangles, self.state.fill);
+    }
+
+    /// A light bar for a character no font can draw.
+    fn draw_missing_glyph(&mut self, canvas: &mut Canvas) {
+        let size = self.text.font_size;
+        let advance = size * GLYPH_ADVANCE_EM;
+        let to_device = self.glyph_to_device(1.0);
+        let bar = [
+            (0.0, 0.0),
+            (advance * 0.9, 0.0),
+            (advance * 0.9, size * TEXT_BAR_EM),
+            (0.0, size * TEXT_BAR_EM),
+        ]
+        .iter()
+        .map(|point| to_device.apply(*point))
+        .collect();
+        canvas.fill(&[bar], lighten(self.state.fill, TEXT_LIGHTEN));
+        self.advance(advance);
+    }
+
+    /// Moves the text position `distance` along the baseline.
+    fn advance(&mut self, distance: f32) {
+        self.text.matrix = Matrix([1.0, 0.0, 0.0, 1.0, distance, 0.0]).then(self.text.matrix);
+    }
+
+    /// Images fill the unit square mapped through the CTM.
+    fn draw_image(&self, name: &[u8], canvas: &mut Canvas) {
+        let Some(stream) = self.image_stream(name) else {
+            return;
+        };
+        let to_device = self.state.ctm.then(self.device);
+        let Some(from_device) = to_device.invert() else {
+            return;
+        };
+        let corners: Vec<(f32, f32)> = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+            .iter()
+            .map(|point| to_device.apply(*point))
+            .collect();
+
+        match decode_image(stream) {
+            Some(image) => {
+                let (min_x, max_x, min_y, max_y) = bounds(&corners);
+                for y in min_y..max_y.min(canvas.height) {
+                    for x in min_x..max_x.min(canvas.width) {
+                        let (u, v) = from_device.apply((x as f32 + 0.5, y as f32 + 0.5));
+                        if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+                            continue;
+                        }
+                        // Image rows run top to bottom; v runs bottom to top.
+                        let column = (u * image.width as f32) as usize;
+                        let row = ((1.0 - v) * image.height as f32) as usize;
+                        let index =
+                            row.min(image.height - 1) * image.width + column.min(image.width - 1);
+                        canvas.set(x, y, image.pixels[index]);
+                    }
+                }
+            }
+            None => canvas.fill(&[corners], IMAGE_PLACEHOLDER),
+        }
+    }
+
+    fn image_stream(&self, name: &[u8]) -> Option<&Stream> {
+        let (resources, resource_ids) = self.doc.get_page_resources(self.page_id);
+        resources
+            .into_iter()
+            .chain(
+                resource_ids
+                    .into_iter()
+                    .filter_map(|id| self.doc.get_dictionary(id).ok()),
+            )
+            .filter_map(|resources| resources.get_deref(b"XObject", self.doc).ok())
+            .filter_map(|xobjects| xobjects.as_dict().ok())
+            .filter_map(|xobjects| xobjects.get_deref(name, self.doc).ok())
+            .find_map(|object| object.as_stream().ok())
+    }
+}
+
+/// A glyph outline as polygons in canvas pixels, with its curves flattened.
+struct Outline {
+    to_device: Matrix,
+    polygons: Vec<Vec<(f32, f32)>>,
+    /// The last point, in glyph space.
+    current: (f32, f32),
+}
+
+impl Outline {
+    fn new(to_device: Matrix) -> Self {
+        Self {
+            to_device,
+            polygons: Vec::new(),
+            current: (0.0, 0.0),
+        }
+    }
+
+    fn push(&mut self, point: (f32, f32)) {
+        self.current = point;
+        let point = self.to_device.apply(point);
+        match self.polygons.last_mut() {
+            Some(polygon) => polygon.push(point),
+            None => self.polygons.push(vec![point]),
+        }
+    }
+}
+
+impl OutlineBuilder for Outline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.polygons.push(Vec::new());
+        self.push((x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.push((x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (x0, y0) = self.current;
+        for step in 1..=CURVE_SEGMENTS {
+            let t = step as f32 / CURVE_SEGMENTS as f32;
+            let u = 1.0 - t;
+            self.push((
+                u * u * x0 + 2.0 * u * t * x1 + t * t * x,
+                u * u * y0 + 2.0 * u * t * y1 + t * t * y,
+            ));
+        }
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (x0, y0) = self.current;
+        for step in 1..=CURVE_SEGMENTS {
+            let t = step as f32 / CURVE_SEGMENTS as f32;
+            let u = 1.0 - t;
+            let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+            self.push((
+                a * x0 + b * x1 + c * x2 + d * x,
+                a * y0 + b * y1 + c * y2 + d * y,
+            ));
+        }
+    }
+
+    fn close(&mut self) {}
+}
+
+struct DecodedImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+/// 8-bit RGB or greyscale images, uncompressed or Flate-compressed.
+fn decode_image(stream: &Stream) -> Option<DecodedImage> {
+    let dict = &stream.dict;
+    let width = dict.get(b"Width").ok()?.as_i64().ok()? as usize;
+    let height = dict.get(b"Height").ok()?.as_i64().ok()? as usize;
+    if width == 0 || height == 0 || dict.get(b"BitsPerComponent").ok()?.as_i64().ok()? != 8 {
+        return None;
+    }
+    let channels = match dict.get(b"ColorSpace").ok()?.as_name().ok()? {
+        b"DeviceRGB" => 3,
+        b"DeviceGray" => 1,
+        _ => return None,
+    };
+    let data = match dict.get(b"Filter").ok() {
+        None => stream.content.clone(),
+        Some(filter) if filter.as_name().ok()? == b"FlateDecode" => {
+            let mut data = Vec::new();
+            flate2::read::ZlibDecoder::new(stream.content.as_slice())
+                .read_to_end(&mut data)
+                .ok()?;
+            data
+        }
+        Some(_) => return None,
+    };
+    if data.len() < width * height * channels {
+        return None;
+    }
+    let pixels = data
+        .chunks_exact(channels)
+        .take(width * height)
+        .map(|pixel| match pixel {
+            [r, g, b] => [*r, *g, *b],
+            _ => [pixel[0]; 3],
+        })
+        .collect();
+    Some(DecodedImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// An RGB pixel buffer, white to start with.
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[255, 255, 255]; width * height],
+        }
+    }
+
+    fn set(&mut self, x: usize, y: usize, color: [u8; 3]) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+
+    /// Fills the polygons with the even-odd rule, sampling pixel centres.
+    fn fill(&mut self, polygons: &[Vec<(f32, f32)>], color: [u8; 3]) {
+        let points: Vec<(f32, f32)> = polygons.iter().flatten().copied().collect();
+        if points.is_empty() {
+            return;
+        }
+        let (_, _, min_y, max_y) = bounds(&points);
+        let mut crossings = Vec::new();
+        for y in min_y..max_y.min(self.height) {
+            let sample_y = y as f32 + 0.5;
+            crossings.clear();
+            for polygon in polygons {
+                for (index, &(x0, y0)) in polygon.iter().enumerate() {
+                    let (x1, y1) = polygon[(index + 1) % polygon.len()];
+                    if (y0 <= sample_y) != (y1 <= sample_y) {
+                        crossings.push(x0 + (sample_y - y0) / (y1 - y0) * (x1 - x0));
+                    }
+                }
+            }
+            crossings.sort_by(f32::total_cmp);
+            for span in crossings.chunks_exact(2) {
+                let start = (span[0] - 0.5).ceil().max(0.0) as usize;
+                let end = ((span[1] - 0.5).floor() + 1.0).clamp(0.0, self.width as f32) as usize;
+                for x in start..end {
+                    self.pixels[y * self.width + x] = color;
+                }
+            }
+        }
+    }
+
+    /// Averages each `factor` x `factor` block into one pixel.
+    fn downsample(&self, factor: usize) -> Vec<[u8; 3]> {
+        let width = self.width / factor;
+        let height = (self.height / factor).max(1);
+        let mut output = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0u32; 3];
+                let mut count = 0;
+                for sy in y * factor..((y + 1) * factor).min(self.height) {
+                    for sx in x * factor..(x + 1) * factor {
+                        let pixel = self.pixels[sy * self.width + sx];
+                        for (total, channel) in sum.iter_mut().zip(pixel) {
+                            *total += u32::from(channel);
+                        }
+                        count += 1;
+                    }
+                }
+                let count = count.max(1);
+                output.push(sum.map(|total| (total / count) as u8));
+            }
+        }
+        output
+    }
+}
+
+/// Whole-pixel bounds `(min_x, max_x, min_y, max_y)` of the points, clamped
+/// at zero; the maxima are exclusive.
+fn bounds(points: &[(f32, f32)]) -> (usize, usize, usize, usize) {
+    let (mut min_x, mut max_x, mut min_y, mut max_y) = (f32::MAX, f32::MIN, f32::MAX, f32::MIN);
+    for &(x, y) in points {
+        min_x = min_x.min(x);
+        max_x = max_x.max(x);
+        min_y = min_y.min(y);
+        max_y = max_y.max(y);
+    }
+    (
+        min_x.floor().max(0.0) as usize,
+        max_x.ceil().max(0.0) as usize,
+        min_y.floor().max(0.0) as usize,
+        max_y.ceil().max(0.0) as usize,
+    )
+}
+
+fn channel(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn grey_color(grey: f32) -> [u8; 3] {
+    [channel(grey); 3]
+}
+
+fn rgb_color(r: f32, g: f32, b: f32) -> [u8; 3] {
+    [channel(r), channel(g), channel(b)]
+}
+
+fn cmyk_color(c: f32, m: f32, y: f32, k: f32) -> [u8; 3] {
+    [
+        channel((1.0 - c) * (1.0 - k)),
+        channel((1.0 - m) * (1.0 - k)),
+        channel((1.0 - y) * (1.0 - k)),
+    ]
+}
+
+fn lighten(color: [u8; 3], amount: f32) -> [u8; 3] {
+    color.map(|value| (f32::from(value) + (255.0 - f32::from(value)) * amount).round() as u8)
+}
+
+/// Encodes 8-bit RGB pixels as a PNG.
+fn encode_png(width: u32, height: u32, pixels: &[[u8; 3]]) -> Result<Vec<u8>, String> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let data: Vec<u8> = pixels.iter().flatten().copied().collect();
+    encoder
+        .write_header()
+        .and_then(|mut writer| {
+            writer.write_image_data(&data)?;
+            writer.finish()
+        })
+        .map_err(|e| format!("Failed to encode the preview: {}", e))?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use printpdf::lopdf::dictionary;
+    use std::io::Write;
+
+    /// Writes a 200 by 100 point page with the resources `resources` adds and
+    /// the given content stream, renders it `width_px` wide and decodes it.
+    fn render(
+        name: &str,
+        resources: impl FnOnce(&mut Document) -> Dictionary,
+        content: &str,
+        width_px: u32,
+    ) -> Png {
+        let mut doc = Document::with_version("1.5");
+        let resources = resources(&mut doc);
+        let pages_id = doc.new_object_id();
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.as_bytes().to_vec()));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 200.into(), 100.into()],
+            "Contents" => content_id,
+            "Resources" => resources,
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+
+        let path = std::env::temp_dir().join(format!("tt-{}-{}.pdf", name, std::process::id()));
+        doc.save(&path).unwrap();
+        let png = render_first_page(&path, width_px).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        Png {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        }
+    }
+
+    struct Png {
+        width: usize,
+        height: usize,
+        pixels: Vec<u8>,
+    }
+
+    impl Png {
+        fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+            let at = (y * self.width + x) * 3;
+            [self.pixels[at], self.pixels[at + 1], self.pixels[at + 2]]
+        }
+    }
+
+    fn helvetica(_: &mut Document) -> Dictionary {
+        dictionary! {
+            "Font" => dictionary! {
+                "F1" => dictionary! {
+                    "Type" => "Font",
+                    "Subtype" => "Type1",
+                    "BaseFont" => "Helvetica",
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn a_blank_page_is_a_white_png_at_the_requested_width() {
+        let png = render("raster-blank", |_| dictionary! {}, "", 100);
+        assert_eq!((png.width, png.height), (100, 50));
+        assert!(png.pixels.iter().all(|value| *value == 255));
+    }
+
+    #[test]
+    fn builtin_text_is_drawn_as_letters_rather_than_a_bar() {
+        let png = render(
+            "raster-text",
+            helvetica,
+            "BT /F1 60 Tf 20 30 Td (H) Tj ET",
+            200,
+        );
+        let dark = |x: usize, y: usize| png.pixel(x, y)[0] < 128;
+        assert!((0..png.height).any(|y| (0..png.width).any(|x| dark(x, y))));
+        // Up the stems of the H a row crosses ink, the gap between them and
+        // ink again, which a placeholder bar would fill in.
+        let stems = (0..png.height).any(|y| {
+            let row: Vec<bool> = (0..png.width).map(|x| dark(x, y)).collect();
+            let runs = row.windows(2).filter(|pair| pair[0] != pair[1]).count();
+            runs >= 4
+        });
+        assert!(stems);
+    }
+
+    #[test]
+    fn every_printable_character_but_the_space_has_a_glyph() {
+        assert_eq!(BITMAP_FONT[0], [0; 5]);
+        for (code, glyph) in (b'!'..=b'~').zip(&BITMAP_FONT[1..]) {
+            assert_ne!(*glyph, [0; 5], "{:?} has no glyph", code as char);
+        }
+        assert!(HELVETICA_WIDTHS.iter().all(|width| *width > 0));
+    }
+
+    #[test]
+    fn flate_compressed_images_are_drawn() {
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&[255, 0, 0, 0, 0, 255]).unwrap();
+        let image = Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 2,
+                "Height" => 1,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+                "Filter" => "FlateDecode",
+            },
+            encoder.finish().unwrap(),
+        );
+        let resources = |doc: &mut Document| {
+            dictionary! { "XObject" => dictionary! { "Im1" => doc.add_object(image) } }
+        };
+        let png = render(
+            "raster-image",
+            resources,
+            "q 200 0 0 100 0 0 cm /Im1 Do Q",
+            200,
+        );
+        assert_eq!(png.pixel(50, 50), [255, 0, 0]);
+        assert_eq!(png.pixel(150, 50), [0, 0, 255]);
+    }
+}
@@ -0,0 +1,341 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::{clients::non_empty, current_unix_timestamp, open_connection, resolve_db_path};
+
+pub const CREATE_BILL_TO_PROFILES_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS bill_to_profiles (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        address TEXT,
+        email TEXT,
+        phone TEXT,
+        updated_at INTEGER NOT NULL
+    )
+"#;
+
+pub const CREATE_BUSINESS_PROFILES_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS business_profiles (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        address TEXT,
+        email TEXT,
+        phone TEXT,
+        tax_id TEXT,
+        bank_details TEXT,
+        created_at INTEGER NOT NULL
+    )
+"#;
+
+const BILL_TO_PROFILE_COLUMNS: &str = "id, name, address, email, phone, updated_at";
+const BUSINESS_PROFILE_COLUMNS: &str =
+    "id, name, address, email, phone, tax_id, bank_details, created_at";
+
+/// A saved "Bill to" block that can be reused across invoices.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BillToProfile {
+    pub id: i64,
+    pub name: String,
+    pub address: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub updated_at: i64,
+}
+
+/// Payload for `save_bill_to_profile`; with an `id` the profile is updated.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BillToProfileInput {
+    pub id: Option<i64>,
+    pub name: String,
+    pub address: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+}
+
+/// A "Bill from" identity; invoices can be issued under any saved profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusinessProfile {
+    pub id: i64,
+    pub name: String,
+    pub address: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub tax_id: Option<String>,
+    pub bank_details: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusinessProfileInput {
+    pub name: String,
+    pub address: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub tax_id: Option<String>,
+    pub bank_details: Option<String>,
+}
+
+#[tauri::command]
+pub async fn list_bill_to_profiles(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<BillToProfile>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {BILL_TO_PROFILE_COLUMNS}
+                 FROM bill_to_profiles
+                 ORDER BY name COLLATE NOCASE ASC"
+            ))
+            .map_err(|err| err.to_string())?;
+        let profiles = stmt
+            .query_map([], map_bill_to_profile)
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        Ok(profiles)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn save_bill_to_profile(
+    app_handle: tauri::AppHandle,
+    profile: BillToProfileInput,
+) -> Result<BillToProfile, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let name = profile.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Bill to name is required".into());
+    }
+    let address = non_empty(profile.address);
+    let email = non_empty(profile.email);
+    let phone = non_empty(profile.phone);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let now = current_unix_timestamp();
+        let id = match profile.id {
+            Some(id) => {
+                let changed = conn
+                    .execute(
+                        "UPDATE bill_to_profiles
+                         SET name = ?1, address = ?2, email = ?3, phone = ?4, updated_at = ?5
+                         WHERE id = ?6",
+                        params![name, address, email, phone, now, id],
+                    )
+                    .map_err(|err| err.to_string())?;
+                if changed == 0 {
+                    return Err("Bill to profile not found".into());
+                }
+                id
+            }
+            None => {
+                conn.execute(
+                    "INSERT INTO bill_to_profiles (name, address, email, phone, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![name, address, email, phone, now],
+                )
+                .map_err(|err| err.to_string())?;
+                conn.last_insert_rowid()
+            }
+        };
+        fetch_bill_to_profile(&conn, id)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn delete_bill_to_profile(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        conn.execute("DELETE FROM bill_to_profiles WHERE id = ?1", params![id])
+            .map_err(|err| err.to_string())?;
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn list_business_profiles(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<BusinessProfile>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {BUSINESS_PROFILE_COLUMNS}
+                 FROM business_profiles
+                 ORDER BY name COLLATE NOCASE ASC"
+            ))
+            .map_err(|err| err.to_string())?;
+        let profiles = stmt
+            .query_map([], map_business_profile)
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        Ok(profiles)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn create_business_profile(
+    app_handle: tauri::AppHandle,
+    profile: BusinessProfileInput,
+) -> Result<BusinessProfile, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let input = sanitize_business_profile(profile)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        conn.execute(
+            "INSERT INTO business_profiles
+                 (name, address, email, phone, tax_id, bank_details, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                input.name,
+                input.address,
+                input.email,
+                input.phone,
+                input.tax_id,
+                input.bank_details,
+                current_unix_timestamp()
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+        fetch_business_profile(&conn, conn.last_insert_rowid())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn update_business_profile(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    profile: BusinessProfileInput,
+) -> Result<BusinessProfile, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let input = sanitize_business_profile(profile)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let changed = conn
+            .execute(
+                "UPDATE business_profiles
+                 SET name = ?1, address = ?2, email = ?3, phone = ?4,
+                     tax_id = ?5, bank_details = ?6
+                 WHERE id = ?7",
+                params![
+                    input.name,
+                    input.address,
+                    input.email,
+                    input.phone,
+                    input.tax_id,
+                    input.bank_details,
+                    id
+                ],
+            )
+            .map_err(|err| err.to_string())?;
+        if changed == 0 {
+            return Err("Business profile not found".into());
+        }
+        fetch_business_profile(&conn, id)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Deletes the profile. Invoices store a snapshot of the Bill from block, so
+/// existing invoices are unaffected.
+#[tauri::command]
+pub async fn delete_business_profile(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        conn.execute("DELETE FROM business_profiles WHERE id = ?1", params![id])
+            .map_err(|err| err.to_string())?;
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub fn fetch_business_profile(conn: &Connection, id: i64) -> Result<BusinessProfile, String> {
+    conn.query_row(
+        &format!("SELECT {BUSINESS_PROFILE_COLUMNS} FROM business_profiles WHERE id = ?1"),
+        params![id],
+        map_business_profile,
+    )
+    .map_err(|err| match err {
+        rusqlite::Error::QueryReturnedNoRows => "Business profile not found".to_string(),
+        other => other.to_string(),
+    })
+}
+
+pub fn fetch_bill_to_profile(conn: &Connection, id: i64) -> Result<BillToProfile, String> {
+    conn.query_row(
+        &format!("SELECT {BILL_TO_PROFILE_COLUMNS} FROM bill_to_profiles WHERE id = ?1"),
+        params![id],
+        map_bill_to_profile,
+    )
+    .map_err(|err| match err {
+        rusqlite::Error::QueryReturnedNoRows => "Bill to profile not found".to_string(),
+        other => other.to_string(),
+    })
+}
+
+fn map_bill_to_profile(row: &rusqlite::Row) -> rusqlite::Result<BillToProfile> {
+    Ok(BillToProfile {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        address: row.get(2)?,
+        email: row.get(3)?,
+        phone: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+fn map_business_profile(row: &rusqlite::Row) -> rusqlite::Result<BusinessProfile> {
+    Ok(BusinessProfile {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        address: row.get(2)?,
+        email: row.get(3)?,
+        phone: row.get(4)?,
+        tax_id: row.get(5)?,
+        bank_details: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+fn sanitize_business_profile(input: BusinessProfileInput) -> Result<BusinessProfileInput, String> {
+    let name = input.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Business name is required".into());
+    }
+    Ok(BusinessProfileInput {
+        name,
+        address: non_empty(input.address),
+        email: non_empty(input.email),
+        phone: non_empty(input.phone),
+        tax_id: non_empty(input.tax_id),
+        bank_details: non_empty(input.bank_details),
+    })
+}
@@ -0,0 +1,751 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit::{self, AuditAction, EntrySource},
+    current_unix_timestamp, ensure_column, fetch_time_entry, map_time_entry, open_connection,
+    resolve_db_path, TIME_ENTRY_COLUMNS,
+};
+
+pub const CREATE_PROJECTS_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS projects (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL UNIQUE,
+        client_id INTEGER REFERENCES clients(id) ON DELETE SET NULL,
+        created_at INTEGER NOT NULL,
+        archived INTEGER NOT NULL DEFAULT 0,
+        budget_hours REAL,
+        budget_amount REAL,
+        budget_alert_level INTEGER NOT NULL DEFAULT 0,
+        color TEXT,
+        icon TEXT,
+        billing_mode TEXT NOT NULL DEFAULT 'hourly',
+        fixed_fee REAL,
+        rounding_minutes INTEGER,
+        rounding_direction TEXT NOT NULL DEFAULT 'nearest',
+        currency TEXT,
+        estimated_hours REAL,
+        invoice_terms TEXT,
+        fixed_fee_invoice_id INTEGER
+    )
+"#;
+
+const PROJECT_SELECT_SQL: &str = "SELECT p.id, p.name, p.client_id, c.name, p.archived,
+            p.budget_hours, p.budget_amount, p.color, p.icon,
+            p.billing_mode, p.fixed_fee, p.rounding_minutes, p.rounding_direction,
+            p.currency, COALESCE(p.currency, c.currency, 'USD'), p.estimated_hours,
+            p.invoice_terms, p.fixed_fee_invoice_id
+     FROM projects p
+     LEFT JOIN clients c ON c.id = p.client_id";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub id: i64,
+    pub name: String,
+    pub client_id: Option<i64>,
+    pub client_name: Option<String>,
+    pub archived: bool,
+    pub budget_hours: Option<f64>,
+    pub budget_amount: Option<f64>,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub billing_mode: BillingMode,
+    pub fixed_fee: Option<f64>,
+    pub rounding_minutes: Option<i64>,
+    pub rounding_direction: RoundingDirection,
+    /// Currency set on the project itself, if any.
+    pub currency: Option<String>,
+    /// Currency the project bills in: its own, else its client's, else USD.
+    pub effective_currency: String,
+    pub estimated_hours: Option<f64>,
+    pub invoice_terms: Option<String>,
+    /// Invoice the flat fee was billed on; later invoices list the
+    /// project's hours at no charge until that invoice is deleted.
+    pub fixed_fee_invoice_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BillingMode {
+    Hourly,
+    Fixed,
+}
+
+impl BillingMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            BillingMode::Hourly => "hourly",
+            BillingMode::Fixed => "fixed",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "fixed" => BillingMode::Fixed,
+            _ => BillingMode::Hourly,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingDirection {
+    Nearest,
+    Up,
+    Down,
+}
+
+impl RoundingDirection {
+    fn as_str(self) -> &'static str {
+        match self {
+            RoundingDirection::Nearest => "nearest",
+            RoundingDirection::Up => "up",
+            RoundingDirection::Down => "down",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "up" => RoundingDirection::Up,
+            "down" => RoundingDirection::Down,
+            _ => RoundingDirection::Nearest,
+        }
+    }
+}
+
+/// A rounding increment of more than a working day is almost certainly a typo.
+pub const MAX_ROUNDING_MINUTES: i64 = 8 * 60;
+
+/// Longest icon accepted, in chars; enough for ZWJ emoji sequences.
+const MAX_ICON_CHARS: usize = 8;
+
+const DEFAULT_SUGGESTION_LIMIT: usize = 8;
+const MAX_SUGGESTION_LIMIT: usize = 50;
+
+/// Lists projects, leaving out archived ones unless `include_archived` is set.
+#[tauri::command]
+pub async fn list_projects(
+    app_handle: tauri::AppHandle,
+    include_archived: Option<bool>,
+) -> Result<Vec<Project>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let include_archived = include_archived.unwrap_or(false);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        backfill_projects(&conn)?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "{PROJECT_SELECT_SQL}
+                 WHERE ?1 OR p.archived = 0
+                 ORDER BY p.name COLLATE NOCASE ASC"
+            ))
+            .map_err(|err| err.to_string())?;
+        let projects = stmt
+            .query_map(params![include_archived], map_project)
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        Ok(projects)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Suggests tracked project names starting with `prefix` (case-insensitive),
+/// most relevant first. Relevance is how often a project was used, decayed by
+/// how many weeks ago it was last used. Archived projects are never suggested.
+#[tauri::command]
+pub async fn suggest_projects(
+    app_handle: tauri::AppHandle,
+    prefix: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<String>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let prefix = prefix.unwrap_or_default().trim().to_string();
+    let limit = limit
+        .unwrap_or(DEFAULT_SUGGESTION_LIMIT)
+        .clamp(1, MAX_SUGGESTION_LIMIT) as i64;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let pattern = format!("{}%", escape_like(&prefix));
+        let mut stmt = conn
+            .prepare(
+                "SELECT e.project_name
+                 FROM time_entries e
+                 LEFT JOIN projects p ON p.name = e.project_name
+                 WHERE e.project_name LIKE ?1 ESCAPE '\\'
+                   AND e.entry_type = 'work'
+                   AND COALESCE(p.archived, 0) = 0
+                 GROUP BY e.project_name
+                 ORDER BY COUNT(*) / (1.0 + MAX(?2 - MAX(e.start_time), 0) / 604800.0) DESC,
+                          e.project_name COLLATE NOCASE ASC
+                 LIMIT ?3",
+            )
+            .map_err(|err| err.to_string())?;
+        let names = stmt
+            .query_map(params![pattern, current_unix_timestamp(), limit], |row| {
+                row.get(0)
+            })
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|err| err.to_string())?;
+        Ok(names)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        if matches!(ch, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// Assigns (or with `None`, detaches) the client billed for a project.
+#[tauri::command]
+pub async fn set_project_client(
+    app_handle: tauri::AppHandle,
+    project_name: String,
+    client_id: Option<i64>,
+) -> Result<Project, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let name = crate::sanitize_project_name(project_name);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        if let Some(id) = client_id {
+            crate::clients::fetch_client(&conn, id)?;
+        }
+        let project_id = ensure_project(&conn, &name)?;
+        conn.execute(
+            "UPDATE projects SET client_id = ?1 WHERE id = ?2",
+            params![client_id, project_id],
+        )
+        .map_err(|err| err.to_string())?;
+        fetch_project(&conn, &name)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Moves every entry from `from` onto `into` in one transaction, recording
+/// each in the audit log, and returns the number of entries rewritten. When `into` is a new name this is a
+/// rename and the project keeps its settings; otherwise the target's settings
+/// win and the `from` project is removed.
+#[tauri::command]
+pub async fn merge_projects(
+    app_handle: tauri::AppHandle,
+    from: String,
+    into: String,
+) -> Result<usize, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let from = crate::sanitize_project_name(from);
+    let into = crate::sanitize_project_name(into);
+    if from == into {
+        return Err("Choose two different projects to merge".into());
+    }
+
+    let (changed, project) = tauri::async_runtime::spawn_blocking({
+        let from = from.clone();
+        move || {
+            let mut conn = open_connection(db_path)?;
+            backfill_projects(&conn)?;
+            let tx = conn.transaction().map_err(|err| err.to_string())?;
+            let moved = tx
+                .prepare(&format!(
+                    "SELECT {TIME_ENTRY_COLUMNS} FROM time_entries WHERE project_name = ?1"
+                ))
+                .and_then(|mut stmt| {
+                    stmt.query_map(params![from], map_time_entry)?
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .map_err(|err| err.to_string())?;
+            let changed = tx
+                .execute(
+                    "UPDATE time_entries SET project_name = ?1 WHERE project_name = ?2",
+                    params![into, from],
+                )
+                .map_err(|err| err.to_string())?;
+            for old in &moved {
+                let new = fetch_time_entry(&tx, old.id)?;
+                audit::record_change(
+                    &tx,
+                    AuditAction::Update,
+                    EntrySource::Manual,
+                    Some(old),
+                    Some(&new),
+                )?;
+            }
+            if changed == 0 && fetch_project(&tx, &from).is_err() {
+                return Err(format!("Project not found: {}", from));
+            }
+            tx.execute(
+                "UPDATE active_timers SET project_name = ?1 WHERE project_name = ?2",
+                params![into, from],
+            )
+            .map_err(|err| err.to_string())?;
+            // Rules and templates left on `from` would bring the project back
+            // the next time they create an entry.
+            for table in ["recurring_entries", "entry_templates"] {
+                tx.execute(
+                    &format!("UPDATE {table} SET project_name = ?1 WHERE project_name = ?2"),
+                    params![into, from],
+                )
+                .map_err(|err| err.to_string())?;
+            }
+            let target_exists = fetch_project(&tx, &into).is_ok();
+            if target_exists {
+                tx.execute("DELETE FROM projects WHERE name = ?1", params![from])
+                    .map_err(|err| err.to_string())?;
+            } else {
+                tx.execute(
+                    "UPDATE projects SET name = ?1 WHERE name = ?2",
+                    params![into, from],
+                )
+                .map_err(|err| err.to_string())?;
+            }
+            let project = fetch_project(&tx, &into)?;
+            tx.commit().map_err(|err| err.to_string())?;
+            Ok::<_, String>((changed, project))
+        }
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    crate::retarget_active_timer(&app_handle, &from, &project);
+    Ok(changed)
+}
+
+#[tauri::command]
+pub async fn archive_project(
+    app_handle: tauri::AppHandle,
+    project_name: String,
+) -> Result<Project, String> {
+    set_project_archived(&app_handle, project_name, true).await
+}
+
+#[tauri::command]
+pub async fn unarchive_project(
+    app_handle: tauri::AppHandle,
+    project_name: String,
+) -> Result<Project, String> {
+    set_project_archived(&app_handle, project_name, false).await
+}
+
+async fn set_project_archived(
+    app_handle: &tauri::AppHandle,
+    project_name: String,
+    archived: bool,
+) -> Result<Project, String> {
+    let db_path = resolve_db_path(app_handle)?;
+    let name = crate::sanitize_project_name(project_name);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        backfill_projects(&conn)?;
+        let changed = conn
+            .execute(
+                "UPDATE projects SET archived = ?1 WHERE name = ?2",
+                params![archived, name],
+            )
+            .map_err(|err| err.to_string())?;
+        if changed == 0 {
+            return Err(format!("Project not found: {}", name));
+        }
+        fetch_project(&conn, &name)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Sets the display color (`#RRGGBB`) and icon (usually an emoji) for a
+/// project. Passing `None` clears the value.
+#[tauri::command]
+pub async fn set_project_appearance(
+    app_handle: tauri::AppHandle,
+    project_name: String,
+    color: Option<String>,
+    icon: Option<String>,
+) -> Result<Project, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let name = crate::sanitize_project_name(project_name);
+    let color = crate::clients::non_empty(color)
+        .map(|color| sanitize_color(&color))
+        .transpose()?;
+    let icon = crate::clients::non_empty(icon);
+    if icon
+        .as_ref()
+        .is_some_and(|icon| icon.chars().count() > MAX_ICON_CHARS)
+    {
+        return Err("Project icon must be a single emoji or short symbol".into());
+    }
+
+    let project = tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let project_id = ensure_project(&conn, &name)?;
+        conn.execute(
+            "UPDATE projects SET color = ?1, icon = ?2 WHERE id = ?3",
+            params![color, icon, project_id],
+        )
+        .map_err(|err| err.to_string())?;
+        fetch_project(&conn, &name)
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    // A running timer for this project shows the icon in the tray label.
+    crate::refresh_active_timer_appearance(&app_handle, &project);
+    Ok(project)
+}
+
+/// Switches a project between hourly billing and a flat fee. Fixed-fee
+/// projects are invoiced as a single line regardless of tracked hours.
+#[tauri::command]
+pub async fn set_project_billing(
+    app_handle: tauri::AppHandle,
+    project_name: String,
+    billing_mode: BillingMode,
+    fixed_fee: Option<f64>,
+) -> Result<Project, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let name = crate::sanitize_project_name(project_name);
+    let fixed_fee = match billing_mode {
+        BillingMode::Fixed => match fixed_fee {
+            Some(fee) if fee.is_finite() && fee > 0.0 => Some(fee),
+            _ => return Err("Fixed-fee projects need a fee greater than zero".into()),
+        },
+        BillingMode::Hourly => None,
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let project_id = ensure_project(&conn, &name)?;
+        conn.execute(
+            "UPDATE projects SET billing_mode = ?1, fixed_fee = ?2 WHERE id = ?3",
+            params![billing_mode.as_str(), fixed_fee, project_id],
+        )
+        .map_err(|err| err.to_string())?;
+        fetch_project(&conn, &name)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Sets how timer durations for a project are rounded when the timer stops.
+/// `None` (or zero) minutes turns rounding off.
+#[tauri::command]
+pub async fn set_project_rounding(
+    app_handle: tauri::AppHandle,
+    project_name: String,
+    rounding_minutes: Option<i64>,
+    rounding_direction: Option<RoundingDirection>,
+) -> Result<Project, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let name = crate::sanitize_project_name(project_name);
+    let rounding_minutes = match rounding_minutes {
+        Some(minutes) if !(0..=MAX_ROUNDING_MINUTES).contains(&minutes) => {
+            return Err(format!(
+                "Rounding must be between 0 and {} minutes",
+                MAX_ROUNDING_MINUTES
+            ))
+        }
+        Some(0) | None => None,
+        other => other,
+    };
+    let direction = rounding_direction.unwrap_or(RoundingDirection::Nearest);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let project_id = ensure_project(&conn, &name)?;
+        conn.execute(
+            "UPDATE projects SET rounding_minutes = ?1, rounding_direction = ?2 WHERE id = ?3",
+            params![rounding_minutes, direction.as_str(), project_id],
+        )
+        .map_err(|err| err.to_string())?;
+        fetch_project(&conn, &name)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Overrides the currency a project bills in. `None` falls back to the
+/// client's currency.
+#[tauri::command]
+pub async fn set_project_currency(
+    app_handle: tauri::AppHandle,
+    project_name: String,
+    currency: Option<String>,
+) -> Result<Project, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let name = crate::sanitize_project_name(project_name);
+    let currency = crate::clients::non_empty(currency)
+        .map(|code| crate::clients::sanitize_currency_code(&code))
+        .transpose()?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let project_id = ensure_project(&conn, &name)?;
+        conn.execute(
+            "UPDATE projects SET currency = ?1 WHERE id = ?2",
+            params![currency, project_id],
+        )
+        .map_err(|err| err.to_string())?;
+        fetch_project(&conn, &name)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Records how many hours a project was estimated to take; `None` clears it.
+#[tauri::command]
+pub async fn set_project_estimate(
+    app_handle: tauri::AppHandle,
+    project_name: String,
+    estimated_hours: Option<f64>,
+) -> Result<Project, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let name = crate::sanitize_project_name(project_name);
+    let estimated_hours = match estimated_hours {
+        Some(hours) if !hours.is_finite() || hours <= 0.0 => {
+            return Err("Estimate must be a positive number of hours".into())
+        }
+        other => other,
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let project_id = ensure_project(&conn, &name)?;
+        conn.execute(
+            "UPDATE projects SET estimated_hours = ?1 WHERE id = ?2",
+            params![estimated_hours, project_id],
+        )
+        .map_err(|err| err.to_string())?;
+        fetch_project(&conn, &name)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Sets terms text (payment terms, PO number) printed on invoices that
+/// include this project. `None` clears it.
+#[tauri::command]
+pub async fn set_project_invoice_terms(
+    app_handle: tauri::AppHandle,
+    project_name: String,
+    invoice_terms: Option<String>,
+) -> Result<Project, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let name = crate::sanitize_project_name(project_name);
+    let invoice_terms = crate::clients::non_empty(invoice_terms);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let project_id = ensure_project(&conn, &name)?;
+        conn.execute(
+            "UPDATE projects SET invoice_terms = ?1 WHERE id = ?2",
+            params![invoice_terms, project_id],
+        )
+        .map_err(|err| err.to_string())?;
+        fetch_project(&conn, &name)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Returns the project's rounding increment (minutes) and direction, or
+/// `None` when the project does not round.
+pub fn rounding_rule_for(
+    conn: &Connection,
+    name: &str,
+) -> Result<Option<(i64, RoundingDirection)>, String> {
+    conn.query_row(
+        "SELECT rounding_minutes, rounding_direction FROM projects WHERE name = ?1",
+        params![name],
+        |row| Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, String>(1)?)),
+    )
+    .optional()
+    .map(|rule| match rule {
+        Some((Some(minutes), direction)) if minutes > 0 => {
+            Some((minutes, RoundingDirection::parse(&direction)))
+        }
+        _ => None,
+    })
+    .map_err(|err| err.to_string())
+}
+
+/// Rounds `seconds` to a multiple of `increment_minutes`. Work is never
+/// rounded away entirely: the result is at least one increment.
+pub fn round_duration(seconds: i64, increment_minutes: i64, direction: RoundingDirection) -> i64 {
+    let increment = increment_minutes * 60;
+    if increment <= 0 {
+        return seconds;
+    }
+    let steps = match direction {
+        RoundingDirection::Up => (seconds + increment - 1) / increment,
+        RoundingDirection::Down => seconds / increment,
+        RoundingDirection::Nearest => (seconds + increment / 2) / increment,
+    };
+    steps.max(1) * increment
+}
+
+/// Returns the flat fee for a fixed-fee project, or `None` when it bills hourly.
+pub fn fixed_fee_for(conn: &Connection, name: &str) -> Result<Option<f64>, String> {
+    conn.query_row(
+        "SELECT fixed_fee FROM projects WHERE name = ?1 AND billing_mode = 'fixed'",
+        params![name],
+        |row| row.get::<_, Option<f64>>(0),
+    )
+    .optional()
+    .map(Option::flatten)
+    .map_err(|err| err.to_string())
+}
+
+/// Returns the flat fee for a fixed-fee project and whether an invoice other
+/// than `invoice_id` has billed it already, or `None` when it bills hourly.
+pub fn fixed_fee_billing(
+    conn: &Connection,
+    name: &str,
+    invoice_id: Option<i64>,
+) -> Result<Option<(f64, bool)>, String> {
+    conn.query_row(
+        "SELECT fixed_fee, fixed_fee_invoice_id IS NOT NULL AND fixed_fee_invoice_id IS NOT ?2
+         FROM projects WHERE name = ?1 AND billing_mode = 'fixed'",
+        params![name, invoice_id],
+        |row| {
+            let billed: bool = row.get(1)?;
+            Ok(row.get::<_, Option<f64>>(0)?.map(|fee| (fee, billed)))
+        },
+    )
+    .optional()
+    .map(Option::flatten)
+    .map_err(|err| err.to_string())
+}
+
+/// Returns the `(color, icon)` pair for a project, or `(None, None)` if the
+/// project has no row yet.
+pub fn project_appearance(
+    conn: &Connection,
+    name: &str,
+) -> Result<(Option<String>, Option<String>), String> {
+    conn.query_row(
+        "SELECT color, icon FROM projects WHERE name = ?1",
+        params![name],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+    .map(|appearance| appearance.unwrap_or((None, None)))
+    .map_err(|err| err.to_string())
+}
+
+/// Currency the project bills in: its own, then its client's, then USD.
+pub fn project_currency(conn: &Connection, name: &str) -> Result<String, String> {
+    conn.query_row(
+        "SELECT COALESCE(p.currency, c.currency, 'USD')
+         FROM projects p
+         LEFT JOIN clients c ON c.id = p.client_id
+         WHERE p.name = ?1",
+        params![name],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(|currency| currency.unwrap_or_else(|| "USD".to_string()))
+    .map_err(|err| err.to_string())
+}
+
+fn sanitize_color(color: &str) -> Result<String, String> {
+    let hex = color.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return Err("Project color must be a hex value like #4F8BFF".into());
+    }
+    Ok(format!("#{}", hex.to_ascii_uppercase()))
+}
+
+/// Returns the id of the named project, creating a bare row when the name is new.
+pub fn ensure_project(conn: &Connection, name: &str) -> Result<i64, String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO projects (name, created_at) VALUES (?1, ?2)",
+        params![name, current_unix_timestamp()],
+    )
+    .map_err(|err| err.to_string())?;
+    conn.query_row(
+        "SELECT id FROM projects WHERE name = ?1",
+        params![name],
+        |row| row.get(0),
+    )
+    .map_err(|err| err.to_string())
+}
+
+pub fn fetch_project(conn: &Connection, name: &str) -> Result<Project, String> {
+    conn.query_row(
+        &format!("{PROJECT_SELECT_SQL} WHERE p.name = ?1"),
+        params![name],
+        map_project,
+    )
+    .map_err(|err| match err {
+        rusqlite::Error::QueryReturnedNoRows => format!("Project not found: {}", name),
+        other => other.to_string(),
+    })
+}
+
+/// Entries created before the projects table existed only carry a name, so
+/// register those names as projects. Breaks never get a project row.
+pub fn backfill_projects(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO projects (name, created_at)
+         SELECT project_name, MIN(start_time) FROM time_entries
+         WHERE entry_type = 'work'
+         GROUP BY project_name",
+        [],
+    )
+    .map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+pub fn ensure_project_columns(conn: &Connection) -> Result<(), String> {
+    ensure_column(conn, "projects", "archived", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "projects", "budget_hours", "REAL")?;
+    ensure_column(conn, "projects", "budget_amount", "REAL")?;
+    ensure_column(conn, "projects", "budget_alert_level", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "projects", "color", "TEXT")?;
+    ensure_column(conn, "projects", "icon", "TEXT")?;
+    ensure_column(conn, "projects", "billing_mode", "TEXT NOT NULL DEFAULT 'hourly'")?;
+    ensure_column(conn, "projects", "fixed_fee", "REAL")?;
+    ensure_column(conn, "projects", "rounding_minutes", "INTEGER")?;
+    ensure_column(conn, "projects", "rounding_direction", "TEXT NOT NULL DEFAULT 'nearest'")?;
+    ensure_column(conn, "projects", "currency", "TEXT")?;
+    ensure_column(conn, "projects", "estimated_hours", "REAL")?;
+    ensure_column(conn, "projects", "invoice_terms", "TEXT")?;
+    ensure_column(conn, "projects", "fixed_fee_invoice_id", "INTEGER")?;
+    Ok(())
+}
+
+fn map_project(row: &rusqlite::Row) -> rusqlite::Result<Project> {
+    Ok(Project {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        client_id: row.get(2)?,
+        client_name: row.get(3)?,
+        archived: row.get(4)?,
+        budget_hours: row.get(5)?,
+        budget_amount: row.get(6)?,
+        color: row.get(7)?,
+        icon: row.get(8)?,
+        billing_mode: BillingMode::parse(&row.get::<_, String>(9)?),
+        fixed_fee: row.get(10)?,
+        rounding_minutes: row.get(11)?,
+        rounding_direction: RoundingDirection::parse(&row.get::<_, String>(12)?),
+        currency: row.get(13)?,
+        effective_currency: row.get(14)?,
+        estimated_hours: row.get(15)?,
+        invoice_terms: row.get(16)?,
+        fixed_fee_invoice_id: row.get(17)?,
+    })
+}
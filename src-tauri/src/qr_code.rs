@@ -0,0 +1,578 @@
+/// Byte mode at error correction level M is all payment codes need, and 20
+/// versions hold well over the few hundred bytes they use.
+const MAX_VERSION: usize = 20;
+
+/// Error correction codewords per block at level M, by version.
+const ECC_CODEWORDS_PER_BLOCK: [usize; MAX_VERSION] = [
+    10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26,
+];
+
+/// Error correction blocks at level M, by version.
+const ERROR_CORRECTION_BLOCKS: [usize; MAX_VERSION] = [
+    1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16,
+];
+
+/// Format bits for level M.
+const ECC_LEVEL_M_BITS: u32 = 0;
+
+/// A QR code as a square grid of dark and light modules, without the quiet
+/// zone.
+pub struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encodes `data` in the smallest version that holds it.
+    pub fn encode(data: &[u8]) -> Result<Self, String> {
+        let grid = Grid::with_data(data)?;
+        let mask = (0..8)
+            .min_by_key(|&mask| grid.masked(mask).penalty())
+            .unwrap_or(0);
+        Ok(grid.masked(mask).into())
+    }
+
+    /// Modules per side.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+}
+
+impl From<Grid> for QrCode {
+    fn from(grid: Grid) -> Self {
+        Self {
+            size: grid.size,
+            modules: grid.modules,
+        }
+    }
+}
+
+/// The smallest version that holds `data` in byte mode.
+fn version_for(data: &[u8]) -> Result<usize, String> {
+    (1..=MAX_VERSION)
+        .find(|&version| {
+            let count_bits = if version < 10 { 8 } else { 16 };
+            data.len() < (1 << count_bits)
+                && 4 + count_bits + data.len() * 8 <= data_codewords(version) * 8
+        })
+        .ok_or_else(|| "Too much data for a QR code".to_string())
+}
+
+/// Modules left for data and error correction once the function patterns
+/// are placed.
+fn raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        result -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn data_codewords(version: usize) -> usize {
+    raw_data_modules(version) / 8
+        - ECC_CODEWORDS_PER_BLOCK[version - 1] * ERROR_CORRECTION_BLOCKS[version - 1]
+}
+
+/// Mode indicator, length and data, terminated and padded to capacity.
+fn data_codewords_for(version: usize, data: &[u8]) -> Vec<u8> {
+    let capacity_bits = data_codewords(version) * 8;
+    let mut bits: Vec<bool> = Vec::with_capacity(capacity_bits);
+    let mut push = |value: u32, len: usize| {
+        for i in (0..len).rev() {
+            bits.push((value >> i) & 1 == 1);
+        }
+    };
+    push(0b0100, 4);
+    push(data.len() as u32, if version < 10 { 8 } else { 16 });
+    for &byte in data {
+        push(u32::from(byte), 8);
+    }
+
+    let terminator = (capacity_bits - bits.len()).min(4);
+    bits.extend(std::iter::repeat_n(false, terminator));
+    let padding = (8 - bits.len() % 8) % 8;
+    bits.extend(std::iter::repeat_n(false, padding));
+
+    let mut codewords: Vec<u8> = bits
+        .chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .fold(0u8, |acc, &bit| (acc << 1) | u8::from(bit))
+        })
+        .collect();
+    for pad in [0xEC, 0x11].into_iter().cycle() {
+        if codewords.len() * 8 >= capacity_bits {
+            break;
+        }
+        codewords.push(pad);
+    }
+    codewords
+}
+
+/// Splits the data into blocks, appends each block's Reed-Solomon codewords
+/// and interleaves the result.
+fn add_error_correction(version: usize, data: &[u8]) -> Vec<u8> {
+    let blocks_count = ERROR_CORRECTION_BLOCKS[version - 1];
+    let ecc_len = ECC_CODEWORDS_PER_BLOCK[version - 1];
+    let raw_codewords = raw_data_modules(version) / 8;
+    let short_blocks = blocks_count - raw_codewords % blocks_count;
+    let short_block_len = raw_codewords / blocks_count;
+
+    let divisor = reed_solomon_divisor(ecc_len);
+    let mut blocks: Vec<Vec<u8>> = Vec::with_capacity(blocks_count);
+    let mut offset = 0;
+    for index in 0..blocks_count {
+        let data_len = short_block_len - ecc_len + usize::from(index >= short_blocks);
+        let mut block = data[offset..offset + data_len].to_vec();
+        offset += data_len;
+        let ecc = reed_solomon_remainder(&block, &divisor);
+        if index < short_blocks {
+            block.push(0);
+        }
+        block.extend(ecc);
+        blocks.push(block);
+    }
+
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..blocks[0].len() {
+        for (index, block) in blocks.iter().enumerate() {
+            // Short blocks carry a placeholder where long blocks have one
+            // more data codeword.
+            if i != short_block_len - ecc_len || index >= short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree];
+    result[degree - 1] = 1;
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; divisor.len()];
+    for &byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (value, &coefficient) in result.iter_mut().zip(divisor) {
+            *value ^= gf_multiply(coefficient, factor);
+        }
+    }
+    result
+}
+
+/// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1.
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u32 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= ((u32::from(y) >> i) & 1) * u32::from(x);
+    }
+    z as u8
+}
+
+/// The level and mask with their BCH error correction, masked as the
+/// standard requires.
+fn format_bits(mask: u32) -> u32 {
+    let data = (ECC_LEVEL_M_BITS << 3) | mask;
+    let mut remainder = data;
+    for _ in 0..10 {
+        remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+    }
+    ((data << 10) | remainder) ^ 0x5412
+}
+
+/// The version with its BCH error correction, drawn from version 7 up.
+fn version_bits(version: usize) -> u32 {
+    let mut remainder = version as u32;
+    for _ in 0..12 {
+        remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1F25);
+    }
+    ((version as u32) << 12) | remainder
+}
+
+#[derive(Clone)]
+struct Grid {
+    version: usize,
+    size: usize,
+    modules: Vec<bool>,
+    is_function: Vec<bool>,
+}
+
+impl Grid {
+    fn new(version: usize) -> Self {
+        let size = version * 4 + 17;
+        Self {
+            version,
+            size,
+            modules: vec![false; size * size],
+            is_function: vec![false; size * size],
+        }
+    }
+
+    /// `data` placed in the smallest version that holds it, not yet masked.
+    fn with_data(data: &[u8]) -> Result<Self, String> {
+        let version = version_for(data)?;
+        let codewords = add_error_correction(version, &data_codewords_for(version, data));
+        let mut grid = Self::new(version);
+        grid.draw_function_patterns();
+        grid.draw_codewords(&codewords);
+        Ok(grid)
+    }
+
+    /// A copy with `mask` applied and its format bits drawn.
+    fn masked(&self, mask: u32) -> Self {
+        let mut grid = self.clone();
+        grid.apply_mask(mask);
+        grid.draw_format_bits(mask);
+        grid
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.is_function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self) {
+        for i in 0..self.size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+        self.draw_finder(3, 3);
+        self.draw_finder(self.size - 4, 3);
+        self.draw_finder(3, self.size - 4);
+
+        let positions = self.alignment_positions();
+        let last = positions.len().saturating_sub(1);
+        // The three corners taken by finder patterns.
+        let finder_corners = [(0, 0), (0, last), (last, 0)];
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                if !finder_corners.contains(&(i, j)) {
+                    self.draw_alignment(x, y);
+                }
+            }
+        }
+
+        // Reserve the format areas; the real bits go in once a mask is chosen.
+        self.draw_format_bits(0);
+        self.draw_version();
+    }
+
+    /// A finder pattern and its light separator, centred on `(x, y)`.
+    fn draw_finder(&mut self, x: usize, y: usize) {
+        for dy in -4i32..=4 {
+            for dx in -4i32..=4 {
+                let (xx, yy) = (x as i32 + dx, y as i32 + dy);
+                if (0..self.size as i32).contains(&xx) && (0..self.size as i32).contains(&yy) {
+                    let distance = dx.abs().max(dy.abs());
+                    self.set_function(xx as usize, yy as usize, distance != 2 && distance != 4);
+                }
+            }
+        }
+    }
+
+    fn draw_alignment(&mut self, x: usize, y: usize) {
+        for dy in -2i32..=2 {
+            for dx in -2i32..=2 {
+                let dark = dx.abs().max(dy.abs()) != 1;
+                self.set_function((x as i32 + dx) as usize, (y as i32 + dy) as usize, dark);
+            }
+        }
+    }
+
+    fn alignment_positions(&self) -> Vec<usize> {
+        if self.version == 1 {
+            return Vec::new();
+        }
+        let count = self.version / 7 + 2;
+        let step = (self.version * 4 + count * 2 + 1) / (count * 2 - 2) * 2;
+        let mut positions: Vec<usize> = (0..count - 1).map(|i| self.size - 7 - i * step).collect();
+        positions.push(6);
+        positions.reverse();
+        positions
+    }
+
+    fn draw_format_bits(&mut self, mask: u32) {
+        let bits = format_bits(mask);
+        let bit = |i: usize| (bits >> i) & 1 == 1;
+
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        for i in 0..8 {
+            self.set_function(self.size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, self.size - 15 + i, bit(i));
+        }
+        self.set_function(8, self.size - 8, true);
+    }
+
+    fn draw_version(&mut self) {
+        if self.version < 7 {
+            return;
+        }
+        let bits = version_bits(self.version);
+        for i in 0..18 {
+            let dark = (bits >> i) & 1 == 1;
+            let a = self.size - 11 + i % 3;
+            let b = i / 3;
+            self.set_function(a, b, dark);
+            self.set_function(b, a, dark);
+        }
+    }
+
+    /// Places the codewords in the zigzag order, two columns at a time from
+    /// the bottom right, skipping the vertical timing pattern.
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let total_bits = codewords.len() * 8;
+        let mut bit_index = 0;
+        let mut right = self.size as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vertical in 0..self.size {
+                for j in 0..2 {
+                    let x = (right - j) as usize;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward {
+                        self.size - 1 - vertical
+                    } else {
+                        vertical
+                    };
+                    if !self.is_function[y * self.size + x] && bit_index < total_bits {
+                        let byte = codewords[bit_index >> 3];
+                        self.modules[y * self.size + x] = (byte >> (7 - (bit_index & 7))) & 1 == 1;
+                        bit_index += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let index = y * self.size + x;
+                if invert && !self.is_function[index] {
+                    self.modules[index] = !self.modules[index];
+                }
+            }
+        }
+    }
+
+    /// The standard's penalty score; the mask with the lowest one is used.
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let mut penalty = 0;
+
+        // Runs of five or more modules of one colour, and patterns that look
+        // like finders, along rows and columns.
+        for horizontal in [true, false] {
+            for line in 0..size {
+                let cells: Vec<bool> = (0..size)
+                    .map(|i| {
+                        if horizontal {
+                            self.get(i, line)
+                        } else {
+                            self.get(line, i)
+                        }
+                    })
+                    .collect();
+                let mut run = 1;
+                for i in 1..=size {
+                    if i < size && cells[i] == cells[i - 1] {
+                        run += 1;
+                        continue;
+                    }
+                    if run >= 5 {
+                        penalty += run - 2;
+                    }
+                    run = 1;
+                }
+                const FINDER_LIKE: [bool; 11] = [
+                    true, false, true, true, true, false, true, false, false, false, false,
+                ];
+                for window in cells.windows(11) {
+                    if window == FINDER_LIKE || window.iter().rev().eq(FINDER_LIKE.iter()) {
+                        penalty += 40;
+                    }
+                }
+            }
+        }
+
+        // 2x2 blocks of one colour.
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let color = self.get(x, y);
+                if color == self.get(x + 1, y)
+                    && color == self.get(x, y + 1)
+                    && color == self.get(x + 1, y + 1)
+                {
+                    penalty += 3;
+                }
+            }
+        }
+
+        // Balance of dark and light modules.
+        let total = size * size;
+        let dark = self.modules.iter().filter(|&&dark| dark).count();
+        let deviation = (dark * 20).abs_diff(total * 10);
+        penalty += deviation.div_ceil(total).saturating_sub(1) * 10;
+        penalty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_bits_match_the_standard_table() {
+        let expected = [
+            0b101010000010010,
+            0b101000100100101,
+            0b101111001111100,
+            0b101101101001011,
+            0b100010111111001,
+            0b100000011001110,
+            0b100111110010111,
+            0b100101010100000,
+        ];
+        for (mask, bits) in expected.into_iter().enumerate() {
+            assert_eq!(format_bits(mask as u32), bits, "mask {}", mask);
+        }
+    }
+
+    #[test]
+    fn version_bits_match_the_standard_table() {
+        assert_eq!(version_bits(7), 0x07C94);
+        assert_eq!(version_bits(8), 0x085BC);
+        assert_eq!(version_bits(9), 0x09A99);
+        assert_eq!(version_bits(10), 0x0A4D3);
+    }
+
+    #[test]
+    fn reed_solomon_codewords_match_a_known_block() {
+        // "HELLO WORLD" at version 1-M.
+        let data = [
+            32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17,
+        ];
+        assert_eq!(
+            reed_solomon_remainder(&data, &reed_solomon_divisor(10)),
+            [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]
+        );
+    }
+
+    /// A short EPC payload at version 5 with mask 2, as an independent
+    /// encoder draws it.
+    const EPC_MATRIX: [&str; 37] = [
+        "#######..#.###.#......###...#.#######",
+        "#.....#....##.###...##..##.##.#.....#",
+        "#.###.#.##.##...#.#..#....##..#.###.#",
+        "#.###.#.#...#...###.#....##.#.#.###.#",
+        "#.###.#.#.##..#..#.#.########.#.###.#",
+        "#.....#.##..##..##.......###..#.....#",
+        "#######.#.#.#.#.#.#.#.#.#.#.#.#######",
+        "........#..##..#.##.##...............",
+        "#.#####..##.....#....#...##...#####..",
+        ".#..#...#..##..#..#.#..##.###....##.#",
+        ".....##.#..#..###.##.#..#.##..#..####",
+        "#####.......##.#..#####.#....##....#.",
+        "..#..###.#.#...######...###...####..#",
+        "....##.#.#.........#..#...##..#.....#",
+        ".##..###..##.#.##.#...#.####.#..##..#",
+        ".###.#.#..#.#...................##..#",
+        "###.###.#.#.##.#..#.###..##...####..#",
+        "#.##.#.....###.##....#.##..#.#......#",
+        "##..#.#..##.........#...#.##...#...##",
+        ".#.###..#...##..#....##.#.#....#.....",
+        "#######.#...##..###.#...###..##.....#",
+        "##..#....##.###..#.#.##..#.#.##..###.",
+        "##.#..#.#..#.#.###...#..####..#.###.#",
+        "##...#.....###.#.##..####.###.#..#..#",
+        "#####.##.##.#.###....#.#####..###.#.#",
+        "#....#.##.#..#.#..#.#####..#.#......#",
+        "#.#.###.....#..#####..#...##..#.#..##",
+        "#.#.##.....#..##...####......#......#",
+        "#.#.####.#.#.#.######...###.#####..##",
+        "........#.####.....#..#.##..#...##.#.",
+        "#######..##.##.#..#...#....##.#.##..#",
+        "#.....#.###...##....#..#..#.#...##..#",
+        "#.###.#.#...####..#.#############.#.#",
+        "#.###.#.####..###....#.#.#..######..#",
+        "#.###.#.#.#.##...##.##.....#..#..####",
+        "#.....#...#.###.#.#..#.##.##..#..#..#",
+        "#######.#...###.###.#...####..#...###",
+    ];
+
+    #[test]
+    fn a_short_payment_code_matches_a_reference_matrix() {
+        let payload =
+            "BCD\n002\n1\nSCT\n\nStudio\nDE89370400440532013000\nEUR250.00\n\n\nInvoice 042";
+        let grid = Grid::with_data(payload.as_bytes()).unwrap().masked(2);
+
+        assert_eq!(grid.version, 5);
+        for (y, row) in EPC_MATRIX.iter().enumerate() {
+            let drawn: String = (0..grid.size)
+                .map(|x| if grid.get(x, y) { '#' } else { '.' })
+                .collect();
+            assert_eq!(&drawn, row, "row {}", y);
+        }
+    }
+
+    #[test]
+    fn encoding_picks_one_of_the_masked_grids() {
+        let data = b"BCD\n002\n1\nSCT\n\nStudio\nDE89370400440532013000\nEUR250.00";
+        let qr = QrCode::encode(data).unwrap();
+        let grid = Grid::with_data(data).unwrap();
+
+        assert!((0..8).any(|mask| grid.masked(mask).modules == qr.modules));
+    }
+}
@@ -0,0 +1,509 @@
+use chrono::{
+    Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveTime, TimeZone, Utc, Weekday,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use crate::{
+    create_time_entry, current_unix_timestamp, fixed_offset, open_connection, resolve_db_path,
+    sanitize_hourly_rate, sanitize_project_name, settings, EntrySaveError, UpdateResult,
+};
+
+/// Start of an entry on a past day when neither a time nor working hours say
+/// otherwise.
+const DEFAULT_DAY_START: (u32, u32) = (9, 0);
+
+/// How a quick-add line was read. Nothing is saved until it is committed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickAddParse {
+    pub project_name: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    /// Seconds.
+    pub duration: i64,
+    pub hourly_rate: f64,
+    pub notes: Option<String>,
+    /// The project hasn't been tracked before and will be created.
+    pub new_project: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickAddResult {
+    pub parsed: QuickAddParse,
+    /// The saved entry, when `commit` was set.
+    pub saved: Option<UpdateResult>,
+}
+
+/// Reads lines like "2h yesterday ProjectX fixing login @85": a duration
+/// ("2h", "45m", "1h30m", "1.5h"), an optional day ("today", "yesterday", a
+/// weekday or YYYY-MM-DD), an optional start ("at 9:30", "2pm"), an optional
+/// rate ("@85"), then the project and notes. The first words naming a known
+/// project are the project, otherwise the first word is. Today's entries end
+/// now; other days start at the first working-hours block, else 09:00.
+/// Days and times are read at `utc_offset` minutes east of UTC when given,
+/// else in the device's zone. Returns the interpretation only, unless
+/// `commit` is set.
+#[tauri::command]
+pub async fn quick_add(
+    app_handle: tauri::AppHandle,
+    text: String,
+    commit: Option<bool>,
+    utc_offset: Option<i32>,
+) -> Result<QuickAddResult, EntrySaveError> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let zone = utc_offset.map(fixed_offset).transpose()?;
+
+    let parsed = tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        interpret(&conn, &text, zone)
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    let saved = if commit.unwrap_or(false) {
+        Some(
+            create_time_entry(
+                app_handle,
+                parsed.project_name.clone(),
+                parsed.start_time,
+                parsed.end_time,
+                Some(parsed.hourly_rate),
+                parsed.notes.clone(),
+                utc_offset,
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+
+    Ok(QuickAddResult { parsed, saved })
+}
+
+#[derive(Clone, Default)]
+struct Tokens<'a> {
+    duration: i64,
+    date: Option<NaiveDate>,
+    time: Option<NaiveTime>,
+    rate: Option<f64>,
+    words: Vec<&'a str>,
+}
+
+fn interpret(
+    conn: &Connection,
+    text: &str,
+    zone: Option<FixedOffset>,
+) -> Result<QuickAddParse, String> {
+    let today = match zone {
+        Some(zone) => Utc::now().with_timezone(&zone).date_naive(),
+        None => Local::now().date_naive(),
+    };
+    let tokens = tokenize(text, today)?;
+    if tokens.duration <= 0 {
+        return Err("Add a duration, like 2h or 45m".into());
+    }
+    if tokens.words.is_empty() {
+        return Err("Add a project name".into());
+    }
+
+    let (project_name, known, rest) = split_project(conn, &tokens.words)?;
+    let date = tokens.date.unwrap_or(today);
+    let (start_time, end_time) = match tokens.time {
+        Some(time) => {
+            let start = local_timestamp(date, time, zone)?;
+            (start, start + tokens.duration)
+        }
+        None if date == today => {
+            let now = current_unix_timestamp();
+            (now - tokens.duration, now)
+        }
+        None => {
+            let start = local_timestamp(date, day_start(conn, date)?, zone)?;
+            (start, start + tokens.duration)
+        }
+    };
+
+    let hourly_rate = match tokens.rate {
+        Some(rate) => sanitize_hourly_rate(rate),
+        None => default_rate(conn, &project_name)?,
+    };
+    let notes = (!rest.is_empty()).then(|| rest.join(" "));
+
+    Ok(QuickAddParse {
+        project_name,
+        start_time,
+        end_time,
+        duration: tokens.duration,
+        hourly_rate,
+        notes,
+        new_project: !known,
+    })
+}
+
+/// Durations and days are only read from the run of tokens that opens or
+/// closes the line, so "call re monday prep" keeps its weekday in the notes.
+/// Rates and start times are unambiguous and are read anywhere.
+fn tokenize(text: &str, today: NaiveDate) -> Result<Tokens<'_>, String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut tokens = Tokens::default();
+
+    let mut start = 0;
+    while let Some(taken) = take_token(&words[start..], today, &mut tokens, true)? {
+        start += taken;
+    }
+    // The closing run is the longest tail that reads entirely as tokens.
+    let end = (start..words.len())
+        .find(|&from| reads_as_tokens(&words[from..], today, &tokens))
+        .unwrap_or(words.len());
+
+    let mut index = start;
+    while index < words.len() {
+        let edge = index >= end;
+        match take_token(&words[index..], today, &mut tokens, edge)? {
+            Some(taken) => index += taken,
+            None => {
+                tokens.words.push(words[index]);
+                index += 1;
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn reads_as_tokens(words: &[&str], today: NaiveDate, tokens: &Tokens<'_>) -> bool {
+    let mut tokens = tokens.clone();
+    let mut index = 0;
+    while index < words.len() {
+        match take_token(&words[index..], today, &mut tokens, true) {
+            Ok(Some(taken)) => index += taken,
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Reads the token at the start of `words` into `tokens`, returning how many
+/// words it used, or `None` when the first word isn't one. Durations and days
+/// are only taken at the `edge` of the line.
+fn take_token(
+    words: &[&str],
+    today: NaiveDate,
+    tokens: &mut Tokens<'_>,
+    edge: bool,
+) -> Result<Option<usize>, String> {
+    let Some(word) = words.first() else {
+        return Ok(None);
+    };
+    let lower = word.to_lowercase();
+    if let Some(rate) = lower.strip_prefix('@') {
+        let rate = rate.trim_start_matches('$');
+        tokens.rate = Some(
+            rate.parse::<f64>()
+                .map_err(|_| format!("\"{}\" isn't a rate", word))?,
+        );
+    } else if let Some(seconds) = parse_duration(&lower).filter(|_| edge) {
+        tokens.duration += seconds;
+    } else if let Some(date) = parse_date(&lower, today).filter(|_| edge && tokens.date.is_none()) {
+        tokens.date = Some(date);
+    } else if tokens.time.is_none() && lower == "at" {
+        match words
+            .get(1)
+            .and_then(|next| parse_time(&next.to_lowercase()))
+        {
+            Some(time) => {
+                tokens.time = Some(time);
+                return Ok(Some(2));
+            }
+            None => return Ok(None),
+        }
+    } else if tokens.time.is_none()
+        && (lower.ends_with("am") || lower.ends_with("pm"))
+        && parse_time(&lower).is_some()
+    {
+        tokens.time = parse_time(&lower);
+    } else {
+        return Ok(None);
+    }
+    Ok(Some(1))
+}
+
+/// "2h", "45m", "1h30m", "1.5hrs", "90min"; `None` for anything else.
+fn parse_duration(token: &str) -> Option<i64> {
+    let mut total = 0.0;
+    let mut rest = token;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_len].parse().ok()?;
+        rest = &rest[number_len..];
+        let unit_len = rest
+            .find(|ch: char| !ch.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let seconds = match &rest[..unit_len] {
+            "h" | "hr" | "hrs" | "hour" | "hours" => 3600.0,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60.0,
+            _ => return None,
+        };
+        rest = &rest[unit_len..];
+        total += number * seconds;
+    }
+    (total > 0.0).then(|| total.round() as i64)
+}
+
+/// "today", "yesterday", a weekday (the most recent one, today included) or
+/// YYYY-MM-DD.
+fn parse_date(token: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match token {
+        "today" => return Some(today),
+        "yesterday" => return Some(today - Duration::days(1)),
+        _ => {}
+    }
+    if let Ok(weekday) = token.parse::<Weekday>() {
+        let back =
+            (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        return Some(today - Duration::days(i64::from(back)));
+    }
+    NaiveDate::parse_from_str(token, "%Y-%m-%d").ok()
+}
+
+/// "9:30", "14:00", "2pm", "9:30am".
+fn parse_time(token: &str) -> Option<NaiveTime> {
+    let (clock, offset) = if let Some(clock) = token.strip_suffix("am") {
+        (clock, Some(0))
+    } else if let Some(clock) = token.strip_suffix("pm") {
+        (clock, Some(12))
+    } else {
+        (token, None)
+    };
+    let (hours, minutes) = match clock.split_once(':') {
+        Some((hours, minutes)) => (hours.parse::<u32>().ok()?, minutes.parse::<u32>().ok()?),
+        None if offset.is_some() => (clock.parse::<u32>().ok()?, 0),
+        None => return None,
+    };
+    let hours = match offset {
+        Some(offset) if (1..=12).contains(&hours) => hours % 12 + offset,
+        Some(_) => return None,
+        None => hours,
+    };
+    NaiveTime::from_hms_opt(hours, minutes, 0)
+}
+
+/// The longest run of leading words naming a known project, in its stored
+/// spelling, or the first word as a new project. Returns the remaining words.
+fn split_project<'a>(
+    conn: &Connection,
+    words: &[&'a str],
+) -> Result<(String, bool, Vec<&'a str>), String> {
+    let mut stmt = conn
+        .prepare("SELECT name FROM projects")
+        .map_err(|err| err.to_string())?;
+    let projects = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    for len in (1..=words.len()).rev() {
+        let candidate = words[..len].join(" ");
+        if let Some(name) = projects
+            .iter()
+            .find(|name| name.eq_ignore_ascii_case(&candidate))
+        {
+            return Ok((name.clone(), true, words[len..].to_vec()));
+        }
+    }
+    Ok((
+        sanitize_project_name(words[0].to_string()),
+        false,
+        words[1..].to_vec(),
+    ))
+}
+
+fn day_start(conn: &Connection, date: NaiveDate) -> Result<NaiveTime, String> {
+    let weekday = date.weekday().number_from_monday();
+    let start = settings::load_settings(conn)?
+        .working_hours
+        .iter()
+        .filter(|block| block.days.contains(&weekday))
+        .filter_map(|block| block.bounds().map(|(start, _)| start))
+        .min();
+    Ok(start.unwrap_or_else(|| {
+        NaiveTime::from_hms_opt(DEFAULT_DAY_START.0, DEFAULT_DAY_START.1, 0)
+            .expect("default day start is a valid time")
+    }))
+}
+
+fn local_timestamp(
+    date: NaiveDate,
+    time: NaiveTime,
+    zone: Option<FixedOffset>,
+) -> Result<i64, String> {
+    let local = date.and_time(time);
+    match zone {
+        Some(zone) => zone
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|dt| dt.timestamp()),
+        None => Local
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|dt| dt.timestamp()),
+    }
+    .ok_or_else(|| "That time doesn't exist locally".to_string())
+}
+
+/// The rate last tracked on the project, else its client's default rate.
+fn default_rate(conn: &Connection, project_name: &str) -> Result<f64, String> {
+    let last: Option<f64> = conn
+        .query_row(
+            "SELECT hourly_rate FROM time_entries
+             WHERE project_name = ?1 AND entry_type = 'work'
+             ORDER BY start_time DESC LIMIT 1",
+            params![project_name],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|err| err.to_string())?;
+    if let Some(rate) = last {
+        return Ok(sanitize_hourly_rate(rate));
+    }
+    let client_rate: Option<f64> = conn
+        .query_row(
+            "SELECT c.default_rate FROM projects p
+             JOIN clients c ON c.id = p.client_id
+             WHERE p.name = ?1",
+            params![project_name],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|err| err.to_string())?;
+    Ok(sanitize_hourly_rate(client_rate.unwrap_or(0.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, month, day).unwrap()
+    }
+
+    fn time(hours: u32, minutes: u32) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt(hours, minutes, 0)
+    }
+
+    #[test]
+    fn durations() {
+        for (token, seconds) in [
+            ("2h", Some(7_200)),
+            ("45m", Some(2_700)),
+            ("1h30m", Some(5_400)),
+            ("1.5hrs", Some(5_400)),
+            ("90min", Some(5_400)),
+            ("2hours15minutes", Some(8_100)),
+            ("0h", None),
+            ("h", None),
+            ("30", None),
+            ("2d", None),
+            ("1h30", None),
+        ] {
+            assert_eq!(parse_duration(token), seconds, "{}", token);
+        }
+    }
+
+    #[test]
+    fn times() {
+        for (token, expected) in [
+            ("9:30", time(9, 30)),
+            ("14:00", time(14, 0)),
+            ("2pm", time(14, 0)),
+            ("9:30am", time(9, 30)),
+            ("12am", time(0, 0)),
+            ("12pm", time(12, 0)),
+            ("12:15am", time(0, 15)),
+            ("0am", None),
+            ("13pm", None),
+            ("25:00", None),
+            ("930", None),
+        ] {
+            assert_eq!(parse_time(token), expected, "{}", token);
+        }
+    }
+
+    #[test]
+    fn dates() {
+        // A Wednesday.
+        let today = day(6, 11);
+        for (token, expected) in [
+            ("today", Some(today)),
+            ("yesterday", Some(day(6, 10))),
+            ("wed", Some(today)),
+            ("wednesday", Some(today)),
+            ("mon", Some(day(6, 9))),
+            ("thu", Some(day(6, 5))),
+            ("sunday", Some(day(6, 8))),
+            ("2025-01-31", Some(day(1, 31))),
+            ("2025-02-30", None),
+            ("tomorrow", None),
+        ] {
+            assert_eq!(parse_date(token, today), expected, "{}", token);
+        }
+    }
+
+    #[test]
+    fn tokens_are_picked_out_of_free_text() {
+        let tokens = tokenize(
+            "2h 30m Acme design review @$85 yesterday at 9:30",
+            day(6, 11),
+        )
+        .unwrap();
+        assert_eq!(tokens.duration, 9_000);
+        assert_eq!(tokens.rate, Some(85.0));
+        assert_eq!(tokens.date, Some(day(6, 10)));
+        assert_eq!(tokens.time, time(9, 30));
+        assert_eq!(tokens.words, vec!["Acme", "design", "review"]);
+    }
+
+    #[test]
+    fn at_without_a_time_stays_in_the_notes() {
+        let tokens = tokenize("1h lunch at home 2pm", day(6, 11)).unwrap();
+        assert_eq!(tokens.time, time(14, 0));
+        assert_eq!(tokens.words, vec!["lunch", "at", "home"]);
+    }
+
+    #[test]
+    fn durations_and_days_are_only_read_at_either_end() {
+        let monday = Some(day(6, 9));
+        for (text, duration, date, words) in [
+            (
+                "1h acme call re monday prep",
+                3_600,
+                None,
+                "acme call re monday prep",
+            ),
+            ("monday 1h acme call", 3_600, monday, "acme call"),
+            ("acme call re monday 1h", 3_600, monday, "acme call re"),
+            ("1h acme plan 2h block", 3_600, None, "acme plan 2h block"),
+            ("acme standup 15m mon @60", 900, monday, "acme standup"),
+            (
+                "1h acme call re monday prep at 2pm",
+                3_600,
+                None,
+                "acme call re monday prep",
+            ),
+        ] {
+            let tokens = tokenize(text, day(6, 11)).unwrap();
+            assert_eq!(tokens.duration, duration, "{}", text);
+            assert_eq!(tokens.date, date, "{}", text);
+            assert_eq!(tokens.words.join(" "), words, "{}", text);
+        }
+    }
+
+    #[test]
+    fn bad_rates_are_refused() {
+        assert!(tokenize("1h acme @lots", day(6, 11)).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+use std::sync::Mutex;
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    current_unix_timestamp, discard_timer, send_notification, settings, stop_timer_internal,
+    StopOrigin, TimerState, TimerStatusPayload,
+};
+
+pub const RECOVERED_EVENT: &str = "timer://recovered-long-running";
+
+const DEFAULT_RESTORED_TIMER_MAX_HOURS: u32 = 12;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveredTimers {
+    pub threshold_hours: u32,
+    pub timers: Vec<TimerStatusPayload>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecoveryAction {
+    /// Save the entry cut off at `end_time`, or after the threshold.
+    Truncate,
+    /// Let the timer carry on as restored.
+    Keep,
+    /// Drop the timer without saving anything.
+    Discard,
+}
+
+/// Restored timers still waiting for the user's decision.
+#[derive(Default)]
+pub struct RecoveryState {
+    inner: Mutex<RecoveryInner>,
+}
+
+#[derive(Default)]
+struct RecoveryInner {
+    timer_ids: Vec<i64>,
+    threshold_hours: u32,
+}
+
+#[tauri::command]
+pub async fn get_recovered_timers(
+    app_handle: tauri::AppHandle,
+) -> Result<Option<RecoveredTimers>, String> {
+    let (timer_ids, threshold_hours) = {
+        let state = app_handle.state::<RecoveryState>();
+        let guard = state
+            .inner
+            .lock()
+            .map_err(|_| "Recovery state is unavailable")?;
+        (guard.timer_ids.clone(), guard.threshold_hours)
+    };
+    if timer_ids.is_empty() {
+        return Ok(None);
+    }
+    let timers = app_handle
+        .state::<TimerState>()
+        .statuses()
+        .into_iter()
+        .filter(|status| status.timer_id.is_some_and(|id| timer_ids.contains(&id)))
+        .collect();
+    Ok(Some(RecoveredTimers {
+        threshold_hours,
+        timers,
+    }))
+}
+
+/// Settles one recovered timer. Truncating without an `end_time` keeps the
+/// threshold's worth of tracked time. Returns the running timers afterwards.
+#[tauri::command]
+pub async fn resolve_recovered_timer(
+    app_handle: tauri::AppHandle,
+    timer_id: i64,
+    action: RecoveryAction,
+    end_time: Option<i64>,
+) -> Result<Vec<TimerStatusPayload>, String> {
+    let threshold_hours = {
+        let state = app_handle.state::<RecoveryState>();
+        let mut guard = state
+            .inner
+            .lock()
+            .map_err(|_| "Recovery state is unavailable")?;
+        let Some(index) = guard.timer_ids.iter().position(|id| *id == timer_id) else {
+            return Err("That timer is not awaiting a decision".into());
+        };
+        guard.timer_ids.remove(index);
+        guard.threshold_hours
+    };
+
+    match action {
+        RecoveryAction::Keep => {}
+        RecoveryAction::Discard => {
+            discard_timer(app_handle.clone(), Some(timer_id)).await?;
+        }
+        RecoveryAction::Truncate => {
+            let status = app_handle
+                .state::<TimerState>()
+                .statuses()
+                .into_iter()
+                .find(|status| status.timer_id == Some(timer_id))
+                .ok_or("Timer not found")?;
+            let start_time = status.start_time.unwrap_or_default();
+            let stop_at = match end_time {
+                Some(end) if end > current_unix_timestamp() => {
+                    return Err("End time cannot be in the future".into());
+                }
+                Some(end) if end <= start_time => {
+                    return Err("End time must be after the timer started".into());
+                }
+                Some(end) => end,
+                None => {
+                    start_time
+                        + status.paused_seconds.unwrap_or(0)
+                        + i64::from(threshold_hours) * 3600
+                }
+            };
+            stop_timer_internal(
+                &app_handle,
+                Some(timer_id),
+                StopOrigin::Window,
+                Some(stop_at),
+            )
+            .await?;
+        }
+    }
+
+    Ok(app_handle.state::<TimerState>().statuses())
+}
+
+/// Flags timers restored at launch that have been running longer than the
+/// threshold, e.g. one started days ago before a reboot. `excluded` holds
+/// timers already offered a fix by the heartbeat check.
+pub fn check_recovered_timers(
+    app: &AppHandle,
+    conn: &Connection,
+    excluded: &[i64],
+) -> Result<(), String> {
+    let threshold_hours = settings::load_settings(conn)?
+        .restored_timer_max_hours
+        .unwrap_or(DEFAULT_RESTORED_TIMER_MAX_HOURS);
+    if threshold_hours == 0 {
+        return Ok(());
+    }
+
+    let timers: Vec<TimerStatusPayload> = app
+        .state::<TimerState>()
+        .statuses()
+        .into_iter()
+        .filter(|status| status.timer_id.is_some_and(|id| !excluded.contains(&id)))
+        .filter(|status| {
+            status
+                .elapsed_seconds
+                .is_some_and(|elapsed| elapsed > i64::from(threshold_hours) * 3600)
+        })
+        .collect();
+    if timers.is_empty() {
+        return Ok(());
+    }
+
+    {
+        let state = app.state::<RecoveryState>();
+        let mut guard = state
+            .inner
+            .lock()
+            .map_err(|_| "Recovery state is unavailable")?;
+        guard.timer_ids = timers.iter().filter_map(|status| status.timer_id).collect();
+        guard.threshold_hours = threshold_hours;
+    }
+    let _ = app.emit(
+        RECOVERED_EVENT,
+        RecoveredTimers {
+            threshold_hours,
+            timers,
+        },
+    );
+    send_notification(
+        app,
+        "Long-running timer recovered",
+        &format!(
+            "A timer restored at launch has been running for over {} hours. Open Time Tracker to truncate, keep or discard it.",
+            threshold_hours
+        ),
+    );
+    Ok(())
+}
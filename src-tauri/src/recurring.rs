@@ -0,0 +1,508 @@
+use std::sync::Mutex;
+
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    audit::{self, AuditAction, EntrySource},
+    calculate_amount, current_unix_timestamp, fetch_time_entry, local_utc_offset_minutes,
+    open_connection, projects, resolve_db_path, sanitize_hourly_rate, sanitize_notes,
+    sanitize_project_name,
+    settings::WorkBlock,
+    ENTRY_TYPE_WORK,
+};
+
+pub const RECURRING_GENERATED_EVENT: &str = "entries://recurring-generated";
+
+/// Entries saved automatically on a weekly schedule, e.g. a weekday standup.
+/// `days` is a JSON array of ISO weekdays; `starts_at` and `ends_at` are
+/// local "HH:MM".
+pub const CREATE_RECURRING_ENTRIES_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS recurring_entries (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        project_name TEXT NOT NULL,
+        hourly_rate REAL NOT NULL DEFAULT 0,
+        notes TEXT,
+        days TEXT NOT NULL,
+        starts_at TEXT NOT NULL,
+        ends_at TEXT NOT NULL,
+        enabled INTEGER NOT NULL DEFAULT 1,
+        created_at INTEGER NOT NULL
+    )
+"#;
+
+/// One row per rule and local day already generated, so generation can run
+/// any number of times. Deleting a generated entry keeps its row, and the
+/// entry stays deleted.
+pub const CREATE_RECURRING_ENTRY_RUNS_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS recurring_entry_runs (
+        rule_id INTEGER NOT NULL REFERENCES recurring_entries(id) ON DELETE CASCADE,
+        day TEXT NOT NULL,
+        entry_id INTEGER,
+        PRIMARY KEY (rule_id, day)
+    )
+"#;
+
+const RULE_COLUMNS: &str =
+    "id, project_name, hourly_rate, notes, days, starts_at, ends_at, enabled, created_at";
+/// Days missed while the app was closed are caught up, up to this many.
+const MAX_CATCH_UP_DAYS: i64 = 31;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringRule {
+    pub id: i64,
+    pub project_name: String,
+    pub hourly_rate: f64,
+    pub notes: Option<String>,
+    #[serde(flatten)]
+    pub schedule: WorkBlock,
+    pub enabled: bool,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringRuleInput {
+    pub project_name: String,
+    pub hourly_rate: Option<f64>,
+    pub notes: Option<String>,
+    #[serde(flatten)]
+    pub schedule: WorkBlock,
+    pub enabled: Option<bool>,
+}
+
+/// The minute generation last ran in.
+#[derive(Default)]
+pub struct RecurringState {
+    last_minute: Mutex<Option<i64>>,
+}
+
+#[tauri::command]
+pub async fn list_recurring_rules(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<RecurringRule>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {RULE_COLUMNS} FROM recurring_entries ORDER BY starts_at ASC, id ASC"
+            ))
+            .map_err(|err| err.to_string())?;
+        let rules = stmt
+            .query_map([], map_rule)
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        Ok(rules)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Saves the rule and generates today's entry if it has already ended.
+#[tauri::command]
+pub async fn create_recurring_rule(
+    app_handle: tauri::AppHandle,
+    rule: RecurringRuleInput,
+) -> Result<RecurringRule, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let input = sanitize_rule_input(rule)?;
+
+    let (rule, generated) = tauri::async_runtime::spawn_blocking(move || {
+        let mut conn = open_connection(db_path)?;
+        conn.execute(
+            "INSERT INTO recurring_entries (project_name, hourly_rate, notes, days, starts_at, ends_at, enabled, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                input.project_name,
+                input.hourly_rate,
+                input.notes,
+                input.days,
+                input.start,
+                input.end,
+                input.enabled,
+                current_unix_timestamp()
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+        let rule = fetch_rule(&conn, conn.last_insert_rowid())?;
+        let generated = generate_recurring_entries(&mut conn, current_unix_timestamp())?;
+        Ok::<_, String>((rule, generated))
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    notify_generated(&app_handle, generated);
+    Ok(rule)
+}
+
+/// Changes apply to days not generated yet; existing entries are kept.
+#[tauri::command]
+pub async fn update_recurring_rule(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    rule: RecurringRuleInput,
+) -> Result<RecurringRule, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let input = sanitize_rule_input(rule)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let changed = conn
+            .execute(
+                "UPDATE recurring_entries
+                 SET project_name = ?1, hourly_rate = ?2, notes = ?3, days = ?4, starts_at = ?5,
+                     ends_at = ?6, enabled = ?7
+                 WHERE id = ?8",
+                params![
+                    input.project_name,
+                    input.hourly_rate,
+                    input.notes,
+                    input.days,
+                    input.start,
+                    input.end,
+                    input.enabled,
+                    id
+                ],
+            )
+            .map_err(|err| err.to_string())?;
+        if changed == 0 {
+            return Err("Recurring entry not found".into());
+        }
+        fetch_rule(&conn, id)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Deletes the rule; entries it already generated are kept.
+#[tauri::command]
+pub async fn delete_recurring_rule(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut conn = open_connection(db_path)?;
+        let tx = conn.transaction().map_err(|err| err.to_string())?;
+        tx.execute(
+            "DELETE FROM recurring_entry_runs WHERE rule_id = ?1",
+            params![id],
+        )
+        .map_err(|err| err.to_string())?;
+        tx.execute("DELETE FROM recurring_entries WHERE id = ?1", params![id])
+            .map_err(|err| err.to_string())?;
+        tx.commit().map_err(|err| err.to_string())?;
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Runs generation now and returns how many entries were created.
+#[tauri::command]
+pub async fn run_recurring_entries(app_handle: tauri::AppHandle) -> Result<usize, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    let generated = tauri::async_runtime::spawn_blocking(move || {
+        let mut conn = open_connection(db_path)?;
+        generate_recurring_entries(&mut conn, current_unix_timestamp())
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    notify_generated(&app_handle, generated);
+    Ok(generated)
+}
+
+/// Called at launch and on every scheduler tick; generates entries once per
+/// minute, so an occurrence is saved within a minute of its end.
+pub fn check_due_occurrences(app: &AppHandle) {
+    let now = current_unix_timestamp();
+    let state = app.state::<RecurringState>();
+    let mut last_minute = state.last_minute.lock().expect("recurring state poisoned");
+    if *last_minute == Some(now / 60) {
+        return;
+    }
+    let Ok(generated) = resolve_db_path(app)
+        .and_then(open_connection)
+        .and_then(|mut conn| generate_recurring_entries(&mut conn, now))
+    else {
+        return;
+    };
+    *last_minute = Some(now / 60);
+    drop(last_minute);
+    notify_generated(app, generated);
+}
+
+/// Creates the occurrences of each enabled rule from its creation day (or the
+/// catch-up limit) that have ended by `now`, skipping days already
+/// generated; today's is saved once it is over, not ahead of time. Returns
+/// the number of entries created.
+pub fn generate_recurring_entries(conn: &mut Connection, now: i64) -> Result<usize, String> {
+    let today = Local
+        .timestamp_opt(now, 0)
+        .single()
+        .map_or_else(|| Local::now().date_naive(), |at| at.date_naive());
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {RULE_COLUMNS} FROM recurring_entries WHERE enabled = 1"
+        ))
+        .map_err(|err| err.to_string())?;
+    let rules = stmt
+        .query_map([], map_rule)
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+    drop(stmt);
+
+    let earliest = today - Duration::days(MAX_CATCH_UP_DAYS);
+    let mut generated = 0;
+    for rule in rules {
+        let Some((start, end)) = rule.schedule.bounds() else {
+            continue;
+        };
+        let created = Local
+            .timestamp_opt(rule.created_at, 0)
+            .single()
+            .map_or(today, |at| at.date_naive());
+        let mut day = created.max(earliest);
+        while day <= today {
+            let weekday = day.weekday().number_from_monday();
+            if rule.schedule.days.contains(&weekday) {
+                let start_time = Local.from_local_datetime(&day.and_time(start)).earliest();
+                let end_time = Local.from_local_datetime(&day.and_time(end)).earliest();
+                if let (Some(start_time), Some(end_time)) = (start_time, end_time) {
+                    if end_time.timestamp() <= now {
+                        let tx = conn.transaction().map_err(|err| err.to_string())?;
+                        if materialize(
+                            &tx,
+                            &rule,
+                            day,
+                            start_time.timestamp(),
+                            end_time.timestamp(),
+                        )? {
+                            generated += 1;
+                        }
+                        tx.commit().map_err(|err| err.to_string())?;
+                    }
+                }
+            }
+            day += Duration::days(1);
+        }
+    }
+    Ok(generated)
+}
+
+/// Saves one occurrence unless that day was already generated. The entry and
+/// its run are written in the caller's transaction, so neither is left
+/// without the other.
+fn materialize(
+    conn: &Connection,
+    rule: &RecurringRule,
+    day: NaiveDate,
+    start_time: i64,
+    end_time: i64,
+) -> Result<bool, String> {
+    let day = day.format("%Y-%m-%d").to_string();
+    let exists = conn
+        .query_row(
+            "SELECT 1 FROM recurring_entry_runs WHERE rule_id = ?1 AND day = ?2",
+            params![rule.id, day],
+            |_| Ok(()),
+        )
+        .optional()
+        .map_err(|err| err.to_string())?
+        .is_some();
+    if exists {
+        return Ok(false);
+    }
+
+    projects::ensure_project(conn, &rule.project_name)?;
+    let duration = end_time - start_time;
+    conn.execute(
+        "INSERT INTO time_entries (project_name, start_time, end_time, duration, hourly_rate, amount, notes, entry_type, utc_offset)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            rule.project_name,
+            start_time,
+            end_time,
+            duration,
+            rule.hourly_rate,
+            calculate_amount(duration, rule.hourly_rate),
+            rule.notes,
+            ENTRY_TYPE_WORK,
+            local_utc_offset_minutes(start_time)
+        ],
+    )
+    .map_err(|err| err.to_string())?;
+    let entry = fetch_time_entry(conn, conn.last_insert_rowid())?;
+    conn.execute(
+        "INSERT INTO recurring_entry_runs (rule_id, day, entry_id) VALUES (?1, ?2, ?3)",
+        params![rule.id, day, entry.id],
+    )
+    .map_err(|err| err.to_string())?;
+    audit::record_change(
+        conn,
+        AuditAction::Create,
+        EntrySource::Recurring,
+        None,
+        Some(&entry),
+    )?;
+    Ok(true)
+}
+
+fn notify_generated(app: &AppHandle, generated: usize) {
+    if generated > 0 {
+        let _ = app.emit(RECURRING_GENERATED_EVENT, generated);
+    }
+}
+
+fn fetch_rule(conn: &Connection, id: i64) -> Result<RecurringRule, String> {
+    conn.query_row(
+        &format!("SELECT {RULE_COLUMNS} FROM recurring_entries WHERE id = ?1"),
+        params![id],
+        map_rule,
+    )
+    .map_err(|err| match err {
+        rusqlite::Error::QueryReturnedNoRows => "Recurring entry not found".to_string(),
+        other => other.to_string(),
+    })
+}
+
+fn map_rule(row: &rusqlite::Row) -> rusqlite::Result<RecurringRule> {
+    let days: String = row.get(4)?;
+    Ok(RecurringRule {
+        id: row.get(0)?,
+        project_name: row.get(1)?,
+        hourly_rate: row.get(2)?,
+        notes: row.get(3)?,
+        schedule: WorkBlock {
+            days: serde_json::from_str(&days).unwrap_or_default(),
+            start: row.get(5)?,
+            end: row.get(6)?,
+        },
+        enabled: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+struct SanitizedRule {
+    project_name: String,
+    hourly_rate: f64,
+    notes: Option<String>,
+    /// JSON array, ready to store.
+    days: String,
+    start: String,
+    end: String,
+    enabled: bool,
+}
+
+fn sanitize_rule_input(input: RecurringRuleInput) -> Result<SanitizedRule, String> {
+    let Some((start, end)) = input.schedule.bounds() else {
+        return Err("Recurring entries must use HH:MM times".into());
+    };
+    if end <= start {
+        return Err("Recurring entries must end after they start".into());
+    }
+    let mut days = input.schedule.days;
+    if days.is_empty() || days.iter().any(|day| !(1..=7).contains(day)) {
+        return Err("Days must be between 1 (Monday) and 7 (Sunday)".into());
+    }
+    days.sort_unstable();
+    days.dedup();
+
+    Ok(SanitizedRule {
+        project_name: sanitize_project_name(input.project_name),
+        hourly_rate: sanitize_hourly_rate(input.hourly_rate.unwrap_or(0.0)),
+        notes: sanitize_notes(input.notes)?,
+        days: serde_json::to_string(&days).map_err(|err| err.to_string())?,
+        start: start.format("%H:%M").to_string(),
+        end: end.format("%H:%M").to_string(),
+        enabled: input.enabled.unwrap_or(true),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection(name: &str) -> Connection {
+        let path = std::env::temp_dir().join(format!("tt-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        open_connection(path).expect("test database")
+    }
+
+    fn local(day: NaiveDate, time: &str) -> i64 {
+        let time = chrono::NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+        Local
+            .from_local_datetime(&day.and_time(time))
+            .earliest()
+            .unwrap()
+            .timestamp()
+    }
+
+    fn insert_daily_standup(conn: &Connection, created_at: i64) {
+        conn.execute(
+            "INSERT INTO recurring_entries (project_name, hourly_rate, days, starts_at, ends_at, created_at)
+             VALUES ('Standup', 60, '[1,2,3,4,5,6,7]', '09:00', '09:15', ?1)",
+            [created_at],
+        )
+        .expect("insert rule");
+    }
+
+    fn entry_starts(conn: &Connection) -> Vec<i64> {
+        let mut stmt = conn
+            .prepare("SELECT start_time FROM time_entries ORDER BY start_time")
+            .unwrap();
+        stmt.query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn an_occurrence_is_saved_once_it_has_ended() {
+        let mut conn = connection("recurring-due");
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let yesterday = today.pred_opt().unwrap();
+        insert_daily_standup(&conn, local(yesterday, "08:00"));
+
+        let generated = generate_recurring_entries(&mut conn, local(today, "09:10")).unwrap();
+        assert_eq!(generated, 1);
+        assert_eq!(entry_starts(&conn), vec![local(yesterday, "09:00")]);
+
+        let generated = generate_recurring_entries(&mut conn, local(today, "09:15")).unwrap();
+        assert_eq!(generated, 1);
+        assert_eq!(
+            entry_starts(&conn),
+            vec![local(yesterday, "09:00"), local(today, "09:00")]
+        );
+        assert_eq!(
+            generate_recurring_entries(&mut conn, local(today, "18:00")).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn every_saved_occurrence_has_its_run() {
+        let mut conn = connection("recurring-runs");
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        insert_daily_standup(&conn, local(today - Duration::days(3), "08:00"));
+        generate_recurring_entries(&mut conn, local(today, "12:00")).unwrap();
+
+        let unmatched: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM time_entries
+                 WHERE id NOT IN (SELECT entry_id FROM recurring_entry_runs)",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(unmatched, 0);
+        assert_eq!(entry_starts(&conn).len(), 4);
+    }
+}
@@ -0,0 +1,528 @@
+use std::{path::Path, sync::Mutex};
+
+use chrono::{Datelike, Days, Duration, Local, Months, NaiveDate, TimeZone};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    clients, current_unix_timestamp, finalize_invoice, invoice_labels::InvoiceLanguage,
+    open_connection, prepare_invoice, profiles, query_invoice_entries, resolve_db_path,
+    BusinessInfo, Invoice, InvoiceEntryFilter, InvoiceGrouping, InvoiceLineMode,
+    InvoicePaymentTerms, InvoiceRounding,
+};
+
+pub const RECURRING_INVOICES_EVENT: &str = "invoices://recurring-generated";
+
+/// Invoices billed to a client once per period from the work tracked in it,
+/// e.g. monthly. `template` is the JSON of everything else `prepare_invoice`
+/// takes, and `next_period_start` the local "YYYY-MM-DD" the next unbilled
+/// period starts on.
+pub const CREATE_RECURRING_INVOICES_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS recurring_invoices (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        client_id INTEGER NOT NULL REFERENCES clients(id),
+        cadence TEXT NOT NULL,
+        template TEXT NOT NULL,
+        next_period_start TEXT NOT NULL,
+        enabled INTEGER NOT NULL DEFAULT 1,
+        last_invoice_id INTEGER REFERENCES invoices(id),
+        created_at INTEGER NOT NULL
+    )
+"#;
+
+const RULE_COLUMNS: &str =
+    "id, client_id, cadence, template, next_period_start, enabled, last_invoice_id, created_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvoiceCadence {
+    Weekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl InvoiceCadence {
+    fn as_str(self) -> &'static str {
+        match self {
+            InvoiceCadence::Weekly => "weekly",
+            InvoiceCadence::Monthly => "monthly",
+            InvoiceCadence::Quarterly => "quarterly",
+            InvoiceCadence::Yearly => "yearly",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "weekly" => InvoiceCadence::Weekly,
+            "quarterly" => InvoiceCadence::Quarterly,
+            "yearly" => InvoiceCadence::Yearly,
+            _ => InvoiceCadence::Monthly,
+        }
+    }
+
+    /// The day after the period starting on `start`.
+    fn period_end(self, start: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            InvoiceCadence::Weekly => start.checked_add_days(Days::new(7)),
+            InvoiceCadence::Monthly => start.checked_add_months(Months::new(1)),
+            InvoiceCadence::Quarterly => start.checked_add_months(Months::new(3)),
+            InvoiceCadence::Yearly => start.checked_add_months(Months::new(12)),
+        }
+    }
+
+    /// Monday, the first of the month, of the quarter or of the year.
+    fn current_period_start(self, day: NaiveDate) -> NaiveDate {
+        let start = match self {
+            InvoiceCadence::Weekly => {
+                Some(day - Duration::days(day.weekday().num_days_from_monday() as i64))
+            }
+            InvoiceCadence::Monthly => day.with_day(1),
+            InvoiceCadence::Quarterly => {
+                NaiveDate::from_ymd_opt(day.year(), day.month0() / 3 * 3 + 1, 1)
+            }
+            InvoiceCadence::Yearly => NaiveDate::from_ymd_opt(day.year(), 1, 1),
+        };
+        start.unwrap_or(day)
+    }
+}
+
+/// Everything but the client and period passed to `prepare_invoice` for each
+/// period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringInvoiceTemplate {
+    business_info: BusinessInfo,
+    business_profile_id: Option<i64>,
+    client_profile_id: Option<i64>,
+    line_mode: Option<InvoiceLineMode>,
+    group_by: Option<InvoiceGrouping>,
+    language: Option<InvoiceLanguage>,
+    /// Only `dueInDays` and the text; a fixed due date would have passed by
+    /// the second invoice.
+    payment_terms: Option<InvoicePaymentTerms>,
+    #[serde(default)]
+    timesheet: bool,
+    rounding: Option<InvoiceRounding>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringInvoiceRule {
+    pub id: i64,
+    pub client_id: i64,
+    pub cadence: InvoiceCadence,
+    #[serde(flatten)]
+    pub template: RecurringInvoiceTemplate,
+    /// First day of the next period to bill, as "YYYY-MM-DD". It's billed
+    /// once the period has ended.
+    pub next_period_start: String,
+    pub enabled: bool,
+    pub last_invoice_id: Option<i64>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringInvoiceRuleInput {
+    pub client_id: i64,
+    pub cadence: InvoiceCadence,
+    /// "YYYY-MM-DD" the first billed period starts on. Defaults to the start
+    /// of the current period; on update, leaving it out keeps the schedule.
+    pub starts_on: Option<String>,
+    #[serde(flatten)]
+    pub template: RecurringInvoiceTemplate,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Default)]
+pub struct RecurringInvoiceState {
+    /// The local day pending invoices were last generated for.
+    last_day: Mutex<Option<NaiveDate>>,
+    /// Held while invoices are generated, so the daily run and
+    /// `generate_pending_invoices` can't bill the same period twice.
+    generating: tauri::async_runtime::Mutex<()>,
+}
+
+#[tauri::command]
+pub async fn list_recurring_invoices(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<RecurringInvoiceRule>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        load_rules(&conn, false)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn create_recurring_invoice(
+    app_handle: tauri::AppHandle,
+    rule: RecurringInvoiceRuleInput,
+) -> Result<RecurringInvoiceRule, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let input = sanitize_rule_input(rule)?;
+    let start = input
+        .starts_on
+        .unwrap_or_else(|| format_day(input.cadence.current_period_start(today())));
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        check_references(&conn, input.client_id, &input.template)?;
+        conn.execute(
+            "INSERT INTO recurring_invoices (client_id, cadence, template, next_period_start, enabled, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                input.client_id,
+                input.cadence.as_str(),
+                input.template_json,
+                start,
+                input.enabled,
+                current_unix_timestamp()
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+        fetch_rule(&conn, conn.last_insert_rowid())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Changes apply from the next period billed; invoices already issued are
+/// kept.
+#[tauri::command]
+pub async fn update_recurring_invoice(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    rule: RecurringInvoiceRuleInput,
+) -> Result<RecurringInvoiceRule, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let input = sanitize_rule_input(rule)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        check_references(&conn, input.client_id, &input.template)?;
+        let changed = conn
+            .execute(
+                "UPDATE recurring_invoices
+                 SET client_id = ?1, cadence = ?2, template = ?3,
+                     next_period_start = COALESCE(?4, next_period_start), enabled = ?5
+                 WHERE id = ?6",
+                params![
+                    input.client_id,
+                    input.cadence.as_str(),
+                    input.template_json,
+                    input.starts_on,
+                    input.enabled,
+                    id
+                ],
+            )
+            .map_err(|err| err.to_string())?;
+        if changed == 0 {
+            return Err("Recurring invoice not found".into());
+        }
+        fetch_rule(&conn, id)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Deletes the rule; invoices it already issued are kept.
+#[tauri::command]
+pub async fn delete_recurring_invoice(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        conn.execute("DELETE FROM recurring_invoices WHERE id = ?1", params![id])
+            .map_err(|err| err.to_string())?;
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Issues every invoice whose period has ended and returns them. Rules that
+/// fail are logged and retried on the next run.
+#[tauri::command]
+pub async fn generate_pending_invoices(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<Invoice>, String> {
+    let invoices = generate_due_invoices(&app_handle).await?;
+    notify_generated(&app_handle, invoices.len());
+    Ok(invoices)
+}
+
+/// Called at launch and on every scheduler tick; generates pending invoices
+/// in the background once per local day. A failed run is retried the next
+/// day or from `generate_pending_invoices`.
+pub fn check_day_rollover(app: &AppHandle) {
+    let today = today();
+    let state = app.state::<RecurringInvoiceState>();
+    let mut last_day = state
+        .last_day
+        .lock()
+        .expect("recurring invoice state poisoned");
+    if *last_day == Some(today) {
+        return;
+    }
+    *last_day = Some(today);
+    drop(last_day);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match generate_due_invoices(&app).await {
+            Ok(invoices) => notify_generated(&app, invoices.len()),
+            Err(err) => eprintln!("Failed to generate recurring invoices: {}", err),
+        }
+    });
+}
+
+/// Bills each enabled rule's ended periods in order, one rule at a time; a
+/// rule that fails is logged and left on the period it failed on, without
+/// holding up the others.
+async fn generate_due_invoices(app: &AppHandle) -> Result<Vec<Invoice>, String> {
+    let state = app.state::<RecurringInvoiceState>();
+    let _generating = state.generating.lock().await;
+    let db_path = resolve_db_path(app)?;
+    let today = today();
+    let rules = tauri::async_runtime::spawn_blocking({
+        let db_path = db_path.clone();
+        move || {
+            let conn = open_connection(db_path)?;
+            load_rules(&conn, true)
+        }
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    let mut invoices = Vec::new();
+    for rule in rules {
+        if let Err(err) = generate_rule_invoices(app, &db_path, &rule, today, &mut invoices).await {
+            eprintln!("Failed to generate recurring invoice {}: {}", rule.id, err);
+        }
+    }
+    Ok(invoices)
+}
+
+/// Bills `rule`'s periods that ended by `today` through the regular
+/// `prepare_invoice` and `finalize_invoice` pipeline, moving the rule on to
+/// the next period after each. Invoices only bill tracked work: a period
+/// without uninvoiced work for the client is skipped without an invoice, so
+/// a client whose projects bill a fixed fee is only invoiced for periods
+/// with time tracked on them.
+async fn generate_rule_invoices(
+    app: &AppHandle,
+    db_path: &Path,
+    rule: &RecurringInvoiceRule,
+    today: NaiveDate,
+    invoices: &mut Vec<Invoice>,
+) -> Result<(), String> {
+    let Some(mut start) = parse_day(&rule.next_period_start) else {
+        return Err(format!(
+            "Unreadable next period \"{}\"",
+            rule.next_period_start
+        ));
+    };
+    let filter = InvoiceEntryFilter {
+        client_id: Some(rule.client_id),
+        ..InvoiceEntryFilter::default()
+    };
+    while let Some(end) = rule.cadence.period_end(start).filter(|end| *end <= today) {
+        let (start_time, end_time) = (day_start(start)?, day_start(end)?);
+        let has_work = tauri::async_runtime::spawn_blocking({
+            let db_path = db_path.to_path_buf();
+            let filter = filter.clone();
+            move || {
+                let conn = open_connection(db_path)?;
+                query_invoice_entries(&conn, Some(start_time), Some(end_time), &filter)
+                    .map(|entries| !entries.is_empty())
+            }
+        })
+        .await
+        .map_err(|err| err.to_string())??;
+
+        let mut invoice_id = None;
+        if has_work {
+            let template = rule.template.clone();
+            let draft = prepare_invoice(
+                app.clone(),
+                template.business_info,
+                Some(start_time),
+                Some(end_time),
+                Some(rule.client_id),
+                Some(filter.clone()),
+                template.client_profile_id,
+                template.business_profile_id,
+                template.line_mode,
+                template.group_by,
+                template.language,
+                template.payment_terms,
+                Some(template.timesheet),
+                template.rounding,
+            )
+            .await?;
+            let invoice = finalize_invoice(app.clone(), draft).await?;
+            invoice_id = Some(invoice.id);
+            invoices.push(invoice);
+        }
+
+        let next_start = format_day(end);
+        let rule_id = rule.id;
+        tauri::async_runtime::spawn_blocking({
+            let db_path = db_path.to_path_buf();
+            move || {
+                let conn = open_connection(db_path)?;
+                conn.execute(
+                    "UPDATE recurring_invoices
+                     SET next_period_start = ?1, last_invoice_id = COALESCE(?2, last_invoice_id)
+                     WHERE id = ?3",
+                    params![next_start, invoice_id, rule_id],
+                )
+                .map_err(|err| err.to_string())
+            }
+        })
+        .await
+        .map_err(|err| err.to_string())??;
+        start = end;
+    }
+    Ok(())
+}
+
+fn notify_generated(app: &AppHandle, generated: usize) {
+    if generated > 0 {
+        let _ = app.emit(RECURRING_INVOICES_EVENT, generated);
+    }
+}
+
+fn load_rules(conn: &Connection, enabled_only: bool) -> Result<Vec<RecurringInvoiceRule>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {RULE_COLUMNS} FROM recurring_invoices
+             WHERE ?1 = 0 OR enabled = 1
+             ORDER BY id ASC"
+        ))
+        .map_err(|err| err.to_string())?;
+    let rules = stmt
+        .query_map(params![enabled_only], map_rule)
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+    Ok(rules)
+}
+
+fn fetch_rule(conn: &Connection, id: i64) -> Result<RecurringInvoiceRule, String> {
+    conn.query_row(
+        &format!("SELECT {RULE_COLUMNS} FROM recurring_invoices WHERE id = ?1"),
+        params![id],
+        map_rule,
+    )
+    .map_err(|err| match err {
+        rusqlite::Error::QueryReturnedNoRows => "Recurring invoice not found".to_string(),
+        other => other.to_string(),
+    })
+}
+
+fn map_rule(row: &rusqlite::Row) -> rusqlite::Result<RecurringInvoiceRule> {
+    let cadence: String = row.get(2)?;
+    let template: String = row.get(3)?;
+    Ok(RecurringInvoiceRule {
+        id: row.get(0)?,
+        client_id: row.get(1)?,
+        cadence: InvoiceCadence::parse(&cadence),
+        template: serde_json::from_str(&template).map_err(|err| {
+            rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(err))
+        })?,
+        next_period_start: row.get(4)?,
+        enabled: row.get(5)?,
+        last_invoice_id: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+/// The client and any profiles the template names must exist.
+fn check_references(
+    conn: &Connection,
+    client_id: i64,
+    template: &RecurringInvoiceTemplate,
+) -> Result<(), String> {
+    clients::fetch_client(conn, client_id)?;
+    if let Some(id) = template.business_profile_id {
+        profiles::fetch_business_profile(conn, id)?;
+    }
+    if let Some(id) = template.client_profile_id {
+        profiles::fetch_bill_to_profile(conn, id)?;
+    }
+    Ok(())
+}
+
+struct SanitizedRule {
+    client_id: i64,
+    cadence: InvoiceCadence,
+    starts_on: Option<String>,
+    template: RecurringInvoiceTemplate,
+    /// `template` as stored.
+    template_json: String,
+    enabled: bool,
+}
+
+fn sanitize_rule_input(input: RecurringInvoiceRuleInput) -> Result<SanitizedRule, String> {
+    let starts_on = match input.starts_on.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(value) => {
+            let day = parse_day(value).ok_or("Start dates must be YYYY-MM-DD")?;
+            // Anchoring on the 29th to 31st would drift in shorter months.
+            if input.cadence != InvoiceCadence::Weekly && day.day() > 28 {
+                return Err(
+                    "Monthly, quarterly and yearly invoices must start on day 1 to 28".into(),
+                );
+            }
+            Some(format_day(day))
+        }
+    };
+    if input
+        .template
+        .payment_terms
+        .as_ref()
+        .is_some_and(|terms| terms.due_date.is_some())
+    {
+        return Err("Recurring invoices take a number of days to pay, not a due date".into());
+    }
+    if let Some(rounding) = input.template.rounding.as_ref() {
+        rounding.validate()?;
+    }
+    let template_json = serde_json::to_string(&input.template).map_err(|err| err.to_string())?;
+
+    Ok(SanitizedRule {
+        client_id: input.client_id,
+        cadence: input.cadence,
+        starts_on,
+        template: input.template,
+        template_json,
+        enabled: input.enabled.unwrap_or(true),
+    })
+}
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+fn parse_day(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+fn format_day(day: NaiveDate) -> String {
+    day.format("%Y-%m-%d").to_string()
+}
+
+/// Local midnight at the start of `day`.
+fn day_start(day: NaiveDate) -> Result<i64, String> {
+    day.and_hms_opt(0, 0, 0)
+        .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+        .map(|midnight| midnight.timestamp())
+        .ok_or_else(|| "Unable to resolve local time".to_string())
+}
@@ -0,0 +1,259 @@
+use std::{collections::HashSet, sync::Mutex};
+
+use chrono::{Datelike, Duration, Local, TimeZone};
+use rusqlite::params;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    current_settings, current_today_totals, current_unix_timestamp, day_bounds_timestamps,
+    format_duration, open_connection, resolve_db_path, send_notification, TimerState,
+    TimerStatusPayload,
+};
+
+pub const LONG_RUNNING_EVENT: &str = "timer://long-running";
+pub const FORGOT_TO_START_EVENT: &str = "reminder://forgot-to-start";
+pub const TARGET_REACHED_EVENT: &str = "timer://target-reached";
+pub const DAILY_CAP_EVENT: &str = "reminder://daily-cap";
+
+/// Reminders already sent, so each fires once per timer or work block.
+#[derive(Default)]
+pub struct ReminderState {
+    long_running: Mutex<HashSet<i64>>,
+    /// Start timestamps of work blocks already reminded about.
+    forgot_to_start: Mutex<HashSet<i64>>,
+    targets_reached: Mutex<HashSet<i64>>,
+    /// Start of the day the daily cap warning last fired for.
+    daily_cap_day: Mutex<Option<i64>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DailyCapPayload {
+    cap_hours: f64,
+    tracked_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ForgotToStartPayload {
+    block_start: i64,
+    block_end: i64,
+    minutes_into_block: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct LongRunningPayload {
+    #[serde(flatten)]
+    timer: TimerStatusPayload,
+    threshold_hours: u32,
+}
+
+/// Reminds once per timer when it has tracked longer than the configured
+/// number of hours, typically because it was left running overnight.
+pub fn check_long_running_timers(app: &AppHandle) {
+    let Ok(settings) = current_settings(app) else {
+        return;
+    };
+    let threshold_hours = settings.long_running_reminder_hours;
+    if threshold_hours == 0 {
+        return;
+    }
+
+    let statuses = app.state::<TimerState>().statuses();
+    let state = app.state::<ReminderState>();
+    let mut reminded = state.long_running.lock().expect("reminder state poisoned");
+    // Forget stopped timers so the set doesn't grow without bound.
+    reminded.retain(|id| statuses.iter().any(|status| status.timer_id == Some(*id)));
+
+    for status in statuses {
+        let (Some(timer_id), Some(elapsed)) = (status.timer_id, status.elapsed_seconds) else {
+            continue;
+        };
+        if elapsed < i64::from(threshold_hours) * 3600 || !reminded.insert(timer_id) {
+            continue;
+        }
+
+        send_notification(
+            app,
+            "Timer still running",
+            &format!(
+                "{} has been running for {}. Did you forget to stop it?",
+                status.project_name.as_deref().unwrap_or("A timer"),
+                format_duration(elapsed)
+            ),
+        );
+        let _ = app.emit(
+            LONG_RUNNING_EVENT,
+            LongRunningPayload {
+                timer: status,
+                threshold_hours,
+            },
+        );
+    }
+}
+
+/// Reminds once per work block when, `forgot_to_start_minutes` into it, no
+/// timer is running and nothing has been tracked since the block began.
+pub fn check_forgot_to_start(app: &AppHandle) {
+    let Ok(settings) = current_settings(app) else {
+        return;
+    };
+    if settings.working_hours.is_empty() || !app.state::<TimerState>().statuses().is_empty() {
+        return;
+    }
+
+    let now = Local::now();
+    let today = now.date_naive();
+    let weekday = today.weekday().number_from_monday();
+    let grace = Duration::minutes(i64::from(settings.forgot_to_start_minutes));
+    let block = settings.working_hours.iter().find_map(|block| {
+        if !block.days.contains(&weekday) {
+            return None;
+        }
+        let (start, end) = block.bounds()?;
+        let start = Local
+            .from_local_datetime(&today.and_time(start))
+            .earliest()?;
+        let end = Local.from_local_datetime(&today.and_time(end)).earliest()?;
+        (start + grace <= now && now < end).then_some((start.timestamp(), end.timestamp()))
+    });
+    let Some((block_start, block_end)) = block else {
+        return;
+    };
+
+    let state = app.state::<ReminderState>();
+    let mut reminded = state
+        .forgot_to_start
+        .lock()
+        .expect("reminder state poisoned");
+    if reminded.contains(&block_start) {
+        return;
+    }
+    let tracked: i64 = match resolve_db_path(app)
+        .and_then(open_connection)
+        .and_then(|conn| {
+            conn.query_row(
+                "SELECT COUNT(*) FROM time_entries WHERE end_time > ?1",
+                params![block_start],
+                |row| row.get(0),
+            )
+            .map_err(|err| err.to_string())
+        }) {
+        Ok(count) => count,
+        Err(_) => return,
+    };
+    // Either way this block is settled; it is checked once, not re-armed.
+    reminded.retain(|start| *start > current_unix_timestamp() - 24 * 3600);
+    reminded.insert(block_start);
+    if tracked > 0 {
+        return;
+    }
+
+    let minutes_into_block = (now.timestamp() - block_start) / 60;
+    send_notification(
+        app,
+        "Forgot to start a timer?",
+        &format!(
+            "You're {} minutes into your working hours and nothing is being tracked.",
+            minutes_into_block
+        ),
+    );
+    let _ = app.emit(
+        FORGOT_TO_START_EVENT,
+        ForgotToStartPayload {
+            block_start,
+            block_end,
+            minutes_into_block,
+        },
+    );
+}
+
+/// Notifies once when each countdown timer reaches its target.
+pub fn check_timer_targets(app: &AppHandle) {
+    let statuses = app.state::<TimerState>().statuses();
+    if statuses
+        .iter()
+        .all(|status| status.target_seconds.is_none())
+    {
+        return;
+    }
+
+    let state = app.state::<ReminderState>();
+    let mut reached = state
+        .targets_reached
+        .lock()
+        .expect("reminder state poisoned");
+    reached.retain(|id| statuses.iter().any(|status| status.timer_id == Some(*id)));
+    for status in statuses {
+        let (Some(timer_id), Some(0)) = (status.timer_id, status.remaining_seconds) else {
+            continue;
+        };
+        if !reached.insert(timer_id) {
+            continue;
+        }
+        send_notification(
+            app,
+            "Target reached",
+            &format!(
+                "{} has reached its target of {}.",
+                status.project_name.as_deref().unwrap_or("Your timer"),
+                format_duration(status.target_seconds.unwrap_or_default())
+            ),
+        );
+        let _ = app.emit(TARGET_REACHED_EVENT, status);
+    }
+}
+
+/// Warns once a day when today's saved entries plus the running timers pass
+/// the configured cap.
+pub fn check_daily_cap(app: &AppHandle) {
+    let Ok(settings) = current_settings(app) else {
+        return;
+    };
+    let Some(cap_hours) = settings.daily_hours_cap.filter(|cap| *cap > 0.0) else {
+        return;
+    };
+    let Ok((day_start, _)) = day_bounds_timestamps(None) else {
+        return;
+    };
+    let state = app.state::<ReminderState>();
+    let mut alerted_day = state.daily_cap_day.lock().expect("reminder state poisoned");
+    if *alerted_day == Some(day_start) {
+        return;
+    }
+    let Ok(totals) = current_today_totals(app) else {
+        return;
+    };
+
+    // A timer started before midnight only counts its time since then.
+    let since_midnight = current_unix_timestamp() - day_start;
+    let running: i64 = app
+        .state::<TimerState>()
+        .statuses()
+        .iter()
+        .filter_map(|status| status.elapsed_seconds)
+        .map(|elapsed| elapsed.min(since_midnight))
+        .sum();
+    let tracked_seconds = totals.total_seconds + running;
+    if (tracked_seconds as f64) < cap_hours * 3600.0 {
+        return;
+    }
+    *alerted_day = Some(day_start);
+    drop(alerted_day);
+
+    send_notification(
+        app,
+        "Daily limit reached",
+        &format!(
+            "You've tracked {} today, past your {:.1} hour limit.",
+            format_duration(tracked_seconds),
+            cap_hours
+        ),
+    );
+    let _ = app.emit(
+        DAILY_CAP_EVENT,
+        DailyCapPayload {
+            cap_hours,
+            tracked_seconds,
+        },
+    );
+}
@@ -0,0 +1,1974 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, Duration, Local, Months, NaiveDate, TimeZone, Timelike, Utc, Weekday};
+use rusqlite::{params, params_from_iter, types::Value as SqlValue, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::{
+    current_settings, current_unix_timestamp, fixed_offset, invoice_fonts, open_connection,
+    pdf_generator::{
+        generate_monthly_report as generate_monthly_report_pdf, generate_table_report,
+        MonthlyReportDocument, ReportEarnings, ReportProject, ReportTable, TableReportDocument,
+    },
+    resolve_db_path, INVOICE_STATUS_PAID, INVOICE_TYPE_CREDIT_NOTE, INVOICE_TYPE_INVOICE,
+};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTotals {
+    pub project_name: String,
+    pub currency: String,
+    pub hours: f64,
+    pub amount: f64,
+    pub entry_count: i64,
+}
+
+/// Range totals for one client and its projects. Projects without a client
+/// are grouped under a `None` client id. `amount` is only meaningful when all
+/// of the client's projects share a currency; see each project's `currency`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientTotals {
+    pub client_id: Option<i64>,
+    pub client_name: Option<String>,
+    pub hours: f64,
+    pub amount: f64,
+    pub entry_count: i64,
+    pub projects: Vec<ProjectTotals>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimateComparison {
+    pub project_name: String,
+    pub estimated_hours: f64,
+    pub actual_hours: f64,
+    /// Actual minus estimate; positive means over.
+    pub variance_hours: f64,
+    pub variance_percent: f64,
+    pub weeks: Vec<EstimateWeek>,
+}
+
+/// Hours logged in one local week and the running total up to its end.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimateWeek {
+    pub week_start: i64,
+    pub hours: f64,
+    pub cumulative_hours: f64,
+    pub percent_of_estimate: f64,
+}
+
+#[tauri::command]
+pub async fn get_client_project_totals(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+) -> Result<Vec<ClientTotals>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_client_project_totals(&conn, start_time, end_time)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub fn query_client_project_totals(
+    conn: &Connection,
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<Vec<ClientTotals>, String> {
+    // Unassigned projects sort last, after every named client.
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.client_id, c.name, e.project_name,
+                    COALESCE(SUM(e.duration), 0), COALESCE(SUM(e.amount), 0), COUNT(*),
+                    COALESCE(p.currency, c.currency, 'USD')
+             FROM time_entries e
+             LEFT JOIN projects p ON p.name = e.project_name
+             LEFT JOIN clients c ON c.id = p.client_id
+             WHERE e.start_time >= ?1 AND e.start_time < ?2 AND e.entry_type = 'work'
+             GROUP BY p.client_id, e.project_name
+             ORDER BY c.name IS NULL, c.name COLLATE NOCASE, p.client_id,
+                      e.project_name COLLATE NOCASE",
+        )
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map(params![start_ts, end_ts], |row| {
+            Ok((
+                row.get::<_, Option<i64>>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                ProjectTotals {
+                    project_name: row.get(2)?,
+                    hours: row.get::<_, i64>(3)? as f64 / 3600.0,
+                    amount: row.get(4)?,
+                    entry_count: row.get(5)?,
+                    currency: row.get(6)?,
+                },
+            ))
+        })
+        .map_err(|err| err.to_string())?;
+
+    let mut clients: Vec<ClientTotals> = Vec::new();
+    for row in rows {
+        let (client_id, client_name, project) = row.map_err(|err| err.to_string())?;
+        // Rows arrive ordered by client, so a new id starts a new group.
+        if clients.last().map(|client| client.client_id) != Some(client_id) {
+            clients.push(ClientTotals {
+                client_id,
+                client_name,
+                hours: 0.0,
+                amount: 0.0,
+                entry_count: 0,
+                projects: Vec::new(),
+            });
+        }
+        let client = clients.last_mut().expect("client group was just pushed");
+        client.hours += project.hours;
+        client.amount += project.amount;
+        client.entry_count += project.entry_count;
+        client.projects.push(project);
+    }
+
+    Ok(clients)
+}
+
+/// Billable earnings of one project, split by whether they have been
+/// invoiced yet.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectEarnings {
+    pub project_name: String,
+    pub currency: String,
+    pub hours: f64,
+    pub amount: f64,
+    pub invoiced_amount: f64,
+    pub uninvoiced_hours: f64,
+    pub uninvoiced_amount: f64,
+}
+
+/// Billable earnings of one client and its projects, grouped like
+/// `ClientTotals`; amounts add up every currency its projects use.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientEarnings {
+    pub client_id: Option<i64>,
+    pub client_name: Option<String>,
+    pub hours: f64,
+    pub amount: f64,
+    pub invoiced_amount: f64,
+    pub uninvoiced_hours: f64,
+    pub uninvoiced_amount: f64,
+    pub projects: Vec<ProjectEarnings>,
+}
+
+/// Billable work started in `start_time..end_time` by client and project,
+/// with how much of it is still waiting to be invoiced. Non-billable work
+/// earns nothing and is left out.
+#[tauri::command]
+pub async fn get_earnings_report(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+) -> Result<Vec<ClientEarnings>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_earnings_report(&conn, start_time, end_time)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub fn query_earnings_report(
+    conn: &Connection,
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<Vec<ClientEarnings>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.client_id, c.name, e.project_name,
+                    COALESCE(p.currency, c.currency, 'USD'),
+                    SUM(e.duration), SUM(e.amount),
+                    SUM(CASE WHEN e.invoice_id IS NULL THEN 0 ELSE e.amount END),
+                    SUM(CASE WHEN e.invoice_id IS NULL THEN e.duration ELSE 0 END),
+                    SUM(CASE WHEN e.invoice_id IS NULL THEN e.amount ELSE 0 END)
+             FROM time_entries e
+             LEFT JOIN projects p ON p.name = e.project_name
+             LEFT JOIN clients c ON c.id = p.client_id
+             WHERE e.start_time >= ?1 AND e.start_time < ?2 AND e.entry_type = 'work'
+               AND e.billable = 1
+             GROUP BY p.client_id, e.project_name
+             ORDER BY c.name IS NULL, c.name COLLATE NOCASE, p.client_id,
+                      e.project_name COLLATE NOCASE",
+        )
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map(params![start_ts, end_ts], |row| {
+            Ok((
+                row.get::<_, Option<i64>>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                ProjectEarnings {
+                    project_name: row.get(2)?,
+                    currency: row.get(3)?,
+                    hours: row.get::<_, i64>(4)? as f64 / 3600.0,
+                    amount: row.get(5)?,
+                    invoiced_amount: row.get(6)?,
+                    uninvoiced_hours: row.get::<_, i64>(7)? as f64 / 3600.0,
+                    uninvoiced_amount: row.get(8)?,
+                },
+            ))
+        })
+        .map_err(|err| err.to_string())?;
+
+    let mut clients: Vec<ClientEarnings> = Vec::new();
+    for row in rows {
+        let (client_id, client_name, project) = row.map_err(|err| err.to_string())?;
+        // Rows arrive ordered by client, so a new id starts a new group.
+        if clients.last().map(|client| client.client_id) != Some(client_id) {
+            clients.push(ClientEarnings {
+                client_id,
+                client_name,
+                hours: 0.0,
+                amount: 0.0,
+                invoiced_amount: 0.0,
+                uninvoiced_hours: 0.0,
+                uninvoiced_amount: 0.0,
+                projects: Vec::new(),
+            });
+        }
+        let client = clients.last_mut().expect("client group was just pushed");
+        client.hours += project.hours;
+        client.amount += project.amount;
+        client.invoiced_amount += project.invoiced_amount;
+        client.uninvoiced_hours += project.uninvoiced_hours;
+        client.uninvoiced_amount += project.uninvoiced_amount;
+        client.projects.push(project);
+    }
+
+    Ok(clients)
+}
+
+/// Work and break time for one local day.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakDay {
+    pub day_start: i64,
+    pub work_seconds: i64,
+    pub break_seconds: i64,
+    pub break_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakReport {
+    pub work_seconds: i64,
+    pub break_seconds: i64,
+    pub break_count: i64,
+    pub days: Vec<BreakDay>,
+}
+
+/// Breaks versus work over a range, per local day, for days with any entries.
+#[tauri::command]
+pub async fn get_break_report(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+) -> Result<BreakReport, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_break_report(&conn, start_time, end_time)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub fn query_break_report(
+    conn: &Connection,
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<BreakReport, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT start_time, duration, entry_type
+             FROM time_entries
+             WHERE start_time >= ?1 AND start_time < ?2
+             ORDER BY start_time",
+        )
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map(params![start_ts, end_ts], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|err| err.to_string())?;
+
+    let mut report = BreakReport {
+        work_seconds: 0,
+        break_seconds: 0,
+        break_count: 0,
+        days: Vec::new(),
+    };
+    for row in rows {
+        let (start_time, duration, entry_type) = row.map_err(|err| err.to_string())?;
+        let day_start = local_day_start(start_time);
+        if report.days.last().map(|day| day.day_start) != Some(day_start) {
+            report.days.push(BreakDay {
+                day_start,
+                work_seconds: 0,
+                break_seconds: 0,
+                break_count: 0,
+            });
+        }
+        let day = report.days.last_mut().expect("day group was just pushed");
+        if entry_type == "break" {
+            day.break_seconds += duration;
+            day.break_count += 1;
+            report.break_seconds += duration;
+            report.break_count += 1;
+        } else {
+            day.work_seconds += duration;
+            report.work_seconds += duration;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Work tracked on one local day. `amount` adds up every currency.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailySummary {
+    /// Local "YYYY-MM-DD".
+    pub date: String,
+    pub day_start: i64,
+    pub total_seconds: i64,
+    pub amount: f64,
+    pub entry_count: i64,
+}
+
+/// One row per local day with work in `start_time..end_time`, oldest first.
+/// Days without entries are left out. Days are taken at `utc_offset` minutes
+/// east of UTC when given; otherwise each entry counts toward the day where it
+/// was tracked, falling back to the device's zone for older entries.
+#[tauri::command]
+pub async fn get_daily_summaries(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+    utc_offset: Option<i32>,
+) -> Result<Vec<DailySummary>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let shift_seconds = offset_seconds(utc_offset)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut stmt = conn
+            .prepare(
+                "WITH shifted AS (
+                     SELECT duration, amount,
+                            COALESCE(
+                                ?3,
+                                utc_offset * 60,
+                                CAST(strftime('%s', start_time, 'unixepoch', 'localtime') AS INTEGER) - start_time
+                            ) AS shift,
+                            start_time
+                     FROM time_entries
+                     WHERE start_time >= ?1 AND start_time < ?2 AND entry_type = 'work'
+                 )
+                 SELECT date(start_time + shift, 'unixepoch') AS day,
+                        MIN(CAST(strftime('%s', date(start_time + shift, 'unixepoch')) AS INTEGER) - shift),
+                        COALESCE(SUM(duration), 0),
+                        COALESCE(SUM(amount), 0),
+                        COUNT(*)
+                 FROM shifted
+                 GROUP BY day
+                 ORDER BY day ASC",
+            )
+            .map_err(|err| err.to_string())?;
+        let summaries = stmt
+            .query_map(params![start_time, end_time, shift_seconds], |row| {
+                Ok(DailySummary {
+                    date: row.get(0)?,
+                    day_start: row.get(1)?,
+                    total_seconds: row.get(2)?,
+                    amount: row.get(3)?,
+                    entry_count: row.get(4)?,
+                })
+            })
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        Ok(summaries)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Work tracked on one local day, for charts.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyTotal {
+    /// Local "YYYY-MM-DD".
+    pub date: String,
+    pub seconds: i64,
+    /// Adds up every currency.
+    pub amount: f64,
+}
+
+/// Every local day touched by `start_time..end_time` in order, with zeros
+/// for days without work so each day gets a bar. Days are taken at
+/// `utc_offset` minutes east of UTC when given, else in the device's zone.
+#[tauri::command]
+pub async fn get_daily_totals(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+    utc_offset: Option<i32>,
+) -> Result<Vec<DailyTotal>, String> {
+    if end_time <= start_time {
+        return Err("The end of the range must be after its start".into());
+    }
+    let db_path = resolve_db_path(&app_handle)?;
+    let shift_seconds = offset_seconds(utc_offset)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_daily_totals(&conn, start_time, end_time, shift_seconds)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// `shift_seconds` is from `offset_seconds`.
+pub fn query_daily_totals(
+    conn: &Connection,
+    start_ts: i64,
+    end_ts: i64,
+    shift_seconds: Option<i64>,
+) -> Result<Vec<DailyTotal>, String> {
+    let first = date_at(start_ts, shift_seconds)?;
+    let last = date_at(end_ts - 1, shift_seconds)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT CASE WHEN ?3 IS NULL THEN date(start_time, 'unixepoch', 'localtime')
+                         ELSE date(start_time + ?3, 'unixepoch') END AS day,
+                    SUM(duration), SUM(amount)
+             FROM time_entries
+             WHERE start_time >= ?1 AND start_time < ?2 AND entry_type = 'work'
+             GROUP BY day",
+        )
+        .map_err(|err| err.to_string())?;
+    let mut worked = stmt
+        .query_map(params![start_ts, end_ts, shift_seconds], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                (row.get::<_, i64>(1)?, row.get::<_, f64>(2)?),
+            ))
+        })
+        .map_err(|err| err.to_string())?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    Ok(first
+        .iter_days()
+        .take_while(|day| *day <= last)
+        .map(|day| {
+            let date = day.format("%Y-%m-%d").to_string();
+            let (seconds, amount) = worked.remove(&date).unwrap_or((0, 0.0));
+            DailyTotal {
+                date,
+                seconds,
+                amount,
+            }
+        })
+        .collect())
+}
+
+/// Tracked time on every day of a year, for a contribution-style heatmap.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityHeatmap {
+    pub year: i32,
+    /// The busiest day's time, to scale colours against.
+    pub max_seconds: i64,
+    /// Every day from January 1st in order, including days without work.
+    pub days: Vec<HeatmapDay>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapDay {
+    /// Local "YYYY-MM-DD".
+    pub date: String,
+    pub seconds: i64,
+}
+
+/// Days are taken at `utc_offset` minutes east of UTC when given, else in
+/// the device's zone.
+#[tauri::command]
+pub async fn get_activity_heatmap(
+    app_handle: tauri::AppHandle,
+    year: i32,
+    utc_offset: Option<i32>,
+) -> Result<ActivityHeatmap, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let shift_seconds = offset_seconds(utc_offset)?;
+    let first = NaiveDate::from_ymd_opt(year, 1, 1).ok_or("Invalid year")?;
+    let next = NaiveDate::from_ymd_opt(year + 1, 1, 1).ok_or("Invalid year")?;
+    let start_ts = midnight_at(first, shift_seconds)?;
+    let end_ts = midnight_at(next, shift_seconds)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let days: Vec<HeatmapDay> = query_daily_totals(&conn, start_ts, end_ts, shift_seconds)?
+            .into_iter()
+            .map(|day| HeatmapDay {
+                date: day.date,
+                seconds: day.seconds,
+            })
+            .collect();
+        Ok(ActivityHeatmap {
+            year,
+            max_seconds: days.iter().map(|day| day.seconds).max().unwrap_or(0),
+            days,
+        })
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// When tracked time falls, by local hour of day and day of week.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeDistribution {
+    pub total_seconds: i64,
+    /// 24 buckets, midnight to 11 pm.
+    pub hours: Vec<HourBucket>,
+    /// 7 buckets, Monday first.
+    pub weekdays: Vec<WeekdayBucket>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HourBucket {
+    pub hour: u32,
+    pub seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeekdayBucket {
+    /// ISO weekday: 1 for Monday through 7 for Sunday.
+    pub weekday: u32,
+    pub seconds: i64,
+}
+
+/// Buckets work started in `start_time..end_time` by the local hours and
+/// weekdays it was done in. An entry spanning several hours is split across
+/// them, scaled so its buckets add up to its recorded duration.
+#[tauri::command]
+pub async fn get_time_distribution(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+) -> Result<TimeDistribution, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_time_distribution(&conn, start_time, end_time)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub fn query_time_distribution(
+    conn: &Connection,
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<TimeDistribution, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT start_time, end_time, duration FROM time_entries
+             WHERE start_time >= ?1 AND start_time < ?2 AND entry_type = 'work'
+               AND end_time > start_time",
+        )
+        .map_err(|err| err.to_string())?;
+    let entries = stmt
+        .query_map(params![start_ts, end_ts], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    let mut hours = [0.0_f64; 24];
+    let mut weekdays = [0.0_f64; 7];
+    for (start, end, duration) in entries {
+        // Pauses and rounding make the duration differ from the span.
+        let scale = duration as f64 / (end - start) as f64;
+        let mut at = start;
+        while at < end {
+            let Some(local) = Local.timestamp_opt(at, 0).single() else {
+                break;
+            };
+            let into_hour = i64::from(local.minute() * 60 + local.second());
+            let slice_end = (at - into_hour + 3600).min(end);
+            let seconds = (slice_end - at) as f64 * scale;
+            hours[local.hour() as usize] += seconds;
+            weekdays[local.weekday().num_days_from_monday() as usize] += seconds;
+            at = slice_end;
+        }
+    }
+
+    Ok(TimeDistribution {
+        total_seconds: hours.iter().sum::<f64>().round() as i64,
+        hours: (0..24)
+            .map(|hour| HourBucket {
+                hour,
+                seconds: hours[hour as usize].round() as i64,
+            })
+            .collect(),
+        weekdays: (1..=7)
+            .map(|weekday| WeekdayBucket {
+                weekday,
+                seconds: weekdays[weekday as usize - 1].round() as i64,
+            })
+            .collect(),
+    })
+}
+
+/// Time per segment label within a project; see `segments`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentTotals {
+    pub project_name: String,
+    pub label: String,
+    pub seconds: i64,
+    pub segment_count: i64,
+}
+
+#[tauri::command]
+pub async fn get_segment_totals(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+) -> Result<Vec<SegmentTotals>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT e.project_name, s.label,
+                        COALESCE(SUM(s.end_time - s.start_time), 0), COUNT(*)
+                 FROM time_entry_segments s
+                 JOIN time_entries e ON e.id = s.entry_id
+                 WHERE e.start_time >= ?1 AND e.start_time < ?2
+                 GROUP BY e.project_name, s.label
+                 ORDER BY e.project_name COLLATE NOCASE, 3 DESC",
+            )
+            .map_err(|err| err.to_string())?;
+        let totals = stmt
+            .query_map(params![start_time, end_time], |row| {
+                Ok(SegmentTotals {
+                    project_name: row.get(0)?,
+                    label: row.get(1)?,
+                    seconds: row.get(2)?,
+                    segment_count: row.get(3)?,
+                })
+            })
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        Ok(totals)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Compares each estimated project's tracked hours to its estimate, with a
+/// week-by-week running total so overruns can be traced to when they began.
+#[tauri::command]
+pub async fn get_estimate_report(
+    app_handle: tauri::AppHandle,
+    include_archived: Option<bool>,
+) -> Result<Vec<EstimateComparison>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let include_archived = include_archived.unwrap_or(false);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_estimate_report(&conn, include_archived)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub fn query_estimate_report(
+    conn: &Connection,
+    include_archived: bool,
+) -> Result<Vec<EstimateComparison>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.name, p.estimated_hours, e.start_time, e.duration
+             FROM projects p
+             LEFT JOIN time_entries e ON e.project_name = p.name AND e.entry_type = 'work'
+             WHERE p.estimated_hours IS NOT NULL AND (?1 OR p.archived = 0)
+             ORDER BY p.name COLLATE NOCASE, e.start_time",
+        )
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map(params![include_archived], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+            ))
+        })
+        .map_err(|err| err.to_string())?;
+
+    let mut report: Vec<EstimateComparison> = Vec::new();
+    for row in rows {
+        let (project_name, estimated_hours, start_time, duration) =
+            row.map_err(|err| err.to_string())?;
+        if report.last().map(|item| &item.project_name) != Some(&project_name) {
+            report.push(EstimateComparison {
+                project_name,
+                estimated_hours,
+                actual_hours: 0.0,
+                variance_hours: 0.0,
+                variance_percent: 0.0,
+                weeks: Vec::new(),
+            });
+        }
+        let item = report.last_mut().expect("project group was just pushed");
+        // A project with no entries still appears, with zero actuals.
+        let (Some(start_time), Some(duration)) = (start_time, duration) else {
+            continue;
+        };
+
+        let hours = duration as f64 / 3600.0;
+        item.actual_hours += hours;
+        let week_start = local_week_start(start_time);
+        match item.weeks.last_mut() {
+            Some(week) if week.week_start == week_start => week.hours += hours,
+            _ => item.weeks.push(EstimateWeek {
+                week_start,
+                hours,
+                cumulative_hours: 0.0,
+                percent_of_estimate: 0.0,
+            }),
+        }
+    }
+
+    for item in &mut report {
+        item.variance_hours = item.actual_hours - item.estimated_hours;
+        item.variance_percent = item.variance_hours / item.estimated_hours * 100.0;
+        let mut cumulative = 0.0;
+        for week in &mut item.weeks {
+            cumulative += week.hours;
+            week.cumulative_hours = cumulative;
+            week.percent_of_estimate = cumulative / item.estimated_hours * 100.0;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Totals for one week, by project and by local day.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklySummary {
+    /// Local midnight starting the week, and starting the next one.
+    pub week_start: i64,
+    pub week_end: i64,
+    pub total_seconds: i64,
+    /// Summed across currencies; see each project's currency.
+    pub amount: f64,
+    pub entry_count: i64,
+    /// Most time first.
+    pub projects: Vec<WeeklyProjectTotal>,
+    /// All seven days in order, including days without work.
+    pub days: Vec<WeeklyDayTotal>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyProjectTotal {
+    pub project_name: String,
+    pub currency: String,
+    pub seconds: i64,
+    pub amount: f64,
+    pub entry_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyDayTotal {
+    /// Local "YYYY-MM-DD".
+    pub date: String,
+    pub day_start: i64,
+    pub seconds: i64,
+    pub amount: f64,
+    pub entry_count: i64,
+}
+
+/// The week containing `week_start`, which is snapped back to the first day
+/// of the week set in settings. Entries count toward the local day and week
+/// they started in, taken at `utc_offset` minutes east of UTC when given.
+#[tauri::command]
+pub async fn get_weekly_summary(
+    app_handle: tauri::AppHandle,
+    week_start: i64,
+    utc_offset: Option<i32>,
+) -> Result<WeeklySummary, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let first_day = current_settings(&app_handle)?.week_start();
+    let shift_seconds = offset_seconds(utc_offset)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_weekly_summary(&conn, week_start, first_day, shift_seconds)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// `shift_seconds` is from `offset_seconds`.
+pub fn query_weekly_summary(
+    conn: &Connection,
+    ts: i64,
+    first_day: Weekday,
+    shift_seconds: Option<i64>,
+) -> Result<WeeklySummary, String> {
+    let day = date_at(ts, shift_seconds).map_err(|_| "Invalid week start")?;
+    let offset = (7 + day.weekday().num_days_from_monday() - first_day.num_days_from_monday()) % 7;
+    let first = day - Duration::days(i64::from(offset));
+    let dates: Vec<NaiveDate> = (0..7).map(|n| first + Duration::days(n)).collect();
+    let week_start = midnight_at(first, shift_seconds)?;
+    let week_end = midnight_at(first + Duration::days(7), shift_seconds)?;
+    let projects = query_range_projects(conn, week_start, week_end)?;
+    let days = query_range_days(conn, &dates, shift_seconds)?;
+
+    Ok(WeeklySummary {
+        week_start,
+        week_end,
+        total_seconds: projects.iter().map(|project| project.seconds).sum(),
+        amount: projects.iter().map(|project| project.amount).sum(),
+        entry_count: projects.iter().map(|project| project.entry_count).sum(),
+        projects,
+        days,
+    })
+}
+
+/// Totals per project of entries started between `start_ts` and `end_ts`,
+/// most time first.
+fn query_range_projects(
+    conn: &Connection,
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<Vec<WeeklyProjectTotal>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.project_name, COALESCE(p.currency, c.currency, 'USD'),
+                    SUM(e.duration), SUM(e.amount), COUNT(*)
+             FROM time_entries e
+             LEFT JOIN projects p ON p.name = e.project_name
+             LEFT JOIN clients c ON c.id = p.client_id
+             WHERE e.start_time >= ?1 AND e.start_time < ?2 AND e.entry_type = 'work'
+             GROUP BY e.project_name
+             ORDER BY SUM(e.duration) DESC, e.project_name COLLATE NOCASE",
+        )
+        .map_err(|err| err.to_string())?;
+    let projects = stmt
+        .query_map(params![start_ts, end_ts], |row| {
+            Ok(WeeklyProjectTotal {
+                project_name: row.get(0)?,
+                currency: row.get(1)?,
+                seconds: row.get(2)?,
+                amount: row.get(3)?,
+                entry_count: row.get(4)?,
+            })
+        })
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+    Ok(projects)
+}
+
+/// Totals for each of `dates`, which must be consecutive, with zeros for
+/// days without work.
+fn query_range_days(
+    conn: &Connection,
+    dates: &[NaiveDate],
+    shift_seconds: Option<i64>,
+) -> Result<Vec<WeeklyDayTotal>, String> {
+    let (Some(first), Some(last)) = (dates.first(), dates.last()) else {
+        return Ok(Vec::new());
+    };
+    let start_ts = midnight_at(*first, shift_seconds)?;
+    let end_ts = midnight_at(*last + Duration::days(1), shift_seconds)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT CASE WHEN ?3 IS NULL THEN date(start_time, 'unixepoch', 'localtime')
+                         ELSE date(start_time + ?3, 'unixepoch') END AS day,
+                    SUM(duration), SUM(amount), COUNT(*)
+             FROM time_entries
+             WHERE start_time >= ?1 AND start_time < ?2 AND entry_type = 'work'
+             GROUP BY day",
+        )
+        .map_err(|err| err.to_string())?;
+    let worked = stmt
+        .query_map(params![start_ts, end_ts, shift_seconds], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, f64>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    let mut days = Vec::with_capacity(dates.len());
+    for &date in dates {
+        let date_text = date.format("%Y-%m-%d").to_string();
+        let (seconds, amount, entry_count) = worked
+            .iter()
+            .find(|(day, ..)| *day == date_text)
+            .map_or((0, 0.0, 0), |(_, seconds, amount, count)| {
+                (*seconds, *amount, *count)
+            });
+        days.push(WeeklyDayTotal {
+            date: date_text,
+            day_start: midnight_at(date, shift_seconds)?,
+            seconds,
+            amount,
+            entry_count,
+        });
+    }
+    Ok(days)
+}
+
+/// Renders a PDF report of `month` ("YYYY-MM") into Downloads: totals
+/// against the month before, hours and earnings per project, and hours per
+/// day. Returns the PDF's path.
+#[tauri::command]
+pub async fn generate_monthly_report(
+    app_handle: tauri::AppHandle,
+    month: String,
+) -> Result<String, String> {
+    let first = NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+        .map_err(|_| "The month must be given as YYYY-MM".to_string())?;
+    let db_path = resolve_db_path(&app_handle)?;
+    let fonts = invoice_fonts(&current_settings(&app_handle)?)?;
+    let downloads_path = app_handle
+        .path()
+        .download_dir()
+        .map_err(|e| format!("Failed to get downloads directory: {}", e))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let next = first
+            .checked_add_months(Months::new(1))
+            .ok_or("Invalid month")?;
+        let previous = first
+            .checked_sub_months(Months::new(1))
+            .ok_or("Invalid month")?;
+        let month_start = local_midnight(first)?;
+        let current = query_range_projects(&conn, month_start, local_midnight(next)?)?;
+        let before = query_range_projects(&conn, local_midnight(previous)?, month_start)?;
+        let dates: Vec<NaiveDate> = first.iter_days().take_while(|day| *day < next).collect();
+        let days = query_range_days(&conn, &dates, None)?;
+
+        let mut earnings: BTreeMap<&str, (f64, f64)> = BTreeMap::new();
+        for project in &current {
+            earnings.entry(&project.currency).or_default().0 += project.amount;
+        }
+        for project in &before {
+            earnings.entry(&project.currency).or_default().1 += project.amount;
+        }
+        let previous_hours = |name: &str| {
+            before
+                .iter()
+                .find(|project| project.project_name == name)
+                .map_or(0.0, |project| project.seconds as f64 / 3600.0)
+        };
+        let mut projects: Vec<ReportProject> = current
+            .iter()
+            .map(|project| ReportProject {
+                name: project.project_name.clone(),
+                currency: project.currency.clone(),
+                hours: project.seconds as f64 / 3600.0,
+                amount: project.amount,
+                previous_hours: previous_hours(&project.project_name),
+            })
+            .collect();
+        for project in &before {
+            if !current
+                .iter()
+                .any(|c| c.project_name == project.project_name)
+            {
+                projects.push(ReportProject {
+                    name: project.project_name.clone(),
+                    currency: project.currency.clone(),
+                    hours: 0.0,
+                    amount: 0.0,
+                    previous_hours: project.seconds as f64 / 3600.0,
+                });
+            }
+        }
+
+        let document = MonthlyReportDocument {
+            month: first.format("%B %Y").to_string(),
+            previous_month: previous.format("%B %Y").to_string(),
+            hours: current.iter().map(|p| p.seconds).sum::<i64>() as f64 / 3600.0,
+            previous_hours: before.iter().map(|p| p.seconds).sum::<i64>() as f64 / 3600.0,
+            entry_count: current.iter().map(|p| p.entry_count).sum(),
+            previous_entry_count: before.iter().map(|p| p.entry_count).sum(),
+            earnings: earnings
+                .into_iter()
+                .map(|(currency, (amount, previous_amount))| ReportEarnings {
+                    currency: currency.to_string(),
+                    amount,
+                    previous_amount,
+                })
+                .collect(),
+            projects,
+            days: days
+                .iter()
+                .zip(&dates)
+                .map(|(day, date)| (*date, day.seconds as f64 / 3600.0))
+                .collect(),
+            fonts,
+            generated_at: current_unix_timestamp(),
+        };
+        let output_path =
+            downloads_path.join(format!("monthly_report_{}.pdf", first.format("%Y-%m")));
+        let output_path = output_path.to_str().ok_or("Invalid file path")?.to_string();
+        generate_monthly_report_pdf(document, &output_path)?;
+        Ok(output_path)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// One project's hours and earnings in a range and the range before it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectComparison {
+    pub project_name: String,
+    pub currency: String,
+    pub hours: f64,
+    pub previous_hours: f64,
+    pub hours_delta: f64,
+    pub amount: f64,
+    pub previous_amount: f64,
+    pub amount_delta: f64,
+}
+
+/// Earnings in one currency in a range and the range before it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrencyComparison {
+    pub currency: String,
+    pub amount: f64,
+    pub previous_amount: f64,
+    pub amount_delta: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodComparison {
+    pub start_time: i64,
+    pub end_time: i64,
+    pub previous_start_time: i64,
+    pub previous_end_time: i64,
+    pub hours: f64,
+    pub previous_hours: f64,
+    pub hours_delta: f64,
+    pub earnings: Vec<CurrencyComparison>,
+    /// Most time in the range first; projects only worked on in the range
+    /// before come last.
+    pub projects: Vec<ProjectComparison>,
+}
+
+/// Compares `start_time..end_time` with the equivalent range just before
+/// it: the same number of whole months or days when the range starts and
+/// ends at local midnight (so this month is compared with all of last
+/// month, and this week with last week), otherwise the same length of time.
+#[tauri::command]
+pub async fn compare_periods(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+) -> Result<PeriodComparison, String> {
+    if end_time <= start_time {
+        return Err("The end of the range must be after its start".into());
+    }
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_period_comparison(&conn, start_time, end_time)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub fn query_period_comparison(
+    conn: &Connection,
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<PeriodComparison, String> {
+    let previous_start = previous_range_start(start_ts, end_ts)?;
+    let current = query_range_projects(conn, start_ts, end_ts)?;
+    let before = query_range_projects(conn, previous_start, start_ts)?;
+
+    let mut projects: Vec<ProjectComparison> = Vec::new();
+    for (project, is_current) in current
+        .iter()
+        .map(|project| (project, true))
+        .chain(before.iter().map(|project| (project, false)))
+    {
+        let hours = project.seconds as f64 / 3600.0;
+        let existing = projects
+            .iter_mut()
+            .find(|compared| compared.project_name == project.project_name);
+        let compared = match existing {
+            Some(compared) => compared,
+            None => {
+                projects.push(ProjectComparison {
+                    project_name: project.project_name.clone(),
+                    currency: project.currency.clone(),
+                    hours: 0.0,
+                    previous_hours: 0.0,
+                    hours_delta: 0.0,
+                    amount: 0.0,
+                    previous_amount: 0.0,
+                    amount_delta: 0.0,
+                });
+                projects.last_mut().expect("project was just pushed")
+            }
+        };
+        if is_current {
+            compared.hours = hours;
+            compared.amount = project.amount;
+        } else {
+            compared.previous_hours = hours;
+            compared.previous_amount = project.amount;
+        }
+        compared.hours_delta = compared.hours - compared.previous_hours;
+        compared.amount_delta = compared.amount - compared.previous_amount;
+    }
+
+    let mut earnings: Vec<CurrencyComparison> = Vec::new();
+    for project in &projects {
+        match earnings
+            .iter_mut()
+            .find(|total| total.currency == project.currency)
+        {
+            Some(total) => {
+                total.amount += project.amount;
+                total.previous_amount += project.previous_amount;
+                total.amount_delta += project.amount_delta;
+            }
+            None => earnings.push(CurrencyComparison {
+                currency: project.currency.clone(),
+                amount: project.amount,
+                previous_amount: project.previous_amount,
+                amount_delta: project.amount_delta,
+            }),
+        }
+    }
+    earnings.sort_by(|a, b| a.currency.cmp(&b.currency));
+
+    let hours: f64 = projects.iter().map(|project| project.hours).sum();
+    let previous_hours: f64 = projects.iter().map(|project| project.previous_hours).sum();
+    Ok(PeriodComparison {
+        start_time: start_ts,
+        end_time: end_ts,
+        previous_start_time: previous_start,
+        previous_end_time: start_ts,
+        hours,
+        previous_hours,
+        hours_delta: hours - previous_hours,
+        earnings,
+        projects,
+    })
+}
+
+/// Where the range before `start_ts..end_ts` starts; it ends at `start_ts`.
+fn previous_range_start(start_ts: i64, end_ts: i64) -> Result<i64, String> {
+    let local = |ts: i64| {
+        Local
+            .timestamp_opt(ts, 0)
+            .single()
+            .ok_or_else(|| "Invalid range".to_string())
+    };
+    let (start, end) = (local(start_ts)?, local(end_ts)?);
+    let (start_day, end_day) = (start.date_naive(), end.date_naive());
+    if local_midnight(start_day)? != start_ts || local_midnight(end_day)? != end_ts {
+        return Ok(start_ts - (end_ts - start_ts));
+    }
+    if start_day.day() == 1 && end_day.day() == 1 {
+        let months = (end_day.year() - start_day.year()) * 12 + end_day.month() as i32
+            - start_day.month() as i32;
+        let previous = start_day
+            .checked_sub_months(Months::new(months as u32))
+            .ok_or("Invalid range")?;
+        return local_midnight(previous);
+    }
+    local_midnight(start_day - (end_day - start_day))
+}
+
+/// What `run_report` aggregates rows by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportGrouping {
+    Day,
+    /// Weeks starting on the day set in settings.
+    Week,
+    Month,
+    Project,
+    Client,
+}
+
+/// Filters and grouping for `run_report`. Empty fields don't filter.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportParams {
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    pub project_names: Option<Vec<String>>,
+    pub client_ids: Option<Vec<i64>>,
+    /// Only billable work when true, only non-billable when false.
+    pub billable: Option<bool>,
+    pub group_by: ReportGrouping,
+}
+
+/// One group of a report in one currency.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportRow {
+    /// "YYYY-MM-DD" for days and weeks (their first day), "YYYY-MM" for
+    /// months, the project name, or the client id ("" for no client).
+    pub key: String,
+    pub label: String,
+    pub currency: String,
+    pub seconds: i64,
+    pub amount: f64,
+    pub entry_count: i64,
+}
+
+/// Aggregates work matching `params`, so report views can be built without
+/// a command of their own. Groups of dates come out in order, projects and
+/// clients by name.
+#[tauri::command]
+pub async fn run_report(
+    app_handle: tauri::AppHandle,
+    params: ReportParams,
+) -> Result<Vec<ReportRow>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let first_day = current_settings(&app_handle)?.week_start();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_report(&conn, &params, first_day)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub fn query_report(
+    conn: &Connection,
+    params: &ReportParams,
+    first_day: Weekday,
+) -> Result<Vec<ReportRow>, String> {
+    let mut clauses = vec!["e.entry_type = 'work'".to_string()];
+    let mut values: Vec<SqlValue> = Vec::new();
+    if let Some(start) = params.start_time {
+        clauses.push("e.start_time >= ?".into());
+        values.push(start.into());
+    }
+    if let Some(end) = params.end_time {
+        clauses.push("e.start_time < ?".into());
+        values.push(end.into());
+    }
+    if let Some(names) = params
+        .project_names
+        .as_ref()
+        .filter(|names| !names.is_empty())
+    {
+        clauses.push(format!(
+            "e.project_name IN ({})",
+            vec!["?"; names.len()].join(", ")
+        ));
+        values.extend(
+            names
+                .iter()
+                .map(|name| SqlValue::from(name.trim().to_string())),
+        );
+    }
+    if let Some(ids) = params.client_ids.as_ref().filter(|ids| !ids.is_empty()) {
+        clauses.push(format!(
+            "p.client_id IN ({})",
+            vec!["?"; ids.len()].join(", ")
+        ));
+        values.extend(ids.iter().map(|id| SqlValue::from(*id)));
+    }
+    if let Some(billable) = params.billable {
+        clauses.push("e.billable = ?".into());
+        values.push(i64::from(billable).into());
+    }
+
+    let local = "e.start_time, 'unixepoch', 'localtime'";
+    let key = match params.group_by {
+        ReportGrouping::Day => format!("date({local})"),
+        // Back to the first day of the week; %w counts from Sunday.
+        ReportGrouping::Week => format!(
+            "date({local}, '-' || ((CAST(strftime('%w', {local}) AS INTEGER) + {}) % 7) || ' days')",
+            7 - first_day.num_days_from_sunday()
+        ),
+        ReportGrouping::Month => format!("strftime('%Y-%m', {local})"),
+        ReportGrouping::Project => "e.project_name".to_string(),
+        ReportGrouping::Client => "COALESCE(CAST(p.client_id AS TEXT), '')".to_string(),
+    };
+    let (label, order) = match params.group_by {
+        ReportGrouping::Day | ReportGrouping::Week | ReportGrouping::Month => (key.as_str(), "key"),
+        ReportGrouping::Project => ("e.project_name", "label COLLATE NOCASE"),
+        ReportGrouping::Client => (
+            "COALESCE(c.name, 'No client')",
+            "p.client_id IS NULL, label COLLATE NOCASE",
+        ),
+    };
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {key} AS key, {label} AS label,
+                    COALESCE(p.currency, c.currency, 'USD') AS currency,
+                    SUM(e.duration), SUM(e.amount), COUNT(*)
+             FROM time_entries e
+             LEFT JOIN projects p ON p.name = e.project_name
+             LEFT JOIN clients c ON c.id = p.client_id
+             WHERE {}
+             GROUP BY key, 3
+             ORDER BY {order}, key, 3",
+            clauses.join(" AND ")
+        ))
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values), |row| {
+            Ok(ReportRow {
+                key: row.get(0)?,
+                label: row.get(1)?,
+                currency: row.get(2)?,
+                seconds: row.get(3)?,
+                amount: row.get(4)?,
+                entry_count: row.get(5)?,
+            })
+        })
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+    Ok(rows)
+}
+
+/// Lifetime numbers about how and when work gets done.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonalStats {
+    pub total_hours: f64,
+    pub total_entries: i64,
+    pub days_worked: i64,
+    /// Consecutive local days with work up to today, or up to yesterday
+    /// while nothing is tracked yet today.
+    pub current_streak_days: i64,
+    pub longest_streak_days: i64,
+    /// Over days with work.
+    pub average_daily_hours: f64,
+    /// Minutes after local midnight the first entry of a day starts at,
+    /// on average; `None` without any work.
+    pub average_start_minutes: Option<i64>,
+    pub longest_session: Option<LongestSession>,
+    pub first_entry_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LongestSession {
+    pub project_name: String,
+    pub start_time: i64,
+    pub seconds: i64,
+}
+
+#[tauri::command]
+pub async fn get_personal_stats(app_handle: tauri::AppHandle) -> Result<PersonalStats, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_personal_stats(&conn)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub fn query_personal_stats(conn: &Connection) -> Result<PersonalStats, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT date(start_time, 'unixepoch', 'localtime') AS day, MIN(start_time),
+                    SUM(duration), COUNT(*)
+             FROM time_entries
+             WHERE entry_type = 'work'
+             GROUP BY day
+             ORDER BY day",
+        )
+        .map_err(|err| err.to_string())?;
+    let days = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    let mut longest_streak = 0;
+    let mut streak = 0;
+    let mut previous: Option<NaiveDate> = None;
+    let mut start_minutes = Vec::with_capacity(days.len());
+    for (day, first_start, ..) in &days {
+        let Ok(date) = NaiveDate::parse_from_str(day, "%Y-%m-%d") else {
+            continue;
+        };
+        streak = match previous {
+            Some(previous) if date - previous == Duration::days(1) => streak + 1,
+            _ => 1,
+        };
+        longest_streak = longest_streak.max(streak);
+        previous = Some(date);
+        if let Some(local) = Local.timestamp_opt(*first_start, 0).single() {
+            start_minutes.push(i64::from(local.hour() * 60 + local.minute()));
+        }
+    }
+    let today = Local::now().date_naive();
+    let current_streak = match previous {
+        Some(last) if today - last <= Duration::days(1) => streak,
+        _ => 0,
+    };
+
+    let total_seconds: i64 = days.iter().map(|(_, _, seconds, _)| seconds).sum();
+    let days_worked = days.len() as i64;
+    let longest_session = conn
+        .query_row(
+            "SELECT project_name, start_time, duration FROM time_entries
+             WHERE entry_type = 'work'
+             ORDER BY duration DESC, start_time ASC
+             LIMIT 1",
+            [],
+            |row| {
+                Ok(LongestSession {
+                    project_name: row.get(0)?,
+                    start_time: row.get(1)?,
+                    seconds: row.get(2)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(|err| err.to_string())?;
+
+    Ok(PersonalStats {
+        total_hours: total_seconds as f64 / 3600.0,
+        total_entries: days.iter().map(|(.., count)| count).sum(),
+        days_worked,
+        current_streak_days: current_streak,
+        longest_streak_days: longest_streak,
+        average_daily_hours: if days_worked > 0 {
+            total_seconds as f64 / 3600.0 / days_worked as f64
+        } else {
+            0.0
+        },
+        average_start_minutes: (!start_minutes.is_empty())
+            .then(|| start_minutes.iter().sum::<i64>() / start_minutes.len() as i64),
+        longest_session,
+        first_entry_at: days.first().map(|(_, first_start, ..)| *first_start),
+    })
+}
+
+/// An amount in one currency.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrencyAmount {
+    pub currency: String,
+    pub amount: f64,
+}
+
+/// A year's work and invoicing in one place, for handing to an accountant.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YearlySummary {
+    pub year: i32,
+    pub total_hours: f64,
+    /// Billable work by currency; hours include non-billable work too.
+    pub earned: Vec<CurrencyAmount>,
+    /// All twelve months in order.
+    pub months: Vec<YearlyMonth>,
+    pub clients: Vec<YearlyClientTotal>,
+    pub invoices: Vec<YearlyInvoiceTotals>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YearlyMonth {
+    /// 1 for January.
+    pub month: u32,
+    pub hours: f64,
+    pub earned: Vec<CurrencyAmount>,
+    /// Invoice totals less credit notes, by the month they were issued in.
+    pub invoiced: Vec<CurrencyAmount>,
+}
+
+/// One client's year in one currency. Work on projects without a client
+/// and invoices not billed to one come under a `None` client id.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YearlyClientTotal {
+    pub client_id: Option<i64>,
+    pub client_name: Option<String>,
+    pub currency: String,
+    pub hours: f64,
+    pub earned: f64,
+    /// Less credit notes.
+    pub invoiced: f64,
+}
+
+/// Invoices and credit notes issued in the year in one currency.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YearlyInvoiceTotals {
+    pub currency: String,
+    pub invoice_count: i64,
+    pub credit_note_count: i64,
+    pub invoiced: f64,
+    /// Negative, as credit notes are stored.
+    pub credited: f64,
+    pub net: f64,
+    /// Totals of the year's invoices marked paid.
+    pub paid: f64,
+}
+
+/// Hours, tracked earnings and invoicing for the local calendar `year`, by
+/// month and by client.
+#[tauri::command]
+pub async fn get_yearly_summary(
+    app_handle: tauri::AppHandle,
+    year: i32,
+) -> Result<YearlySummary, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_yearly_summary(&conn, year)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Renders `get_yearly_summary` as a PDF in Downloads and returns its path.
+#[tauri::command]
+pub async fn generate_yearly_summary_pdf(
+    app_handle: tauri::AppHandle,
+    year: i32,
+) -> Result<String, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let fonts = invoice_fonts(&current_settings(&app_handle)?)?;
+    let downloads_path = app_handle
+        .path()
+        .download_dir()
+        .map_err(|e| format!("Failed to get downloads directory: {}", e))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let summary = query_yearly_summary(&conn, year)?;
+        let amounts = |amounts: &[CurrencyAmount]| {
+            amounts
+                .iter()
+                .map(|amount| format!("{:.2} {}", amount.amount, amount.currency))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let columns = |columns: &[(f32, &str)]| {
+            columns
+                .iter()
+                .map(|(x, name)| (*x, name.to_string()))
+                .collect()
+        };
+
+        let mut overview = vec![vec![
+            "Hours tracked".to_string(),
+            format!("{:.2}", summary.total_hours),
+        ]];
+        if !summary.earned.is_empty() {
+            overview.push(vec!["Billable earnings".into(), amounts(&summary.earned)]);
+        }
+        let tables = vec![
+            ReportTable {
+                heading: "Overview".into(),
+                columns: columns(&[(20.0, ""), (70.0, "")]),
+                rows: overview,
+            },
+            ReportTable {
+                heading: "Months".into(),
+                columns: columns(&[
+                    (20.0, "Month"),
+                    (50.0, "Hours"),
+                    (72.0, "Billable earnings"),
+                    (132.0, "Invoiced"),
+                ]),
+                rows: summary
+                    .months
+                    .iter()
+                    .map(|month| {
+                        vec![
+                            NaiveDate::from_ymd_opt(year, month.month, 1)
+                                .map_or_else(String::new, |date| date.format("%B").to_string()),
+                            format!("{:.2}", month.hours),
+                            amounts(&month.earned),
+                            amounts(&month.invoiced),
+                        ]
+                    })
+                    .collect(),
+            },
+            ReportTable {
+                heading: "Clients".into(),
+                columns: columns(&[
+                    (20.0, "Client"),
+                    (100.0, "Hours"),
+                    (122.0, "Billable earnings"),
+                    (158.0, "Invoiced"),
+                ]),
+                rows: summary
+                    .clients
+                    .iter()
+                    .map(|client| {
+                        vec![
+                            client
+                                .client_name
+                                .clone()
+                                .unwrap_or_else(|| "No client".into()),
+                            format!("{:.2}", client.hours),
+                            format!("{:.2} {}", client.earned, client.currency),
+                            format!("{:.2} {}", client.invoiced, client.currency),
+                        ]
+                    })
+                    .collect(),
+            },
+            ReportTable {
+                heading: "Invoices".into(),
+                columns: columns(&[
+                    (20.0, "Currency"),
+                    (42.0, "Invoices"),
+                    (60.0, "Credit notes"),
+                    (82.0, "Invoiced"),
+                    (110.0, "Credited"),
+                    (138.0, "Net"),
+                    (166.0, "Paid"),
+                ]),
+                rows: summary
+                    .invoices
+                    .iter()
+                    .map(|totals| {
+                        vec![
+                            totals.currency.clone(),
+                            totals.invoice_count.to_string(),
+                            totals.credit_note_count.to_string(),
+                            format!("{:.2}", totals.invoiced),
+                            format!("{:.2}", totals.credited),
+                            format!("{:.2}", totals.net),
+                            format!("{:.2}", totals.paid),
+                        ]
+                    })
+                    .collect(),
+            },
+        ];
+
+        let output_path = downloads_path.join(format!("yearly_summary_{}.pdf", year));
+        let output_path = output_path.to_str().ok_or("Invalid file path")?.to_string();
+        generate_table_report(
+            TableReportDocument {
+                title: "Yearly summary".into(),
+                subtitle: year.to_string(),
+                tables,
+                fonts,
+                generated_at: current_unix_timestamp(),
+            },
+            &output_path,
+        )?;
+        Ok(output_path)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub fn query_yearly_summary(conn: &Connection, year: i32) -> Result<YearlySummary, String> {
+    let first = NaiveDate::from_ymd_opt(year, 1, 1).ok_or("Invalid year")?;
+    let next = NaiveDate::from_ymd_opt(year + 1, 1, 1).ok_or("Invalid year")?;
+    let start_ts = local_midnight(first)?;
+    let end_ts = local_midnight(next)?;
+    let mut months: Vec<YearlyMonth> = (1..=12)
+        .map(|month| YearlyMonth {
+            month,
+            hours: 0.0,
+            earned: Vec::new(),
+            invoiced: Vec::new(),
+        })
+        .collect();
+    let mut earned = Vec::new();
+
+    // Work by month and currency.
+    let mut stmt = conn
+        .prepare(
+            "SELECT CAST(strftime('%m', e.start_time, 'unixepoch', 'localtime') AS INTEGER) AS month,
+                    COALESCE(p.currency, c.currency, 'USD') AS currency,
+                    SUM(e.duration), SUM(CASE WHEN e.billable = 1 THEN e.amount ELSE 0 END)
+             FROM time_entries e
+             LEFT JOIN projects p ON p.name = e.project_name
+             LEFT JOIN clients c ON c.id = p.client_id
+             WHERE e.start_time >= ?1 AND e.start_time < ?2 AND e.entry_type = 'work'
+             GROUP BY month, 2
+             ORDER BY month, 2",
+        )
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map(params![start_ts, end_ts], |row| {
+            Ok((
+                row.get::<_, u32>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, f64>(3)?,
+            ))
+        })
+        .map_err(|err| err.to_string())?;
+    for row in rows {
+        let (month, currency, seconds, amount) = row.map_err(|err| err.to_string())?;
+        if let Some(month) = months.get_mut(month as usize - 1) {
+            month.hours += seconds as f64 / 3600.0;
+            add_amount(&mut month.earned, &currency, amount);
+        }
+        add_amount(&mut earned, &currency, amount);
+    }
+
+    // Invoicing by month and currency.
+    let mut stmt = conn
+        .prepare(
+            "SELECT CAST(strftime('%m', created_at, 'unixepoch', 'localtime') AS INTEGER) AS month,
+                    currency, SUM(total_amount)
+             FROM invoices
+             WHERE created_at >= ?1 AND created_at < ?2
+             GROUP BY month, currency
+             ORDER BY month, currency",
+        )
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map(params![start_ts, end_ts], |row| {
+            Ok((
+                row.get::<_, u32>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, f64>(2)?,
+            ))
+        })
+        .map_err(|err| err.to_string())?;
+    for row in rows {
+        let (month, currency, amount) = row.map_err(|err| err.to_string())?;
+        if let Some(month) = months.get_mut(month as usize - 1) {
+            add_amount(&mut month.invoiced, &currency, amount);
+        }
+    }
+
+    // Clients: work and invoicing merged on client and currency.
+    let mut stmt = conn
+        .prepare(
+            "SELECT client_id, (SELECT name FROM clients WHERE id = client_id), currency,
+                    SUM(seconds), SUM(earned), SUM(invoiced)
+             FROM (
+                 SELECT p.client_id AS client_id,
+                        COALESCE(p.currency, c.currency, 'USD') AS currency,
+                        e.duration AS seconds,
+                        CASE WHEN e.billable = 1 THEN e.amount ELSE 0 END AS earned,
+                        0 AS invoiced
+                 FROM time_entries e
+                 LEFT JOIN projects p ON p.name = e.project_name
+                 LEFT JOIN clients c ON c.id = p.client_id
+                 WHERE e.start_time >= ?1 AND e.start_time < ?2 AND e.entry_type = 'work'
+                 UNION ALL
+                 SELECT client_id, currency, 0, 0, total_amount
+                 FROM invoices
+                 WHERE created_at >= ?1 AND created_at < ?2
+             )
+             GROUP BY client_id, currency
+             ORDER BY client_id IS NULL, 2 COLLATE NOCASE, client_id, currency",
+        )
+        .map_err(|err| err.to_string())?;
+    let clients = stmt
+        .query_map(params![start_ts, end_ts], |row| {
+            Ok(YearlyClientTotal {
+                client_id: row.get(0)?,
+                client_name: row.get(1)?,
+                currency: row.get(2)?,
+                hours: row.get::<_, i64>(3)? as f64 / 3600.0,
+                earned: row.get(4)?,
+                invoiced: row.get(5)?,
+            })
+        })
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT currency,
+                    SUM(invoice_type = ?3), SUM(invoice_type = ?4),
+                    TOTAL(CASE WHEN invoice_type = ?3 THEN total_amount END),
+                    TOTAL(CASE WHEN invoice_type = ?4 THEN total_amount END),
+                    TOTAL(total_amount),
+                    TOTAL(CASE WHEN invoice_type = ?3 AND status = ?5 THEN total_amount END)
+             FROM invoices
+             WHERE created_at >= ?1 AND created_at < ?2
+             GROUP BY currency
+             ORDER BY currency",
+        )
+        .map_err(|err| err.to_string())?;
+    let invoices = stmt
+        .query_map(
+            params![
+                start_ts,
+                end_ts,
+                INVOICE_TYPE_INVOICE,
+                INVOICE_TYPE_CREDIT_NOTE,
+                INVOICE_STATUS_PAID
+            ],
+            |row| {
+                Ok(YearlyInvoiceTotals {
+                    currency: row.get(0)?,
+                    invoice_count: row.get(1)?,
+                    credit_note_count: row.get(2)?,
+                    invoiced: row.get(3)?,
+                    credited: row.get(4)?,
+                    net: row.get(5)?,
+                    paid: row.get(6)?,
+                })
+            },
+        )
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    Ok(YearlySummary {
+        year,
+        total_hours: months.iter().map(|month| month.hours).sum(),
+        earned,
+        months,
+        clients,
+        invoices,
+    })
+}
+
+fn add_amount(amounts: &mut Vec<CurrencyAmount>, currency: &str, amount: f64) {
+    match amounts.iter_mut().find(|total| total.currency == currency) {
+        Some(total) => total.amount += amount,
+        None => amounts.push(CurrencyAmount {
+            currency: currency.to_string(),
+            amount,
+        }),
+    }
+}
+
+/// Seconds to add to a timestamp for `utc_offset` minutes east of UTC;
+/// `None` stays `None` and means the device's zone.
+fn offset_seconds(utc_offset: Option<i32>) -> Result<Option<i64>, String> {
+    utc_offset
+        .map(|minutes| fixed_offset(minutes).map(|offset| i64::from(offset.local_minus_utc())))
+        .transpose()
+}
+
+/// Midnight starting `day`, `shift_seconds` east of UTC or in the device's
+/// zone.
+fn midnight_at(day: NaiveDate, shift_seconds: Option<i64>) -> Result<i64, String> {
+    match shift_seconds {
+        Some(shift) => day
+            .and_hms_opt(0, 0, 0)
+            .map(|midnight| midnight.and_utc().timestamp() - shift)
+            .ok_or_else(|| "Unable to resolve local time".to_string()),
+        None => local_midnight(day),
+    }
+}
+
+/// The day `ts` falls on, `shift_seconds` east of UTC or in the device's
+/// zone.
+fn date_at(ts: i64, shift_seconds: Option<i64>) -> Result<NaiveDate, String> {
+    match shift_seconds {
+        Some(shift) => Utc
+            .timestamp_opt(ts + shift, 0)
+            .single()
+            .map(|at| at.date_naive()),
+        None => Local
+            .timestamp_opt(ts, 0)
+            .single()
+            .map(|at| at.date_naive()),
+    }
+    .ok_or_else(|| "Invalid range".to_string())
+}
+
+fn local_midnight(day: NaiveDate) -> Result<i64, String> {
+    day.and_hms_opt(0, 0, 0)
+        .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+        .map(|midnight| midnight.timestamp())
+        .ok_or_else(|| "Unable to resolve local time".to_string())
+}
+
+/// Unix timestamp of local midnight on the day containing `ts`.
+pub fn local_day_start(ts: i64) -> i64 {
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .and_then(|local| local.date_naive().and_hms_opt(0, 0, 0))
+        .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+        .map(|midnight| midnight.timestamp())
+        .unwrap_or(ts)
+}
+
+/// Unix timestamp of local midnight on the Monday of the week containing `ts`.
+pub fn local_week_start(ts: i64) -> i64 {
+    let Some(local) = Local.timestamp_opt(ts, 0).single() else {
+        return ts;
+    };
+    let monday = local.date_naive() - Duration::days(local.weekday().num_days_from_monday() as i64);
+    monday
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+        .map(|midnight| midnight.timestamp())
+        .unwrap_or(ts)
+}
@@ -0,0 +1,275 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::{
+    clients, current_unix_timestamp, open_connection, resolve_db_path, send_notification,
+};
+
+pub const CREATE_RETAINERS_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS retainers (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        client_id INTEGER NOT NULL REFERENCES clients(id),
+        hours REAL NOT NULL,
+        starts_at INTEGER NOT NULL,
+        ends_at INTEGER,
+        note TEXT,
+        low_balance_alerted INTEGER NOT NULL DEFAULT 0,
+        created_at INTEGER NOT NULL
+    )
+"#;
+
+pub const RETAINER_LOW_BALANCE_EVENT: &str = "retainer://low-balance";
+
+/// Share of purchased hours used before the retainer counts as nearly exhausted.
+const LOW_BALANCE_RATIO: f64 = 0.9;
+
+const RETAINER_COLUMNS: &str =
+    "r.id, r.client_id, c.name, r.hours, r.starts_at, r.ends_at, r.note, r.created_at";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Retainer {
+    pub id: i64,
+    pub client_id: i64,
+    pub client_name: Option<String>,
+    pub hours: f64,
+    pub starts_at: i64,
+    pub ends_at: Option<i64>,
+    pub note: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetainerBalance {
+    pub retainer: Retainer,
+    pub used_hours: f64,
+    pub remaining_hours: f64,
+    pub percent_used: f64,
+}
+
+#[tauri::command]
+pub async fn create_retainer(
+    app_handle: tauri::AppHandle,
+    client_id: i64,
+    hours: f64,
+    starts_at: Option<i64>,
+    ends_at: Option<i64>,
+    note: Option<String>,
+) -> Result<Retainer, String> {
+    if !hours.is_finite() || hours <= 0.0 {
+        return Err("Retainer hours must be greater than zero".into());
+    }
+    let starts_at = starts_at.unwrap_or_else(current_unix_timestamp);
+    if ends_at.is_some_and(|end| end <= starts_at) {
+        return Err("Retainer end must be after its start".into());
+    }
+    let db_path = resolve_db_path(&app_handle)?;
+    let note = clients::non_empty(note);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        clients::fetch_client(&conn, client_id)?;
+        conn.execute(
+            "INSERT INTO retainers (client_id, hours, starts_at, ends_at, note, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![client_id, hours, starts_at, ends_at, note, current_unix_timestamp()],
+        )
+        .map_err(|err| err.to_string())?;
+        fetch_retainer(&conn, conn.last_insert_rowid())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn list_retainers(
+    app_handle: tauri::AppHandle,
+    client_id: Option<i64>,
+) -> Result<Vec<Retainer>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {RETAINER_COLUMNS}
+                 FROM retainers r
+                 LEFT JOIN clients c ON c.id = r.client_id
+                 WHERE ?1 IS NULL OR r.client_id = ?1
+                 ORDER BY r.starts_at DESC"
+            ))
+            .map_err(|err| err.to_string())?;
+        let retainers = stmt
+            .query_map(params![client_id], map_retainer)
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        Ok(retainers)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn delete_retainer(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        conn.execute("DELETE FROM retainers WHERE id = ?1", params![id])
+            .map_err(|err| err.to_string())?;
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Balance of the client's current retainer, or `None` if none is active.
+#[tauri::command]
+pub async fn get_retainer_balance(
+    app_handle: tauri::AppHandle,
+    client_id: i64,
+) -> Result<Option<RetainerBalance>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        active_retainer_balance(&conn, client_id, current_unix_timestamp())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// The retainer in effect at `at` for `client_id` (latest start wins), along
+/// with the hours drawn down by that client's projects since it started.
+pub fn active_retainer_balance(
+    conn: &Connection,
+    client_id: i64,
+    at: i64,
+) -> Result<Option<RetainerBalance>, String> {
+    let retainer = conn
+        .query_row(
+            &format!(
+                "SELECT {RETAINER_COLUMNS}
+                 FROM retainers r
+                 LEFT JOIN clients c ON c.id = r.client_id
+                 WHERE r.client_id = ?1
+                   AND r.starts_at <= ?2
+                   AND (r.ends_at IS NULL OR r.ends_at > ?2)
+                 ORDER BY r.starts_at DESC
+                 LIMIT 1"
+            ),
+            params![client_id, at],
+            map_retainer,
+        )
+        .optional()
+        .map_err(|err| err.to_string())?;
+    let Some(retainer) = retainer else {
+        return Ok(None);
+    };
+
+    let used_seconds: i64 = conn
+        .query_row(
+            "SELECT COALESCE(SUM(duration), 0)
+             FROM time_entries
+             WHERE project_name IN (SELECT name FROM projects WHERE client_id = ?1)
+               AND entry_type = 'work'
+               AND start_time >= ?2
+               AND (?3 IS NULL OR start_time < ?3)",
+            params![client_id, retainer.starts_at, retainer.ends_at],
+            |row| row.get(0),
+        )
+        .map_err(|err| err.to_string())?;
+
+    let used_hours = used_seconds as f64 / 3600.0;
+    Ok(Some(RetainerBalance {
+        used_hours,
+        remaining_hours: retainer.hours - used_hours,
+        percent_used: used_hours / retainer.hours * 100.0,
+        retainer,
+    }))
+}
+
+/// Warns once per retainer when the client's project work draws it down past
+/// the low-balance threshold. Advisory only, so errors are ignored.
+pub fn check_retainer_balance(app: &AppHandle, project_name: &str) {
+    let Ok(conn) = resolve_db_path(app).and_then(open_connection) else {
+        return;
+    };
+    let client_id: Option<i64> = conn
+        .query_row(
+            "SELECT client_id FROM projects WHERE name = ?1",
+            params![project_name],
+            |row| row.get(0),
+        )
+        .optional()
+        .ok()
+        .flatten()
+        .flatten();
+    let Some(client_id) = client_id else {
+        return;
+    };
+    let Ok(Some(balance)) = active_retainer_balance(&conn, client_id, current_unix_timestamp())
+    else {
+        return;
+    };
+    if balance.percent_used < LOW_BALANCE_RATIO * 100.0 {
+        return;
+    }
+    let newly_flagged = conn
+        .execute(
+            "UPDATE retainers SET low_balance_alerted = 1
+             WHERE id = ?1 AND low_balance_alerted = 0",
+            params![balance.retainer.id],
+        )
+        .unwrap_or(0);
+    if newly_flagged == 0 {
+        return;
+    }
+
+    let client_name = balance
+        .retainer
+        .client_name
+        .clone()
+        .unwrap_or_else(|| "Client".into());
+    let _ = app.emit(RETAINER_LOW_BALANCE_EVENT, balance.clone());
+    send_notification(
+        app,
+        "Retainer running low",
+        &format!(
+            "{} has {:.1} of {:.1} retainer hours left.",
+            client_name,
+            balance.remaining_hours.max(0.0),
+            balance.retainer.hours
+        ),
+    );
+}
+
+fn fetch_retainer(conn: &Connection, id: i64) -> Result<Retainer, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {RETAINER_COLUMNS}
+             FROM retainers r
+             LEFT JOIN clients c ON c.id = r.client_id
+             WHERE r.id = ?1"
+        ),
+        params![id],
+        map_retainer,
+    )
+    .map_err(|err| err.to_string())
+}
+
+fn map_retainer(row: &rusqlite::Row) -> rusqlite::Result<Retainer> {
+    Ok(Retainer {
+        id: row.get(0)?,
+        client_id: row.get(1)?,
+        client_name: row.get(2)?,
+        hours: row.get(3)?,
+        starts_at: row.get(4)?,
+        ends_at: row.get(5)?,
+        note: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
@@ -0,0 +1,203 @@
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::{current_unix_timestamp, open_connection, resolve_db_path, TimeEntry, TimerState};
+
+/// Labelled stretches ("design", "review") inside one timer. While the timer
+/// runs its segments carry `active_timer_id`; on stop they move to the saved
+/// entry through `entry_id`. The newest open segment has no `end_time`.
+pub const CREATE_TIME_ENTRY_SEGMENTS_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS time_entry_segments (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        entry_id INTEGER REFERENCES time_entries(id) ON DELETE CASCADE,
+        active_timer_id INTEGER,
+        label TEXT NOT NULL,
+        start_time INTEGER NOT NULL,
+        end_time INTEGER
+    )
+"#;
+
+const MAX_SEGMENT_LABEL_LENGTH: usize = 100;
+
+const SEGMENT_COLUMNS: &str = "id, label, start_time, end_time";
+
+/// Segment durations are wall-clock time, pauses included.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerSegment {
+    pub id: i64,
+    pub label: String,
+    pub start_time: i64,
+    pub end_time: Option<i64>,
+    pub duration: i64,
+}
+
+/// Closes the timer's current segment and starts a new one named `label`.
+/// Returns the timer's segments so far.
+#[tauri::command]
+pub async fn add_timer_segment(
+    app_handle: tauri::AppHandle,
+    timer_id: Option<i64>,
+    label: String,
+) -> Result<Vec<TimerSegment>, String> {
+    let label = label.trim().to_string();
+    if label.is_empty() {
+        return Err("Segment label is required".into());
+    }
+    if label.chars().count() > MAX_SEGMENT_LABEL_LENGTH {
+        return Err(format!(
+            "Segment labels must be at most {} characters",
+            MAX_SEGMENT_LABEL_LENGTH
+        ));
+    }
+    let timer_id = resolve_timer_id(&app_handle, timer_id)?;
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let now = current_unix_timestamp();
+        conn.execute(
+            "UPDATE time_entry_segments SET end_time = ?1
+             WHERE active_timer_id = ?2 AND end_time IS NULL",
+            params![now, timer_id],
+        )
+        .map_err(|err| err.to_string())?;
+        conn.execute(
+            "INSERT INTO time_entry_segments (active_timer_id, label, start_time)
+             VALUES (?1, ?2, ?3)",
+            params![timer_id, label, now],
+        )
+        .map_err(|err| err.to_string())?;
+        query_segments(&conn, "active_timer_id", timer_id)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn list_timer_segments(
+    app_handle: tauri::AppHandle,
+    timer_id: Option<i64>,
+) -> Result<Vec<TimerSegment>, String> {
+    let timer_id = resolve_timer_id(&app_handle, timer_id)?;
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_segments(&conn, "active_timer_id", timer_id)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_entry_segments(
+    app_handle: tauri::AppHandle,
+    entry_id: i64,
+) -> Result<Vec<TimerSegment>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        query_segments(&conn, "entry_id", entry_id)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Hands a stopped timer's segments to the entries saved for it, cut off at
+/// `end_time`. With a midnight split each segment goes to the entry it
+/// started in; segments starting after the stop are dropped.
+pub fn attach_segments(
+    conn: &Connection,
+    timer_id: i64,
+    end_time: i64,
+    entries: &[&TimeEntry],
+) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM time_entry_segments WHERE active_timer_id = ?1 AND start_time >= ?2",
+        params![timer_id, end_time],
+    )
+    .map_err(|err| err.to_string())?;
+    conn.execute(
+        "UPDATE time_entry_segments SET end_time = ?1
+         WHERE active_timer_id = ?2 AND (end_time IS NULL OR end_time > ?1)",
+        params![end_time, timer_id],
+    )
+    .map_err(|err| err.to_string())?;
+    for entry in entries {
+        conn.execute(
+            "UPDATE time_entry_segments SET entry_id = ?1, active_timer_id = NULL
+             WHERE active_timer_id = ?2 AND start_time < ?3",
+            params![entry.id, timer_id, entry.end_time.max(entry.start_time + 1)],
+        )
+        .map_err(|err| err.to_string())?;
+    }
+    // Anything left is past the last entry's end because rounding pulled it in.
+    if let Some(last) = entries.last() {
+        conn.execute(
+            "UPDATE time_entry_segments SET entry_id = ?1, active_timer_id = NULL
+             WHERE active_timer_id = ?2",
+            params![last.id, timer_id],
+        )
+        .map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+/// Drops the segments of a timer that was discarded.
+pub fn clear_timer_segments(conn: &Connection, timer_id: i64) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM time_entry_segments WHERE active_timer_id = ?1",
+        params![timer_id],
+    )
+    .map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+fn resolve_timer_id(app_handle: &tauri::AppHandle, timer_id: Option<i64>) -> Result<i64, String> {
+    let statuses = app_handle.state::<TimerState>().statuses();
+    let status = match timer_id {
+        Some(id) => statuses.iter().find(|status| status.timer_id == Some(id)),
+        None => statuses.last(),
+    };
+    status
+        .and_then(|status| status.timer_id)
+        .ok_or_else(|| match timer_id {
+            Some(_) => "Timer not found".to_string(),
+            None => "No timer is currently running".to_string(),
+        })
+}
+
+fn query_segments(
+    conn: &Connection,
+    owner_column: &str,
+    owner_id: i64,
+) -> Result<Vec<TimerSegment>, String> {
+    let now = current_unix_timestamp();
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {SEGMENT_COLUMNS}
+             FROM time_entry_segments
+             WHERE {owner_column} = ?1
+             ORDER BY start_time ASC, id ASC"
+        ))
+        .map_err(|err| err.to_string())?;
+    let segments = stmt
+        .query_map(params![owner_id], |row| {
+            let start_time: i64 = row.get(2)?;
+            let end_time: Option<i64> = row.get(3)?;
+            Ok(TimerSegment {
+                id: row.get(0)?,
+                label: row.get(1)?,
+                start_time,
+                end_time,
+                duration: (end_time.unwrap_or(now) - start_time).max(0),
+            })
+        })
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+    Ok(segments)
+}
@@ -0,0 +1,520 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{NaiveTime, Weekday};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::Manager;
+
+use crate::{
+    backup, email, open_connection, pdf_generator,
+    projects::{RoundingDirection, MAX_ROUNDING_MINUTES},
+    resolve_db_path, signing,
+};
+
+pub const CREATE_SETTINGS_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS settings (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    )
+"#;
+
+/// User preferences stored as one JSON value per key in the `settings` table.
+/// Missing keys fall back to the defaults below, so new settings can be added
+/// without a migration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub prompt_notes_on_stop: bool,
+    /// Project the tray's "Start Timer" tracks; "Quick Task" when unset.
+    pub default_tray_project: Option<String>,
+    /// Rate for the default tray project; the last used rate when unset.
+    pub default_tray_rate: Option<f64>,
+    /// Minutes without keyboard or mouse input before a running timer is
+    /// flagged as idle; 0 turns idle detection off.
+    pub idle_threshold_minutes: u32,
+    /// Hours a timer can run before a reminder to stop it; 0 turns it off.
+    pub long_running_reminder_hours: u32,
+    /// Blocks when a timer is expected to be running; empty turns off the
+    /// "forgot to start" reminder.
+    pub working_hours: Vec<WorkBlock>,
+    /// Grace period into a work block before that reminder fires.
+    pub forgot_to_start_minutes: u32,
+    /// Stopped timers shorter than this are discarded instead of saved.
+    pub min_entry_seconds: u32,
+    /// Hours per day after which a warning is sent; unset turns it off.
+    pub daily_hours_cap: Option<f64>,
+    /// Age in hours past which a timer restored at launch needs confirming;
+    /// unset uses 12 hours and 0 turns the check off.
+    pub restored_timer_max_hours: Option<u32>,
+    /// Increment stopped timers are rounded to, e.g. 6 or 15 minutes, for
+    /// projects without their own rule; 0 turns it off.
+    pub rounding_minutes: u32,
+    /// Rounds to the nearest increment when unset.
+    pub rounding_direction: Option<RoundingDirection>,
+    /// Refuse manual entries that overlap others instead of only warning.
+    pub block_overlapping_entries: bool,
+    /// Entries longer than this many hours are saved with a warning; unset
+    /// uses 12 hours and 0 turns the warning off.
+    pub max_entry_hours: Option<f64>,
+    /// Copy of the logo in the app data folder; set with `set_invoice_logo`.
+    pub invoice_logo_path: Option<String>,
+    /// Printed logo width; unset uses 40 mm.
+    pub invoice_logo_width_mm: Option<f64>,
+    /// TrueType fonts for invoices; set with `set_invoice_fonts`.
+    pub invoice_font_path: Option<String>,
+    pub invoice_bold_font_path: Option<String>,
+    /// Outgoing mail server for `send_invoice_email`.
+    pub smtp: Option<email::SmtpSettings>,
+    /// Notify once a day while invoices are overdue.
+    pub overdue_notifications: bool,
+    /// Certificate issued invoices and credit notes are signed with;
+    /// unsigned when unset.
+    pub pdf_signing: Option<signing::SigningSettings>,
+    /// ISO weekday weekly reports start on, 1 = Monday through 7 = Sunday;
+    /// Monday when unset.
+    pub week_start_day: Option<u32>,
+    /// Backs up automatically into a folder; off when unset.
+    pub scheduled_backups: Option<backup::BackupSchedule>,
+}
+
+impl AppSettings {
+    pub fn week_start(&self) -> Weekday {
+        match self.week_start_day {
+            Some(day @ 2..=7) => Weekday::try_from(day as u8 - 1).unwrap_or(Weekday::Mon),
+            _ => Weekday::Mon,
+        }
+    }
+
+    /// The default rounding increment (minutes) and direction, if enabled.
+    pub fn rounding_rule(&self) -> Option<(i64, RoundingDirection)> {
+        (self.rounding_minutes > 0).then(|| {
+            (
+                i64::from(self.rounding_minutes),
+                self.rounding_direction
+                    .unwrap_or(RoundingDirection::Nearest),
+            )
+        })
+    }
+}
+
+pub const DEFAULT_LOGO_WIDTH_MM: f64 = 40.0;
+const LOGO_WIDTH_RANGE_MM: (f64, f64) = (10.0, 100.0);
+
+/// A recurring stretch of working time, e.g. Monday–Friday 09:00–17:00.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkBlock {
+    /// ISO weekdays the block applies to, 1 = Monday through 7 = Sunday.
+    pub days: Vec<u32>,
+    /// Local "HH:MM" times; the block must end after it starts.
+    pub start: String,
+    pub end: String,
+}
+
+impl WorkBlock {
+    /// Start and end times, or `None` if either doesn't parse.
+    pub fn bounds(&self) -> Option<(NaiveTime, NaiveTime)> {
+        let start = NaiveTime::parse_from_str(self.start.trim(), "%H:%M").ok()?;
+        let end = NaiveTime::parse_from_str(self.end.trim(), "%H:%M").ok()?;
+        Some((start, end))
+    }
+}
+
+/// The settings without the mail password and signing passphrase, which are
+/// only ever written.
+#[tauri::command]
+pub async fn get_settings(app_handle: tauri::AppHandle) -> Result<AppSettings, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        load_settings(&conn).map(redacted)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Applies a partial update: only the keys present in `settings` are changed.
+#[tauri::command]
+pub async fn update_settings(
+    app_handle: tauri::AppHandle,
+    settings: Map<String, Value>,
+) -> Result<AppSettings, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        apply_settings_patch(&conn, settings).map(redacted)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Copies the PNG or JPEG at `source_path` into the app data folder and uses
+/// it on future invoices. `None` removes the logo.
+#[tauri::command]
+pub async fn set_invoice_logo(
+    app_handle: tauri::AppHandle,
+    source_path: Option<String>,
+) -> Result<AppSettings, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let dir = branding_dir(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let stored = match source_path {
+            Some(source) => {
+                let data = read_branding_file(&source, "logo")?;
+                pdf_generator::validate_logo(&data)?;
+                let extension = if data.starts_with(&[0xFF, 0xD8]) {
+                    "jpg"
+                } else {
+                    "png"
+                };
+                Some(store_branding_file(
+                    &dir,
+                    &format!("invoice_logo.{}", extension),
+                    &data,
+                )?)
+            }
+            None => None,
+        };
+        let current = load_settings(&conn)?;
+        replace_branding_setting(
+            &conn,
+            "invoice_logo_path",
+            current.invoice_logo_path.as_deref(),
+            stored,
+        )
+        .map(redacted)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Copies TrueType fonts into the app data folder for invoices to use in
+/// place of Helvetica. `None` for `regular_path` goes back to Helvetica.
+#[tauri::command]
+pub async fn set_invoice_fonts(
+    app_handle: tauri::AppHandle,
+    regular_path: Option<String>,
+    bold_path: Option<String>,
+) -> Result<AppSettings, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let dir = branding_dir(&app_handle)?;
+    if regular_path.is_none() && bold_path.is_some() {
+        return Err("Choose a regular font before a bold one".into());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let store = |source: Option<String>, name: &str| -> Result<Option<String>, String> {
+            let Some(source) = source else {
+                return Ok(None);
+            };
+            let data = read_branding_file(&source, "font")?;
+            pdf_generator::validate_font(&data)?;
+            store_branding_file(&dir, name, &data).map(Some)
+        };
+        let regular = store(regular_path, "invoice_font_regular.ttf")?;
+        let bold = store(bold_path, "invoice_font_bold.ttf")?;
+
+        let current = load_settings(&conn)?;
+        replace_branding_setting(
+            &conn,
+            "invoice_font_path",
+            current.invoice_font_path.as_deref(),
+            regular,
+        )?;
+        replace_branding_setting(
+            &conn,
+            "invoice_bold_font_path",
+            current.invoice_bold_font_path.as_deref(),
+            bold,
+        )
+        .map(redacted)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// The fields of each setting that hold a secret: never handed to the
+/// webview or written to exports.
+pub const SECRET_FIELDS: [(&str, &str); 2] = [("smtp", "password"), ("pdf_signing", "passphrase")];
+
+/// Settings as handed to the webview, without stored secrets.
+fn redacted(mut settings: AppSettings) -> AppSettings {
+    if let Some(smtp) = settings.smtp.as_mut() {
+        smtp.password = None;
+    }
+    if let Some(signing) = settings.pdf_signing.as_mut() {
+        signing.passphrase = None;
+    }
+    settings
+}
+
+fn branding_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let mut dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|err| err.to_string())?;
+    dir.push("branding");
+    Ok(dir)
+}
+
+fn read_branding_file(source: &str, kind: &str) -> Result<Vec<u8>, String> {
+    fs::read(source).map_err(|err| format!("Couldn't read the {}: {}", kind, err))
+}
+
+fn store_branding_file(dir: &Path, name: &str, data: &[u8]) -> Result<String, String> {
+    fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    let target = dir.join(name);
+    fs::write(&target, data).map_err(|err| err.to_string())?;
+    Ok(target.to_string_lossy().into_owned())
+}
+
+/// Points `key` at `stored`, deleting the file it pointed at before.
+fn replace_branding_setting(
+    conn: &Connection,
+    key: &str,
+    previous: Option<&str>,
+    stored: Option<String>,
+) -> Result<AppSettings, String> {
+    if let Some(previous) = previous {
+        if stored.as_deref() != Some(previous) {
+            let _ = fs::remove_file(previous);
+        }
+    }
+    let mut patch = Map::new();
+    patch.insert(
+        key.into(),
+        serde_json::to_value(stored).map_err(|err| err.to_string())?,
+    );
+    apply_settings_patch(conn, patch)
+}
+
+pub fn load_settings(conn: &Connection) -> Result<AppSettings, String> {
+    let mut merged = settings_to_map(&AppSettings::default())?;
+
+    let mut stmt = conn
+        .prepare("SELECT key, value FROM settings")
+        .map_err(|err| err.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|err| err.to_string())?;
+
+    for row in rows {
+        let (key, raw) = row.map_err(|err| err.to_string())?;
+        // Keys from older versions or values that no longer parse are ignored
+        // rather than making every command fail.
+        if !merged.contains_key(&key) {
+            continue;
+        }
+        if let Ok(value) = serde_json::from_str::<Value>(&raw) {
+            merged.insert(key, value);
+        }
+    }
+
+    serde_json::from_value(Value::Object(merged.clone())).or_else(|_| {
+        // A single bad value shouldn't reset everything; drop the stored
+        // values that fail to deserialize one by one.
+        let defaults = settings_to_map(&AppSettings::default())?;
+        let mut valid = defaults.clone();
+        for (key, value) in merged {
+            let mut candidate = valid.clone();
+            candidate.insert(key.clone(), value.clone());
+            if serde_json::from_value::<AppSettings>(Value::Object(candidate)).is_ok() {
+                valid.insert(key, value);
+            }
+        }
+        serde_json::from_value(Value::Object(valid)).map_err(|err| err.to_string())
+    })
+}
+
+pub fn apply_settings_patch(
+    conn: &Connection,
+    patch: Map<String, Value>,
+) -> Result<AppSettings, String> {
+    let current = load_settings(conn)?;
+    let mut merged = settings_to_map(&current)?;
+
+    for (key, value) in patch {
+        if !merged.contains_key(&key) {
+            return Err(format!("Unknown setting: {}", key));
+        }
+        merged.insert(key, value);
+    }
+
+    let mut updated: AppSettings = serde_json::from_value(Value::Object(merged))
+        .map_err(|err| format!("Invalid settings: {}", err))?;
+    keep_secrets(&mut updated, &current);
+    validate_working_hours(&updated.working_hours)?;
+    if i64::from(updated.rounding_minutes) > MAX_ROUNDING_MINUTES {
+        return Err(format!(
+            "Rounding must be between 0 and {} minutes",
+            MAX_ROUNDING_MINUTES
+        ));
+    }
+    if updated
+        .invoice_logo_width_mm
+        .is_some_and(|width| !(LOGO_WIDTH_RANGE_MM.0..=LOGO_WIDTH_RANGE_MM.1).contains(&width))
+    {
+        return Err(format!(
+            "Logo width must be between {} and {} mm",
+            LOGO_WIDTH_RANGE_MM.0, LOGO_WIDTH_RANGE_MM.1
+        ));
+    }
+    if updated
+        .week_start_day
+        .is_some_and(|day| !(1..=7).contains(&day))
+    {
+        return Err("Weeks must start on day 1 (Monday) to 7 (Sunday)".into());
+    }
+    if updated.max_entry_hours.is_some_and(|hours| hours < 0.0) {
+        return Err("Maximum entry length can't be negative".into());
+    }
+    if let Some(smtp) = updated.smtp.as_ref() {
+        email::validate_smtp(smtp)?;
+    }
+    if let Some(schedule) = updated.scheduled_backups.as_ref() {
+        backup::validate_schedule(schedule)?;
+    }
+    // Only checked when changed, since it runs OpenSSL.
+    if updated.pdf_signing != current.pdf_signing {
+        if let Some(signing) = updated.pdf_signing.as_ref() {
+            signing::validate_signing(signing)?;
+        }
+    }
+    save_settings(conn, &updated)?;
+    Ok(updated)
+}
+
+/// Settings just imported, with `current`'s secrets where the imported ones
+/// were left out of the export.
+pub fn restore_secrets(conn: &Connection, current: &AppSettings) -> Result<(), String> {
+    let mut imported = load_settings(conn)?;
+    keep_secrets(&mut imported, current);
+    save_settings(conn, &imported)
+}
+
+/// The webview never sees secrets, so a mail server saved without a password
+/// keeps the stored password of the same user, and a certificate saved
+/// without a passphrase keeps the stored one; an empty value removes it.
+fn keep_secrets(updated: &mut AppSettings, current: &AppSettings) {
+    if let Some(smtp) = updated.smtp.as_mut() {
+        match smtp.password.as_deref() {
+            None => {
+                smtp.password = current
+                    .smtp
+                    .as_ref()
+                    .filter(|saved| saved.username == smtp.username)
+                    .and_then(|saved| saved.password.clone());
+            }
+            Some("") => smtp.password = None,
+            Some(_) => {}
+        }
+    }
+    if let Some(signing) = updated.pdf_signing.as_mut() {
+        match signing.passphrase.as_deref() {
+            None => {
+                signing.passphrase = current
+                    .pdf_signing
+                    .as_ref()
+                    .filter(|saved| saved.certificate_path == signing.certificate_path)
+                    .and_then(|saved| saved.passphrase.clone());
+            }
+            Some("") => signing.passphrase = None,
+            Some(_) => {}
+        }
+    }
+}
+
+fn validate_working_hours(blocks: &[WorkBlock]) -> Result<(), String> {
+    for block in blocks {
+        let Some((start, end)) = block.bounds() else {
+            return Err(format!(
+                "Working hours must use HH:MM times, got {}–{}",
+                block.start, block.end
+            ));
+        };
+        if end <= start {
+            return Err("Working hours must end after they start".into());
+        }
+        if block.days.is_empty() || block.days.iter().any(|day| !(1..=7).contains(day)) {
+            return Err("Working days must be between 1 (Monday) and 7 (Sunday)".into());
+        }
+    }
+    Ok(())
+}
+
+fn save_settings(conn: &Connection, settings: &AppSettings) -> Result<(), String> {
+    for (key, value) in settings_to_map(settings)? {
+        conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            params![key, value.to_string()],
+        )
+        .map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+fn settings_to_map(settings: &AppSettings) -> Result<Map<String, Value>, String> {
+    match serde_json::to_value(settings).map_err(|err| err.to_string())? {
+        Value::Object(map) => Ok(map),
+        _ => Err("Settings must serialize to an object".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn connection() -> Connection {
+        let conn = Connection::open_in_memory().expect("in-memory database");
+        conn.execute(CREATE_SETTINGS_TABLE_SQL, [])
+            .expect("settings table");
+        conn
+    }
+
+    fn smtp_patch(password: Option<&str>) -> Map<String, Value> {
+        let mut smtp = json!({
+            "host": "mail.example.com",
+            "username": "me@example.com",
+            "fromAddress": "me@example.com",
+        });
+        if let Some(password) = password {
+            smtp["password"] = json!(password);
+        }
+        let mut patch = Map::new();
+        patch.insert("smtp".into(), smtp);
+        patch
+    }
+
+    fn stored_password(conn: &Connection) -> Option<String> {
+        load_settings(conn)
+            .unwrap()
+            .smtp
+            .and_then(|smtp| smtp.password)
+    }
+
+    #[test]
+    fn the_mail_password_is_written_but_never_read_back() {
+        let conn = connection();
+        let saved = apply_settings_patch(&conn, smtp_patch(Some("secret"))).unwrap();
+        assert_eq!(redacted(saved).smtp.unwrap().password, None);
+        assert_eq!(stored_password(&conn).as_deref(), Some("secret"));
+
+        // Saving the server again as the webview saw it keeps the password.
+        apply_settings_patch(&conn, smtp_patch(None)).unwrap();
+        assert_eq!(stored_password(&conn).as_deref(), Some("secret"));
+
+        apply_settings_patch(&conn, smtp_patch(Some(""))).unwrap();
+        assert_eq!(stored_password(&conn), None);
+    }
+}
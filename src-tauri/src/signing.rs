@@ -0,0 +1,379 @@
+use std::{
+    collections::hash_map::RandomState,
+    fs,
+    hash::{BuildHasher, Hasher},
+    io::{self, Write},
+    path::Path,
+    process::{Command, Stdio},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use chrono::Local;
+use printpdf::lopdf::{dictionary, Document, Object, StringFormat};
+use serde::{Deserialize, Serialize};
+
+use crate::PrivateFile;
+
+/// Room reserved in the PDF for the DER signature. The signer's certificate
+/// chain makes up most of it.
+const SIGNATURE_BYTES: usize = 16_384;
+/// Stands in for each ByteRange offset until the file's layout is known;
+/// wide enough that the real offsets always fit in its place.
+const BYTE_RANGE_PLACEHOLDER: i64 = 9_999_999_999;
+/// Hands the passphrase to OpenSSL without putting it on the command line.
+const PASSPHRASE_ENV: &str = "TIME_TRACKER_SIGNING_PASSPHRASE";
+const PASSPHRASE_ARG: &str = "env:TIME_TRACKER_SIGNING_PASSPHRASE";
+/// A one-off passphrase the private key is encrypted with while it's on
+/// disk for signing, even when the certificate itself has none.
+const KEY_PASSPHRASE_ENV: &str = "TIME_TRACKER_SIGNING_KEY_PASSPHRASE";
+const KEY_PASSPHRASE_ARG: &str = "env:TIME_TRACKER_SIGNING_KEY_PASSPHRASE";
+/// Tells apart the key files of invoices signed at the same time.
+static KEY_FILE_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// The PKCS#12 (.p12 / .pfx) certificate issued invoices are signed with.
+///
+/// Signing runs OpenSSL 1.1 or later. Windows doesn't ship it and macOS
+/// ships LibreSSL, which can't sign, so `openssl_path` can point at one
+/// installed separately, e.g. Homebrew's.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningSettings {
+    pub certificate_path: String,
+    /// Never sent back to the webview. Saving the same certificate without
+    /// one keeps the stored passphrase; an empty one removes it.
+    pub passphrase: Option<String>,
+    /// `openssl` on the PATH when unset.
+    #[serde(default)]
+    pub openssl_path: Option<String>,
+}
+
+impl SigningSettings {
+    fn passphrase(&self) -> &str {
+        self.passphrase.as_deref().unwrap_or_default()
+    }
+
+    fn openssl(&self) -> &str {
+        match self.openssl_path.as_deref().map(str::trim) {
+            Some(path) if !path.is_empty() => path,
+            _ => "openssl",
+        }
+    }
+}
+
+/// OpenSSL must be usable and the certificate must exist and open with the
+/// passphrase.
+pub fn validate_signing(signing: &SigningSettings) -> Result<(), String> {
+    openssl_version(signing.openssl())?;
+    if !Path::new(signing.certificate_path.trim()).is_file() {
+        return Err("The signing certificate file doesn't exist".into());
+    }
+    unlock_certificate(signing, "").map(|_| ())
+}
+
+/// The version of the OpenSSL at `openssl_path` (`openssl` on the PATH when
+/// unset), or why invoices can't be signed with it, for the settings screen.
+#[tauri::command]
+pub async fn check_pdf_signing(openssl_path: Option<String>) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let signing = SigningSettings {
+            certificate_path: String::new(),
+            passphrase: None,
+            openssl_path,
+        };
+        openssl_version(signing.openssl())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Runs `openssl version`, refusing LibreSSL and versions before 1.1, which
+/// lack the commands signing needs.
+fn openssl_version(openssl: &str) -> Result<String, String> {
+    let output = run_openssl(openssl, &["version"], "", "", None)?;
+    let version = String::from_utf8_lossy(&output).trim().to_string();
+    if version.starts_with("LibreSSL") {
+        return Err(format!(
+            "Signing invoices needs OpenSSL, but {} was found; install OpenSSL and set its path",
+            version
+        ));
+    }
+    let major_minor = version.strip_prefix("OpenSSL ").and_then(|rest| {
+        rest.split('.')
+            .take(2)
+            .map(str::parse::<u32>)
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+    });
+    match major_minor.as_deref() {
+        Some([major, minor]) if (*major, *minor) >= (1, 1) => Ok(version),
+        _ => Err(format!(
+            "Signing invoices needs OpenSSL 1.1 or later, but {} was found",
+            version
+        )),
+    }
+}
+
+/// Signs the PDF at `path` in place with a detached PKCS#7 signature over
+/// the whole file, in an invisible signature field on the first page, so
+/// PDF readers can show who issued it and that it hasn't been altered since.
+/// The key is written to `scratch_dir` while OpenSSL signs.
+pub fn sign_pdf(path: &str, signing: &SigningSettings, scratch_dir: &Path) -> Result<(), String> {
+    let mut doc =
+        Document::load(path).map_err(|e| format!("Failed to read the PDF to sign: {}", e))?;
+    let page_id = *doc
+        .get_pages()
+        .values()
+        .next()
+        .ok_or("The PDF to sign has no pages")?;
+
+    let signature_id = doc.add_object(dictionary! {
+        "Type" => "Sig",
+        "Filter" => "Adobe.PPKLite",
+        "SubFilter" => "adbe.pkcs7.detached",
+        "ByteRange" => vec![Object::Integer(0), BYTE_RANGE_PLACEHOLDER.into(), BYTE_RANGE_PLACEHOLDER.into(), BYTE_RANGE_PLACEHOLDER.into()],
+        "Contents" => Object::String(vec![0; SIGNATURE_BYTES], StringFormat::Hexadecimal),
+        "M" => Object::string_literal(signing_time()),
+    });
+    // Invisible through its empty rectangle; flags 132 are Print and Locked.
+    let field_id = doc.add_object(dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "Widget",
+        "FT" => "Sig",
+        "T" => Object::string_literal("Signature"),
+        "V" => signature_id,
+        "Rect" => vec![0.into(), 0.into(), 0.into(), 0.into()],
+        "F" => 132,
+        "P" => page_id,
+    });
+    let page = doc
+        .get_dictionary_mut(page_id)
+        .map_err(|err| err.to_string())?;
+    match page.get_mut(b"Annots") {
+        Ok(Object::Array(annots)) => annots.push(field_id.into()),
+        _ => page.set("Annots", vec![Object::from(field_id)]),
+    }
+    doc.catalog_mut().map_err(|err| err.to_string())?.set(
+        "AcroForm",
+        dictionary! {
+            "Fields" => vec![Object::from(field_id)],
+            // Signatures exist; append-only from here.
+            "SigFlags" => 3,
+        },
+    );
+
+    let mut pdf = Vec::new();
+    doc.save_to(&mut pdf)
+        .map_err(|e| format!("Failed to write the signed PDF: {}", e))?;
+
+    // The signature covers everything but its own hex string.
+    let placeholder = format!("<{}>", "0".repeat(SIGNATURE_BYTES * 2));
+    let contents_start =
+        find(&pdf, placeholder.as_bytes(), 0).ok_or("Couldn't find the signature placeholder")?;
+    let contents_end = contents_start + placeholder.len();
+    let range_key = find(&pdf, b"/ByteRange", 0).ok_or("Couldn't find the byte range")?;
+    let range_start = find(&pdf, b"[", range_key).ok_or("Couldn't find the byte range")? + 1;
+    let range_end = find(&pdf, b"]", range_start).ok_or("Couldn't find the byte range")?;
+    let byte_range = format!(
+        "0 {} {} {}",
+        contents_start,
+        contents_end,
+        pdf.len() - contents_end
+    );
+    let width = range_end - range_start;
+    if byte_range.len() > width {
+        return Err("The PDF is too large to sign".into());
+    }
+    pdf[range_start..range_end].copy_from_slice(format!("{:<width$}", byte_range).as_bytes());
+
+    let mut signed = pdf[..contents_start].to_vec();
+    signed.extend_from_slice(&pdf[contents_end..]);
+    let signature = sign_detached(signing, &signed, scratch_dir)?;
+    if signature.len() > SIGNATURE_BYTES {
+        return Err("The signature is too large for the space reserved in the PDF".into());
+    }
+    let hex: String = signature
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect();
+    pdf[contents_start + 1..contents_start + 1 + hex.len()].copy_from_slice(hex.as_bytes());
+
+    fs::write(path, pdf).map_err(|e| format!("Failed to save the signed PDF: {}", e))
+}
+
+/// The signer's certificate and private key as PEM, the key encrypted with
+/// `key_passphrase` so it isn't written to disk in the clear. An empty
+/// passphrase, which OpenSSL refuses to encrypt with, leaves it out.
+fn unlock_certificate(signing: &SigningSettings, key_passphrase: &str) -> Result<Vec<u8>, String> {
+    if key_passphrase.is_empty() {
+        read_pkcs12(signing, &["-clcerts", "-nokeys"], "")
+    } else {
+        read_pkcs12(
+            signing,
+            &["-clcerts", "-passout", KEY_PASSPHRASE_ARG],
+            key_passphrase,
+        )
+    }
+}
+
+fn read_pkcs12(
+    signing: &SigningSettings,
+    extra_args: &[&str],
+    key_passphrase: &str,
+) -> Result<Vec<u8>, String> {
+    let mut args = vec![
+        "pkcs12",
+        "-in",
+        signing.certificate_path.trim(),
+        "-passin",
+        PASSPHRASE_ARG,
+    ];
+    args.extend_from_slice(extra_args);
+    let run = |args: &[&str]| {
+        run_openssl(
+            signing.openssl(),
+            args,
+            signing.passphrase(),
+            key_passphrase,
+            None,
+        )
+    };
+    match run(&args) {
+        Ok(pem) => Ok(pem),
+        // Certificates exported by older tools use ciphers OpenSSL 3 only
+        // reads in legacy mode.
+        Err(err) => {
+            args.push("-legacy");
+            run(&args).map_err(|_| {
+                format!(
+                    "Couldn't open the signing certificate; check the passphrase ({})",
+                    err
+                )
+            })
+        }
+    }
+}
+
+/// A DER-encoded CMS signature of `content` that doesn't embed it, carrying
+/// the certificate chain so readers can trace the signer to a trusted root.
+fn sign_detached(
+    signing: &SigningSettings,
+    content: &[u8],
+    scratch_dir: &Path,
+) -> Result<Vec<u8>, String> {
+    let key_passphrase = one_off_passphrase();
+    let signer = unlock_certificate(signing, &key_passphrase)?;
+    let chain = read_pkcs12(signing, &["-cacerts", "-nokeys"], "")?;
+    let id = format!(
+        "{}_{}",
+        std::process::id(),
+        KEY_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    let private = |name: &str, contents: &[u8]| {
+        PrivateFile::create(scratch_dir.join(format!("{}_{}.pem", name, id)), contents)
+            .map_err(|e| format!("Failed to prepare the signing certificate: {}", e))
+    };
+    // Both are deleted when this returns.
+    let signer_file = private("signer", &signer)?;
+    let chain_file = private("chain", &chain)?;
+    let signer_arg = signer_file.path().to_string_lossy();
+    let chain_arg = chain_file.path().to_string_lossy();
+    let mut args = vec![
+        "cms",
+        "-sign",
+        "-binary",
+        "-outform",
+        "DER",
+        "-md",
+        "sha256",
+        "-signer",
+        &signer_arg,
+        "-inkey",
+        &signer_arg,
+        "-passin",
+        KEY_PASSPHRASE_ARG,
+    ];
+    if chain.windows(5).any(|window| window == b"BEGIN") {
+        args.extend(["-certfile", &chain_arg]);
+    }
+    run_openssl(signing.openssl(), &args, "", &key_passphrase, Some(content))
+        .map_err(|err| format!("Failed to sign the invoice: {}", err))
+}
+
+/// 128 unpredictable bits as hex, from the randomly keyed hasher the
+/// standard library seeds from the OS.
+fn one_off_passphrase() -> String {
+    (0..2)
+        .map(|round| {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u32(KEY_FILE_COUNTER.fetch_add(1, Ordering::Relaxed));
+            hasher.write_u32(round);
+            hasher.write_u128(
+                std::time::SystemTime::UNIX_EPOCH
+                    .elapsed()
+                    .unwrap_or_default()
+                    .as_nanos(),
+            );
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
+
+/// Runs `openssl` with `input` on stdin and returns its stdout.
+fn run_openssl(
+    openssl: &str,
+    args: &[&str],
+    passphrase: &str,
+    key_passphrase: &str,
+    input: Option<&[u8]>,
+) -> Result<Vec<u8>, String> {
+    let mut child = Command::new(openssl)
+        .args(args)
+        .env(PASSPHRASE_ENV, passphrase)
+        .env(KEY_PASSPHRASE_ENV, key_passphrase)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => format!(
+                "Signing invoices needs OpenSSL 1.1 or later, which wasn't found at \"{}\"",
+                openssl
+            ),
+            _ => format!("Couldn't start OpenSSL: {}", err),
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.unwrap_or_default())
+            .map_err(|err| err.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|err| err.to_string())?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(stderr
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or("OpenSSL failed")
+            .trim()
+            .to_string())
+    }
+}
+
+/// Now as a PDF date, e.g. "D:20250301120000+01'00'".
+fn signing_time() -> String {
+    let now = Local::now();
+    format!(
+        "D:{}{}'",
+        now.format("%Y%m%d%H%M%S"),
+        now.format("%:z").to_string().replace(':', "'")
+    )
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|index| from + index)
+}
@@ -0,0 +1,696 @@
+use std::path::Path;
+
+use chrono::{Datelike, Duration};
+use rusqlite::params;
+use serde::Deserialize;
+use tauri::Manager;
+
+use crate::{
+    archive::ZipWriter,
+    entry_local_date, load_stored_invoice, map_time_entry, open_connection, projects,
+    resolve_db_path,
+    ubl::{escape, iso_date},
+    TimeEntry, ENTRY_TYPE_WORK, TIME_ENTRY_COLUMNS,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpreadsheetFormat {
+    Csv,
+    Xlsx,
+}
+
+impl SpreadsheetFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            SpreadsheetFormat::Csv => "csv",
+            SpreadsheetFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+/// Writes the invoice's billed entries as a CSV or XLSX file next to its PDF,
+/// for clients whose accounts payable want a spreadsheet too. Entries of
+/// fixed-fee projects show their hours only, with the fee as its own row;
+/// invoices without linked entries list their line items. Whatever rounding
+/// or minimums added on top of the entries is an adjustment row, so the
+/// rows add up to the invoice's total. Returns the file's path.
+#[tauri::command]
+pub async fn export_invoice_lines(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    format: SpreadsheetFormat,
+) -> Result<String, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let (invoice, render) = load_stored_invoice(&conn, id)?;
+
+        let mut rows = Vec::new();
+        if render.entries.is_empty() {
+            for item in &render.line_items {
+                rows.push(vec![
+                    item.date.clone().map(Cell::from).unwrap_or(Cell::Empty),
+                    Cell::Empty,
+                    item.description.as_str().into(),
+                    if item.fixed_fee {
+                        Cell::Empty
+                    } else {
+                        item.quantity.into()
+                    },
+                    item.unit_price.into(),
+                    item.amount.into(),
+                ]);
+            }
+        } else {
+            let mut fixed_fee_projects = Vec::new();
+            for entry in &render.entries {
+                if projects::fixed_fee_for(&conn, &entry.project_name)?.is_some() {
+                    fixed_fee_projects.push(entry.project_name.clone());
+                }
+            }
+            for entry in &render.entries {
+                let fixed_fee = fixed_fee_projects.contains(&entry.project_name);
+                rows.push(vec![
+                    entry_local_date(entry)
+                        .format("%Y-%m-%d")
+                        .to_string()
+                        .into(),
+                    entry.project_name.as_str().into(),
+                    entry.notes.clone().map(Cell::from).unwrap_or(Cell::Empty),
+                    (entry.duration as f64 / 3600.0).into(),
+                    if fixed_fee {
+                        Cell::Empty
+                    } else {
+                        entry.hourly_rate.into()
+                    },
+                    if fixed_fee {
+                        Cell::Empty
+                    } else {
+                        entry.amount.into()
+                    },
+                ]);
+            }
+            for item in render.line_items.iter().filter(|item| item.fixed_fee) {
+                rows.push(vec![
+                    item.date.clone().map(Cell::from).unwrap_or(Cell::Empty),
+                    Cell::Empty,
+                    item.description.as_str().into(),
+                    Cell::Empty,
+                    Cell::Empty,
+                    item.amount.into(),
+                ]);
+            }
+        }
+        let total: f64 = render.line_items.iter().map(|item| item.amount).sum();
+        let note = render
+            .rounding
+            .map(|rounding| rounding.note(render.language.labels()));
+        push_totals(&mut rows, total, note);
+
+        let sheet = Sheet {
+            name: format!("Invoice {}", invoice.id),
+            header: vec![
+                "Date".to_string(),
+                "Project".to_string(),
+                "Description".to_string(),
+                "Hours".to_string(),
+                format!("Rate ({})", render.currency),
+                format!("Amount ({})", render.currency),
+            ],
+            rows,
+        };
+        let output_path = Path::new(&invoice.file_path).with_extension(format.extension());
+        std::fs::write(&output_path, sheet.render(format)?)
+            .map_err(|e| format!("Failed to write the spreadsheet: {}", e))?;
+        output_path
+            .to_str()
+            .map(str::to_string)
+            .ok_or_else(|| "Invalid file path".to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// What each worksheet of `export_entries_workbook` holds.
+/// Ends an invoice sheet with its total, after an adjustment row for the
+/// difference between the amounts listed and the total, if there is one.
+/// The amount is the last of the six columns.
+fn push_totals(rows: &mut Vec<Vec<Cell>>, total: f64, note: Option<String>) {
+    let listed: f64 = rows
+        .iter()
+        .filter_map(|row| match row.last() {
+            Some(Cell::Number(amount)) => Some(*amount),
+            _ => None,
+        })
+        .sum();
+    let adjustment = total - listed;
+    if adjustment.abs() >= 0.005 {
+        rows.push(vec![
+            Cell::Empty,
+            Cell::Empty,
+            note.unwrap_or_else(|| "Adjustment".to_string()).into(),
+            Cell::Empty,
+            Cell::Empty,
+            adjustment.into(),
+        ]);
+    }
+    rows.push(vec![
+        Cell::Empty,
+        Cell::Empty,
+        "Total".into(),
+        Cell::Empty,
+        Cell::Empty,
+        total.into(),
+    ]);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkbookGrouping {
+    Project,
+    /// Local weeks starting on Monday.
+    Week,
+}
+
+/// Writes the work entries in `start_time..end_time` as an XLSX workbook in
+/// Downloads: a summary sheet, then one sheet per project or week listing
+/// its entries and ending in its totals, one per currency. Returns the
+/// file's path.
+#[tauri::command]
+pub async fn export_entries_workbook(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+    group_by: WorkbookGrouping,
+) -> Result<String, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let downloads_path = app_handle
+        .path()
+        .download_dir()
+        .map_err(|e| format!("Failed to get downloads directory: {}", e))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {TIME_ENTRY_COLUMNS} FROM time_entries
+                 WHERE start_time >= ?1 AND start_time < ?2 AND entry_type = ?3
+                 ORDER BY start_time ASC"
+            ))
+            .map_err(|err| err.to_string())?;
+        let entries = stmt
+            .query_map(
+                params![start_time, end_time, ENTRY_TYPE_WORK],
+                map_time_entry,
+            )
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        if entries.is_empty() {
+            return Err("No time entries in the selected range".into());
+        }
+
+        // Entries arrive in time order, so weeks come out in order too.
+        let mut groups: Vec<(String, Vec<&TimeEntry>)> = Vec::new();
+        for entry in &entries {
+            let name = match group_by {
+                WorkbookGrouping::Project => entry.project_name.clone(),
+                WorkbookGrouping::Week => {
+                    let day = entry_local_date(entry);
+                    let monday =
+                        day - Duration::days(i64::from(day.weekday().num_days_from_monday()));
+                    format!("Week of {}", monday.format("%Y-%m-%d"))
+                }
+            };
+            match groups.iter_mut().find(|(group, _)| *group == name) {
+                Some((_, members)) => members.push(entry),
+                None => groups.push((name, vec![entry])),
+            }
+        }
+        if group_by == WorkbookGrouping::Project {
+            groups.sort_by_key(|(name, _)| name.to_lowercase());
+        }
+
+        let mut summary = Sheet {
+            name: "Summary".into(),
+            header: [
+                match group_by {
+                    WorkbookGrouping::Project => "Project",
+                    WorkbookGrouping::Week => "Week",
+                },
+                "Hours",
+                "Amount",
+                "Currency",
+            ]
+            .map(String::from)
+            .to_vec(),
+            rows: Vec::new(),
+        };
+        let mut sheets = Vec::new();
+        for (name, members) in &groups {
+            let mut header = vec!["Date".to_string()];
+            if group_by == WorkbookGrouping::Week {
+                header.push("Project".to_string());
+            }
+            header.extend(["Description", "Hours", "Rate", "Amount", "Currency"].map(String::from));
+            let mut rows = Vec::new();
+            // (currency, hours, amount)
+            let mut totals: Vec<(&str, f64, f64)> = Vec::new();
+            for entry in members {
+                let hours = entry.duration as f64 / 3600.0;
+                let mut row = vec![Cell::from(
+                    entry_local_date(entry).format("%Y-%m-%d").to_string(),
+                )];
+                if group_by == WorkbookGrouping::Week {
+                    row.push(entry.project_name.as_str().into());
+                }
+                row.extend([
+                    entry.notes.clone().map_or(Cell::Empty, Cell::from),
+                    hours.into(),
+                    entry.hourly_rate.into(),
+                    entry.amount.into(),
+                    entry.currency.as_str().into(),
+                ]);
+                rows.push(row);
+                match totals
+                    .iter_mut()
+                    .find(|(currency, ..)| *currency == entry.currency)
+                {
+                    Some(total) => {
+                        total.1 += hours;
+                        total.2 += entry.amount;
+                    }
+                    None => totals.push((&entry.currency, hours, entry.amount)),
+                }
+            }
+            for (currency, hours, amount) in totals {
+                let mut row = vec![Cell::Empty];
+                if group_by == WorkbookGrouping::Week {
+                    row.push(Cell::Empty);
+                }
+                row.extend([
+                    "Total".into(),
+                    hours.into(),
+                    Cell::Empty,
+                    amount.into(),
+                    currency.into(),
+                ]);
+                rows.push(row);
+                summary.rows.push(vec![
+                    name.as_str().into(),
+                    hours.into(),
+                    amount.into(),
+                    currency.into(),
+                ]);
+            }
+            sheets.push(Sheet {
+                name: name.clone(),
+                header,
+                rows,
+            });
+        }
+        sheets.insert(0, summary);
+
+        let output_path = downloads_path.join(format!(
+            "time_entries_by_{}_{}_{}.xlsx",
+            match group_by {
+                WorkbookGrouping::Project => "project",
+                WorkbookGrouping::Week => "week",
+            },
+            iso_date(start_time),
+            iso_date(end_time - 1)
+        ));
+        std::fs::write(&output_path, workbook_xlsx(&sheets)?)
+            .map_err(|e| format!("Failed to write the spreadsheet: {}", e))?;
+        output_path
+            .to_str()
+            .map(str::to_string)
+            .ok_or_else(|| "Invalid file path".to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+impl From<&str> for Cell {
+    fn from(value: &str) -> Self {
+        Cell::Text(value.to_string())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Cell::Text(value)
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(value: f64) -> Self {
+        Cell::Number(value)
+    }
+}
+
+/// One worksheet: a bold header row followed by data rows.
+#[derive(Debug, Clone, Default)]
+pub struct Sheet {
+    pub name: String,
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Sheet {
+    pub fn render(&self, format: SpreadsheetFormat) -> Result<Vec<u8>, String> {
+        match format {
+            SpreadsheetFormat::Csv => Ok(self.to_csv().into_bytes()),
+            SpreadsheetFormat::Xlsx => self.to_xlsx(),
+        }
+    }
+
+    /// RFC 4180 CSV with CRLF line endings. Numbers are written with two
+    /// decimals, which is what accounting imports expect.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        let header = self.header.iter().map(|title| csv_field(title));
+        csv.push_str(&header.collect::<Vec<_>>().join(","));
+        csv.push_str("\r\n");
+        for row in &self.rows {
+            let fields = row.iter().map(|cell| match cell {
+                Cell::Text(text) => csv_field(text),
+                Cell::Number(number) => format!("{:.2}", number),
+                Cell::Empty => String::new(),
+            });
+            csv.push_str(&fields.collect::<Vec<_>>().join(","));
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+
+    /// A workbook holding only this sheet.
+    pub fn to_xlsx(&self) -> Result<Vec<u8>, String> {
+        workbook_xlsx(std::slice::from_ref(self))
+    }
+
+    /// Excel rejects sheet names over 31 characters or containing any of
+    /// `[]:*?/\`.
+    fn sheet_name(&self) -> String {
+        let name: String = self
+            .name
+            .chars()
+            .filter(|ch| !"[]:*?/\\".contains(*ch))
+            .take(31)
+            .collect();
+        if name.trim().is_empty() {
+            "Sheet1".to_string()
+        } else {
+            name
+        }
+    }
+
+    fn worksheet_xml(&self) -> String {
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n");
+        xml.push_str(
+            "<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">",
+        );
+        xml.push_str("<sheetViews><sheetView workbookViewId=\"0\"><pane ySplit=\"1\" topLeftCell=\"A2\" activePane=\"bottomLeft\" state=\"frozen\"/></sheetView></sheetViews>");
+        xml.push_str("<sheetData>");
+        let header: Vec<Cell> = self
+            .header
+            .iter()
+            .map(|title| title.as_str().into())
+            .collect();
+        for (index, row) in std::iter::once(&header).chain(&self.rows).enumerate() {
+            let row_number = index + 1;
+            // Style 1 is the bold header, 2 two-decimal numbers.
+            let text_style = if index == 0 { " s=\"1\"" } else { "" };
+            xml.push_str(&format!("<row r=\"{}\">", row_number));
+            for (column, cell) in row.iter().enumerate() {
+                let reference = format!("{}{}", column_name(column), row_number);
+                match cell {
+                    Cell::Text(text) => xml.push_str(&format!(
+                        "<c r=\"{}\" t=\"inlineStr\"{}><is><t xml:space=\"preserve\">{}</t></is></c>",
+                        reference,
+                        text_style,
+                        escape(&xml_text(text))
+                    )),
+                    Cell::Number(number) if number.is_finite() => xml.push_str(&format!(
+                        "<c r=\"{}\" s=\"2\"><v>{}</v></c>",
+                        reference, number
+                    )),
+                    Cell::Number(_) | Cell::Empty => {}
+                }
+            }
+            xml.push_str("</row>");
+        }
+        xml.push_str("</sheetData></worksheet>");
+        xml
+    }
+}
+
+/// A minimal Office Open XML workbook with one worksheet per sheet, in
+/// order. Text is written as inline strings so no shared string table is
+/// needed.
+pub fn workbook_xlsx(sheets: &[Sheet]) -> Result<Vec<u8>, String> {
+    if sheets.is_empty() {
+        return Err("A workbook needs at least one sheet".into());
+    }
+    // Excel compares sheet names case-insensitively.
+    let mut names: Vec<String> = Vec::new();
+    for sheet in sheets {
+        let base = sheet.sheet_name();
+        let mut name = base.clone();
+        let mut copy = 1;
+        while names
+            .iter()
+            .any(|taken| taken.to_lowercase() == name.to_lowercase())
+        {
+            copy += 1;
+            let suffix = format!(" ({})", copy);
+            let kept: String = base.chars().take(31 - suffix.len()).collect();
+            name = format!("{}{}", kept, suffix);
+        }
+        names.push(name);
+    }
+
+    let mut zip = ZipWriter::new();
+    zip.add_file(
+        "[Content_Types].xml",
+        content_types_xml(sheets.len()).as_bytes(),
+    )?;
+    zip.add_file("_rels/.rels", ROOT_RELS_XML.as_bytes())?;
+    zip.add_file("xl/workbook.xml", workbook_xml(&names).as_bytes())?;
+    zip.add_file(
+        "xl/_rels/workbook.xml.rels",
+        workbook_rels_xml(sheets.len()).as_bytes(),
+    )?;
+    zip.add_file("xl/styles.xml", STYLES_XML.as_bytes())?;
+    for (index, sheet) in sheets.iter().enumerate() {
+        zip.add_file(
+            &format!("xl/worksheets/sheet{}.xml", index + 1),
+            sheet.worksheet_xml().as_bytes(),
+        )?;
+    }
+    zip.finish()
+}
+
+/// Quotes the field if it needs it. Text starting with `=`, `+`, `-` or `@`
+/// gets a leading `'`, so a spreadsheet opening the file shows it rather
+/// than running it as a formula.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// Drops control characters XML 1.0 can't carry, keeping tabs and line
+/// breaks.
+fn xml_text(value: &str) -> String {
+    value
+        .chars()
+        .filter(|ch| !ch.is_control() || matches!(ch, '\t' | '\n' | '\r'))
+        .collect()
+}
+
+/// Spreadsheet column letters: 0 is "A", 25 "Z", 26 "AA".
+fn column_name(index: usize) -> String {
+    let mut name = Vec::new();
+    let mut index = index + 1;
+    while index > 0 {
+        let remainder = (index - 1) % 26;
+        name.push(b'A' + remainder as u8);
+        index = (index - 1) / 26;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+fn workbook_xml(sheet_names: &[String]) -> String {
+    let sheets: String = sheet_names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            format!(
+                "<sheet name=\"{}\" sheetId=\"{}\" r:id=\"rId{}\"/>",
+                escape(name),
+                index + 1,
+                index + 1
+            )
+        })
+        .collect();
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+            "<workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\"",
+            " xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">",
+            "<sheets>{}</sheets></workbook>",
+        ),
+        sheets
+    )
+}
+
+fn content_types_xml(sheet_count: usize) -> String {
+    let worksheets: String = (1..=sheet_count)
+        .map(|number| {
+            format!(
+                "<Override PartName=\"/xl/worksheets/sheet{}.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>",
+                number
+            )
+        })
+        .collect();
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+            "<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">",
+            "<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>",
+            "<Default Extension=\"xml\" ContentType=\"application/xml\"/>",
+            "<Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>",
+            "{}",
+            "<Override PartName=\"/xl/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/>",
+            "</Types>",
+        ),
+        worksheets
+    )
+}
+
+const ROOT_RELS_XML: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+    "<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+    "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/>",
+    "</Relationships>",
+);
+
+/// Worksheets take `rId1` onwards, in order; styles come after them.
+fn workbook_rels_xml(sheet_count: usize) -> String {
+    let worksheets: String = (1..=sheet_count)
+        .map(|number| {
+            format!(
+                "<Relationship Id=\"rId{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet{}.xml\"/>",
+                number, number
+            )
+        })
+        .collect();
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+            "<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+            "{}",
+            "<Relationship Id=\"rId{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/>",
+            "</Relationships>",
+        ),
+        worksheets,
+        sheet_count + 1
+    )
+}
+
+/// Cell formats: 0 default, 1 bold, 2 numbers with two decimals ("0.00").
+const STYLES_XML: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+    "<styleSheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">",
+    "<fonts count=\"2\"><font><sz val=\"11\"/><name val=\"Calibri\"/></font>",
+    "<font><b/><sz val=\"11\"/><name val=\"Calibri\"/></font></fonts>",
+    "<fills count=\"2\"><fill><patternFill patternType=\"none\"/></fill>",
+    "<fill><patternFill patternType=\"gray125\"/></fill></fills>",
+    "<borders count=\"1\"><border><left/><right/><top/><bottom/><diagonal/></border></borders>",
+    "<cellStyleXfs count=\"1\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\"/></cellStyleXfs>",
+    "<cellXfs count=\"3\">",
+    "<xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\"/>",
+    "<xf numFmtId=\"0\" fontId=\"1\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyFont=\"1\"/>",
+    "<xf numFmtId=\"2\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyNumberFormat=\"1\"/>",
+    "</cellXfs>",
+    "<cellStyles count=\"1\"><cellStyle name=\"Normal\" xfId=\"0\" builtinId=\"0\"/></cellStyles>",
+    "</styleSheet>",
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry_row(amount: f64) -> Vec<Cell> {
+        vec![
+            "2026-03-02".into(),
+            "Website".into(),
+            Cell::Empty,
+            1.0.into(),
+            amount.into(),
+            amount.into(),
+        ]
+    }
+
+    #[test]
+    fn rounding_on_top_of_the_entries_is_an_adjustment_row() {
+        let mut rows = vec![entry_row(40.0), entry_row(35.5)];
+        push_totals(&mut rows, 90.0, Some("Rounded up to 15 minutes".into()));
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[2][2], Cell::from("Rounded up to 15 minutes"));
+        assert_eq!(rows[2][5], Cell::Number(14.5));
+        assert_eq!(rows[3][2], Cell::from("Total"));
+        assert_eq!(rows[3][5], Cell::Number(90.0));
+    }
+
+    #[test]
+    fn entries_that_add_up_need_no_adjustment() {
+        let mut rows = vec![entry_row(40.0), entry_row(35.5)];
+        push_totals(&mut rows, 75.5, None);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2][2], Cell::from("Total"));
+    }
+
+    #[test]
+    fn csv_text_that_looks_like_a_formula_is_written_as_text() {
+        let sheet = Sheet {
+            name: "Invoice".into(),
+            header: vec!["Description".into(), "Amount".into()],
+            rows: vec![
+                vec!["=HYPERLINK(\"x\",\"y\")".into(), (-5.0).into()],
+                vec!["@SUM(A1)".into(), Cell::Empty],
+                vec!["+1".into(), Cell::Empty],
+                vec!["-call".into(), Cell::Empty],
+                vec!["Design, phase 2".into(), Cell::Empty],
+            ],
+        };
+        assert_eq!(
+            sheet.to_csv(),
+            "Description,Amount\r\n\
+             \"'=HYPERLINK(\"\"x\"\",\"\"y\"\")\",-5.00\r\n\
+             '@SUM(A1),\r\n\
+             '+1,\r\n\
+             '-call,\r\n\
+             \"Design, phase 2\",\r\n"
+        );
+    }
+}
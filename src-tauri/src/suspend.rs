@@ -0,0 +1,365 @@
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    continuous_seconds, current_unix_timestamp, open_connection, resolve_db_path,
+    send_notification, stop_timer_internal, StopOrigin, TimerState, TimerStatusPayload,
+    BACKGROUND_CHECK_INTERVAL,
+};
+
+pub const RESUMED_EVENT: &str = "timer://resumed";
+
+/// Extra wall-clock time between two scheduler ticks that counts as a
+/// suspend rather than a slow tick.
+const MIN_SUSPEND_SECONDS: i64 = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuspendReason {
+    Sleep,
+    /// The screen was locked; `suspended_at` is the last tick before that.
+    Lock,
+    /// The app wasn't running: a crash or forced shutdown. `suspended_at` is
+    /// the timers' last heartbeat.
+    Shutdown,
+}
+
+/// A stretch the machine spent asleep or locked, or the app spent closed,
+/// while timers were running.
+#[derive(Debug, Clone, Serialize)]
+pub struct SuspendPeriod {
+    pub reason: SuspendReason,
+    pub suspended_at: i64,
+    pub resumed_at: i64,
+    /// Timers that were running (and not paused) when the machine went to sleep.
+    pub timer_ids: Vec<i64>,
+    /// After a shutdown, when each timer was last seen running; its entry
+    /// ends there when stopped. `suspended_at` is the earliest of these.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub last_seen: Vec<TimerHeartbeat>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimerHeartbeat {
+    pub timer_id: i64,
+    pub at: i64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuspendAction {
+    /// Count the time asleep as worked.
+    Keep,
+    /// Save the entry ending when the machine went to sleep (or at the last
+    /// heartbeat after a shutdown).
+    Stop,
+}
+
+#[derive(Default)]
+pub struct SuspendState {
+    inner: Mutex<SuspendInner>,
+}
+
+#[derive(Default)]
+struct SuspendInner {
+    /// Wall-clock and `continuous_seconds` readings at the last tick.
+    last_tick: Option<(i64, i64)>,
+    /// The last tick before the screen was seen locked, while it still is.
+    locked_since: Option<i64>,
+    pending: Option<SuspendPeriod>,
+}
+
+/// The suspend waiting for the user's decision, if any.
+#[tauri::command]
+pub async fn get_suspend_period(
+    app_handle: tauri::AppHandle,
+) -> Result<Option<SuspendPeriod>, String> {
+    let state = app_handle.state::<SuspendState>();
+    let guard = state
+        .inner
+        .lock()
+        .map_err(|_| "Suspend state is unavailable")?;
+    Ok(guard.pending.clone())
+}
+
+/// Applies `action` to every timer that was running across the suspend and
+/// returns the running timers afterwards.
+#[tauri::command]
+pub async fn resolve_suspend(
+    app_handle: tauri::AppHandle,
+    action: SuspendAction,
+) -> Result<Vec<TimerStatusPayload>, String> {
+    let period = {
+        let state = app_handle.state::<SuspendState>();
+        let mut guard = state
+            .inner
+            .lock()
+            .map_err(|_| "Suspend state is unavailable")?;
+        guard.pending.take()
+    };
+    let Some(period) = period else {
+        return Err("There is no suspend to resolve".into());
+    };
+
+    if let SuspendAction::Stop = action {
+        let running = app_handle.state::<TimerState>().statuses();
+        for id in period.timer_ids {
+            // Timers stopped since the resume have already been saved.
+            if running.iter().any(|status| status.timer_id == Some(id)) {
+                let stop_at = period
+                    .last_seen
+                    .iter()
+                    .find(|heartbeat| heartbeat.timer_id == id)
+                    .map_or(period.suspended_at, |heartbeat| heartbeat.at);
+                stop_timer_internal(&app_handle, Some(id), StopOrigin::Window, Some(stop_at))
+                    .await?;
+            }
+        }
+    }
+
+    Ok(app_handle.state::<TimerState>().statuses())
+}
+
+/// Called on every scheduler tick. The scheduler thread doesn't run while
+/// the machine sleeps, so a tick arriving far later than the interval means
+/// the machine was suspended in between. Lateness is measured on a clock
+/// that can't be set, so setting the wall clock forwards isn't mistaken for
+/// a suspend. A screen locked for as long counts too, once it is unlocked.
+pub fn check_suspend(app: &AppHandle) {
+    let now = current_unix_timestamp();
+    let continuous = continuous_seconds();
+    let locked = screen_locked();
+    let state = app.state::<SuspendState>();
+    let mut guard = state.inner.lock().expect("suspend state poisoned");
+    let last_tick = guard.last_tick.replace((now, continuous));
+    let Some((last_tick, last_continuous)) = last_tick else {
+        if locked {
+            guard.locked_since = Some(now);
+        }
+        return;
+    };
+    let expected = BACKGROUND_CHECK_INTERVAL.as_secs() as i64;
+    let slept = continuous - last_continuous >= expected + MIN_SUSPEND_SECONDS;
+    let unlocked = if locked {
+        guard.locked_since.get_or_insert(last_tick);
+        None
+    } else {
+        guard.locked_since.take()
+    };
+    let (reason, away_since) = match unlocked {
+        Some(since) if now - since >= expected + MIN_SUSPEND_SECONDS => {
+            (SuspendReason::Lock, since)
+        }
+        _ if slept => (SuspendReason::Sleep, last_tick),
+        _ => return,
+    };
+
+    let timer_ids: Vec<i64> = app
+        .state::<TimerState>()
+        .statuses()
+        .iter()
+        .filter(|status| !status.is_paused && status.start_time.is_some_and(|at| at < away_since))
+        .filter_map(|status| status.timer_id)
+        .collect();
+    if timer_ids.is_empty() {
+        return;
+    }
+
+    // A second suspend before the first is resolved extends it.
+    let suspended_at = guard
+        .pending
+        .as_ref()
+        .map_or(away_since, |pending| pending.suspended_at.min(away_since));
+    let period = SuspendPeriod {
+        reason,
+        suspended_at,
+        resumed_at: now,
+        timer_ids,
+        last_seen: Vec::new(),
+    };
+    guard.pending = Some(period.clone());
+    drop(guard);
+
+    let _ = app.emit(RESUMED_EVENT, period);
+    let body = match reason {
+        SuspendReason::Lock => "A timer kept running while your screen was locked. Open Time Tracker to keep the time or end the entry when it was locked.",
+        _ => "A timer kept running while your computer was asleep. Open Time Tracker to keep the time or end the entry when it went to sleep.",
+    };
+    send_notification(app, "Welcome back", body);
+}
+
+/// Whether the session's screen is locked, where the platform says.
+#[cfg(target_os = "windows")]
+fn screen_locked() -> bool {
+    #[link(name = "user32")]
+    extern "system" {
+        fn OpenInputDesktop(flags: u32, inherit: i32, desired_access: u32) -> isize;
+        fn CloseDesktop(desktop: isize) -> i32;
+    }
+    const DESKTOP_SWITCHDESKTOP: u32 = 0x0100;
+
+    // SAFETY: OpenInputDesktop takes no pointers; a handle it returns is
+    // closed right away.
+    let desktop = unsafe { OpenInputDesktop(0, 0, DESKTOP_SWITCHDESKTOP) };
+    if desktop == 0 {
+        // The lock screen runs on a desktop the user's processes can't open.
+        return true;
+    }
+    // SAFETY: `desktop` is the handle just opened.
+    unsafe { CloseDesktop(desktop) };
+    false
+}
+
+/// Reads `CGSSessionScreenIsLocked` from the console user's session in the
+/// IORegistry root.
+#[cfg(target_os = "macos")]
+fn screen_locked() -> bool {
+    std::process::Command::new("ioreg")
+        .args(["-n", "Root", "-d", "1"])
+        .output()
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout).contains("\"CGSSessionScreenIsLocked\"=Yes")
+        })
+        .unwrap_or(false)
+}
+
+/// systemd-logind's `LockedHint` for this session, which desktop
+/// environments set while their lock screen is up.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn screen_locked() -> bool {
+    let session = std::env::var("XDG_SESSION_ID").unwrap_or_else(|_| "self".into());
+    std::process::Command::new("loginctl")
+        .args(["show-session", &session, "--property=LockedHint", "--value"])
+        .output()
+        .map(|output| output.status.success() && output.stdout.trim_ascii() == b"yes")
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn screen_locked() -> bool {
+    false
+}
+
+/// Stamps each running timer with the current time, so a crash can later be
+/// told apart from time actually worked. Paused timers keep the stamp from
+/// when they were paused; starting, resuming or otherwise saving a timer
+/// stamps it too.
+pub fn record_heartbeat(app: &AppHandle) {
+    let Ok(conn) = resolve_db_path(app).and_then(open_connection) else {
+        return;
+    };
+    let _ = conn.execute(
+        "UPDATE active_timers SET last_heartbeat = ?1 WHERE paused_at IS NULL",
+        params![current_unix_timestamp()],
+    );
+}
+
+/// Called as the app exits normally, whether quit from the tray, the app
+/// menu or by the system logging out, so restored timers keep counting the
+/// time the app was closed as before.
+pub fn clear_heartbeats(app: &AppHandle) {
+    let Ok(conn) = resolve_db_path(app).and_then(open_connection) else {
+        return;
+    };
+    let _ = conn.execute("UPDATE active_timers SET last_heartbeat = NULL", []);
+}
+
+/// Runs once the persisted timers are restored. If their last heartbeat is
+/// well in the past the app was not running in between, so the user is
+/// offered to end the entries there instead of counting the downtime.
+/// Returns the ids of the timers included in that offer.
+pub fn check_restored_timers(app: &AppHandle, conn: &Connection) -> Result<Vec<i64>, String> {
+    let now = current_unix_timestamp();
+    let last_seen = stale_heartbeats(conn, now)?;
+    let Some(suspended_at) = last_seen.iter().map(|heartbeat| heartbeat.at).min() else {
+        return Ok(Vec::new());
+    };
+
+    let period = SuspendPeriod {
+        reason: SuspendReason::Shutdown,
+        suspended_at,
+        resumed_at: now,
+        timer_ids: last_seen
+            .iter()
+            .map(|heartbeat| heartbeat.timer_id)
+            .collect(),
+        last_seen,
+    };
+    {
+        let state = app.state::<SuspendState>();
+        let mut guard = state
+            .inner
+            .lock()
+            .map_err(|_| "Suspend state is unavailable")?;
+        guard.pending = Some(period.clone());
+    }
+    let timer_ids = period.timer_ids.clone();
+    let _ = app.emit(RESUMED_EVENT, period);
+    send_notification(
+        app,
+        "Timer restored",
+        "Time Tracker was closed unexpectedly while a timer was running. Open it to keep the time or end the entry when it was last seen.",
+    );
+    Ok(timer_ids)
+}
+
+/// Timers whose last heartbeat is too long ago for the app to have been
+/// running since, with when each was last seen.
+fn stale_heartbeats(conn: &Connection, now: i64) -> Result<Vec<TimerHeartbeat>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, last_heartbeat FROM active_timers
+             WHERE last_heartbeat IS NOT NULL AND paused_at IS NULL
+             ORDER BY id ASC",
+        )
+        .map_err(|err| err.to_string())?;
+    let heartbeats = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    let expected = BACKGROUND_CHECK_INTERVAL.as_secs() as i64;
+    Ok(heartbeats
+        .into_iter()
+        .filter(|(_, heartbeat)| now - heartbeat >= expected + MIN_SUSPEND_SECONDS)
+        .map(|(timer_id, at)| TimerHeartbeat { timer_id, at })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_timer_is_ended_at_its_own_heartbeat() {
+        let path = std::env::temp_dir().join(format!("tt-heartbeats-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let conn = open_connection(path).expect("test database");
+        conn.execute_batch(
+            "INSERT INTO active_timers (id, project_name, start_time, last_heartbeat)
+                 VALUES (1, 'Website', 0, 1000);
+             INSERT INTO active_timers (id, project_name, start_time, last_heartbeat)
+                 VALUES (2, 'Admin', 500, 4000);
+             INSERT INTO active_timers (id, project_name, start_time, last_heartbeat, paused_at)
+                 VALUES (3, 'Paused', 0, 900, 900);
+             INSERT INTO active_timers (id, project_name, start_time, last_heartbeat)
+                 VALUES (4, 'Quit cleanly', 0, NULL);",
+        )
+        .unwrap();
+
+        let last_seen: Vec<(i64, i64)> = stale_heartbeats(&conn, 10_000)
+            .unwrap()
+            .iter()
+            .map(|heartbeat| (heartbeat.timer_id, heartbeat.at))
+            .collect();
+        assert_eq!(last_seen, vec![(1, 1000), (2, 4000)]);
+        assert!(stale_heartbeats(&conn, 4_100)
+            .unwrap()
+            .iter()
+            .all(|h| h.timer_id == 1));
+    }
+}
@@ -0,0 +1,275 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit::EntrySource, clients::non_empty, current_unix_timestamp, enforce_no_overlap,
+    open_connection, persist_time_entry, resolve_db_path, run_entry_checks, sanitize_hourly_rate,
+    sanitize_notes, sanitize_project_name, undo, EntrySaveError, NewTimeEntry, TimeEntry,
+};
+
+/// Saved entries for recurring chores, e.g. "weekly invoicing admin, 30 min".
+/// `tags` is a JSON array of strings used to group templates in the picker.
+pub const CREATE_ENTRY_TEMPLATES_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS entry_templates (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        project_name TEXT NOT NULL,
+        hourly_rate REAL NOT NULL DEFAULT 0,
+        default_duration INTEGER NOT NULL,
+        notes TEXT,
+        tags TEXT NOT NULL DEFAULT '[]',
+        created_at INTEGER NOT NULL
+    )
+"#;
+
+const TEMPLATE_COLUMNS: &str =
+    "id, name, project_name, hourly_rate, default_duration, notes, tags, created_at";
+const MAX_TEMPLATE_TAGS: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryTemplate {
+    pub id: i64,
+    pub name: String,
+    pub project_name: String,
+    pub hourly_rate: f64,
+    /// Seconds.
+    pub default_duration: i64,
+    pub notes: Option<String>,
+    pub tags: Vec<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryTemplateInput {
+    /// Defaults to the project name.
+    pub name: Option<String>,
+    pub project_name: String,
+    pub hourly_rate: Option<f64>,
+    pub default_duration: i64,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[tauri::command]
+pub async fn list_entry_templates(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<EntryTemplate>, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {TEMPLATE_COLUMNS} FROM entry_templates ORDER BY name COLLATE NOCASE ASC"
+            ))
+            .map_err(|err| err.to_string())?;
+        let templates = stmt
+            .query_map([], map_template)
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        Ok(templates)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn create_entry_template(
+    app_handle: tauri::AppHandle,
+    template: EntryTemplateInput,
+) -> Result<EntryTemplate, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let input = sanitize_template_input(template)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        conn.execute(
+            "INSERT INTO entry_templates (name, project_name, hourly_rate, default_duration, notes, tags, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                input.name,
+                input.project_name,
+                input.hourly_rate,
+                input.default_duration,
+                input.notes,
+                input.tags,
+                current_unix_timestamp()
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+        fetch_template(&conn, conn.last_insert_rowid())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn update_entry_template(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    template: EntryTemplateInput,
+) -> Result<EntryTemplate, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let input = sanitize_template_input(template)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let changed = conn
+            .execute(
+                "UPDATE entry_templates
+                 SET name = ?1, project_name = ?2, hourly_rate = ?3, default_duration = ?4,
+                     notes = ?5, tags = ?6
+                 WHERE id = ?7",
+                params![
+                    input.name,
+                    input.project_name,
+                    input.hourly_rate,
+                    input.default_duration,
+                    input.notes,
+                    input.tags,
+                    id
+                ],
+            )
+            .map_err(|err| err.to_string())?;
+        if changed == 0 {
+            return Err("Template not found".into());
+        }
+        fetch_template(&conn, id)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn delete_entry_template(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        conn.execute("DELETE FROM entry_templates WHERE id = ?1", params![id])
+            .map_err(|err| err.to_string())?;
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Saves an entry from the template starting at `date`, or ending now when
+/// `date` is omitted. The entry records `utc_offset` as the zone it was
+/// tracked in, else the device's.
+#[tauri::command]
+pub async fn create_entry_from_template(
+    app_handle: tauri::AppHandle,
+    template_id: i64,
+    date: Option<i64>,
+    utc_offset: Option<i32>,
+) -> Result<TimeEntry, EntrySaveError> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let (template, start_time) = {
+        let db_path = db_path.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let conn = open_connection(db_path)?;
+            let template = fetch_template(&conn, template_id)?;
+            let start_time =
+                date.unwrap_or_else(|| current_unix_timestamp() - template.default_duration);
+            enforce_no_overlap(&conn, 0, start_time, start_time + template.default_duration)?;
+            Ok::<_, EntrySaveError>((template, start_time))
+        })
+        .await
+        .map_err(|err| err.to_string())??
+    };
+
+    let new_entry = NewTimeEntry {
+        project_name: template.project_name,
+        start_time,
+        end_time: start_time + template.default_duration,
+        hourly_rate: template.hourly_rate,
+        notes: template.notes,
+        paused_seconds: 0,
+        apply_rounding: false,
+        is_break: false,
+        billable: true,
+        utc_offset,
+        source: EntrySource::Manual,
+    };
+
+    let entry = persist_time_entry(db_path, new_entry).await?;
+    undo::record_created(&app_handle, "Add entry from template", vec![entry.id]);
+    run_entry_checks(&app_handle, &entry.project_name);
+    Ok(entry)
+}
+
+fn fetch_template(conn: &Connection, id: i64) -> Result<EntryTemplate, String> {
+    conn.query_row(
+        &format!("SELECT {TEMPLATE_COLUMNS} FROM entry_templates WHERE id = ?1"),
+        params![id],
+        map_template,
+    )
+    .map_err(|err| match err {
+        rusqlite::Error::QueryReturnedNoRows => "Template not found".to_string(),
+        other => other.to_string(),
+    })
+}
+
+fn map_template(row: &rusqlite::Row) -> rusqlite::Result<EntryTemplate> {
+    let tags: String = row.get(6)?;
+    Ok(EntryTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        project_name: row.get(2)?,
+        hourly_rate: row.get(3)?,
+        default_duration: row.get(4)?,
+        notes: row.get(5)?,
+        tags: serde_json::from_str(&tags).unwrap_or_default(),
+        created_at: row.get(7)?,
+    })
+}
+
+struct SanitizedTemplate {
+    name: String,
+    project_name: String,
+    hourly_rate: f64,
+    default_duration: i64,
+    notes: Option<String>,
+    /// JSON array, ready to store.
+    tags: String,
+}
+
+fn sanitize_template_input(input: EntryTemplateInput) -> Result<SanitizedTemplate, String> {
+    let project_name = sanitize_project_name(input.project_name);
+    if input.default_duration <= 0 {
+        return Err("Template duration must be greater than zero".into());
+    }
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input
+        .tags
+        .into_iter()
+        .filter_map(|tag| non_empty(Some(tag)))
+    {
+        if !tags
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(&tag))
+        {
+            tags.push(tag);
+        }
+    }
+    if tags.len() > MAX_TEMPLATE_TAGS {
+        return Err(format!(
+            "Templates can have at most {} tags",
+            MAX_TEMPLATE_TAGS
+        ));
+    }
+
+    Ok(SanitizedTemplate {
+        name: non_empty(input.name).unwrap_or_else(|| project_name.clone()),
+        project_name,
+        hourly_rate: sanitize_hourly_rate(input.hourly_rate.unwrap_or(0.0)),
+        default_duration: input.default_duration,
+        notes: sanitize_notes(input.notes)?,
+        tags: serde_json::to_string(&tags).map_err(|err| err.to_string())?,
+    })
+}
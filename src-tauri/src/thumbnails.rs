@@ -0,0 +1,67 @@
+use std::fs;
+use std::path::PathBuf;
+
+use rusqlite::{params, OptionalExtension};
+use tauri::{AppHandle, Manager};
+
+use crate::{open_connection, pdf_raster, resolve_db_path};
+
+/// Wide enough for a list row preview on a high-density screen.
+const THUMBNAIL_WIDTH_PX: u32 = 240;
+
+/// The path of a PNG preview of the invoice's first page, rendered on first
+/// request and again whenever the PDF is newer than the cached image.
+#[tauri::command]
+pub async fn get_invoice_thumbnail(app_handle: AppHandle, id: i64) -> Result<String, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+    let thumbnail_path = thumbnail_path(&app_handle, id)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let pdf_path: String = conn
+            .query_row(
+                "SELECT file_path FROM invoices WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|err| err.to_string())?
+            .ok_or("Invoice not found")?;
+        let pdf_path = PathBuf::from(pdf_path);
+        let pdf_modified = fs::metadata(&pdf_path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|_| "The invoice PDF is missing".to_string())?;
+
+        let fresh = fs::metadata(&thumbnail_path)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified >= pdf_modified);
+        if !fresh {
+            let png = pdf_raster::render_first_page(&pdf_path, THUMBNAIL_WIDTH_PX)?;
+            fs::write(&thumbnail_path, png)
+                .map_err(|e| format!("Failed to save thumbnail: {}", e))?;
+        }
+        thumbnail_path
+            .to_str()
+            .map(str::to_string)
+            .ok_or_else(|| "Invalid file path".to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Drops the cached preview of a deleted invoice.
+pub fn remove_invoice_thumbnail(app_handle: &AppHandle, id: i64) {
+    if let Ok(path) = thumbnail_path(app_handle, id) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn thumbnail_path(app_handle: &AppHandle, id: i64) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|err| err.to_string())?
+        .join("thumbnails");
+    fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+    Ok(dir.join(format!("invoice_{}.png", id)))
+}
@@ -0,0 +1,126 @@
+use rusqlite::{params_from_iter, types::Value as SqlValue};
+use serde::Deserialize;
+use tauri::Manager;
+
+use crate::{
+    clients, current_settings, invoice_fonts,
+    invoice_labels::InvoiceLanguage,
+    map_time_entry, open_connection,
+    pdf_generator::{self, InvoicePeriod, TimesheetDocument},
+    resolve_db_path,
+    ubl::iso_date,
+    ENTRY_TYPE_WORK, TIME_ENTRY_COLUMNS,
+};
+
+/// Narrows the entries on a timesheet. Empty fields don't filter.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimesheetFilter {
+    /// Also printed under the period.
+    pub client_id: Option<i64>,
+    pub project_names: Option<Vec<String>>,
+    /// Leaves out non-billable work.
+    pub billable_only: Option<bool>,
+}
+
+/// Renders the work tracked between `start_time` and `end_time` as a
+/// timesheet PDF in Downloads: one row per entry with its date, times,
+/// project, notes and hours, a total per day and sign-off lines. Unlike an
+/// invoice nothing is stored or marked as billed. Returns the PDF's path.
+#[tauri::command]
+pub async fn generate_timesheet_pdf(
+    app_handle: tauri::AppHandle,
+    start_time: i64,
+    end_time: i64,
+    filter: Option<TimesheetFilter>,
+    language: Option<InvoiceLanguage>,
+) -> Result<String, String> {
+    if end_time <= start_time {
+        return Err("The end of the range must be after its start".into());
+    }
+    let filter = filter.unwrap_or_default();
+    let db_path = resolve_db_path(&app_handle)?;
+    let fonts = invoice_fonts(&current_settings(&app_handle)?)?;
+    let downloads_path = app_handle
+        .path()
+        .download_dir()
+        .map_err(|e| format!("Failed to get downloads directory: {}", e))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let mut clauses = vec!["start_time >= ? AND start_time < ? AND entry_type = ?".to_string()];
+        let mut values: Vec<SqlValue> = vec![
+            start_time.into(),
+            end_time.into(),
+            ENTRY_TYPE_WORK.to_string().into(),
+        ];
+        if filter.billable_only.unwrap_or(false) {
+            clauses.push("billable = 1".into());
+        }
+        if let Some(client_id) = filter.client_id {
+            clauses.push("project_name IN (SELECT name FROM projects WHERE client_id = ?)".into());
+            values.push(client_id.into());
+        }
+        if let Some(names) = filter
+            .project_names
+            .as_ref()
+            .filter(|names| !names.is_empty())
+        {
+            let placeholders = vec!["?"; names.len()].join(", ");
+            clauses.push(format!("project_name IN ({placeholders})"));
+            values.extend(
+                names
+                    .iter()
+                    .map(|name| SqlValue::from(name.trim().to_string())),
+            );
+        }
+
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {TIME_ENTRY_COLUMNS} FROM time_entries
+                 WHERE {}
+                 ORDER BY start_time ASC",
+                clauses.join(" AND ")
+            ))
+            .map_err(|err| err.to_string())?;
+        let entries = stmt
+            .query_map(params_from_iter(values), map_time_entry)
+            .map_err(|err| err.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        if entries.is_empty() {
+            return Err("No time entries match the selected range and filters".into());
+        }
+        let client_name = filter
+            .client_id
+            .map(|id| clients::fetch_client(&conn, id).map(|client| client.name))
+            .transpose()?;
+
+        let output_path = downloads_path.join(format!(
+            "timesheet_{}_{}.pdf",
+            iso_date(start_time),
+            iso_date(end_time - 1)
+        ));
+        let output_path = output_path.to_str().ok_or("Invalid file path")?.to_string();
+        pdf_generator::generate_timesheet(
+            TimesheetDocument {
+                entries: entries
+                    .into_iter()
+                    .map(pdf_generator::TimeEntry::from)
+                    .collect(),
+                // A range ending at midnight prints the day before as its last.
+                period: InvoicePeriod {
+                    start_time,
+                    end_time: end_time - 1,
+                },
+                client_name,
+                fonts,
+                language: language.unwrap_or_default(),
+            },
+            &output_path,
+        )?;
+        Ok(output_path)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
@@ -0,0 +1,507 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use chrono::{Local, TimeZone};
+
+use crate::{
+    bank_account::BankAccount,
+    load_stored_invoice, open_connection,
+    pdf_generator::{invoice_number, DocumentKind},
+    resolve_db_path, InvoiceRender,
+};
+
+/// UN/ECE recommendation 20 unit codes.
+const UNIT_HOUR: &str = "HUR";
+const UNIT_ONE: &str = "C62";
+
+/// Writes a saved invoice as UBL 2.1 XML next to its PDF, from the same
+/// stored data the PDF is drawn from. Returns the XML file's path.
+#[tauri::command]
+pub async fn export_invoice_ubl(app_handle: tauri::AppHandle, id: i64) -> Result<String, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = open_connection(db_path)?;
+        let (invoice, render) = load_stored_invoice(&conn, id)?;
+        let xml = invoice_xml(invoice.id, &render)?;
+
+        let output_path = Path::new(&invoice.file_path).with_extension("xml");
+        std::fs::write(&output_path, xml)
+            .map_err(|e| format!("Failed to write e-invoice: {}", e))?;
+        output_path
+            .to_str()
+            .map(str::to_string)
+            .ok_or_else(|| "Invalid file path".to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// The invoice as a UBL 2.1 document following EN 16931. No VAT is charged,
+/// so every line is in the "not subject to VAT" category, and a tax ID is
+/// given as a tax registration rather than a VAT number, which that category
+/// rules out. Credit notes are typed as such, with negative amounts and a
+/// reference to the invoice. Fails when either country is missing, since
+/// both addresses need one.
+pub fn invoice_xml(id: i64, render: &InvoiceRender) -> Result<String, String> {
+    let info = &render.business_info;
+    let seller_country = country_code(info.country.as_deref(), "business")?;
+    let buyer_country = country_code(info.client_country.as_deref(), "client")?;
+    let currency = escape(&render.currency);
+    let total: f64 = render.line_items.iter().map(|item| item.amount).sum();
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(concat!(
+        "<Invoice xmlns=\"urn:oasis:names:specification:ubl:schema:xsd:Invoice-2\"",
+        " xmlns:cac=\"urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2\"",
+        " xmlns:cbc=\"urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2\">\n",
+    ));
+    element(
+        &mut xml,
+        1,
+        "cbc:CustomizationID",
+        "urn:cen.eu:en16931:2017",
+    );
+    element(&mut xml, 1, "cbc:ID", &invoice_number(id));
+    element(&mut xml, 1, "cbc:IssueDate", &iso_date(render.issued_at));
+    if let Some(due_date) = render.due_date {
+        element(&mut xml, 1, "cbc:DueDate", &iso_date(due_date));
+    }
+    let type_code = match render.kind {
+        DocumentKind::CreditNote { .. } => "381",
+        _ => "380",
+    };
+    element(&mut xml, 1, "cbc:InvoiceTypeCode", type_code);
+    if let Some(notes) = info.notes.as_deref() {
+        element(&mut xml, 1, "cbc:Note", notes);
+    }
+    element(&mut xml, 1, "cbc:DocumentCurrencyCode", &render.currency);
+
+    if let Some((start, end)) = render.period {
+        xml.push_str("  <cac:InvoicePeriod>\n");
+        element(&mut xml, 2, "cbc:StartDate", &iso_date(start));
+        element(&mut xml, 2, "cbc:EndDate", &iso_date(end));
+        xml.push_str("  </cac:InvoicePeriod>\n");
+    }
+    if let DocumentKind::CreditNote {
+        invoice_id,
+        invoice_issued_at,
+    } = render.kind
+    {
+        xml.push_str("  <cac:BillingReference>\n");
+        xml.push_str("    <cac:InvoiceDocumentReference>\n");
+        element(&mut xml, 3, "cbc:ID", &invoice_number(invoice_id));
+        element(&mut xml, 3, "cbc:IssueDate", &iso_date(invoice_issued_at));
+        xml.push_str("    </cac:InvoiceDocumentReference>\n");
+        xml.push_str("  </cac:BillingReference>\n");
+    }
+
+    xml.push_str("  <cac:AccountingSupplierParty>\n");
+    party(
+        &mut xml,
+        &info.name,
+        info.address.as_deref(),
+        &seller_country,
+        info.email.as_deref(),
+        info.phone.as_deref(),
+        info.tax_id.as_deref(),
+    );
+    xml.push_str("  </cac:AccountingSupplierParty>\n");
+
+    xml.push_str("  <cac:AccountingCustomerParty>\n");
+    party(
+        &mut xml,
+        info.client_name.as_deref().unwrap_or_default(),
+        info.client_address.as_deref(),
+        &buyer_country,
+        info.client_email.as_deref(),
+        info.client_phone.as_deref(),
+        None,
+    );
+    xml.push_str("  </cac:AccountingCustomerParty>\n");
+
+    if let Some(account) = info.bank_account.as_ref() {
+        payment_means(&mut xml, account);
+    }
+    if let Some(terms) = render.payment_terms.as_deref() {
+        xml.push_str("  <cac:PaymentTerms>\n");
+        element(&mut xml, 2, "cbc:Note", terms);
+        xml.push_str("  </cac:PaymentTerms>\n");
+    }
+
+    xml.push_str("  <cac:TaxTotal>\n");
+    amount(&mut xml, 2, "cbc:TaxAmount", 0.0, &currency);
+    xml.push_str("    <cac:TaxSubtotal>\n");
+    amount(&mut xml, 3, "cbc:TaxableAmount", total, &currency);
+    amount(&mut xml, 3, "cbc:TaxAmount", 0.0, &currency);
+    xml.push_str("      <cac:TaxCategory>\n");
+    element(&mut xml, 4, "cbc:ID", "O");
+    element(&mut xml, 4, "cbc:TaxExemptionReason", "Not subject to VAT");
+    tax_scheme(&mut xml, 4);
+    xml.push_str("      </cac:TaxCategory>\n");
+    xml.push_str("    </cac:TaxSubtotal>\n");
+    xml.push_str("  </cac:TaxTotal>\n");
+
+    xml.push_str("  <cac:LegalMonetaryTotal>\n");
+    for tag in [
+        "cbc:LineExtensionAmount",
+        "cbc:TaxExclusiveAmount",
+        "cbc:TaxInclusiveAmount",
+        "cbc:PayableAmount",
+    ] {
+        amount(&mut xml, 2, tag, total, &currency);
+    }
+    xml.push_str("  </cac:LegalMonetaryTotal>\n");
+
+    for (index, item) in render.line_items.iter().enumerate() {
+        let unit = if item.fixed_fee { UNIT_ONE } else { UNIT_HOUR };
+        xml.push_str("  <cac:InvoiceLine>\n");
+        element(&mut xml, 2, "cbc:ID", &(index + 1).to_string());
+        let _ = writeln!(
+            xml,
+            "    <cbc:InvoicedQuantity unitCode=\"{}\">{:.2}</cbc:InvoicedQuantity>",
+            unit, item.quantity
+        );
+        amount(
+            &mut xml,
+            2,
+            "cbc:LineExtensionAmount",
+            item.amount,
+            &currency,
+        );
+        xml.push_str("    <cac:Item>\n");
+        element(&mut xml, 3, "cbc:Name", &item.description);
+        xml.push_str("      <cac:ClassifiedTaxCategory>\n");
+        element(&mut xml, 4, "cbc:ID", "O");
+        tax_scheme(&mut xml, 4);
+        xml.push_str("      </cac:ClassifiedTaxCategory>\n");
+        xml.push_str("    </cac:Item>\n");
+        xml.push_str("    <cac:Price>\n");
+        amount(&mut xml, 3, "cbc:PriceAmount", item.unit_price, &currency);
+        xml.push_str("    </cac:Price>\n");
+        xml.push_str("  </cac:InvoiceLine>\n");
+    }
+
+    xml.push_str("</Invoice>\n");
+    Ok(xml)
+}
+
+/// A country as the upper-case two-letter code e-invoices take.
+fn country_code(value: Option<&str>, whose: &str) -> Result<String, String> {
+    let code = value.unwrap_or_default().trim().to_ascii_uppercase();
+    if code.len() != 2 || !code.bytes().all(|byte| byte.is_ascii_uppercase()) {
+        return Err(format!(
+            "Set the {}'s country as a two-letter code, such as DE, to export an e-invoice",
+            whose
+        ));
+    }
+    Ok(code)
+}
+
+fn party(
+    xml: &mut String,
+    name: &str,
+    address: Option<&str>,
+    country: &str,
+    email: Option<&str>,
+    phone: Option<&str>,
+    tax_id: Option<&str>,
+) {
+    xml.push_str("    <cac:Party>\n");
+    xml.push_str("      <cac:PartyName>\n");
+    element(xml, 4, "cbc:Name", name);
+    xml.push_str("      </cac:PartyName>\n");
+
+    // Addresses are free text: the first line is the street and the rest
+    // is kept together as additional street lines. The address is always
+    // given, if only as its country.
+    let lines: Vec<&str> = address
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    xml.push_str("      <cac:PostalAddress>\n");
+    if let Some((street, rest)) = lines.split_first() {
+        element(xml, 4, "cbc:StreetName", street);
+        if !rest.is_empty() {
+            element(xml, 4, "cbc:AdditionalStreetName", &rest.join(", "));
+        }
+    }
+    xml.push_str("        <cac:Country>\n");
+    element(xml, 5, "cbc:IdentificationCode", country);
+    xml.push_str("        </cac:Country>\n");
+    xml.push_str("      </cac:PostalAddress>\n");
+
+    // A tax registration (BT-32) under a scheme other than VAT.
+    if let Some(tax_id) = tax_id {
+        xml.push_str("      <cac:PartyTaxScheme>\n");
+        element(xml, 4, "cbc:CompanyID", tax_id);
+        xml.push_str("        <cac:TaxScheme>\n");
+        element(xml, 5, "cbc:ID", "TAX");
+        xml.push_str("        </cac:TaxScheme>\n");
+        xml.push_str("      </cac:PartyTaxScheme>\n");
+    }
+    xml.push_str("      <cac:PartyLegalEntity>\n");
+    element(xml, 4, "cbc:RegistrationName", name);
+    xml.push_str("      </cac:PartyLegalEntity>\n");
+
+    if email.is_some() || phone.is_some() {
+        xml.push_str("      <cac:Contact>\n");
+        if let Some(phone) = phone {
+            element(xml, 4, "cbc:Telephone", phone);
+        }
+        if let Some(email) = email {
+            element(xml, 4, "cbc:ElectronicMail", email);
+        }
+        xml.push_str("      </cac:Contact>\n");
+    }
+    xml.push_str("    </cac:Party>\n");
+}
+
+/// SEPA credit transfer when there's an IBAN, any credit transfer otherwise.
+fn payment_means(xml: &mut String, account: &BankAccount) {
+    let Some(account_id) = account
+        .iban
+        .as_deref()
+        .or(account.account_number.as_deref())
+    else {
+        return;
+    };
+    let code = if account.iban.is_some() { "58" } else { "30" };
+    xml.push_str("  <cac:PaymentMeans>\n");
+    element(xml, 2, "cbc:PaymentMeansCode", code);
+    xml.push_str("    <cac:PayeeFinancialAccount>\n");
+    element(xml, 3, "cbc:ID", account_id);
+    if let Some(holder) = account.account_holder.as_deref() {
+        element(xml, 3, "cbc:Name", holder);
+    }
+    if let Some(bic) = account.bic.as_deref() {
+        xml.push_str("      <cac:FinancialInstitutionBranch>\n");
+        element(xml, 4, "cbc:ID", bic);
+        xml.push_str("      </cac:FinancialInstitutionBranch>\n");
+    }
+    xml.push_str("    </cac:PayeeFinancialAccount>\n");
+    xml.push_str("  </cac:PaymentMeans>\n");
+}
+
+fn tax_scheme(xml: &mut String, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let _ = writeln!(xml, "{indent}<cac:TaxScheme>");
+    element(xml, depth + 1, "cbc:ID", "VAT");
+    let _ = writeln!(xml, "{indent}</cac:TaxScheme>");
+}
+
+fn element(xml: &mut String, depth: usize, tag: &str, value: &str) {
+    let _ = writeln!(
+        xml,
+        "{}<{tag}>{}</{tag}>",
+        "  ".repeat(depth),
+        escape(value)
+    );
+}
+
+fn amount(xml: &mut String, depth: usize, tag: &str, value: f64, currency: &str) {
+    let _ = writeln!(
+        xml,
+        "{}<{tag} currencyID=\"{currency}\">{:.2}</{tag}>",
+        "  ".repeat(depth),
+        value
+    );
+}
+
+pub fn iso_date(timestamp: i64) -> String {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map(|dt| dt.date_naive())
+        .unwrap_or_default()
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{invoice_labels::InvoiceLanguage, pdf_generator::InvoiceLineItem, BusinessInfo};
+
+    /// What `invoice_xml` writes for `render()`: both addresses carry their
+    /// country (BR-09, BR-11) and the seller's tax ID isn't given as a VAT
+    /// number next to category O (BR-O-02).
+    const FIXTURE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Invoice xmlns="urn:oasis:names:specification:ubl:schema:xsd:Invoice-2" xmlns:cac="urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2" xmlns:cbc="urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2">
+  <cbc:CustomizationID>urn:cen.eu:en16931:2017</cbc:CustomizationID>
+  <cbc:ID>042</cbc:ID>
+  <cbc:IssueDate>2024-06-01</cbc:IssueDate>
+  <cbc:DueDate>2024-06-15</cbc:DueDate>
+  <cbc:InvoiceTypeCode>380</cbc:InvoiceTypeCode>
+  <cbc:DocumentCurrencyCode>EUR</cbc:DocumentCurrencyCode>
+  <cac:AccountingSupplierParty>
+    <cac:Party>
+      <cac:PartyName>
+        <cbc:Name>Studio &amp; Co</cbc:Name>
+      </cac:PartyName>
+      <cac:PostalAddress>
+        <cbc:StreetName>Hauptstraße 1</cbc:StreetName>
+        <cbc:AdditionalStreetName>10115 Berlin</cbc:AdditionalStreetName>
+        <cac:Country>
+          <cbc:IdentificationCode>DE</cbc:IdentificationCode>
+        </cac:Country>
+      </cac:PostalAddress>
+      <cac:PartyTaxScheme>
+        <cbc:CompanyID>12/345/67890</cbc:CompanyID>
+        <cac:TaxScheme>
+          <cbc:ID>TAX</cbc:ID>
+        </cac:TaxScheme>
+      </cac:PartyTaxScheme>
+      <cac:PartyLegalEntity>
+        <cbc:RegistrationName>Studio &amp; Co</cbc:RegistrationName>
+      </cac:PartyLegalEntity>
+    </cac:Party>
+  </cac:AccountingSupplierParty>
+  <cac:AccountingCustomerParty>
+    <cac:Party>
+      <cac:PartyName>
+        <cbc:Name>Client BV</cbc:Name>
+      </cac:PartyName>
+      <cac:PostalAddress>
+        <cac:Country>
+          <cbc:IdentificationCode>NL</cbc:IdentificationCode>
+        </cac:Country>
+      </cac:PostalAddress>
+      <cac:PartyLegalEntity>
+        <cbc:RegistrationName>Client BV</cbc:RegistrationName>
+      </cac:PartyLegalEntity>
+    </cac:Party>
+  </cac:AccountingCustomerParty>
+  <cac:TaxTotal>
+    <cbc:TaxAmount currencyID="EUR">0.00</cbc:TaxAmount>
+    <cac:TaxSubtotal>
+      <cbc:TaxableAmount currencyID="EUR">250.00</cbc:TaxableAmount>
+      <cbc:TaxAmount currencyID="EUR">0.00</cbc:TaxAmount>
+      <cac:TaxCategory>
+        <cbc:ID>O</cbc:ID>
+        <cbc:TaxExemptionReason>Not subject to VAT</cbc:TaxExemptionReason>
+        <cac:TaxScheme>
+          <cbc:ID>VAT</cbc:ID>
+        </cac:TaxScheme>
+      </cac:TaxCategory>
+    </cac:TaxSubtotal>
+  </cac:TaxTotal>
+  <cac:LegalMonetaryTotal>
+    <cbc:LineExtensionAmount currencyID="EUR">250.00</cbc:LineExtensionAmount>
+    <cbc:TaxExclusiveAmount currencyID="EUR">250.00</cbc:TaxExclusiveAmount>
+    <cbc:TaxInclusiveAmount currencyID="EUR">250.00</cbc:TaxInclusiveAmount>
+    <cbc:PayableAmount currencyID="EUR">250.00</cbc:PayableAmount>
+  </cac:LegalMonetaryTotal>
+  <cac:InvoiceLine>
+    <cbc:ID>1</cbc:ID>
+    <cbc:InvoicedQuantity unitCode="HUR">2.50</cbc:InvoicedQuantity>
+    <cbc:LineExtensionAmount currencyID="EUR">250.00</cbc:LineExtensionAmount>
+    <cac:Item>
+      <cbc:Name>Hours worked</cbc:Name>
+      <cac:ClassifiedTaxCategory>
+        <cbc:ID>O</cbc:ID>
+        <cac:TaxScheme>
+          <cbc:ID>VAT</cbc:ID>
+        </cac:TaxScheme>
+      </cac:ClassifiedTaxCategory>
+    </cac:Item>
+    <cac:Price>
+      <cbc:PriceAmount currencyID="EUR">100.00</cbc:PriceAmount>
+    </cac:Price>
+  </cac:InvoiceLine>
+</Invoice>
+"#;
+
+    fn render() -> InvoiceRender {
+        InvoiceRender {
+            id: Some(42),
+            business_info: BusinessInfo {
+                name: "Studio & Co".into(),
+                address: Some("Hauptstraße 1\n10115 Berlin".into()),
+                email: None,
+                phone: None,
+                tax_id: Some("12/345/67890".into()),
+                bank_details: None,
+                client_name: Some("Client BV".into()),
+                client_address: None,
+                client_email: None,
+                client_phone: None,
+                country: Some("de".into()),
+                client_country: Some("NL".into()),
+                bank_account: None,
+                notes: None,
+                terms: None,
+            },
+            entries: Vec::new(),
+            line_items: vec![InvoiceLineItem {
+                date: None,
+                description: "Hours worked".into(),
+                quantity: 2.5,
+                unit_price: 100.0,
+                amount: 250.0,
+                fixed_fee: false,
+            }],
+            period: None,
+            currency: "EUR".into(),
+            client_id: None,
+            language: InvoiceLanguage::default(),
+            // Noon UTC, the same date in any time zone the tests run in.
+            due_date: Some(1_718_452_800),
+            payment_terms: None,
+            issued_at: 1_717_243_200,
+            preview: false,
+            kind: DocumentKind::Invoice,
+            timesheet: false,
+            rounding: None,
+        }
+    }
+
+    #[test]
+    fn an_invoice_matches_the_fixture() {
+        assert_eq!(invoice_xml(42, &render()).unwrap(), FIXTURE);
+    }
+
+    #[test]
+    fn a_credit_note_refers_to_the_invoice_number() {
+        let mut render = render();
+        render.kind = DocumentKind::CreditNote {
+            invoice_id: 7,
+            invoice_issued_at: 1_717_243_200,
+        };
+
+        let xml = invoice_xml(43, &render).unwrap();
+
+        assert!(xml.contains("<cbc:InvoiceTypeCode>381</cbc:InvoiceTypeCode>"));
+        assert!(xml.contains("    <cac:InvoiceDocumentReference>\n      <cbc:ID>007</cbc:ID>"));
+    }
+
+    #[test]
+    fn an_invoice_without_a_country_is_refused() {
+        let mut render = render();
+        render.business_info.client_country = None;
+        assert!(invoice_xml(42, &render)
+            .unwrap_err()
+            .contains("client's country"));
+
+        render.business_info.client_country = Some("Netherlands".into());
+        assert!(invoice_xml(42, &render).is_err());
+    }
+}
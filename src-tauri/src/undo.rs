@@ -0,0 +1,269 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    audit::{self, AuditAction, EntrySource},
+    fetch_time_entry, open_connection, projects, resolve_db_path, TimeEntry, ENTRY_TYPE_WORK,
+};
+
+/// How many entry changes can be undone, newest first.
+const UNDO_LIMIT: usize = 20;
+
+/// A change to time entries and what it takes to reverse it. Entries are
+/// kept as they were before the change.
+enum EntryChange {
+    Created(Vec<i64>),
+    Updated(Vec<TimeEntry>),
+    Deleted(Vec<TimeEntry>),
+}
+
+struct UndoRecord {
+    description: String,
+    change: EntryChange,
+}
+
+/// Recent entry changes in memory; the stack does not survive a restart.
+#[derive(Default)]
+pub struct UndoState {
+    stack: Mutex<VecDeque<UndoRecord>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoneChange {
+    pub description: String,
+    pub entry_ids: Vec<i64>,
+}
+
+/// Reverts the most recent recorded entry change. Segments of a deleted
+/// entry are not brought back.
+#[tauri::command]
+pub async fn undo_last_change(app_handle: tauri::AppHandle) -> Result<UndoneChange, String> {
+    let db_path = resolve_db_path(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app_handle.state::<UndoState>();
+        // Held until the revert is committed, so no change is recorded on top
+        // of the one being undone.
+        let mut stack = state
+            .stack
+            .lock()
+            .map_err(|_| "Undo history is unavailable")?;
+        let mut conn = open_connection(db_path)?;
+        undo_last(&mut conn, &mut stack)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Reverts the newest record in one transaction and drops it from `stack`
+/// once that has committed; a revert that fails leaves it to be tried again.
+fn undo_last(
+    conn: &mut Connection,
+    stack: &mut VecDeque<UndoRecord>,
+) -> Result<UndoneChange, String> {
+    let record = stack.back().ok_or("Nothing to undo")?;
+    let tx = conn.transaction().map_err(|err| err.to_string())?;
+    let entry_ids = revert(&tx, &record.change)?;
+    tx.commit().map_err(|err| err.to_string())?;
+    let description = record.description.clone();
+    stack.pop_back();
+    Ok(UndoneChange {
+        description,
+        entry_ids,
+    })
+}
+
+/// Forgets every recorded change, e.g. once an import has replaced the
+/// entries they refer to.
+pub fn clear(app: &AppHandle) {
+    if let Ok(mut stack) = app.state::<UndoState>().stack.lock() {
+        stack.clear();
+    }
+}
+
+pub fn record_created(app: &AppHandle, description: impl Into<String>, ids: Vec<i64>) {
+    push(app, description.into(), EntryChange::Created(ids));
+}
+
+/// `before` holds the entries as they were before the update.
+pub fn record_updated(app: &AppHandle, description: impl Into<String>, before: Vec<TimeEntry>) {
+    push(app, description.into(), EntryChange::Updated(before));
+}
+
+pub fn record_deleted(app: &AppHandle, description: impl Into<String>, deleted: Vec<TimeEntry>) {
+    push(app, description.into(), EntryChange::Deleted(deleted));
+}
+
+fn push(app: &AppHandle, description: String, change: EntryChange) {
+    let state = app.state::<UndoState>();
+    let Ok(mut stack) = state.stack.lock() else {
+        return;
+    };
+    if stack.len() == UNDO_LIMIT {
+        stack.pop_front();
+    }
+    stack.push_back(UndoRecord {
+        description,
+        change,
+    });
+}
+
+fn revert(conn: &Connection, change: &EntryChange) -> Result<Vec<i64>, String> {
+    match change {
+        EntryChange::Created(ids) => {
+            for id in ids {
+                // Already deleted by hand; nothing left to undo for this one.
+                let Ok(entry) = fetch_time_entry(conn, *id) else {
+                    continue;
+                };
+                audit::record_change(
+                    conn,
+                    AuditAction::Delete,
+                    EntrySource::Manual,
+                    Some(&entry),
+                    None,
+                )?;
+                conn.execute(
+                    "DELETE FROM time_entry_segments WHERE entry_id = ?1",
+                    params![id],
+                )
+                .map_err(|err| err.to_string())?;
+                conn.execute("DELETE FROM time_entries WHERE id = ?1", params![id])
+                    .map_err(|err| err.to_string())?;
+            }
+            Ok(ids.clone())
+        }
+        EntryChange::Updated(entries) => {
+            for entry in entries {
+                let current = fetch_time_entry(conn, entry.id)
+                    .map_err(|_| "The edited entry has since been deleted".to_string())?;
+                conn.execute(
+                    "UPDATE time_entries
+                         SET project_name = ?1, start_time = ?2, end_time = ?3, duration = ?4,
+                             hourly_rate = ?5, amount = ?6, notes = ?7, raw_duration = ?8,
+                             entry_type = ?9, billable = ?10
+                         WHERE id = ?11",
+                    params![
+                        entry.project_name,
+                        entry.start_time,
+                        entry.end_time,
+                        entry.duration,
+                        entry.hourly_rate,
+                        entry.amount,
+                        entry.notes,
+                        entry.raw_duration,
+                        entry.entry_type,
+                        entry.billable,
+                        entry.id
+                    ],
+                )
+                .map_err(|err| err.to_string())?;
+                let restored = fetch_time_entry(conn, entry.id)?;
+                audit::record_change(
+                    conn,
+                    AuditAction::Update,
+                    EntrySource::Manual,
+                    Some(&current),
+                    Some(&restored),
+                )?;
+            }
+            Ok(entries.iter().map(|entry| entry.id).collect())
+        }
+        EntryChange::Deleted(entries) => {
+            for entry in entries {
+                if entry.entry_type == ENTRY_TYPE_WORK {
+                    projects::ensure_project(conn, &entry.project_name)?;
+                }
+                conn.execute(
+                    "INSERT INTO time_entries (id, project_name, start_time, end_time, duration, hourly_rate, amount, notes, raw_duration, entry_type, billable, starred, utc_offset, invoice_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13,
+                             (SELECT id FROM invoices WHERE id = ?14))",
+                    params![
+                        entry.id,
+                        entry.project_name,
+                        entry.start_time,
+                        entry.end_time,
+                        entry.duration,
+                        entry.hourly_rate,
+                        entry.amount,
+                        entry.notes,
+                        entry.raw_duration,
+                        entry.entry_type,
+                        entry.billable,
+                        entry.starred,
+                        entry.utc_offset,
+                        entry.invoice_id
+                    ],
+                )
+                .map_err(|err| err.to_string())?;
+                let restored = fetch_time_entry(conn, entry.id)?;
+                audit::record_change(
+                    conn,
+                    AuditAction::Create,
+                    EntrySource::Manual,
+                    None,
+                    Some(&restored),
+                )?;
+            }
+            Ok(entries.iter().map(|entry| entry.id).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection(name: &str) -> Connection {
+        let path = std::env::temp_dir().join(format!("tt-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        open_connection(path).expect("test database")
+    }
+
+    fn record(description: &str, change: EntryChange) -> UndoRecord {
+        UndoRecord {
+            description: description.into(),
+            change,
+        }
+    }
+
+    #[test]
+    fn a_change_that_cannot_be_reverted_stays_on_the_stack() {
+        let mut conn = connection("undo-failed");
+        conn.execute(
+            "INSERT INTO time_entries (id, project_name, start_time, end_time, duration)
+             VALUES (1, 'Website', 0, 3600, 3600)",
+            [],
+        )
+        .unwrap();
+        let edited = fetch_time_entry(&conn, 1).unwrap();
+        conn.execute("DELETE FROM time_entries WHERE id = 1", [])
+            .unwrap();
+        let mut stack = VecDeque::from([record("Edit entry", EntryChange::Updated(vec![edited]))]);
+
+        assert!(undo_last(&mut conn, &mut stack).is_err());
+        assert_eq!(stack.len(), 1);
+    }
+
+    #[test]
+    fn a_reverted_change_leaves_the_stack() {
+        let mut conn = connection("undo-created");
+        conn.execute(
+            "INSERT INTO time_entries (id, project_name, start_time, end_time, duration)
+             VALUES (1, 'Website', 0, 3600, 3600)",
+            [],
+        )
+        .unwrap();
+        let mut stack = VecDeque::from([record("Add entry", EntryChange::Created(vec![1]))]);
+
+        let undone = undo_last(&mut conn, &mut stack).unwrap();
+        assert_eq!(undone.description, "Add entry");
+        assert_eq!(undone.entry_ids, vec![1]);
+        assert!(stack.is_empty());
+        assert!(fetch_time_entry(&conn, 1).is_err());
+    }
+}
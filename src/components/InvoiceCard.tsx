@@ -20,7 +20,7 @@ export function InvoiceCard({ invoice, onPreview }: InvoiceCardProps) {
           <div className="invoice-card__number">Invoice #{String(invoice.id).padStart(3, '0')}</div>
           <div className="invoice-card__date">{formattedDate}</div>
         </div>
-        <div className="invoice-card__amount">{formatCurrency(invoice.totalAmount)}</div>
+        <div className="invoice-card__amount">{formatCurrency(invoice.totalAmount, invoice.currency)}</div>
       </div>
 
       <div className="invoice-card__actions">
@@ -12,15 +12,28 @@ type RawTimeEntry = {
   duration: number;
   hourly_rate: number;
   amount: number;
+  notes: string | null;
+  currency: string;
+  entry_type?: "work" | "break";
+  billable?: boolean;
+  starred?: boolean;
+  utc_offset?: number | null;
+  invoice_id?: number | null;
 };
 
 type OverlapWarning = {
   overlapping_entries: RawTimeEntry[];
 };
 
+type EntryWarning = {
+  kind: "too_long" | "ends_in_future";
+  message: string;
+};
+
 type UpdateResult = {
   entry: RawTimeEntry;
   overlap_warning?: OverlapWarning;
+  warnings?: EntryWarning[];
 };
 
 const toTimeEntry = (raw: RawTimeEntry): TimeEntry => ({
@@ -31,6 +44,13 @@ const toTimeEntry = (raw: RawTimeEntry): TimeEntry => ({
   duration: raw.duration,
   hourlyRate: raw.hourly_rate,
   amount: raw.amount,
+  notes: raw.notes ?? null,
+  currency: raw.currency ?? "USD",
+  entryType: raw.entry_type ?? "work",
+  billable: raw.billable ?? true,
+  starred: raw.starred ?? false,
+  utcOffset: raw.utc_offset ?? null,
+  invoiceId: raw.invoice_id ?? null,
 });
 
 type TimerStatus = {
@@ -39,6 +59,8 @@ type TimerStatus = {
   start_time: number | null;
   elapsed_seconds: number | null;
   hourly_rate: number | null;
+  is_paused?: boolean;
+  paused_seconds?: number | null;
 };
 
 type TodayTotals = {
@@ -63,12 +85,17 @@ const parseError = (error: unknown): string => {
   if (typeof error === "string") {
     return error;
   }
+  // Entry saves fail with `{ message, conflicts }`.
+  if (typeof error === "object" && error !== null && "message" in error) {
+    return String(error.message);
+  }
   return "Something went wrong. Please try again.";
 };
 
 export function useTimeTracker() {
   const [projectName, setProjectName] = useState("");
   const [isRunning, setIsRunning] = useState(false);
+  const [isPaused, setIsPaused] = useState(false);
   const [startTimestamp, setStartTimestamp] = useState<number | null>(null);
   const [elapsedSeconds, setElapsedSeconds] = useState(0);
   const [entriesVersion, setEntriesVersion] = useState(0);
@@ -113,13 +140,16 @@ export function useTimeTracker() {
       const wasRunning = wasRunningRef.current;
 
       setIsRunning(running);
+      setIsPaused(running && Boolean(status.is_paused));
 
       if (running) {
         const startSeconds =
           typeof status.start_time === "number"
             ? status.start_time
             : Math.floor(Date.now() / 1000);
-        setStartTimestamp(startSeconds * 1000);
+        // Shift the start by paused time so the local tick matches the backend.
+        const pausedSeconds = status.paused_seconds ?? 0;
+        setStartTimestamp((startSeconds + pausedSeconds) * 1000);
         const elapsed =
           typeof status.elapsed_seconds === "number"
             ? Math.max(0, status.elapsed_seconds)
@@ -194,7 +224,7 @@ export function useTimeTracker() {
   }, [applyStatus]);
 
   useEffect(() => {
-    if (!isRunning || startTimestamp === null) {
+    if (!isRunning || isPaused || startTimestamp === null) {
       return undefined;
     }
 
@@ -208,7 +238,7 @@ export function useTimeTracker() {
     const timerId = window.setInterval(tick, 1000);
 
     return () => window.clearInterval(timerId);
-  }, [isRunning, startTimestamp]);
+  }, [isRunning, isPaused, startTimestamp]);
 
   useEffect(() => {
     if (typeof window === "undefined") {
@@ -355,6 +385,7 @@ export function useTimeTracker() {
     projectName,
     setProjectName,
     isRunning,
+    isPaused,
     startTimestamp,
     elapsedSeconds,
     todayTotalSeconds,
@@ -1,11 +1,20 @@
-const currencyFormatter = new Intl.NumberFormat(undefined, {
-  style: "currency",
-  currency: "USD",
-  minimumFractionDigits: 2,
-  maximumFractionDigits: 2,
-});
+const currencyFormatters = new Map<string, Intl.NumberFormat>();
 
-export const formatCurrency = (value: number): string => {
+const getFormatter = (currency: string): Intl.NumberFormat => {
+  let formatter = currencyFormatters.get(currency);
+  if (!formatter) {
+    formatter = new Intl.NumberFormat(undefined, {
+      style: "currency",
+      currency,
+      minimumFractionDigits: 2,
+      maximumFractionDigits: 2,
+    });
+    currencyFormatters.set(currency, formatter);
+  }
+  return formatter;
+};
+
+export const formatCurrency = (value: number, currency = "USD"): string => {
   const safeValue = Number.isFinite(value) ? value : 0;
-  return currencyFormatter.format(safeValue);
+  return getFormatter(currency).format(safeValue);
 };
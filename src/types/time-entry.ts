@@ -6,6 +6,13 @@ export type TimeEntry = {
   duration: number;
   hourlyRate: number;
   amount: number;
+  notes: string | null;
+  currency: string;
+  entryType: "work" | "break";
+  billable: boolean;
+  starred: boolean;
+  utcOffset: number | null;
+  invoiceId: number | null;
 };
 
 export type Invoice = {
@@ -17,4 +24,13 @@ export type Invoice = {
   totalAmount: number;
   filePath: string;
   entryCount: number;
+  clientId: number | null;
+  currency: string;
+  dueDate: number | null;
+  paymentTerms: string | null;
+  invoiceType: "invoice" | "credit_note";
+  creditedInvoiceId: number | null;
+  status: "issued" | "sent" | "paid";
+  sentAt: number | null;
+  paidAt: number | null;
 };